@@ -6,7 +6,7 @@ fn main() {
     App::new()
         .init_resource::<FontIndex>()
         .add_plugins(DefaultPlugins)
-        .add_plugin(TerminalPlugin)
+        .add_plugin(TerminalPlugin::default())
         .insert_resource(ClearColor(Color::BLACK))
         .add_plugin(TiledCameraPlugin)
         .add_startup_system(spawn_terminal)
@@ -5,7 +5,7 @@ use bevy_tiled_camera::*;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(TerminalPlugin)
+        .add_plugin(TerminalPlugin::default())
         .add_plugin(TiledCameraPlugin)
         .insert_resource(ClearColor(Color::BLACK))
         .add_startup_system(spawn_terminal)
@@ -8,7 +8,7 @@ use bracket_random::prelude::*;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(TerminalPlugin)
+        .add_plugin(TerminalPlugin::default())
         .add_plugin(TiledCameraPlugin)
         .insert_resource(ClearColor(Color::BLACK))
         .init_resource::<Noise>()
@@ -119,6 +119,7 @@ fn noise(time: Res<Time>, mut noise: ResMut<Noise>, mut query: Query<&mut Termin
                 glyph: '▒',
                 fg_color: Color::rgb(col, col, col),
                 bg_color: Color::BLACK,
+                ..Default::default()
             };
         }
 
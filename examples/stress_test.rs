@@ -11,7 +11,7 @@ fn main() {
     App::new()
         .init_resource::<Pause>()
         .add_plugins(DefaultPlugins)
-        .add_plugin(TerminalPlugin)
+        .add_plugin(TerminalPlugin::default())
         .add_plugin(TiledCameraPlugin)
         .insert_resource(ClearColor(Color::BLACK))
         .add_plugin(LogDiagnosticsPlugin::default())
@@ -65,6 +65,7 @@ fn spam_terminal(keys: Res<Input<KeyCode>>, mut pause: ResMut<Pause>, mut q: Que
                 glyph,
                 fg_color: fg,
                 bg_color: bg,
+                ..Default::default()
             }
         }
         let top = term.top_index() as i32;
@@ -0,0 +1,210 @@
+//! Describing terminal changes for screen readers.
+//!
+//! Bevy doesn't have an accessibility api, so changes are sent as
+//! [TerminalAccessibilityEvent]s, similar to an ARIA live region. Forward them to
+//! a screen reader or text-to-speech library of your choice.
+
+use bevy::prelude::*;
+
+use crate::{tile_diff::PreviousTiles, Terminal, Tile};
+
+/// Plugin which sends a [TerminalAccessibilityEvent] for every changed tile of
+/// each terminal with a [TerminalAccessibilityRegion].
+pub struct TerminalAccessibilityPlugin;
+
+impl Plugin for TerminalAccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TerminalAccessibilityEvent>()
+            .add_system(terminal_accessibility_events);
+    }
+}
+
+/// The semantic role of a terminal, used to decide how it's changes should be
+/// announced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    /// General text content.
+    Document,
+    /// A log where new lines are added over time.
+    Log,
+    /// A list of selectable options.
+    Menu,
+    /// A status display, like a health bar or score.
+    Status,
+    /// A grid of tiles, like a game map.
+    Grid,
+}
+
+/// Terminal component marking a terminal as accessible, with it's semantic role
+/// and a label describing it.
+///
+/// The component keeps a copy of the terminal's tiles from the previous frame,
+/// which changes are found by comparing against.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([40, 5]))
+///         .insert(TerminalAccessibilityRegion::new(AccessibilityRole::Log, "Message log"));
+/// }
+///
+/// fn speak(mut events: EventReader<TerminalAccessibilityEvent>) {
+///     for ev in events.iter() {
+///         info!("{}", ev.description);
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone)]
+pub struct TerminalAccessibilityRegion {
+    pub role: AccessibilityRole,
+    pub label: String,
+    previous: PreviousTiles,
+}
+
+impl TerminalAccessibilityRegion {
+    pub fn new(role: AccessibilityRole, label: impl Into<String>) -> Self {
+        Self {
+            role,
+            label: label.into(),
+            previous: PreviousTiles::default(),
+        }
+    }
+}
+
+/// Event sent when a tile of an accessible terminal changes.
+#[derive(Debug, Clone)]
+pub struct TerminalAccessibilityEvent {
+    /// The entity with the [TerminalAccessibilityRegion].
+    pub entity: Entity,
+    pub role: AccessibilityRole,
+    /// The position of the tile in the terminal.
+    pub xy: [i32; 2],
+    /// The new tile.
+    pub tile: Tile,
+    /// A readable description of the change, including the region's label,
+    /// the glyph and it's colors.
+    pub description: String,
+}
+
+/// A readable description of a tile, for example `'@' white on black`.
+pub fn describe_tile(tile: &Tile) -> String {
+    format!(
+        "'{}' {} on {}",
+        tile.glyph,
+        describe_color(tile.fg_color),
+        describe_color(tile.bg_color)
+    )
+}
+
+/// The name of a color if it matches one of bevy's named colors, otherwise it's
+/// hex code.
+fn describe_color(color: Color) -> String {
+    let named = [
+        (Color::BLACK, "black"),
+        (Color::WHITE, "white"),
+        (Color::GRAY, "gray"),
+        (Color::SILVER, "silver"),
+        (Color::RED, "red"),
+        (Color::MAROON, "maroon"),
+        (Color::ORANGE, "orange"),
+        (Color::YELLOW, "yellow"),
+        (Color::GREEN, "green"),
+        (Color::DARK_GREEN, "dark green"),
+        (Color::LIME_GREEN, "lime green"),
+        (Color::CYAN, "cyan"),
+        (Color::TEAL, "teal"),
+        (Color::BLUE, "blue"),
+        (Color::MIDNIGHT_BLUE, "midnight blue"),
+        (Color::NAVY, "navy"),
+        (Color::PURPLE, "purple"),
+        (Color::PINK, "pink"),
+        (Color::FUCHSIA, "fuchsia"),
+        (Color::NONE, "transparent"),
+    ];
+    let rgba = color.as_rgba_f32();
+    named
+        .iter()
+        .find(|(c, _)| c.as_rgba_f32() == rgba)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| {
+            let [r, g, b, _] = rgba.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        })
+}
+
+fn terminal_accessibility_events(
+    mut events: EventWriter<TerminalAccessibilityEvent>,
+    mut q: Query<(Entity, &Terminal, &mut TerminalAccessibilityRegion), Changed<Terminal>>,
+) {
+    for (entity, term, mut region) in q.iter_mut() {
+        let TerminalAccessibilityRegion {
+            role,
+            label,
+            previous,
+        } = &mut *region;
+        previous.update(term, |pos, _, tile| {
+            let xy = [pos.x as i32, pos.y as i32];
+            events.send(TerminalAccessibilityEvent {
+                entity,
+                role: *role,
+                xy,
+                tile: *tile,
+                description: format!("{}: {} at {}, {}", label, describe_tile(tile), xy[0], xy[1]),
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{
+        event::Events,
+        schedule::{Stage, SystemStage},
+    };
+
+    use super::*;
+
+    #[test]
+    fn tile_events() {
+        let mut world = World::new();
+        world.insert_resource(Events::<TerminalAccessibilityEvent>::default());
+        let mut stage = SystemStage::parallel();
+        stage.add_system(terminal_accessibility_events);
+
+        let term = world
+            .spawn()
+            .insert(Terminal::with_size([10, 2]))
+            .insert(TerminalAccessibilityRegion::new(
+                AccessibilityRole::Status,
+                "Status",
+            ))
+            .id();
+        stage.run(&mut world);
+
+        world.get_mut::<Terminal>(term).unwrap().put_tile(
+            [3, 1],
+            Tile {
+                glyph: '@',
+                fg_color: Color::YELLOW,
+                bg_color: Color::rgb(0.0, 0.0, 0.2),
+                ..Default::default()
+            },
+        );
+        stage.run(&mut world);
+
+        let events = world
+            .get_resource::<Events<TerminalAccessibilityEvent>>()
+            .unwrap();
+        let mut reader = events.get_reader();
+        let sent: Vec<_> = reader.iter(events).collect();
+        assert_eq!(1, sent.len());
+        assert_eq!([3, 1], sent[0].xy);
+        assert_eq!(AccessibilityRole::Status, sent[0].role);
+        assert_eq!("Status: '@' yellow on #000033 at 3, 1", sent[0].description);
+    }
+}
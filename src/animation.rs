@@ -0,0 +1,439 @@
+//! Keyframe animations of whole terminals, for cut-scenes and intro screens.
+//!
+//! Clips can be loaded from two formats. `.tanim` files are binary: a 3 byte
+//! header of the magic number `b"TA"` and a format version, currently 1,
+//! followed by each frame as a little endian `f32` time offset, a little
+//! endian `u32` byte length and the frame's tiles encoded by a
+//! [TerminalGlyphEncoder].
+//!
+//! `.tanim.ron` files list each frame's time offset and rows of text, from the
+//! top of the terminal down. The colors are optional and apply to every tile
+//! of the frame, as linear rgba:
+//!
+//! ```ron
+//! (
+//!     frames: [
+//!         (time: 0.0, rows: ["\\o/", " | "]),
+//!         (time: 0.5, rows: ["_o_", " | "], fg_color: Some((1.0, 0.0, 0.0, 1.0))),
+//!     ],
+//! )
+//! ```
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use crate::{Terminal, TerminalGlyphDecoder, TerminalGlyphEncoder, TerminalSnapshot, Tile};
+
+const MAGIC: [u8; 2] = *b"TA";
+const VERSION: u8 = 1;
+
+/// A sequence of keyframes, each a [TerminalSnapshot] shown from it's time
+/// offset in seconds until the next keyframe.
+///
+/// The clip ends at the time offset of it's last frame.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn intro(mut commands: Commands, mut clips: ResMut<Assets<TerminalAnimationClip>>) {
+///     let mut term = Terminal::with_size([20, 3]);
+///     let mut clip = TerminalAnimationClip::default();
+///     for (i, text) in ["Loading", "Loading.", "Loading..", "Loading..."].iter().enumerate() {
+///         term.put_string([1, 1], text);
+///         clip.push_frame(i as f32 * 0.25, term.snapshot());
+///     }
+///
+///     let player = TerminalAnimationPlayer::new(clips.add(clip)).with_looping(true);
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 3]))
+///         .insert(player);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, TypeUuid)]
+#[uuid = "9d3e6a1c-4b27-4f0e-8c55-2a7f1e6b3d90"]
+pub struct TerminalAnimationClip {
+    /// The time offset and snapshot of each keyframe, in order of time.
+    pub frames: Vec<(f32, TerminalSnapshot)>,
+}
+
+impl TerminalAnimationClip {
+    /// Add a keyframe, keeping the frames in order of time.
+    pub fn push_frame(&mut self, time: f32, snapshot: TerminalSnapshot) {
+        let i = self.frames.partition_point(|(t, _)| *t <= time);
+        self.frames.insert(i, (time, snapshot));
+    }
+
+    pub fn with_frame(mut self, time: f32, snapshot: TerminalSnapshot) -> Self {
+        self.push_frame(time, snapshot);
+        self
+    }
+
+    /// The length of the clip in seconds, which is the time offset of it's
+    /// last frame.
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |(t, _)| *t)
+    }
+
+    /// The index of the frame shown at `time`, and how far the clip is from
+    /// that frame to the next, from 0 to 1.
+    pub fn frame_at(&self, time: f32) -> Option<(usize, f32)> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let i = self
+            .frames
+            .partition_point(|(t, _)| *t <= time)
+            .saturating_sub(1);
+        let t = match self.frames.get(i + 1) {
+            Some((next, _)) => {
+                let start = self.frames[i].0;
+                ((time - start) / (next - start).max(f32::EPSILON)).clamp(0.0, 1.0)
+            }
+            None => 0.0,
+        };
+        Some((i, t))
+    }
+
+    /// Write the state of the clip at `time` to the terminal.
+    ///
+    /// If `interpolate` is true tile colors are blended towards the next
+    /// frame. Glyphs always come from the current frame.
+    pub fn apply(&self, time: f32, interpolate: bool, terminal: &mut Terminal) {
+        let (i, t) = match self.frame_at(time) {
+            Some(frame) => frame,
+            None => return,
+        };
+        let current = &self.frames[i].1;
+        let next = self.frames.get(i + 1).map(|(_, next)| next);
+        match next {
+            Some(next) if interpolate && t > 0.0 && next.size() == current.size() => {
+                let tiles = current
+                    .tiles()
+                    .iter()
+                    .zip(next.tiles())
+                    .map(|(a, b)| Tile {
+                        fg_color: lerp_color(a.fg_color, b.fg_color, t),
+                        bg_color: lerp_color(a.bg_color, b.bg_color, t),
+                        ..*a
+                    })
+                    .collect();
+                terminal.restore(&TerminalSnapshot {
+                    tiles,
+                    size: current.size(),
+                });
+            }
+            _ => terminal.restore(current),
+        }
+    }
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let a = Vec4::from(a.as_rgba_f32());
+    let b = Vec4::from(b.as_rgba_f32());
+    let [r, g, b, a] = a.lerp(b, t).to_array();
+    Color::rgba(r, g, b, a)
+}
+
+/// Terminal component which plays a [TerminalAnimationClip] on the terminal.
+///
+/// An [AnimationDoneEvent] is sent when a clip which isn't looping finishes.
+#[derive(Component, Debug, Clone)]
+pub struct TerminalAnimationPlayer {
+    pub clip: Handle<TerminalAnimationClip>,
+    /// Seconds since the clip started.
+    pub elapsed: f32,
+    /// If true the clip restarts once it reaches the end.
+    pub looping: bool,
+    /// If true tile colors are blended between frames, see
+    /// [TerminalAnimationClip::apply].
+    pub interpolate: bool,
+    /// The frame last written to the terminal, so unchanged frames aren't
+    /// written again.
+    current_frame: Option<usize>,
+    finished: bool,
+}
+
+impl TerminalAnimationPlayer {
+    pub fn new(clip: Handle<TerminalAnimationClip>) -> Self {
+        Self {
+            clip,
+            elapsed: 0.0,
+            looping: false,
+            interpolate: false,
+            current_frame: None,
+            finished: false,
+        }
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_interpolation(mut self, interpolate: bool) -> Self {
+        self.interpolate = interpolate;
+        self
+    }
+
+    /// Play the clip again from the start.
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+        self.current_frame = None;
+        self.finished = false;
+    }
+
+    /// Returns true once a clip which isn't looping has reached it's end.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Event sent when the clip of a [TerminalAnimationPlayer] which isn't
+/// looping finishes, with the player's entity.
+pub struct AnimationDoneEvent(pub Entity);
+
+pub(crate) fn terminal_animation_update(
+    time: Res<Time>,
+    clips: Res<Assets<TerminalAnimationClip>>,
+    mut events: EventWriter<AnimationDoneEvent>,
+    mut q: Query<(Entity, &mut Terminal, &mut TerminalAnimationPlayer)>,
+) {
+    for (entity, mut term, mut player) in q.iter_mut() {
+        let clip = match clips.get(&player.clip) {
+            Some(clip) => clip,
+            None => continue,
+        };
+        if player.finished {
+            continue;
+        }
+
+        player.elapsed += time.delta_seconds();
+        let duration = clip.duration();
+        let mut clip_time = player.elapsed;
+        if player.looping && duration > 0.0 {
+            clip_time %= duration;
+        } else if player.elapsed >= duration {
+            player.finished = true;
+            events.send(AnimationDoneEvent(entity));
+        }
+
+        let frame = clip.frame_at(clip_time).map(|(i, _)| i);
+        if player.interpolate || frame != player.current_frame {
+            clip.apply(clip_time, player.interpolate, &mut term);
+            player.current_frame = frame;
+        }
+    }
+}
+
+/// Encode a clip in the binary `.tanim` format, see the [module docs](self).
+pub fn encode_clip(clip: &TerminalAnimationClip) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(VERSION);
+    for (time, snapshot) in clip.frames.iter() {
+        let frame = TerminalGlyphEncoder::encode(snapshot.tiles(), snapshot.size());
+        bytes.extend_from_slice(&time.to_le_bytes());
+        bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&frame);
+    }
+    bytes
+}
+
+/// Decode a clip from the binary `.tanim` format, see the [module docs](self).
+pub fn decode_clip(bytes: &[u8]) -> Result<TerminalAnimationClip, anyhow::Error> {
+    if bytes.get(0..2) != Some(&MAGIC[..]) {
+        anyhow::bail!("Data isn't a terminal animation clip");
+    }
+    if bytes[2..].first() != Some(&VERSION) {
+        anyhow::bail!("Unsupported terminal animation clip version");
+    }
+    let mut clip = TerminalAnimationClip::default();
+    let mut rest = &bytes[3..];
+    while !rest.is_empty() {
+        if rest.len() < 8 {
+            anyhow::bail!("Terminal animation clip ended unexpectedly");
+        }
+        let time = f32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let frame = rest
+            .get(8..8 + len)
+            .ok_or_else(|| anyhow::anyhow!("Terminal animation clip ended unexpectedly"))?;
+        clip.push_frame(time, TerminalGlyphDecoder::decode(frame)?);
+        rest = &rest[8 + len..];
+    }
+    Ok(clip)
+}
+
+/// The contents of a `.tanim.ron` file.
+#[derive(Deserialize)]
+struct ClipMeta {
+    frames: Vec<FrameMeta>,
+}
+
+#[derive(Deserialize)]
+struct FrameMeta {
+    time: f32,
+    /// Rows of text from the top of the terminal down.
+    rows: Vec<String>,
+    #[serde(default)]
+    fg_color: Option<(f32, f32, f32, f32)>,
+    #[serde(default)]
+    bg_color: Option<(f32, f32, f32, f32)>,
+}
+
+impl FrameMeta {
+    fn snapshot(&self) -> TerminalSnapshot {
+        let width = self.rows.iter().map(|r| r.chars().count()).max();
+        let mut term = Terminal::with_size([width.unwrap_or(0) as u32, self.rows.len() as u32]);
+        let color = |c: Option<(f32, f32, f32, f32)>, default| match c {
+            Some((r, g, b, a)) => Color::rgba_linear(r, g, b, a),
+            None => default,
+        };
+        let default = Tile::default();
+        let fg_color = color(self.fg_color, default.fg_color);
+        let bg_color = color(self.bg_color, default.bg_color);
+        for t in term.iter_mut() {
+            t.fg_color = fg_color;
+            t.bg_color = bg_color;
+        }
+        let top = self.rows.len() as i32 - 1;
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, glyph) in row.chars().enumerate() {
+                term.get_tile_mut([x as i32, top - y as i32]).glyph = glyph;
+            }
+        }
+        term.snapshot()
+    }
+}
+
+/// Loads [TerminalAnimationClip]s from `.tanim` and `.tanim.ron` files, see
+/// the [module docs](self) for the formats.
+#[derive(Default)]
+pub struct TerminalAnimationClipAssetLoader;
+
+impl AssetLoader for TerminalAnimationClipAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let extension = load_context.path().extension();
+            let clip = if extension.and_then(|ext| ext.to_str()) == Some("ron") {
+                let meta: ClipMeta = ron::de::from_bytes(bytes)?;
+                meta.frames
+                    .iter()
+                    .fold(TerminalAnimationClip::default(), |clip, frame| {
+                        clip.with_frame(frame.time, frame.snapshot())
+                    })
+            } else {
+                decode_clip(bytes)?
+            };
+            load_context.set_default_asset(LoadedAsset::new(clip));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tanim", "tanim.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+
+    #[test]
+    fn play() {
+        let frame = |glyph, color| {
+            let mut term = Terminal::with_size([2, 1]);
+            for t in term.iter_mut() {
+                t.glyph = glyph;
+                t.fg_color = color;
+            }
+            term.snapshot()
+        };
+        let clip = TerminalAnimationClip::default()
+            .with_frame(1.0, frame('b', Color::WHITE))
+            .with_frame(0.0, frame('a', Color::BLACK));
+        assert_eq!(1.0, clip.duration());
+        assert_eq!(Some((0, 0.5)), clip.frame_at(0.5));
+        assert_eq!(Some((1, 0.0)), clip.frame_at(2.0));
+
+        let mut term = Terminal::with_size([4, 4]);
+        clip.apply(0.25, true, &mut term);
+        assert_eq!([2, 1], term.size().to_array());
+        assert_eq!('a', term.get_char([0, 0]));
+        assert_eq!(
+            Color::rgba(0.25, 0.25, 0.25, 1.0),
+            term.get_tile([1, 0]).fg_color
+        );
+
+        // Binary clips survive a round trip
+        let decoded = decode_clip(&encode_clip(&clip)).unwrap();
+        assert_eq!(2, decoded.frames.len());
+        assert_eq!(1.0, decoded.frames[1].0);
+        assert_eq!(clip.frames[1].1.tiles(), decoded.frames[1].1.tiles());
+
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<TerminalAnimationClip>()
+            .add_event::<AnimationDoneEvent>()
+            .add_system(terminal_animation_update);
+        let handle = app
+            .world
+            .get_resource_mut::<Assets<TerminalAnimationClip>>()
+            .unwrap()
+            .add(clip);
+        let mut player = TerminalAnimationPlayer::new(handle);
+        // Skip to the end, time doesn't advance in the test
+        player.elapsed = 1.0;
+        let entity = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([2, 1]))
+            .insert(player)
+            .id();
+        app.update();
+
+        assert_eq!(
+            'b',
+            app.world.get::<Terminal>(entity).unwrap().get_char([0, 0])
+        );
+        let player = app.world.get::<TerminalAnimationPlayer>(entity).unwrap();
+        assert!(player.is_finished());
+        let events = app
+            .world
+            .get_resource::<Events<AnimationDoneEvent>>()
+            .unwrap();
+        assert_eq!(1, events.get_reader().iter(events).count());
+    }
+
+    #[test]
+    fn ron_frames() {
+        let meta: ClipMeta = ron::de::from_str(
+            "(frames: [(time: 0.5, rows: [\"ab\", \"c\"], fg_color: Some((1.0, 0.0, 0.0, 1.0)))])",
+        )
+        .unwrap();
+        let snapshot = meta.frames[0].snapshot();
+        let mut term = Terminal::default();
+        term.restore(&snapshot);
+        assert_eq!([2, 2], term.size().to_array());
+        assert_eq!("ab", term.get_string([0, 1], 2));
+        assert_eq!("c ", term.get_string([0, 0], 2));
+        assert_eq!(
+            Color::rgba_linear(1.0, 0.0, 0.0, 1.0),
+            term.get_tile([0, 0]).fg_color
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Converting images to ascii art.
+//!
+//! Only available with the `image` feature.
+
+use bevy::prelude::*;
+use image::{imageops::FilterType, DynamicImage};
+
+use crate::{code_page_437, Terminal, Tile};
+
+/// A set of tiles used to represent image pixels, sorted by luminance from
+/// darkest to brightest.
+#[derive(Clone, Debug)]
+pub struct AsciiPalette(Vec<(Tile, Color)>);
+
+impl AsciiPalette {
+    /// Create a palette from tiles paired with the color they represent. The
+    /// entries will be sorted by the luminance of their color.
+    pub fn new(mut entries: Vec<(Tile, Color)>) -> Self {
+        entries.sort_by(|(_, a), (_, b)| luminance(*a).total_cmp(&luminance(*b)));
+        Self(entries)
+    }
+
+    /// The palette's tiles and their colors, from darkest to brightest.
+    pub fn entries(&self) -> &[(Tile, Color)] {
+        &self.0
+    }
+
+    /// Create a grayscale palette from code page 437 glyph indices ordered
+    /// from least to most visually dense.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let palette = AsciiPalette::from_charset(b" .:-=+*#%@");
+    /// assert_eq!(10, palette.entries().len());
+    /// ```
+    pub fn from_charset(chars: &[u8]) -> Self {
+        let max = chars.len().saturating_sub(1).max(1) as f32;
+        let entries = chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let tile = Tile {
+                    glyph: code_page_437::index_to_glyph(*c),
+                    ..Default::default()
+                };
+                let v = i as f32 / max;
+                (tile, Color::rgb(v, v, v))
+            })
+            .collect();
+        Self(entries)
+    }
+
+    /// The tile whose color is closest in luminance to `color`.
+    pub fn nearest(&self, color: Color) -> Option<&Tile> {
+        let lum = luminance(color);
+        let i = self.0.partition_point(|(_, c)| luminance(*c) < lum);
+        let below = i.checked_sub(1).and_then(|i| self.0.get(i));
+        match (below, self.0.get(i)) {
+            (Some(a), Some(b)) => {
+                if lum - luminance(a.1) <= luminance(b.1) - lum {
+                    Some(&a.0)
+                } else {
+                    Some(&b.0)
+                }
+            }
+            (a, b) => a.or(b).map(|(tile, _)| tile),
+        }
+    }
+}
+
+/// The relative luminance of a color.
+fn luminance(color: Color) -> f32 {
+    0.2126 * color.r() + 0.7152 * color.g() + 0.0722 * color.b()
+}
+
+impl Terminal {
+    /// Draw an image as ascii art.
+    ///
+    /// The image is downsampled to `size` tiles, with the top left of the image
+    /// drawn at the top left of the area. Each tile is taken from `palette` based
+    /// on the luminance of the corresponding pixel.
+    pub fn put_image_ascii(
+        &mut self,
+        image: &DynamicImage,
+        xy: [i32; 2],
+        size: [u32; 2],
+        palette: &AsciiPalette,
+    ) {
+        let [width, height] = size;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let pixels = image
+            .resize_exact(width, height, FilterType::Triangle)
+            .to_rgba8();
+
+        for (x, y, pixel) in pixels.enumerate_pixels() {
+            // Image rows go from top to bottom, terminal rows from bottom to top
+            let pos = [xy[0] + x as i32, xy[1] + (height - 1 - y) as i32];
+            if !self.is_in_bounds(pos) {
+                continue;
+            }
+
+            let [r, g, b, a] = pixel.0;
+            let color = Color::rgba_u8(r, g, b, a);
+            if let Some(tile) = palette.nearest(color) {
+                self.put_tile(pos, *tile);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayImage, Luma};
+
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        let tile = |glyph| Tile {
+            glyph,
+            ..Default::default()
+        };
+        let palette = AsciiPalette::new(vec![
+            (tile('#'), Color::WHITE),
+            (tile('?'), Color::rgb(f32::NAN, 0.0, 0.0)),
+            (tile(' '), Color::BLACK),
+            (tile('.'), Color::GRAY),
+        ]);
+        let glyphs: Vec<_> = palette.entries().iter().map(|(t, _)| t.glyph).collect();
+        assert_eq!(vec![' ', '.', '#', '?'], glyphs);
+        assert_eq!(Some(&tile('.')), palette.nearest(Color::rgb(0.4, 0.4, 0.4)));
+    }
+
+    #[test]
+    fn gradient() {
+        let charset = b" .:-=+*#%@";
+        let palette = AsciiPalette::from_charset(charset);
+
+        let gradient = GrayImage::from_fn(64, 8, |x, _| Luma([(x * 4) as u8]));
+        let image = DynamicImage::ImageLuma8(gradient);
+
+        let mut term = Terminal::with_size([16, 2]);
+        term.put_image_ascii(&image, [0, 0], [16, 2], &palette);
+
+        let density = |c: char| {
+            let i = code_page_437::glyph_to_index(c);
+            charset.iter().position(|c| *c == i).unwrap()
+        };
+
+        for y in 0..2 {
+            let row: Vec<_> = term.row_iter(y).map(|t| density(t.glyph)).collect();
+            assert!(row.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(0, row[0]);
+            assert!(row[15] >= charset.len() - 2);
+        }
+    }
+}
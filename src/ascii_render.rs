@@ -0,0 +1,110 @@
+//! Rasterizing simple shapes into terminal tiles.
+//!
+//! Each function returns a list of positions paired with tiles which can be
+//! written to a terminal with [Terminal::apply_tiles](crate::Terminal::apply_tiles).
+//!
+//! # Example
+//!
+//! ```
+//! use bevy_ascii_terminal::*;
+//! use bevy_ascii_terminal::ascii_render::*;
+//!
+//! let mut term = Terminal::with_size([20, 20]);
+//! let tile = Tile { glyph: 'o', ..Default::default() };
+//!
+//! term.apply_tiles(rasterize_circle([10, 10], 5, tile));
+//! term.apply_tiles(rasterize_rect([2, 2], [4, 3], tile, true));
+//! ```
+
+use crate::Tile;
+
+/// The outline of a circle, using the midpoint circle algorithm.
+pub fn rasterize_circle(center: [i32; 2], radius: u32, tile: Tile) -> Vec<([i32; 2], Tile)> {
+    let [cx, cy] = center;
+    let mut points = Vec::new();
+
+    let mut x = radius as i32;
+    let mut y = 0;
+    let mut err = 1 - x;
+    while x >= y {
+        for [px, py] in [
+            [x, y],
+            [y, x],
+            [-y, x],
+            [-x, y],
+            [-x, -y],
+            [-y, -x],
+            [y, -x],
+            [x, -y],
+        ] {
+            points.push([cx + px, cy + py]);
+        }
+
+        y += 1;
+        if err < 0 {
+            err += 2 * y + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y - x) + 1;
+        }
+    }
+
+    // Octants overlap along their edges
+    points.sort_unstable();
+    points.dedup();
+    points.into_iter().map(|p| (p, tile)).collect()
+}
+
+/// A rectangle with it's bottom left corner at `xy`, either filled or as an outline.
+pub fn rasterize_rect(
+    xy: [i32; 2],
+    size: [u32; 2],
+    tile: Tile,
+    filled: bool,
+) -> Vec<([i32; 2], Tile)> {
+    let [x, y] = xy;
+    let [width, height] = [size[0] as i32, size[1] as i32];
+
+    let mut points = Vec::new();
+    for py in y..y + height {
+        for px in x..x + width {
+            let edge = px == x || px == x + width - 1 || py == y || py == y + height - 1;
+            if filled || edge {
+                points.push(([px, py], tile));
+            }
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle() {
+        let points = rasterize_circle([0, 0], 5, Tile::default());
+        for ([x, y], _) in points.iter() {
+            let d = ((x * x + y * y) as f32).sqrt();
+            assert!((d - 5.0).abs() < 1.0);
+        }
+        assert!(points.iter().any(|(p, _)| *p == [5, 0]));
+        assert!(points.iter().any(|(p, _)| *p == [0, -5]));
+
+        let single = rasterize_circle([3, 3], 0, Tile::default());
+        assert_eq!(1, single.len());
+    }
+
+    #[test]
+    fn rect() {
+        assert_eq!(
+            12,
+            rasterize_rect([0, 0], [4, 3], Tile::default(), true).len()
+        );
+        assert_eq!(
+            10,
+            rasterize_rect([0, 0], [4, 3], Tile::default(), false).len()
+        );
+        assert!(rasterize_rect([0, 0], [0, 3], Tile::default(), true).is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+//! An editor for painting the glyphs of a font texture in-engine.
+
+use bevy::prelude::*;
+
+use crate::{
+    code_page_437,
+    picking::{TerminalMouseDragEvent, TerminalMousePickEvent},
+    renderer::{
+        font_asset::FontError,
+        material::{font_tile_size, override_glyph},
+    },
+    BuiltInFontHandles, Terminal, Tile,
+};
+
+/// System label for the glyph atlas editor input function.
+pub const TERMINAL_ATLAS_EDITOR_INPUT: &str = "terminal_atlas_editor_input";
+
+/// Plugin which handles input and drawing for every [TerminalGlyphAtlasEditor].
+///
+/// Clicking a glyph in the atlas selects it. Clicking or dragging in the
+/// zoomed view paints the selected glyph with the brush, and `Ctrl+Z` undoes
+/// the most recent stroke.
+pub struct TerminalGlyphAtlasEditorPlugin;
+
+impl Plugin for TerminalGlyphAtlasEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_glyph_atlas_editor_input.label(TERMINAL_ATLAS_EDITOR_INPUT))
+            .add_system(terminal_glyph_atlas_editor_draw.after(TERMINAL_ATLAS_EDITOR_INPUT));
+    }
+}
+
+/// The pixels of a glyph before a stroke, so the stroke can be undone.
+#[derive(Debug, Clone)]
+struct GlyphSnapshot {
+    glyph: usize,
+    pixels: Vec<u8>,
+}
+
+/// Terminal component which draws a font's glyph atlas and a zoomed view of
+/// the selected glyph, which can be painted with the mouse.
+///
+/// The font is found in [BuiltInFontHandles] by `font_name` and edited with
+/// [override_glyph], so every terminal using it shows the changes. The atlas
+/// is drawn with the editor terminal's own font, so set the terminal's
+/// material to the edited font to see them in the atlas too.
+///
+/// The atlas is drawn as 16x16 glyphs in the bottom left of the terminal, laid
+/// out as in the font texture. The zoomed view is drawn to it's right, with
+/// each pixel of the glyph drawn as `zoom` x `zoom` tiles.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn setup(mut commands: Commands) {
+///     let editor = TerminalGlyphAtlasEditor::new("px437_8x8.png")
+///         .with_brush_color([255, 0, 0, 255])
+///         .with_zoom(2);
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([34, 17]))
+///         .insert(editor);
+/// }
+///
+/// fn build(app: &mut App) {
+///     app.add_plugin(TerminalGlyphAtlasEditorPlugin)
+///         .add_startup_system(setup);
+/// }
+/// ```
+#[derive(Component, Debug, Clone)]
+pub struct TerminalGlyphAtlasEditor {
+    /// The name of the edited font in [BuiltInFontHandles].
+    pub font_name: String,
+    /// The index of the glyph being painted.
+    pub selected_glyph: usize,
+    /// The rgba color painted by the brush.
+    pub brush_color: [u8; 4],
+    /// The width and height of the brush in pixels.
+    pub brush_size: u32,
+    /// The width and height in tiles of each pixel in the zoomed view.
+    pub zoom: u32,
+    /// The maximum number of strokes which can be undone.
+    pub max_undo: usize,
+    undo_stack: Vec<GlyphSnapshot>,
+}
+
+impl TerminalGlyphAtlasEditor {
+    pub fn new(font_name: &str) -> Self {
+        Self {
+            font_name: font_name.to_string(),
+            selected_glyph: 0,
+            brush_color: [255; 4],
+            brush_size: 1,
+            zoom: 1,
+            max_undo: 100,
+            undo_stack: Vec::new(),
+        }
+    }
+
+    pub fn with_brush_color(mut self, color: [u8; 4]) -> Self {
+        self.brush_color = color;
+        self
+    }
+
+    pub fn with_brush_size(mut self, size: u32) -> Self {
+        self.brush_size = size;
+        self
+    }
+
+    pub fn with_zoom(mut self, zoom: u32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// The number of strokes which can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Draw the atlas and the zoomed view of the selected glyph.
+    pub fn draw(&self, terminal: &mut Terminal, font: &Image) {
+        terminal.clear();
+        for i in 0..256 {
+            let xy = [(i % 16) as i32, 15 - (i / 16) as i32];
+            let selected = i == self.selected_glyph;
+            let tile = Tile {
+                glyph: code_page_437::index_to_glyph(i as u8),
+                fg_color: if selected { Color::BLACK } else { Color::WHITE },
+                bg_color: if selected { Color::WHITE } else { Color::BLACK },
+                ..Default::default()
+            };
+            put(terminal, xy, tile);
+        }
+
+        let pixels = match glyph_pixels(font, self.selected_glyph) {
+            Some(pixels) => pixels,
+            None => return,
+        };
+        let tile_size = font_tile_size(font);
+        let zoom = self.zoom.max(1);
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let px = i as u32 % tile_size.x;
+            let py = tile_size.y - 1 - i as u32 / tile_size.x;
+            let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            let tile = Tile {
+                glyph: ' ',
+                bg_color: Color::rgba_u8(r, g, b, a),
+                ..Default::default()
+            };
+            for zy in 0..zoom {
+                for zx in 0..zoom {
+                    let x = ZOOM_VIEW_X + (px * zoom + zx) as i32;
+                    put(terminal, [x, (py * zoom + zy) as i32], tile);
+                }
+            }
+        }
+    }
+
+    /// Handle a click at a terminal position. Clicking the atlas selects a
+    /// glyph and clicking the zoomed view starts a new stroke.
+    ///
+    /// Returns true if the editor or the font changed.
+    pub fn click(&mut self, xy: [i32; 2], font: &mut Image) -> bool {
+        let [x, y] = xy;
+        if (0..16).contains(&x) && (0..16).contains(&y) {
+            let glyph = ((15 - y) * 16 + x) as usize;
+            let changed = glyph != self.selected_glyph;
+            self.selected_glyph = glyph;
+            return changed;
+        }
+        if self.zoom_pixel(xy, font).is_none() {
+            return false;
+        }
+        if let Some(pixels) = glyph_pixels(font, self.selected_glyph) {
+            self.undo_stack.push(GlyphSnapshot {
+                glyph: self.selected_glyph,
+                pixels,
+            });
+            if self.undo_stack.len() > self.max_undo {
+                self.undo_stack.remove(0);
+            }
+        }
+        self.drag(xy, font)
+    }
+
+    /// Paint at a terminal position in the zoomed view, continuing the
+    /// current stroke.
+    ///
+    /// Returns true if the font changed.
+    pub fn drag(&mut self, xy: [i32; 2], font: &mut Image) -> bool {
+        match self.zoom_pixel(xy, font) {
+            Some(pixel) => self.paint(font, pixel).unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// Paint the brush onto the selected glyph with it's top left corner at
+    /// the given pixel of the glyph.
+    ///
+    /// Returns true if any pixels changed.
+    pub fn paint(&self, font: &mut Image, pixel: UVec2) -> Result<bool, FontError> {
+        let tile_size = font_tile_size(font);
+        let mut pixels =
+            glyph_pixels(font, self.selected_glyph).ok_or(FontError::UnsupportedFormat)?;
+        let max = (pixel + UVec2::splat(self.brush_size.max(1))).min(tile_size);
+        let mut changed = false;
+        for y in pixel.y..max.y {
+            for x in pixel.x..max.x {
+                let i = ((y * tile_size.x + x) * 4) as usize;
+                changed |= pixels[i..i + 4] != self.brush_color;
+                pixels[i..i + 4].copy_from_slice(&self.brush_color);
+            }
+        }
+        if changed {
+            override_glyph(font, self.selected_glyph, &pixels, tile_size.x, tile_size.y)?;
+        }
+        Ok(changed)
+    }
+
+    /// Restore the glyph painted by the most recent stroke.
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self, font: &mut Image) -> bool {
+        let snapshot = match self.undo_stack.pop() {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+        let tile_size = font_tile_size(font);
+        override_glyph(
+            font,
+            snapshot.glyph,
+            &snapshot.pixels,
+            tile_size.x,
+            tile_size.y,
+        )
+        .is_ok()
+    }
+
+    /// The pixel of the selected glyph drawn at a terminal position in the
+    /// zoomed view, from the top left of the glyph.
+    fn zoom_pixel(&self, xy: [i32; 2], font: &Image) -> Option<UVec2> {
+        let tile_size = font_tile_size(font).as_ivec2();
+        let zoom = self.zoom.max(1) as i32;
+        let local = IVec2::new(xy[0] - ZOOM_VIEW_X, xy[1]);
+        let pixel = IVec2::new(local.x.div_euclid(zoom), local.y.div_euclid(zoom));
+        if pixel.cmplt(IVec2::ZERO).any() || pixel.cmpge(tile_size).any() {
+            return None;
+        }
+        Some(UVec2::new(
+            pixel.x as u32,
+            (tile_size.y - 1 - pixel.y) as u32,
+        ))
+    }
+}
+
+/// The terminal column where the zoomed view starts, one column right of
+/// the atlas.
+const ZOOM_VIEW_X: i32 = 17;
+
+/// The rgba pixels of a glyph in a code page 437 font texture, row by row
+/// from the top.
+fn glyph_pixels(font: &Image, index: usize) -> Option<Vec<u8>> {
+    let size = font.texture_descriptor.size;
+    let image_row_len = size.width as usize * 4;
+    if index >= 256 || font.data.len() != image_row_len * size.height as usize {
+        return None;
+    }
+    let tile_size = font_tile_size(font);
+    let row_len = tile_size.x as usize * 4;
+    let x = (index % 16) * tile_size.x as usize;
+    let y = (index / 16) * tile_size.y as usize;
+    let mut pixels = Vec::with_capacity(row_len * tile_size.y as usize);
+    for row in 0..tile_size.y as usize {
+        let start = (y + row) * image_row_len + x * 4;
+        pixels.extend_from_slice(&font.data[start..start + row_len]);
+    }
+    Some(pixels)
+}
+
+fn put(terminal: &mut Terminal, xy: [i32; 2], tile: Tile) {
+    if terminal.is_in_bounds(xy) {
+        terminal.put_tile(xy, tile);
+    }
+}
+
+fn terminal_glyph_atlas_editor_input(
+    mut picks: EventReader<TerminalMousePickEvent>,
+    mut drags: EventReader<TerminalMouseDragEvent>,
+    keys: Res<Input<KeyCode>>,
+    fonts: Res<BuiltInFontHandles>,
+    mut images: ResMut<Assets<Image>>,
+    mut q: Query<(Entity, &mut TerminalGlyphAtlasEditor)>,
+) {
+    let undo = keys.just_pressed(KeyCode::Z)
+        && (keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl));
+    let picks: Vec<_> = picks
+        .iter()
+        .filter(|p| p.button == MouseButton::Left)
+        .collect();
+    let drags: Vec<_> = drags
+        .iter()
+        .filter(|d| d.button == MouseButton::Left)
+        .collect();
+
+    for (entity, mut editor) in q.iter_mut() {
+        let font = match fonts.get(&editor.font_name).and_then(|h| images.get_mut(h)) {
+            Some(font) => font,
+            None => continue,
+        };
+        if undo {
+            editor.undo(font);
+        }
+        let mut clicked = false;
+        for pick in picks.iter().filter(|p| p.entity == entity) {
+            clicked = true;
+            editor.click(pick.xy, font);
+        }
+        // The frame a button is pressed is handled by the click
+        if clicked {
+            continue;
+        }
+        for drag in drags.iter().filter(|d| d.entity == entity) {
+            editor.drag(drag.xy, font);
+        }
+    }
+}
+
+fn terminal_glyph_atlas_editor_draw(
+    fonts: Res<BuiltInFontHandles>,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut q: Query<(
+        &TerminalGlyphAtlasEditor,
+        &mut Terminal,
+        ChangeTrackers<TerminalGlyphAtlasEditor>,
+    )>,
+) {
+    let changed_images: Vec<_> = image_events
+        .iter()
+        .filter_map(|e| match e {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (editor, mut term, tracker) in q.iter_mut() {
+        let handle = match fonts.get(&editor.font_name) {
+            Some(handle) => handle,
+            None => continue,
+        };
+        if !tracker.is_changed() && !changed_images.contains(&handle) {
+            continue;
+        }
+        if let Some(font) = images.get(handle) {
+            editor.draw(&mut term, font);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    #[test]
+    fn paint_and_undo() {
+        // A font of 2x2 pixel glyphs
+        let mut font = Image::new_fill(
+            Extent3d {
+                width: 32,
+                height: 32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let red = [255, 0, 0, 255];
+        let mut editor = TerminalGlyphAtlasEditor::new("font")
+            .with_brush_color(red)
+            .with_zoom(2);
+
+        // Glyph 17 is the second glyph of the second row
+        assert!(editor.click([1, 14], &mut font));
+        assert_eq!(17, editor.selected_glyph);
+
+        // The top left pixel is drawn at the top left 2x2 tiles of the view
+        assert!(editor.click([ZOOM_VIEW_X + 1, 3], &mut font));
+        let pixels = glyph_pixels(&font, 17).unwrap();
+        assert_eq!(red, pixels[0..4]);
+        assert_eq!([0; 12], pixels[4..]);
+        assert_eq!(red, font.data[(2 * 32 + 2) * 4..(2 * 32 + 3) * 4]);
+
+        assert!(editor.drag([ZOOM_VIEW_X + 3, 0], &mut font));
+        assert_eq!(red, glyph_pixels(&font, 17).unwrap()[12..16]);
+        assert!(!editor.drag([ZOOM_VIEW_X + 4, 0], &mut font));
+
+        let mut term = Terminal::with_size([21, 16]);
+        editor.draw(&mut term, &font);
+        assert_eq!('☺', term.get_char([1, 15]));
+        assert_eq!(Color::WHITE, term.get_tile([1, 14]).bg_color);
+        assert_eq!(Color::RED, term.get_tile([ZOOM_VIEW_X, 3]).bg_color);
+        assert_eq!(Color::NONE, term.get_tile([ZOOM_VIEW_X, 0]).bg_color);
+
+        // Both the click and the drag were a single stroke
+        assert_eq!(1, editor.undo_len());
+        assert!(editor.undo(&mut font));
+        assert_eq!([0; 16], glyph_pixels(&font, 17).unwrap()[..]);
+        assert!(!editor.undo(&mut font));
+    }
+}
@@ -0,0 +1,163 @@
+//! Keeping a terminal sized to fit the window.
+
+use bevy::prelude::*;
+
+use crate::{
+    renderer::{
+        material::{font_tile_size, TerminalMaterial},
+        plugin::terminal_tile_size,
+        TileScaling,
+    },
+    Terminal,
+};
+
+/// How a [TerminalAutoResize] terminal reacts to the window changing size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResizeStrategy {
+    /// Resize the terminal to as many tiles as fit in the window, based on the
+    /// pixel size of the terminal's font.
+    FitWindow,
+    /// Keep the terminal at a fixed number of tiles and zoom the terminal's
+    /// [camera](TerminalAutoResize::camera) so the terminal fits the window.
+    ///
+    /// The camera's projection should use [ScalingMode::WindowSize](bevy::render::camera::ScalingMode::WindowSize),
+    /// the default for 2d cameras.
+    FixedTileCount(UVec2),
+}
+
+impl ResizeStrategy {
+    /// The terminal size in tiles for a window of the given logical size and a
+    /// font with the given tile size in pixels.
+    pub fn terminal_size(&self, window_size: Vec2, tile_pixels: UVec2) -> UVec2 {
+        match self {
+            ResizeStrategy::FitWindow => (window_size / tile_pixels.max(UVec2::ONE).as_vec2())
+                .floor()
+                .as_uvec2()
+                .max(UVec2::ONE),
+            ResizeStrategy::FixedTileCount(size) => *size,
+        }
+    }
+}
+
+/// Resizes a terminal when the primary window changes size.
+///
+/// A [TerminalResizedEvent] is sent whenever the terminal's size is changed.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TerminalAutoResize {
+    pub strategy: ResizeStrategy,
+    /// The camera zoomed by [ResizeStrategy::FixedTileCount]. If this is `None`
+    /// no camera is zoomed.
+    pub camera: Option<Entity>,
+}
+
+impl TerminalAutoResize {
+    pub fn new(strategy: ResizeStrategy) -> Self {
+        Self {
+            strategy,
+            camera: None,
+        }
+    }
+
+    /// Set the camera zoomed by [ResizeStrategy::FixedTileCount].
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+/// Event sent when a [TerminalAutoResize] terminal is resized, with it's new
+/// size in tiles.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalResizedEvent(pub Entity, pub UVec2);
+
+/// The camera scale which fits an area of the given world size in a window.
+fn fit_scale(world_size: Vec2, window_size: Vec2) -> f32 {
+    let scale = world_size / window_size.max(Vec2::ONE);
+    scale.x.max(scale.y)
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn terminal_auto_resize(
+    windows: Res<Windows>,
+    images: Res<Assets<Image>>,
+    materials: Res<Assets<TerminalMaterial>>,
+    mut terminals: Query<(
+        Entity,
+        &mut Terminal,
+        &TerminalAutoResize,
+        &Handle<TerminalMaterial>,
+        &TileScaling,
+    )>,
+    mut cameras: Query<&mut OrthographicProjection, With<Camera>>,
+    mut events: EventWriter<TerminalResizedEvent>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+
+    for (entity, mut term, auto_resize, material, scaling) in terminals.iter_mut() {
+        let material = match materials.get(material) {
+            Some(material) => material,
+            None => continue,
+        };
+        let image = match material.texture.as_ref().and_then(|t| images.get(t)) {
+            Some(image) => image,
+            None => continue,
+        };
+
+        let size = auto_resize
+            .strategy
+            .terminal_size(window_size, font_tile_size(image));
+        if size != term.size() {
+            term.resize(size.into());
+            events.send(TerminalResizedEvent(entity, size));
+        }
+
+        if let ResizeStrategy::FixedTileCount(_) = auto_resize.strategy {
+            let mut projection = match auto_resize.camera.map(|c| cameras.get_mut(c)) {
+                Some(Ok(projection)) => projection,
+                _ => continue,
+            };
+            let world_size = size.as_vec2() * terminal_tile_size(material, scaling, &images);
+            let scale = fit_scale(world_size, window_size);
+            if projection.scale != scale {
+                projection.scale = scale;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategies() {
+        let fit = ResizeStrategy::FitWindow;
+        assert_eq!(
+            UVec2::new(80, 37),
+            fit.terminal_size(Vec2::new(640.0, 300.0), UVec2::new(8, 8))
+        );
+        assert_eq!(
+            UVec2::new(1, 1),
+            fit.terminal_size(Vec2::new(4.0, 0.0), UVec2::new(8, 8))
+        );
+
+        let fixed = ResizeStrategy::FixedTileCount(UVec2::new(40, 20));
+        assert_eq!(
+            UVec2::new(40, 20),
+            fixed.terminal_size(Vec2::new(640.0, 300.0), UVec2::new(8, 8))
+        );
+        // The axis which needs the most zooming out wins
+        assert_eq!(
+            160.0 / 300.0,
+            fit_scale(Vec2::new(320.0, 160.0), Vec2::new(640.0, 300.0))
+        );
+        assert_eq!(
+            2.0,
+            fit_scale(Vec2::new(320.0, 160.0), Vec2::new(160.0, 160.0))
+        );
+    }
+}
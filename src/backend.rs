@@ -0,0 +1,258 @@
+//! Drawing terminals without a gpu, for example to a Linux VTY.
+//!
+//! Only available with the `terminal_backend` feature. A backend is selected
+//! with [TerminalPlugin::with_backend](crate::TerminalPlugin::with_backend),
+//! which replaces the gpu renderer. Every terminal which changes is written
+//! to the backend at the end of the frame.
+//!
+//! Terminals are written at their [TerminalBackendPosition], so several
+//! terminals can share an output as long as they don't overlap.
+
+use std::{
+    io::{self, Stdout, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    renderer::font_asset::{TerminalFontAsset, TerminalFontMetrics},
+    terminal_color256, Terminal,
+};
+
+/// A platform output which terminals are written to instead of being rendered
+/// by the gpu.
+pub trait TerminalPlatformBackend: Send + Sync + 'static {
+    /// Write the whole terminal to the output, including any drawn layers.
+    ///
+    /// `position` is the top left of the terminal in the output, in tiles. `font`
+    /// describes the glyphs for backends which draw pixels. Backends which draw
+    /// text, like [VtyBackend], can ignore it.
+    fn write_terminal(
+        &mut self,
+        terminal: &Terminal,
+        position: UVec2,
+        font: &TerminalFontAsset,
+    ) -> io::Result<()>;
+}
+
+/// Where a terminal is written in a backend's output, in tiles from the top
+/// left. Terminals without one are written at the top left.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalBackendPosition(pub UVec2);
+
+/// A backend which draws terminals with ANSI escape codes, for text consoles
+/// such as a Linux VTY.
+///
+/// Colors are mapped to the nearest color of the 256 color palette, see
+/// [terminal_color256]. Glyphs are written as their unicode characters, so
+/// the console's font decides how they look.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::backend::VtyBackend;
+///
+/// let plugin = TerminalPlugin::new().with_backend(VtyBackend::stdout());
+/// ```
+pub struct VtyBackend<W: Write> {
+    writer: W,
+}
+
+impl VtyBackend<Stdout> {
+    /// A backend which writes to the standard output.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write> VtyBackend<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// The output the backend writes to.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write + Send + Sync + 'static> TerminalPlatformBackend for VtyBackend<W> {
+    fn write_terminal(
+        &mut self,
+        terminal: &Terminal,
+        position: UVec2,
+        _font: &TerminalFontAsset,
+    ) -> io::Result<()> {
+        let tiles = terminal.composited_tiles();
+        let width = terminal.width() as usize;
+        let mut out = String::new();
+        let mut colors = None;
+        // Rows are written from the top of the terminal. Console lines and
+        // columns start at 1.
+        for (row, y) in (0..terminal.height() as usize).rev().enumerate() {
+            out.push_str(&format!(
+                "\x1b[{};{}H",
+                position.y as usize + row + 1,
+                position.x + 1
+            ));
+            for tile in &tiles[y * width..(y + 1) * width] {
+                let fg = terminal_color256::nearest(tile.fg_color);
+                let bg = terminal_color256::nearest(tile.bg_color);
+                if colors != Some((fg, bg)) {
+                    out.push_str(&format!("\x1b[38;5;{}m\x1b[48;5;{}m", fg, bg));
+                    colors = Some((fg, bg));
+                }
+                out.push(tile.glyph);
+            }
+        }
+        out.push_str("\x1b[0m");
+        self.writer.write_all(out.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Resource holding the backend selected with
+/// [TerminalPlugin::with_backend](crate::TerminalPlugin::with_backend), and
+/// the font passed to it.
+pub struct TerminalBackend {
+    pub backend: Arc<Mutex<dyn TerminalPlatformBackend>>,
+    /// Defaults to an 8x8 code page 437 font with no image.
+    pub font: TerminalFontAsset,
+}
+
+impl TerminalBackend {
+    pub fn new(backend: Arc<Mutex<dyn TerminalPlatformBackend>>) -> Self {
+        Self {
+            backend,
+            font: default_font(),
+        }
+    }
+}
+
+/// An 8x8 code page 437 font with no image.
+fn default_font() -> TerminalFontAsset {
+    TerminalFontAsset {
+        image: Handle::default(),
+        tile_count: UVec2::new(16, 16),
+        tile_size: UVec2::new(8, 8),
+        clip_color: Color::BLACK,
+        metrics: TerminalFontMetrics::from_tile_height(8),
+        sdf_mode: false,
+        kerning: 0,
+        pixel_density_hint: 1.0,
+    }
+}
+
+/// Write every changed or moved terminal to the backend.
+#[allow(clippy::type_complexity)]
+pub(crate) fn terminal_backend_write(
+    backend: Res<TerminalBackend>,
+    q: Query<
+        (&Terminal, Option<&TerminalBackendPosition>),
+        Or<(Changed<Terminal>, Changed<TerminalBackendPosition>)>,
+    >,
+) {
+    for (term, position) in q.iter() {
+        let position = position.copied().unwrap_or_default().0;
+        let result = match backend.backend.lock() {
+            Ok(mut output) => output.write_terminal(term, position, &backend.font),
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            warn!("Failed to write terminal to backend: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin, input::InputPlugin, window::WindowPlugin};
+
+    use super::*;
+    use crate::{TerminalBundle, TerminalPlugin, Tile};
+
+    /// A writer which can be read from after it's moved into the app.
+    #[derive(Clone, Default)]
+    struct MockWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for MockWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn vty() {
+        let mut term = Terminal::with_size([2, 2]);
+        let red = Tile {
+            glyph: 'a',
+            fg_color: Color::RED,
+            bg_color: Color::BLACK,
+            ..Default::default()
+        };
+        term.put_tile([0, 1], red);
+        term.put_tile([1, 1], Tile { glyph: 'b', ..red });
+        term.put_tile(
+            [0, 0],
+            Tile {
+                glyph: 'c',
+                ..Default::default()
+            },
+        );
+
+        let mut backend = VtyBackend::new(Vec::new());
+        backend
+            .write_terminal(&term, UVec2::ZERO, &default_font())
+            .unwrap();
+        let out = String::from_utf8(backend.writer().clone()).unwrap();
+        assert_eq!(
+            "\x1b[1;1H\x1b[38;5;196m\x1b[48;5;0mab\x1b[2;1H\x1b[38;5;15m\x1b[48;5;0mc \x1b[0m",
+            out
+        );
+
+        // Layers are drawn over the terminal, and the position moves the rows
+        term.draw_layer(1, |layer| layer.put_char([1, 0], 'd'));
+        let mut backend = VtyBackend::new(Vec::new());
+        backend
+            .write_terminal(&term, UVec2::new(4, 2), &default_font())
+            .unwrap();
+        let out = String::from_utf8(backend.writer().clone()).unwrap();
+        assert_eq!(
+            "\x1b[3;5H\x1b[38;5;196m\x1b[48;5;0mab\x1b[4;5H\x1b[38;5;15m\x1b[48;5;0mcd\x1b[0m",
+            out
+        );
+
+        // The gpu renderer is replaced by the backend
+        let writer = MockWriter::default();
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(InputPlugin)
+            .add_plugin(TerminalPlugin::new().with_backend(VtyBackend::new(writer.clone())));
+        app.world
+            .spawn()
+            .insert_bundle(TerminalBundle::new().with_size([3, 1]));
+        app.world
+            .spawn()
+            .insert_bundle(TerminalBundle::new().with_size([3, 1]))
+            .insert(TerminalBackendPosition(UVec2::new(0, 1)));
+        app.update();
+        assert!(app.world.get_resource::<TerminalBackend>().is_some());
+        let out = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("\x1b[1;1H"));
+        assert!(out.contains("\x1b[2;1H"));
+        assert!(out.ends_with("   \x1b[0m"));
+
+        // Unchanged terminals aren't written again
+        writer.0.lock().unwrap().clear();
+        app.update();
+        assert!(writer.0.lock().unwrap().is_empty());
+    }
+}
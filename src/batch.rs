@@ -0,0 +1,146 @@
+//! Collecting draw commands to apply to a terminal all at once.
+
+use crate::{ascii_render::rasterize_rect, Terminal, Tile};
+
+/// A single command recorded by a [TerminalBatchDrawer].
+#[derive(Clone, Debug)]
+pub enum DrawCommand {
+    /// See [Terminal::put_char].
+    PutChar { xy: [i32; 2], glyph: char },
+    /// See [Terminal::put_string].
+    PutString { xy: [i32; 2], string: String },
+    /// See [Terminal::put_tile].
+    PutTile { xy: [i32; 2], tile: Tile },
+    /// Fill a rectangle with it's bottom left corner at `xy` with a tile. Any
+    /// part of the rectangle outside the terminal is skipped.
+    FillRect {
+        xy: [i32; 2],
+        size: [u32; 2],
+        tile: Tile,
+    },
+}
+
+/// A list of draw commands which can be built up anywhere, then written to a
+/// terminal in one go with [Terminal::apply_batch].
+///
+/// Since the terminal is only borrowed once, change detection for the terminal
+/// is only triggered once for the whole batch.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+///
+/// let mut batch = TerminalBatchDrawer::new();
+/// batch.fill_rect([0, 0], [10, 3], Tile { glyph: '.', ..Default::default() });
+/// batch.put_string([1, 1], "Hello");
+///
+/// let mut term = Terminal::with_size([10, 3]);
+/// term.apply_batch(batch);
+/// assert_eq!(".Hello....", term.get_string([0, 1], 10));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerminalBatchDrawer {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl TerminalBatchDrawer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put_char(&mut self, xy: [i32; 2], glyph: char) {
+        self.commands.push(DrawCommand::PutChar { xy, glyph });
+    }
+
+    pub fn put_string(&mut self, xy: [i32; 2], string: impl Into<String>) {
+        self.commands.push(DrawCommand::PutString {
+            xy,
+            string: string.into(),
+        });
+    }
+
+    pub fn put_tile(&mut self, xy: [i32; 2], tile: Tile) {
+        self.commands.push(DrawCommand::PutTile { xy, tile });
+    }
+
+    pub fn fill_rect(&mut self, xy: [i32; 2], size: [u32; 2], tile: Tile) {
+        self.commands.push(DrawCommand::FillRect { xy, size, tile });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+impl Terminal {
+    /// Apply every command in a batch.
+    ///
+    /// All [DrawCommand::FillRect] commands are applied first, in the order they
+    /// were added, followed by the remaining commands in order. A fill will never
+    /// overwrite a tile written by another command in the same batch.
+    pub fn apply_batch(&mut self, batch: TerminalBatchDrawer) {
+        let (fills, writes): (Vec<_>, Vec<_>) = batch
+            .commands
+            .into_iter()
+            .partition(|c| matches!(c, DrawCommand::FillRect { .. }));
+
+        for command in fills.into_iter().chain(writes) {
+            match command {
+                DrawCommand::PutChar { xy, glyph } => self.put_char(xy, glyph),
+                DrawCommand::PutString { xy, string } => self.put_string(xy, &string),
+                DrawCommand::PutTile { xy, tile } => self.put_tile(xy, tile),
+                DrawCommand::FillRect { xy, size, tile } => {
+                    self.apply_tiles(rasterize_rect(xy, size, tile, true))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Color;
+
+    use super::*;
+
+    #[test]
+    fn matches_individual_commands() {
+        let fill = Tile {
+            glyph: '#',
+            bg_color: Color::BLUE,
+            ..Default::default()
+        };
+        let tile = Tile {
+            glyph: '@',
+            fg_color: Color::RED,
+            ..Default::default()
+        };
+
+        let mut expected = Terminal::with_size([12, 6]);
+        expected.apply_tiles(rasterize_rect([0, 0], [12, 6], fill, true));
+        expected.apply_tiles(rasterize_rect([8, 3], [10, 10], fill, true));
+        expected.put_string([1, 1], "batch");
+        expected.put_char([2, 4], 'x');
+        expected.put_tile([3, 4], tile);
+
+        // Fills are hoisted before the other commands
+        let mut batch = TerminalBatchDrawer::new();
+        batch.put_string([1, 1], "batch");
+        batch.fill_rect([0, 0], [12, 6], fill);
+        batch.put_char([2, 4], 'x');
+        batch.put_tile([3, 4], tile);
+        batch.fill_rect([8, 3], [10, 10], fill);
+
+        let mut term = Terminal::with_size([12, 6]);
+        term.apply_batch(batch);
+
+        assert_eq!(expected.tiles.slice(..), term.tiles.slice(..));
+        assert_eq!("batch", term.get_string([1, 1], 5));
+        assert_eq!(Color::BLUE, term.get_tile([1, 1]).bg_color);
+    }
+}
@@ -0,0 +1,108 @@
+//! Blending new tile colors with the colors already in the terminal.
+
+use bevy::prelude::*;
+
+use crate::{code_page_437, Terminal};
+
+/// How [Terminal::put_char_blend] combines a tile's existing colors with new ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileBlendMode {
+    /// Overwrite the existing colors, the same as [Terminal::put_char_formatted].
+    Replace,
+    /// Add the new colors to the existing ones, clamped to 1.
+    Additive,
+    /// Multiply the existing colors by the new ones.
+    Multiply,
+    /// Blend from the existing colors towards the new ones by the given amount,
+    /// where 0 keeps the existing colors and 1 replaces them.
+    AlphaOver(f32),
+}
+
+impl TileBlendMode {
+    /// Blend a new color with an existing one.
+    pub fn blend(&self, existing: Color, new: Color) -> Color {
+        let a = Vec4::from(existing.as_rgba_f32());
+        let b = Vec4::from(new.as_rgba_f32());
+        let blended = match *self {
+            TileBlendMode::Replace => return new,
+            TileBlendMode::Additive => (a + b).min(Vec4::ONE),
+            TileBlendMode::Multiply => a * b,
+            TileBlendMode::AlphaOver(t) => a.lerp(b, t.clamp(0.0, 1.0)),
+        };
+        Color::from(blended)
+    }
+}
+
+impl Terminal {
+    /// Insert a character, blending the given colors with the tile's existing
+    /// colors.
+    ///
+    /// `glyph` is a code page 437 index and always replaces the existing glyph.
+    /// Colors are blended in srgb space, see [TileBlendMode] for the available
+    /// modes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let mut term = Terminal::with_size([10, 10]);
+    /// term.put_bg_color([5, 5], Color::BLUE);
+    /// // A faint red flash over the existing background
+    /// term.put_char_blend([5, 5], b'*', Color::WHITE, Color::RED, TileBlendMode::AlphaOver(0.25));
+    /// ```
+    pub fn put_char_blend(
+        &mut self,
+        xy: [i32; 2],
+        glyph: u8,
+        fg: Color,
+        bg: Color,
+        blend: TileBlendMode,
+    ) {
+        let tile = self.get_tile_mut(xy);
+        tile.glyph = code_page_437::index_to_glyph(glyph);
+        tile.fg_color = blend.blend(tile.fg_color, fg);
+        tile.bg_color = blend.blend(tile.bg_color, bg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blend_modes() {
+        let existing = Color::rgba(0.5, 0.25, 1.0, 1.0);
+        let new = Color::rgba(1.0, 0.5, 0.0, 0.5);
+        let blend = |mode: TileBlendMode| {
+            let mut term = Terminal::with_size([2, 2]);
+            term.put_fg_color([1, 1], existing);
+            term.put_bg_color([1, 1], existing);
+            term.put_char_blend([1, 1], b'A', new, new, mode);
+            assert_eq!('A', term.get_char([1, 1]));
+            let tile = term.get_tile([1, 1]);
+            assert_eq!(tile.fg_color, tile.bg_color);
+            tile.fg_color
+        };
+
+        assert_eq!(new, blend(TileBlendMode::Replace));
+        assert_eq!(
+            Color::rgba(1.0, 0.75, 1.0, 1.0),
+            blend(TileBlendMode::Additive)
+        );
+        assert_eq!(
+            Color::rgba(0.5, 0.125, 0.0, 0.5),
+            blend(TileBlendMode::Multiply)
+        );
+        assert_eq!(existing, blend(TileBlendMode::AlphaOver(0.0)));
+        assert_eq!(new, blend(TileBlendMode::AlphaOver(1.0)));
+        assert_eq!(
+            Color::rgba(0.75, 0.375, 0.5, 0.75),
+            blend(TileBlendMode::AlphaOver(0.5))
+        );
+        // Amounts outside 0 to 1 are clamped
+        assert_eq!(existing, blend(TileBlendMode::AlphaOver(-1.0)));
+        assert_eq!(new, blend(TileBlendMode::AlphaOver(2.0)));
+    }
+}
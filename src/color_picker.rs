@@ -0,0 +1,312 @@
+//! An HSV color picker widget.
+
+use bevy::prelude::*;
+
+use crate::{picking::TerminalMousePickEvent, Terminal, Tile};
+
+/// Plugin which updates any [TerminalColorPicker] on a terminal entity when
+/// it's clicked with the left mouse button.
+///
+/// The picker is redrawn and a [ColorChangedEvent] is sent whenever the color
+/// changes.
+pub struct TerminalColorPickerPlugin;
+
+impl Plugin for TerminalColorPickerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ColorChangedEvent>()
+            .add_system(terminal_color_picker_input);
+    }
+}
+
+/// Event sent when the color of a [TerminalColorPicker] is changed by a click.
+#[derive(Debug, Clone, Copy)]
+pub struct ColorChangedEvent {
+    /// The entity with the [TerminalColorPicker].
+    pub entity: Entity,
+    pub color: Color,
+}
+
+/// A color picker drawn as a hue bar with a saturation/value square to its right.
+///
+/// The hue bar runs from red at the top through the full hue range to the
+/// bottom. In the square saturation increases to the right and value increases
+/// upwards.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([20, 10]);
+/// let mut picker = TerminalColorPicker::new(UVec2::new(1, 1), Color::RED);
+///
+/// picker.draw(&mut term);
+/// // Click the top right of the saturation/value square
+/// picker.pick([11, 8]);
+/// assert_eq!(Color::RED, picker.current_color);
+/// ```
+#[derive(Component, Clone)]
+pub struct TerminalColorPicker {
+    /// The bottom left of the picker in the terminal.
+    pub pos: UVec2,
+    pub current_color: Color,
+    /// The width of the hue bar in tiles.
+    pub hue_bar_width: u32,
+    /// The width and height of the saturation/value square in tiles. This is
+    /// also the height of the hue bar.
+    pub sv_square_size: u32,
+    /// The hsv the current color was last set from. This keeps the hue and
+    /// saturation stable when they can't be recovered from the color itself.
+    hsv: [f32; 3],
+}
+
+impl Default for TerminalColorPicker {
+    fn default() -> Self {
+        Self::new(UVec2::ZERO, Color::WHITE)
+    }
+}
+
+impl TerminalColorPicker {
+    pub fn new(pos: UVec2, color: Color) -> Self {
+        Self {
+            pos,
+            current_color: color,
+            hue_bar_width: 2,
+            sv_square_size: 8,
+            hsv: color_to_hsv(color),
+        }
+    }
+
+    pub fn with_hue_bar_width(mut self, width: u32) -> Self {
+        self.hue_bar_width = width;
+        self
+    }
+
+    pub fn with_sv_square_size(mut self, size: u32) -> Self {
+        self.sv_square_size = size;
+        self
+    }
+
+    /// The size of the whole picker in tiles.
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(
+            self.hue_bar_width + 1 + self.sv_square_size,
+            self.sv_square_size,
+        )
+    }
+
+    /// Draw the picker to the terminal.
+    pub fn draw(&self, terminal: &mut Terminal) {
+        let [h, s, v] = self.hsv();
+        let size = self.sv_square_size;
+        let origin = self.pos.as_ivec2();
+
+        for row in 0..size {
+            let hue = self.row_hue(row);
+            let y = origin.y + (size - 1 - row) as i32;
+            let selected = (hue - h).abs() < 0.5 * 360.0 / size as f32;
+            for x in 0..self.hue_bar_width {
+                let glyph = if selected && x == 0 { '►' } else { ' ' };
+                let tile = Tile {
+                    glyph,
+                    fg_color: Color::WHITE,
+                    bg_color: hsv_to_color(hue, 1.0, 1.0),
+                    ..Default::default()
+                };
+                put(terminal, [origin.x + x as i32, y], tile);
+            }
+        }
+
+        let sv_x = origin.x + self.hue_bar_width as i32 + 1;
+        let (sel_x, sel_y) = (self.step(s), self.step(v));
+        for y in 0..size {
+            for x in 0..size {
+                let selected = x == sel_x && y == sel_y;
+                let tile = Tile {
+                    glyph: if selected { 'o' } else { ' ' },
+                    fg_color: if v > 0.5 { Color::BLACK } else { Color::WHITE },
+                    bg_color: hsv_to_color(h, self.fraction(x), self.fraction(y)),
+                    ..Default::default()
+                };
+                put(terminal, [sv_x + x as i32, origin.y + y as i32], tile);
+            }
+        }
+    }
+
+    /// Update the current color from a click at the given terminal position.
+    ///
+    /// Returns true if the color changed.
+    pub fn pick(&mut self, xy: [i32; 2]) -> bool {
+        let size = self.sv_square_size as i32;
+        let local = IVec2::from(xy) - self.pos.as_ivec2();
+        if size == 0 || local.y < 0 || local.y >= size {
+            return false;
+        }
+
+        let [mut h, mut s, mut v] = self.hsv();
+        let sv_x = local.x - self.hue_bar_width as i32 - 1;
+        if local.x >= 0 && local.x < self.hue_bar_width as i32 {
+            h = self.row_hue((size - 1 - local.y) as u32);
+        } else if sv_x >= 0 && sv_x < size {
+            s = self.fraction(sv_x as u32);
+            v = self.fraction(local.y as u32);
+        } else {
+            return false;
+        }
+
+        let color = hsv_to_color(h, s, v);
+        self.hsv = [h, s, v];
+        let changed = color != self.current_color;
+        self.current_color = color;
+        changed
+    }
+
+    /// Set the current color.
+    pub fn set_color(&mut self, color: Color) {
+        self.current_color = color;
+        self.hsv = color_to_hsv(color);
+    }
+
+    fn hsv(&self) -> [f32; 3] {
+        let [h, s, v] = self.hsv;
+        if hsv_to_color(h, s, v) == self.current_color {
+            self.hsv
+        } else {
+            color_to_hsv(self.current_color)
+        }
+    }
+
+    fn row_hue(&self, row: u32) -> f32 {
+        row as f32 * 360.0 / self.sv_square_size.max(1) as f32
+    }
+
+    fn fraction(&self, i: u32) -> f32 {
+        i as f32 / self.sv_square_size.saturating_sub(1).max(1) as f32
+    }
+
+    fn step(&self, fraction: f32) -> u32 {
+        (fraction * self.sv_square_size.saturating_sub(1) as f32).round() as u32
+    }
+}
+
+fn put(terminal: &mut Terminal, xy: [i32; 2], tile: Tile) {
+    if terminal.is_in_bounds(xy) {
+        terminal.put_tile(xy, tile);
+    }
+}
+
+fn hsv_to_color(h: f32, s: f32, v: f32) -> Color {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    Color::rgb(r + m, g + m, b + m)
+}
+
+fn color_to_hsv(color: Color) -> [f32; 3] {
+    let [r, g, b, _] = color.as_rgba_f32();
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    [h, s, max]
+}
+
+fn terminal_color_picker_input(
+    mut picks: EventReader<TerminalMousePickEvent>,
+    mut events: EventWriter<ColorChangedEvent>,
+    mut q: Query<(&mut Terminal, &mut TerminalColorPicker)>,
+) {
+    for pick in picks.iter() {
+        if pick.button != MouseButton::Left {
+            continue;
+        }
+        if let Ok((mut terminal, mut picker)) = q.get_mut(pick.entity) {
+            if picker.pick(pick.xy) {
+                picker.draw(&mut terminal);
+                events.send(ColorChangedEvent {
+                    entity: pick.entity,
+                    color: picker.current_color,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_round_trip() {
+        for color in [
+            Color::RED,
+            Color::GREEN,
+            Color::BLUE,
+            Color::rgb(0.2, 0.4, 0.6),
+        ] {
+            let [h, s, v] = color_to_hsv(color);
+            let [r, g, b, _] = hsv_to_color(h, s, v).as_rgba_f32();
+            let [er, eg, eb, _] = color.as_rgba_f32();
+            assert!((r - er).abs() < 1e-5 && (g - eg).abs() < 1e-5 && (b - eb).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn pick() {
+        let mut picker = TerminalColorPicker::new(UVec2::new(0, 0), Color::RED)
+            .with_hue_bar_width(1)
+            .with_sv_square_size(4);
+
+        // Bottom left of the square is black
+        assert!(picker.pick([2, 0]));
+        assert_eq!(Color::BLACK, picker.current_color);
+
+        // The hue is kept while the color is black
+        assert!(picker.pick([5, 3]));
+        assert_eq!(Color::RED, picker.current_color);
+
+        // Second row from the top of the hue bar
+        assert!(picker.pick([0, 2]));
+        assert_eq!(90.0, color_to_hsv(picker.current_color)[0].round());
+
+        // The gap between the bar and square, and outside the picker
+        assert!(!picker.pick([1, 2]));
+        assert!(!picker.pick([6, 2]));
+        assert!(!picker.pick([2, 4]));
+    }
+
+    #[test]
+    fn draw() {
+        let mut term = Terminal::with_size([10, 5]);
+        let picker = TerminalColorPicker::new(UVec2::new(0, 1), Color::RED)
+            .with_hue_bar_width(1)
+            .with_sv_square_size(4);
+        picker.draw(&mut term);
+
+        assert_eq!('►', term.get_char([0, 4]));
+        assert_eq!(Color::RED, term.get_tile([0, 4]).bg_color);
+        assert_eq!('o', term.get_char([5, 4]));
+        assert_eq!(Color::RED, term.get_tile([5, 4]).bg_color);
+        assert_eq!(Color::BLACK, term.get_tile([2, 1]).bg_color);
+    }
+}
@@ -0,0 +1,131 @@
+//! Named colors for theming terminals.
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// A resource mapping names to colors, so colors can be changed in one place
+/// rather than being hard coded throughout an application.
+///
+/// Every built-in scheme defines `"ui_fg"`, `"ui_bg"`, `"danger"`, `"success"`,
+/// `"warning"` and `"accent"`.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn draw(scheme: Res<TerminalColorScheme>, mut q: Query<&mut Terminal>) {
+///     for mut term in q.iter_mut() {
+///         let format = CharFormat::new(scheme.get("danger"), scheme.get("ui_bg"));
+///         term.put_char_formatted([0, 0], '!', format);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TerminalColorScheme {
+    pub colors: HashMap<&'static str, Color>,
+}
+
+impl Default for TerminalColorScheme {
+    fn default() -> Self {
+        Self::from_colors([
+            ("ui_fg", Color::WHITE),
+            ("ui_bg", Color::BLACK),
+            ("danger", Color::RED),
+            ("success", Color::GREEN),
+            ("warning", Color::YELLOW),
+            ("accent", Color::CYAN),
+        ])
+    }
+}
+
+impl TerminalColorScheme {
+    fn from_colors(colors: impl IntoIterator<Item = (&'static str, Color)>) -> Self {
+        Self {
+            colors: colors.into_iter().collect(),
+        }
+    }
+
+    /// The [Dracula](https://draculatheme.com) color scheme.
+    pub fn dracula() -> Self {
+        Self::from_colors([
+            ("ui_fg", Color::rgb_u8(0xf8, 0xf8, 0xf2)),
+            ("ui_bg", Color::rgb_u8(0x28, 0x2a, 0x36)),
+            ("danger", Color::rgb_u8(0xff, 0x55, 0x55)),
+            ("success", Color::rgb_u8(0x50, 0xfa, 0x7b)),
+            ("warning", Color::rgb_u8(0xf1, 0xfa, 0x8c)),
+            ("accent", Color::rgb_u8(0xbd, 0x93, 0xf9)),
+        ])
+    }
+
+    /// The [Solarized](https://ethanschoonover.com/solarized/) dark color scheme.
+    pub fn solarized_dark() -> Self {
+        Self::from_colors([
+            ("ui_fg", Color::rgb_u8(0x83, 0x94, 0x96)),
+            ("ui_bg", Color::rgb_u8(0x00, 0x2b, 0x36)),
+            ("danger", Color::rgb_u8(0xdc, 0x32, 0x2f)),
+            ("success", Color::rgb_u8(0x85, 0x99, 0x00)),
+            ("warning", Color::rgb_u8(0xb5, 0x89, 0x00)),
+            ("accent", Color::rgb_u8(0x26, 0x8b, 0xd2)),
+        ])
+    }
+
+    /// The Monokai color scheme.
+    pub fn monokai() -> Self {
+        Self::from_colors([
+            ("ui_fg", Color::rgb_u8(0xf8, 0xf8, 0xf2)),
+            ("ui_bg", Color::rgb_u8(0x27, 0x28, 0x22)),
+            ("danger", Color::rgb_u8(0xf9, 0x26, 0x72)),
+            ("success", Color::rgb_u8(0xa6, 0xe2, 0x2e)),
+            ("warning", Color::rgb_u8(0xe6, 0xdb, 0x74)),
+            ("accent", Color::rgb_u8(0x66, 0xd9, 0xef)),
+        ])
+    }
+
+    /// Retrieve a color by name.
+    ///
+    /// Returns white if no color with the given name exists, see
+    /// [TerminalColorScheme::try_get] to check for missing colors.
+    pub fn get(&self, key: &str) -> Color {
+        self.try_get(key).unwrap_or(Color::WHITE)
+    }
+
+    /// Retrieve a color by name, or `None` if it doesn't exist.
+    pub fn try_get(&self, key: &str) -> Option<Color> {
+        self.colors.get(key).copied()
+    }
+
+    /// Set the color for the given name, adding it if it doesn't exist.
+    pub fn set(&mut self, key: &'static str, color: Color) {
+        self.colors.insert(key, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_keys() {
+        for scheme in [
+            TerminalColorScheme::default(),
+            TerminalColorScheme::dracula(),
+            TerminalColorScheme::solarized_dark(),
+            TerminalColorScheme::monokai(),
+        ] {
+            for key in ["ui_fg", "ui_bg", "danger", "success", "warning", "accent"] {
+                assert!(scheme.try_get(key).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn get_set() {
+        let mut scheme = TerminalColorScheme::default();
+        assert_eq!(None, scheme.try_get("water"));
+        assert_eq!(Color::WHITE, scheme.get("water"));
+
+        scheme.set("water", Color::BLUE);
+        assert_eq!(Color::BLUE, scheme.get("water"));
+    }
+}
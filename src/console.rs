@@ -0,0 +1,368 @@
+//! A developer console drawn to a terminal.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::Terminal;
+
+/// System label for the console input function.
+pub const TERMINAL_CONSOLE_INPUT: &str = "terminal_console_input";
+
+/// A console command. It's given the words following the command's name and
+/// the world, through which it can spawn entities and change resources.
+pub type ConsoleCommandHandler = Box<dyn Fn(&[&str], &mut World) + Send + Sync>;
+
+/// Plugin which handles input and drawing for every [TerminalConsole].
+///
+/// The toggle key, backtick by default, shows and hides every console. While a
+/// console is visible typed text goes to it's input line, `Enter` runs the
+/// command, `Backspace` deletes and the up and down arrows browse the command
+/// history.
+pub struct TerminalConsolePlugin {
+    pub toggle_key: KeyCode,
+}
+
+impl Default for TerminalConsolePlugin {
+    fn default() -> Self {
+        Self {
+            toggle_key: KeyCode::Grave,
+        }
+    }
+}
+
+impl TerminalConsolePlugin {
+    /// Set the key which shows and hides the console.
+    pub fn with_toggle_key(mut self, key: KeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+}
+
+impl Plugin for TerminalConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TerminalConsoleToggleKey(self.toggle_key))
+            .add_system(terminal_console_input.label(TERMINAL_CONSOLE_INPUT))
+            .add_system(terminal_console_draw.after(TERMINAL_CONSOLE_INPUT))
+            .add_system(terminal_console_dispatch.exclusive_system().at_end());
+    }
+}
+
+/// Resource holding the key which shows and hides every [TerminalConsole].
+pub struct TerminalConsoleToggleKey(pub KeyCode);
+
+/// Terminal component which turns the terminal into a developer console.
+///
+/// The terminal is hidden while the console isn't visible. The console draws
+/// over the whole terminal, with the input line on the bottom row and the
+/// most recent output above it, so it's usually spawned as a separate terminal
+/// drawn in front of the game.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// struct Player(Vec2);
+///
+/// fn setup(mut commands: Commands) {
+///     let mut console = TerminalConsole::new();
+///     console.register_command("tp", |args, world| {
+///         if let [x, y] = args {
+///             if let (Ok(x), Ok(y)) = (x.parse(), y.parse()) {
+///                 world.insert_resource(Player(Vec2::new(x, y)));
+///             }
+///         }
+///     });
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([60, 10]))
+///         .insert(console);
+/// }
+///
+/// fn build(app: &mut App) {
+///     app.add_plugin(TerminalConsolePlugin::default())
+///         .add_startup_system(setup);
+/// }
+/// ```
+#[derive(Component)]
+pub struct TerminalConsole {
+    /// The text being typed.
+    pub input_line: String,
+    /// Previously run commands, oldest first.
+    pub history: VecDeque<String>,
+    pub command_handlers: HashMap<String, ConsoleCommandHandler>,
+    pub visible: bool,
+    /// Lines printed to the console, oldest first.
+    pub output: VecDeque<String>,
+    /// The maximum number of lines kept in both the history and the output.
+    pub max_lines: usize,
+    /// The index in `history` being browsed, if any.
+    history_index: Option<usize>,
+    /// Commands submitted but not yet run.
+    pending: Vec<String>,
+}
+
+impl Default for TerminalConsole {
+    fn default() -> Self {
+        Self {
+            input_line: String::new(),
+            history: VecDeque::new(),
+            command_handlers: HashMap::default(),
+            visible: false,
+            output: VecDeque::new(),
+            max_lines: 100,
+            history_index: None,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl TerminalConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a command, replacing any existing command with the same name.
+    pub fn register_command(
+        &mut self,
+        name: &str,
+        handler: impl Fn(&[&str], &mut World) + Send + Sync + 'static,
+    ) {
+        self.command_handlers
+            .insert(name.to_string(), Box::new(handler));
+    }
+
+    /// Add a line to the console's output.
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.output.push_back(line.into());
+        while self.output.len() > self.max_lines {
+            self.output.pop_front();
+        }
+    }
+
+    /// Run the input line as a command and clear it.
+    ///
+    /// The command is run at the end of the frame, when the world is
+    /// available to it's handler.
+    pub fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input_line);
+        self.history_index = None;
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.print(format!("> {}", line));
+        if self.history.back().map(|s| s.as_str()) != Some(line) {
+            self.history.push_back(line.to_string());
+            while self.history.len() > self.max_lines {
+                self.history.pop_front();
+            }
+        }
+        self.pending.push(line.to_string());
+    }
+
+    /// Replace the input line with the previous command in the history.
+    pub fn history_prev(&mut self) {
+        let index = match self.history_index {
+            Some(i) => i.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input_line = self.history[index].clone();
+    }
+
+    /// Replace the input line with the next command in the history, or clear
+    /// it after the most recent command.
+    pub fn history_next(&mut self) {
+        let index = match self.history_index {
+            Some(i) => i + 1,
+            None => return,
+        };
+        match self.history.get(index) {
+            Some(line) => {
+                self.input_line = line.clone();
+                self.history_index = Some(index);
+            }
+            None => {
+                self.input_line.clear();
+                self.history_index = None;
+            }
+        }
+    }
+
+    /// Draw the console over the whole terminal.
+    pub fn draw(&self, terminal: &mut Terminal) {
+        terminal.clear();
+        let width = terminal.width() as usize;
+        let input = format!("> {}_", self.input_line);
+        // Keep the end of a long input line in view
+        let skip = input.chars().count().saturating_sub(width);
+        let input: String = input.chars().skip(skip).collect();
+        terminal.put_string([0, 0], &input);
+
+        for (y, line) in (1..terminal.height() as i32).zip(self.output.iter().rev()) {
+            let line: String = line.chars().take(width).collect();
+            terminal.put_string([0, y], &line);
+        }
+    }
+}
+
+fn terminal_console_input(
+    toggle: Res<TerminalConsoleToggleKey>,
+    keys: Res<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut q: Query<&mut TerminalConsole>,
+) {
+    let toggled = keys.just_pressed(toggle.0);
+    let typed: String = chars
+        .iter()
+        .map(|c| c.char)
+        .filter(|c| !c.is_control())
+        .collect();
+    for mut console in q.iter_mut() {
+        if toggled {
+            console.visible = !console.visible;
+            // The toggle key's character isn't typed into the console
+            continue;
+        }
+        if !console.visible {
+            continue;
+        }
+        if !typed.is_empty() {
+            console.input_line.push_str(&typed);
+        }
+        if keys.just_pressed(KeyCode::Back) {
+            console.input_line.pop();
+        }
+        if keys.just_pressed(KeyCode::Up) {
+            console.history_prev();
+        }
+        if keys.just_pressed(KeyCode::Down) {
+            console.history_next();
+        }
+        if keys.just_pressed(KeyCode::Return) {
+            console.submit();
+        }
+    }
+}
+
+fn terminal_console_draw(
+    mut q: Query<
+        (&TerminalConsole, &mut Terminal, Option<&mut Visibility>),
+        Changed<TerminalConsole>,
+    >,
+) {
+    for (console, mut term, visibility) in q.iter_mut() {
+        if let Some(mut visibility) = visibility {
+            if visibility.is_visible != console.visible {
+                visibility.is_visible = console.visible;
+            }
+        }
+        if console.visible {
+            console.draw(&mut term);
+        }
+    }
+}
+
+/// Run submitted commands with access to the world.
+fn terminal_console_dispatch(world: &mut World) {
+    let mut q = world.query::<(Entity, &mut TerminalConsole)>();
+    let pending: Vec<_> = q
+        .iter_mut(world)
+        .filter(|(_, console)| !console.pending.is_empty())
+        .map(|(entity, mut console)| {
+            let lines = std::mem::take(&mut console.pending);
+            // Taken so the handlers can borrow the world
+            let handlers = std::mem::take(&mut console.command_handlers);
+            (entity, lines, handlers)
+        })
+        .collect();
+
+    for (entity, lines, handlers) in pending {
+        let mut unknown = Vec::new();
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let Some((name, args)) = words.split_first() {
+                match handlers.get(*name) {
+                    Some(handler) => handler(args, world),
+                    None => unknown.push(format!("Unknown command: {}", name)),
+                }
+            }
+        }
+
+        if let Some(mut console) = world.get_mut::<TerminalConsole>(entity) {
+            // Commands registered by a handler replace the old ones
+            for (name, handler) in handlers {
+                console.command_handlers.entry(name).or_insert(handler);
+            }
+            for line in unknown {
+                console.print(line);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Teleported(Vec<String>);
+
+    #[test]
+    fn commands() {
+        let mut app = App::new();
+        app.add_plugin(TerminalConsolePlugin::default())
+            .init_resource::<Input<KeyCode>>()
+            .add_event::<ReceivedCharacter>();
+
+        let mut console = TerminalConsole::new();
+        console.register_command("tp", |args, world| {
+            let args = args.iter().map(|s| s.to_string()).collect();
+            world.insert_resource(Teleported(args));
+        });
+        console.input_line = "tp 3 4".to_string();
+        console.submit();
+        console.input_line = "jump".to_string();
+        console.submit();
+        let entity = app
+            .world
+            .spawn()
+            .insert(console)
+            .insert(Terminal::with_size([20, 4]))
+            .id();
+        app.update();
+
+        let teleported = app.world.get_resource::<Teleported>().unwrap();
+        assert_eq!(vec!["3", "4"], teleported.0);
+        let console = app.world.get::<TerminalConsole>(entity).unwrap();
+        assert!(console.command_handlers.contains_key("tp"));
+        assert_eq!(
+            vec!["> tp 3 4", "> jump", "Unknown command: jump"],
+            console.output.iter().collect::<Vec<_>>()
+        );
+
+        let mut console = app.world.get_mut::<TerminalConsole>(entity).unwrap();
+        console.history_prev();
+        assert_eq!("jump", console.input_line);
+        console.history_prev();
+        console.history_prev();
+        assert_eq!("tp 3 4", console.input_line);
+        console.history_next();
+        assert_eq!("jump", console.input_line);
+        console.history_next();
+        assert_eq!("", console.input_line);
+
+        // The toggle key shows the console, which is drawn on the next update
+        let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+        keys.press(KeyCode::Grave);
+        app.update();
+        let console = app.world.get::<TerminalConsole>(entity).unwrap();
+        assert!(console.visible);
+        let term = app.world.get::<Terminal>(entity).unwrap();
+        assert_eq!("> _", term.get_string([0, 0], 3));
+        assert_eq!("Unknown command: jum", term.get_string([0, 1], 20));
+        assert_eq!("> jump", term.get_string([0, 2], 6));
+    }
+}
@@ -0,0 +1,289 @@
+//! Showing log output in a terminal.
+//!
+//! Only available with the `console_log` feature.
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    log::{Level, LogSettings},
+    prelude::*,
+    utils::tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    },
+};
+use tracing_subscriber::{layer::Context, prelude::*, EnvFilter, Layer, Registry};
+
+use crate::Terminal;
+
+/// System label for the function moving logged lines into the [TerminalLog].
+pub const TERMINAL_CONSOLE_LOG_FLUSH: &str = "terminal_console_log_flush";
+
+/// Plugin which shows log output in the [TerminalLog] of a terminal entity.
+///
+/// This replaces bevy's `LogPlugin`, which must be disabled. Output is
+/// still printed to the console and filtered by the [LogSettings] resource or
+/// the `RUST_LOG` environment variable, as it is with the `LogPlugin`.
+///
+/// # Example
+///
+/// ```no_run
+/// use bevy::{log::LogPlugin, prelude::*};
+/// use bevy_ascii_terminal::*;
+///
+/// let mut app = App::new();
+/// let entity = app
+///     .world
+///     .spawn()
+///     .insert_bundle(TerminalBundle::new().with_size([60, 10]))
+///     .id();
+/// app.add_plugins_with(DefaultPlugins, |group| group.disable::<LogPlugin>())
+///     .add_plugin(TerminalPlugin::default())
+///     .add_plugin(TerminalConsoleLogPlugin {
+///         entity,
+///         max_lines: 100,
+///     });
+/// ```
+pub struct TerminalConsoleLogPlugin {
+    /// The terminal entity log lines are written to.
+    pub entity: Entity,
+    /// The maximum number of lines kept by the [TerminalLog].
+    pub max_lines: usize,
+}
+
+impl Plugin for TerminalConsoleLogPlugin {
+    fn build(&self, app: &mut App) {
+        let default_filter = {
+            let settings = app.world.get_resource_or_insert_with(LogSettings::default);
+            format!("{},{}", settings.level, settings.filter)
+        };
+        let buffer = TerminalLogBuffer::default();
+        let filter_layer = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(&default_filter))
+            .unwrap();
+        let subscriber = Registry::default()
+            .with(filter_layer)
+            .with(tracing_subscriber::fmt::Layer::default())
+            .with(TerminalLogLayer(buffer.clone()));
+
+        // Fails if bevy's LogPlugin has already set up logging
+        if tracing_log::LogTracer::init().is_err()
+            || bevy::utils::tracing::subscriber::set_global_default(subscriber).is_err()
+        {
+            warn!("Could not set the global tracing subscriber, log output won't be shown in the terminal. Please disable LogPlugin from bevy's DefaultPlugins");
+        }
+
+        app.insert_resource(buffer)
+            .insert_resource(TerminalConsoleLogTarget {
+                entity: self.entity,
+                max_lines: self.max_lines,
+            })
+            .add_system(terminal_console_log_flush.label(TERMINAL_CONSOLE_LOG_FLUSH))
+            .add_system(terminal_console_log_draw.after(TERMINAL_CONSOLE_LOG_FLUSH));
+    }
+}
+
+/// Terminal component holding log lines, which are drawn over the whole
+/// terminal with the most recent line at the bottom.
+#[derive(Component, Default)]
+pub struct TerminalLog {
+    /// Logged lines and their levels, oldest first.
+    pub lines: VecDeque<(Level, String)>,
+    /// The maximum number of lines kept.
+    pub max_lines: usize,
+}
+
+impl TerminalLog {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines,
+        }
+    }
+
+    /// Add a line, removing the oldest lines past the maximum.
+    pub fn push(&mut self, level: Level, line: impl Into<String>) {
+        self.lines.push_back((level, line.into()));
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    /// The color lines of a log level are drawn with.
+    pub fn level_color(level: Level) -> Color {
+        match level {
+            Level::ERROR => Color::RED,
+            Level::WARN => Color::YELLOW,
+            Level::INFO => Color::WHITE,
+            Level::DEBUG => Color::GRAY,
+            _ => Color::DARK_GRAY,
+        }
+    }
+
+    /// Draw the log over the whole terminal.
+    pub fn draw(&self, terminal: &mut Terminal) {
+        terminal.clear();
+        let width = terminal.width() as usize;
+        for (y, (level, line)) in (0..terminal.height() as i32).zip(self.lines.iter().rev()) {
+            let color = Self::level_color(*level);
+            for (x, c) in (0..).zip(line.chars().take(width)) {
+                let tile = terminal.get_tile_mut([x, y]);
+                tile.glyph = c;
+                tile.fg_color = color;
+            }
+        }
+    }
+}
+
+/// Resource holding the entity and line limit of the [TerminalConsoleLogPlugin].
+struct TerminalConsoleLogTarget {
+    entity: Entity,
+    max_lines: usize,
+}
+
+/// Lines logged since the last frame, shared with the [TerminalLogLayer].
+#[derive(Clone, Default)]
+struct TerminalLogBuffer(Arc<Mutex<Vec<(Level, String)>>>);
+
+/// A tracing layer which formats each event as a line in the [TerminalLogBuffer].
+struct TerminalLogLayer(TerminalLogBuffer);
+
+impl<S: Subscriber> Layer<S> for TerminalLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {}: ",
+            metadata.level(),
+            metadata.module_path().unwrap_or_else(|| metadata.target())
+        );
+        event.record(&mut LineVisitor(&mut line));
+        if let Ok(mut lines) = (self.0).0.lock() {
+            lines.push((*metadata.level(), line));
+        }
+    }
+}
+
+/// Writes an event's message followed by it's other fields as `name=value`.
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.ends_with(": ") {
+            self.0.push(' ');
+        }
+        let _ = match field.name() {
+            "message" => write!(self.0, "{:?}", value),
+            name => write!(self.0, "{}={:?}", name, value),
+        };
+    }
+}
+
+/// Move buffered lines into the target's [TerminalLog], adding the component
+/// if it's missing.
+///
+/// While the target entity doesn't exist only the most recent lines, up to the
+/// line limit, are kept.
+fn terminal_console_log_flush(
+    mut commands: Commands,
+    buffer: Res<TerminalLogBuffer>,
+    target: Res<TerminalConsoleLogTarget>,
+    mut q: Query<Option<&mut TerminalLog>>,
+) {
+    match q.get_mut(target.entity) {
+        Ok(Some(mut log)) => {
+            let lines = match buffer.0.lock() {
+                Ok(mut lines) => std::mem::take(&mut *lines),
+                Err(_) => return,
+            };
+            for (level, line) in lines {
+                log.push(level, line);
+            }
+        }
+        // Lines are kept until the next frame, when the log has been added
+        Ok(None) => {
+            commands
+                .entity(target.entity)
+                .insert(TerminalLog::new(target.max_lines));
+        }
+        Err(_) => {
+            if let Ok(mut lines) = buffer.0.lock() {
+                let excess = lines.len().saturating_sub(target.max_lines);
+                lines.drain(..excess);
+            }
+        }
+    }
+}
+
+fn terminal_console_log_draw(mut q: Query<(&TerminalLog, &mut Terminal), Changed<TerminalLog>>) {
+    for (log, mut term) in q.iter_mut() {
+        log.draw(&mut term);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::tracing::subscriber::with_default;
+
+    use super::*;
+
+    #[test]
+    fn log_to_terminal() {
+        let buffer = TerminalLogBuffer::default();
+        let subscriber = Registry::default().with(TerminalLogLayer(buffer.clone()));
+        with_default(subscriber, || {
+            info!("hello");
+            warn!(count = 3, "careful");
+            error!("boom");
+        });
+
+        let mut app = App::new();
+        let entity = app.world.spawn().insert(Terminal::with_size([40, 4])).id();
+        app.insert_resource(buffer)
+            .insert_resource(TerminalConsoleLogTarget {
+                entity,
+                max_lines: 2,
+            })
+            .add_system(terminal_console_log_flush.label(TERMINAL_CONSOLE_LOG_FLUSH))
+            .add_system(terminal_console_log_draw.after(TERMINAL_CONSOLE_LOG_FLUSH));
+        // The log is added on the first update and filled on the second
+        app.update();
+        app.update();
+
+        let log = app.world.get::<TerminalLog>(entity).unwrap();
+        let path = module_path!();
+        assert_eq!(
+            vec![
+                (Level::WARN, format!("WARN {}: careful count=3", path)),
+                (Level::ERROR, format!("ERROR {}: boom", path)),
+            ],
+            log.lines.iter().cloned().collect::<Vec<_>>()
+        );
+
+        let term = app.world.get::<Terminal>(entity).unwrap();
+        assert_eq!("ERROR", term.get_string([0, 0], 5));
+        assert_eq!(Color::RED, term.get_tile([0, 0]).fg_color);
+        assert_eq!("WARN", term.get_string([0, 1], 4));
+        assert_eq!(Color::YELLOW, term.get_tile([0, 1]).fg_color);
+        assert_eq!(Color::GRAY, TerminalLog::level_color(Level::DEBUG));
+
+        // Lines for a missing terminal are capped at the line limit
+        app.world.despawn(entity);
+        let buffer = app.world.resource::<TerminalLogBuffer>().clone();
+        with_default(
+            Registry::default().with(TerminalLogLayer(buffer.clone())),
+            || {
+                for i in 0..10 {
+                    info!("line {}", i);
+                }
+            },
+        );
+        app.update();
+        let lines = buffer.0.lock().unwrap();
+        assert_eq!(2, lines.len());
+        assert!(lines[1].1.ends_with("line 9"));
+    }
+}
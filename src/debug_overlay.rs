@@ -0,0 +1,412 @@
+//! A debug overlay for inspecting how terminals are rendered.
+//!
+//! The overlay draws the bounds of every terminal mesh, an outline around
+//! each tile quad, and optionally the code page 437 index of each tile's glyph.
+//! It can be toggled at runtime via [TerminalDebugOverlay::toggle_key] (`F3` by default).
+//!
+//! [Terminal::draw_debug_grid] can be used to check a font's glyph layout.
+//!
+//! Only available with the `debug_overlay` feature and only compiled into debug builds.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ascii_terminal::*;
+//! use bevy_ascii_terminal::debug_overlay::*;
+//!
+//! fn main() {
+//!     App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugin(TerminalPlugin::default())
+//!     .add_plugin(TerminalDebugOverlayPlugin)
+//!     .run();
+//! }
+//! ```
+
+use bevy::{
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+    sprite::{ColorMesh2dBundle, Mesh2dHandle},
+    text::{HorizontalAlign, VerticalAlign},
+};
+
+use crate::{
+    code_page_437,
+    renderer::{
+        font_asset::TerminalFontAsset, renderer_vertex_data::TerminalRendererVertexData,
+        uv_mapping::UvMapping, TERMINAL_UPDATE_MESH,
+    },
+    CharFormat, StringFormat, Terminal,
+};
+
+/// The font size glyph indices are rasterized at before being scaled down to
+/// fit inside a tile.
+const INDEX_FONT_SIZE: f32 = 32.0;
+
+/// The width of a cell's contents in a debug grid, enough for a uv coordinate
+/// like `.062`.
+const GRID_CELL_WIDTH: u32 = 4;
+
+/// Plugin which draws a [TerminalDebugOverlay] on top of every terminal.
+pub struct TerminalDebugOverlayPlugin;
+
+impl Plugin for TerminalDebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerminalDebugOverlay>()
+            .add_system(toggle_debug_overlay)
+            .add_system(update_debug_overlay.after(TERMINAL_UPDATE_MESH));
+    }
+}
+
+/// A resource controlling the debug overlay.
+pub struct TerminalDebugOverlay {
+    /// Whether or not the overlay is currently drawn. Defaults to false.
+    pub visible: bool,
+    /// The key which toggles the overlay. Defaults to `F3`.
+    pub toggle_key: KeyCode,
+    /// The font used to draw glyph indices. Glyph indices will not be drawn
+    /// if this is `None`.
+    pub index_font: Option<Handle<Font>>,
+    /// The color of the terminal bounds.
+    pub bounds_color: Color,
+    /// The color of the tile quad outlines.
+    pub tile_color: Color,
+    /// The color of the glyph indices.
+    pub index_color: Color,
+}
+
+impl Default for TerminalDebugOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            toggle_key: KeyCode::F3,
+            index_font: None,
+            bounds_color: Color::RED,
+            tile_color: Color::rgba(0.0, 1.0, 0.0, 0.5),
+            index_color: Color::YELLOW,
+        }
+    }
+}
+
+/// Points to the root entity of a terminal's debug overlay.
+#[derive(Component)]
+struct DebugOverlayRoot(Entity);
+
+fn toggle_debug_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<TerminalDebugOverlay>) {
+    if keys.just_pressed(overlay.toggle_key) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_debug_overlay(
+    mut commands: Commands,
+    overlay: Res<TerminalDebugOverlay>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q_term: Query<(
+        Entity,
+        &Terminal,
+        ChangeTrackers<Terminal>,
+        &TerminalRendererVertexData,
+        ChangeTrackers<TerminalRendererVertexData>,
+        Option<&DebugOverlayRoot>,
+    )>,
+) {
+    for (entity, term, term_changes, vert_data, vert_changes, root) in q_term.iter() {
+        let changed =
+            overlay.is_changed() || term_changes.is_changed() || vert_changes.is_changed();
+        if !changed {
+            continue;
+        }
+
+        if let Some(root) = root {
+            commands.entity(root.0).despawn_recursive();
+            commands.entity(entity).remove::<DebugOverlayRoot>();
+        }
+
+        if !overlay.visible || vert_data.verts.is_empty() {
+            continue;
+        }
+
+        let tile_mesh = line_mesh(&mut meshes, tile_outline_positions(&vert_data.verts));
+        let bounds_mesh = line_mesh(&mut meshes, bounds_positions(&vert_data.verts));
+        let tile_material = materials.add(overlay.tile_color.into());
+        let bounds_material = materials.add(overlay.bounds_color.into());
+
+        let root = commands
+            .spawn_bundle(TransformBundle::from_transform(Transform::from_xyz(
+                0.0, 0.0, 0.1,
+            )))
+            .with_children(|overlay_root| {
+                overlay_root.spawn_bundle(ColorMesh2dBundle {
+                    mesh: tile_mesh,
+                    material: tile_material,
+                    ..Default::default()
+                });
+                overlay_root.spawn_bundle(ColorMesh2dBundle {
+                    mesh: bounds_mesh,
+                    material: bounds_material,
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..Default::default()
+                });
+
+                let font = match &overlay.index_font {
+                    Some(font) => font.clone(),
+                    None => return,
+                };
+
+                for (tile, quad) in term.iter().zip(vert_data.verts.chunks_exact(4)) {
+                    let top_left = Vec3::from(quad[0]);
+                    let bottom_right = Vec3::from(quad[3]);
+                    let size = (bottom_right - top_left).abs();
+                    let center = (top_left + bottom_right) / 2.0;
+                    let scale = size.x.min(size.y) * 0.4 / INDEX_FONT_SIZE;
+
+                    let index = code_page_437::glyph_to_index(tile.glyph);
+                    let style = TextStyle {
+                        font: font.clone(),
+                        font_size: INDEX_FONT_SIZE,
+                        color: overlay.index_color,
+                    };
+                    let alignment = TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    };
+
+                    overlay_root.spawn_bundle(Text2dBundle {
+                        text: Text::with_section(index.to_string(), style, alignment),
+                        transform: Transform::from_xyz(center.x, center.y, 0.2)
+                            .with_scale(Vec3::splat(scale)),
+                        ..Default::default()
+                    });
+                }
+            })
+            .id();
+
+        commands
+            .entity(entity)
+            .add_child(root)
+            .insert(DebugOverlayRoot(root));
+    }
+}
+
+impl Terminal {
+    /// Draw a chart of every glyph cell in a font, to check that each glyph index
+    /// maps to the expected part of the font texture.
+    ///
+    /// Each cell of the font gets a cell in the grid, in the same layout as the
+    /// font texture. A cell shows the code page 437 glyph for it's index, which
+    /// is rendered with the terminal's own font. `show_indices` adds the glyph
+    /// index below it and `show_uvs` adds the uv coordinates of the cell's top
+    /// left corner.
+    ///
+    /// The terminal is resized to fit the grid. It's meant to be drawn to a
+    /// separate terminal layered on top of the one being debugged, using a
+    /// known good font.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    /// use bevy_ascii_terminal::renderer::font_asset::TerminalFontAsset;
+    ///
+    /// fn debug_font(
+    ///     mut commands: Commands,
+    ///     fonts: Res<Assets<TerminalFontAsset>>,
+    ///     asset_server: Res<AssetServer>,
+    /// ) {
+    ///     let font: Handle<TerminalFontAsset> = asset_server.load("my_font.font.png");
+    ///     if let Some(font) = fonts.get(&font) {
+    ///         let mut overlay = TerminalBundle::new();
+    ///         overlay.terminal.draw_debug_grid(font, true, true);
+    ///         overlay.transform.translation.z = 1.0;
+    ///         commands.spawn_bundle(overlay);
+    ///     }
+    /// }
+    /// ```
+    pub fn draw_debug_grid(
+        &mut self,
+        font: &TerminalFontAsset,
+        show_indices: bool,
+        show_uvs: bool,
+    ) {
+        let tile_count = font.tile_count.max(UVec2::ONE);
+        let inner = UVec2::new(
+            GRID_CELL_WIDTH,
+            1 + show_indices as u32 + if show_uvs { 2 } else { 0 },
+        );
+        let stride = inner + UVec2::ONE;
+        let size = tile_count * stride + UVec2::ONE;
+        self.resize(size.into());
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let glyph = match (x % stride.x == 0, y % stride.y == 0) {
+                    (true, true) => '┼',
+                    (true, false) => '│',
+                    (false, true) => '─',
+                    (false, false) => continue,
+                };
+                self.put_char_formatted(
+                    [x as i32, y as i32],
+                    glyph,
+                    CharFormat::new(Color::DARK_GRAY, Color::BLACK),
+                );
+            }
+        }
+
+        for row in 0..tile_count.y {
+            for col in 0..tile_count.x {
+                let index = row * tile_count.x + col;
+                let x = (col * stride.x + 1) as i32;
+                // Font rows go down from the top, terminal rows go up
+                let mut y = (size.y - 1 - (row * stride.y + 1)) as i32;
+
+                if index < 256 {
+                    self.put_char([x + 1, y], code_page_437::index_to_glyph(index as u8));
+                }
+                y -= 1;
+                if show_indices {
+                    self.put_string_formatted(
+                        [x, y],
+                        &index.to_string(),
+                        StringFormat::colors(Color::YELLOW, Color::BLACK),
+                    );
+                    y -= 1;
+                }
+                if show_uvs {
+                    let uv = UvMapping::get_grid_uvs([col, row], tile_count.into())[0];
+                    let format = StringFormat::colors(Color::CYAN, Color::BLACK);
+                    self.put_string_formatted([x, y], &short_uv(uv[0]), format);
+                    self.put_string_formatted([x, y - 1], &short_uv(uv[1]), format);
+                }
+            }
+        }
+    }
+}
+
+/// A uv coordinate without it's leading zero, so `0.25` becomes `.250`.
+fn short_uv(v: f32) -> String {
+    let s = format!("{:.3}", v);
+    match s.strip_prefix('0') {
+        Some(s) => s.to_string(),
+        None => s,
+    }
+}
+
+fn line_mesh(meshes: &mut Assets<Mesh>, positions: Vec<[f32; 3]>) -> Mesh2dHandle {
+    let len = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; len]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; len]);
+    Mesh2dHandle(meshes.add(mesh))
+}
+
+/// Line list positions outlining every tile quad of a terminal mesh.
+fn tile_outline_positions(verts: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let mut positions = Vec::with_capacity(verts.len() * 2);
+    // 0---2
+    // | / |
+    // 1---3
+    for quad in verts.chunks_exact(4) {
+        positions.extend_from_slice(&[quad[0], quad[2]]);
+        positions.extend_from_slice(&[quad[2], quad[3]]);
+        positions.extend_from_slice(&[quad[3], quad[1]]);
+        positions.extend_from_slice(&[quad[1], quad[0]]);
+    }
+    positions
+}
+
+/// Line list positions outlining the bounds of a terminal mesh.
+fn bounds_positions(verts: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let (min, max) = verts.iter().map(|v| Vec3::from(*v)).fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), v| (min.min(v), max.max(v)),
+    );
+
+    let top_left = [min.x, max.y, 0.0];
+    let top_right = [max.x, max.y, 0.0];
+    let bottom_left = [min.x, min.y, 0.0];
+    let bottom_right = [max.x, min.y, 0.0];
+
+    vec![
+        top_left,
+        top_right,
+        top_right,
+        bottom_right,
+        bottom_right,
+        bottom_left,
+        bottom_left,
+        top_left,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::UVec2;
+
+    use crate::renderer::font_asset::TerminalFontMetrics;
+
+    use super::*;
+
+    #[test]
+    fn tile_outlines() {
+        let verts = TerminalRendererVertexData::with_size(UVec2::new(3, 2));
+        let lines = tile_outline_positions(&verts.verts);
+
+        // 4 edges with 2 points each for every tile.
+        assert_eq!(3 * 2 * 8, lines.len());
+    }
+
+    #[test]
+    fn debug_grid() {
+        let font = TerminalFontAsset {
+            image: Handle::default(),
+            tile_count: UVec2::new(16, 16),
+            tile_size: UVec2::new(8, 8),
+            clip_color: Color::BLACK,
+            metrics: TerminalFontMetrics::from_tile_height(8),
+            sdf_mode: false,
+            kerning: 0,
+            pixel_density_hint: 1.0,
+        };
+        let mut term = Terminal::default();
+        term.draw_debug_grid(&font, true, true);
+        // 16 cells of 4x4 plus grid lines
+        assert_eq!(UVec2::new(81, 81), term.size());
+        assert_eq!('┼', term.get_char([0, 80]));
+        assert_eq!('─', term.get_char([1, 80]));
+        assert_eq!('│', term.get_char([5, 79]));
+
+        // Glyph 'A' (65) is the second cell of the fifth row
+        let x = 5 + 1;
+        let y = 80 - (4 * 5 + 1);
+        assert_eq!('A', term.get_char([x + 1, y]));
+        assert_eq!('6', term.get_char([x, y - 1]));
+        assert_eq!('5', term.get_char([x + 1, y - 1]));
+        assert_eq!(".062", string_at(&term, [x, y - 2]));
+        assert_eq!(".250", string_at(&term, [x, y - 3]));
+
+        term.draw_debug_grid(&font, false, false);
+        assert_eq!(UVec2::new(81, 33), term.size());
+    }
+
+    fn string_at(term: &Terminal, xy: [i32; 2]) -> String {
+        (0..4).map(|i| term.get_char([xy[0] + i, xy[1]])).collect()
+    }
+
+    #[test]
+    fn bounds() {
+        let verts = TerminalRendererVertexData::with_size(UVec2::new(4, 2));
+        let lines = bounds_positions(&verts.verts);
+
+        assert_eq!([-2.0, 1.0, 0.0], lines[0]);
+        assert_eq!([2.0, 1.0, 0.0], lines[1]);
+        assert_eq!([2.0, -1.0, 0.0], lines[3]);
+        assert_eq!([-2.0, -1.0, 0.0], lines[5]);
+    }
+}
@@ -0,0 +1,268 @@
+//! A widget for choosing one of the terminal's fonts.
+
+use bevy::prelude::*;
+
+use crate::{
+    BuiltInFontHandles, CharFormat, Terminal, TerminalAction, TerminalInputMap, TerminalMenuFocus,
+};
+
+/// The text drawn below the list of fonts by a [TerminalFontPicker].
+const FONT_PREVIEW_TEXT: &str = "Hello, World!";
+
+/// Plugin which routes keyboard input to any visible [TerminalFontPicker] with
+/// a [TerminalMenuFocus] component.
+///
+/// [TerminalAction::Up] and [TerminalAction::Down] move the highlight and
+/// [TerminalAction::Confirm] selects the highlighted font and sends a
+/// [FontSelectedEvent].
+pub struct TerminalFontPickerPlugin;
+
+impl Plugin for TerminalFontPickerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FontSelectedEvent>()
+            .init_resource::<TerminalInputMap>()
+            .add_system(terminal_font_picker_input);
+    }
+}
+
+/// Event sent when a font is chosen from a [TerminalFontPicker], with the
+/// picker's entity and the font's name in [BuiltInFontHandles].
+#[derive(Debug, Clone)]
+pub struct FontSelectedEvent(pub Entity, pub String);
+
+/// A list of font names with the highlighted font drawn inverted and the
+/// selected font marked, followed by a line of preview text.
+///
+/// A terminal renders with a single font, so the preview text is drawn in the
+/// terminal's own font. To preview the highlighted font, set it on a separate
+/// terminal's material using [TerminalFontPicker::highlighted_font].
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn spawn_picker(mut commands: Commands, fonts: Res<BuiltInFontHandles>) {
+///     let mut bundle = TerminalBundle::new().with_size([30, 10]);
+///     let picker = TerminalFontPicker::new(&fonts);
+///     picker.draw(&mut bundle.terminal, UVec2::new(1, 8), &fonts);
+///     commands
+///         .spawn_bundle(bundle)
+///         .insert(picker)
+///         .insert(TerminalMenuFocus);
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Default)]
+pub struct TerminalFontPicker {
+    /// The name of the chosen font.
+    pub selected: String,
+    /// The names of the fonts to choose from.
+    pub fonts: Vec<String>,
+    /// Invisible pickers aren't drawn and ignore input.
+    pub visible: bool,
+    /// The index in `fonts` of the highlighted font.
+    pub highlighted: usize,
+}
+
+impl TerminalFontPicker {
+    /// Create a visible picker listing every font in `fonts`, with the first
+    /// one selected.
+    pub fn new(fonts: &BuiltInFontHandles) -> Self {
+        let fonts: Vec<String> = fonts.names().into_iter().map(String::from).collect();
+        Self {
+            selected: fonts.first().cloned().unwrap_or_default(),
+            fonts,
+            visible: true,
+            highlighted: 0,
+        }
+    }
+
+    /// The name of the highlighted font, or an empty string if there are no fonts.
+    pub fn highlighted_font(&self) -> &str {
+        self.fonts
+            .get(self.highlighted)
+            .map(|s| s.as_str())
+            .unwrap_or("")
+    }
+
+    /// Move the highlight to the next font, wrapping around at the end.
+    pub fn next(&mut self) {
+        if !self.fonts.is_empty() {
+            self.highlighted = (self.highlighted + 1) % self.fonts.len();
+        }
+    }
+
+    /// Move the highlight to the previous font, wrapping around at the start.
+    pub fn prev(&mut self) {
+        if !self.fonts.is_empty() {
+            self.highlighted = (self.highlighted + self.fonts.len() - 1) % self.fonts.len();
+        }
+    }
+
+    /// Select the highlighted font. Returns it's name, or `None` if there are
+    /// no fonts.
+    pub fn confirm(&mut self) -> Option<&str> {
+        let font = self.fonts.get(self.highlighted)?;
+        self.selected = font.clone();
+        Some(&self.selected)
+    }
+
+    /// Draw the picker with the first font's row at `pos`. Following fonts
+    /// are drawn on the rows below it, then an empty row and the preview text.
+    ///
+    /// Fonts missing from `fonts_resource` are drawn grayed out. Does nothing
+    /// if the picker isn't visible.
+    pub fn draw(&self, terminal: &mut Terminal, pos: UVec2, fonts_resource: &BuiltInFontHandles) {
+        if !self.visible {
+            return;
+        }
+        let width = self
+            .fonts
+            .iter()
+            .map(|s| s.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let [x, y] = [pos.x as i32, pos.y as i32];
+        for (i, font) in self.fonts.iter().enumerate() {
+            let fg = if fonts_resource.get(font).is_some() {
+                Color::WHITE
+            } else {
+                Color::GRAY
+            };
+            let format = if i == self.highlighted {
+                CharFormat::new(Color::BLACK, fg)
+            } else {
+                CharFormat::new(fg, Color::BLACK)
+            };
+            let marker = if *font == self.selected { '*' } else { ' ' };
+            let mut chars = [marker, ' '].into_iter().chain(font.chars());
+            put_row(terminal, [x, y - i as i32], width, &mut chars, format);
+        }
+
+        let preview_y = y - self.fonts.len() as i32 - 1;
+        let mut chars = FONT_PREVIEW_TEXT.chars();
+        let width = width.max(FONT_PREVIEW_TEXT.len());
+        put_row(
+            terminal,
+            [x, preview_y],
+            width,
+            &mut chars,
+            CharFormat::default(),
+        );
+    }
+}
+
+/// Draw `width` tiles from `chars`, padded with spaces and clipped to the terminal.
+fn put_row(
+    terminal: &mut Terminal,
+    xy: [i32; 2],
+    width: usize,
+    chars: &mut impl Iterator<Item = char>,
+    format: CharFormat,
+) {
+    for i in 0..width as i32 {
+        let pos = [xy[0] + i, xy[1]];
+        let ch = chars.next().unwrap_or(' ');
+        if terminal.is_in_bounds(pos) {
+            terminal.put_tile(pos, format.tile(ch));
+        }
+    }
+}
+
+fn terminal_font_picker_input(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<TerminalInputMap>,
+    mut events: EventWriter<FontSelectedEvent>,
+    mut q: Query<(Entity, &mut TerminalFontPicker), With<TerminalMenuFocus>>,
+) {
+    for (entity, mut picker) in q.iter_mut() {
+        if !picker.visible {
+            continue;
+        }
+        if input_map.is_just_pressed(TerminalAction::Down, &keys) {
+            picker.next();
+        }
+        if input_map.is_just_pressed(TerminalAction::Up, &keys) {
+            picker.prev();
+        }
+        if input_map.is_just_pressed(TerminalAction::Confirm, &keys) {
+            if let Some(font) = picker.confirm() {
+                events.send(FontSelectedEvent(entity, font.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+
+    fn fonts() -> BuiltInFontHandles {
+        let mut fonts = BuiltInFontHandles::new();
+        for name in ["b.png", "a.png", "c.png"] {
+            fonts.insert(name, Handle::default(), UVec2::new(8, 8));
+        }
+        fonts
+    }
+
+    #[test]
+    fn confirm() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .add_plugin(TerminalFontPickerPlugin);
+        let picker = TerminalFontPicker::new(&fonts());
+        assert_eq!("a.png", picker.selected);
+        let picker = app
+            .world
+            .spawn()
+            .insert(picker)
+            .insert(TerminalMenuFocus)
+            .id();
+
+        let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+        keys.press(KeyCode::Down);
+        app.update();
+        let events = app
+            .world
+            .get_resource::<Events<FontSelectedEvent>>()
+            .unwrap();
+        assert!(events.is_empty());
+        assert_eq!(
+            "b.png",
+            app.world
+                .get::<TerminalFontPicker>(picker)
+                .unwrap()
+                .highlighted_font()
+        );
+
+        let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+        keys.clear();
+        keys.press(KeyCode::Return);
+        app.update();
+        let events = app
+            .world
+            .get_resource::<Events<FontSelectedEvent>>()
+            .unwrap();
+        let mut reader = events.get_reader();
+        let selected: Vec<_> = reader.iter(events).collect();
+        assert_eq!(1, selected.len());
+        assert_eq!(picker, selected[0].0);
+        assert_eq!("b.png", selected[0].1);
+        let picker = app.world.get::<TerminalFontPicker>(picker).unwrap();
+        assert_eq!("b.png", picker.selected);
+
+        let mut term = Terminal::with_size([20, 6]);
+        let mut picker = picker.clone();
+        picker.fonts.push("missing.png".to_string());
+        picker.draw(&mut term, UVec2::new(0, 5), &fonts());
+        assert_eq!("  a.png", term.get_string([0, 5], 7));
+        assert_eq!("* b.png", term.get_string([0, 4], 7));
+        assert_eq!(Color::WHITE, term.get_tile([0, 4]).bg_color);
+        assert_eq!(Color::GRAY, term.get_tile([2, 2]).fg_color);
+        assert_eq!(FONT_PREVIEW_TEXT, term.get_string([0, 0], 13));
+    }
+}
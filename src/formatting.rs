@@ -100,6 +100,7 @@ impl CharFormat {
             glyph,
             fg_color: self.fg_color,
             bg_color: self.bg_color,
+            ..Default::default()
         }
     }
 }
@@ -170,6 +171,7 @@ impl StringFormat {
             glyph,
             fg_color: self.fg_color,
             bg_color: self.bg_color,
+            ..Default::default()
         }
     }
 }
@@ -0,0 +1,192 @@
+//! Drawing grids of values as colored tiles, for visualizing data such as
+//! pathfinding costs or influence maps.
+
+use bevy::prelude::*;
+
+use crate::{code_page_437, Terminal};
+
+/// A gradient of colors for mapping values to colors, made of `(value, color)`
+/// stops sorted by value in ascending order.
+///
+/// Values between two stops blend between their colors, values outside the
+/// stops get the color of the nearest one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorScale {
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl ColorScale {
+    pub fn new(stops: impl Into<Vec<(f32, Color)>>) -> Self {
+        Self {
+            stops: stops.into(),
+        }
+    }
+
+    /// A dark blue to purple to yellow scale over the range 0 to 1.
+    pub fn plasma() -> Self {
+        Self::from_rgb_u8(&[
+            [13, 8, 135],
+            [126, 3, 168],
+            [204, 71, 120],
+            [248, 149, 64],
+            [240, 249, 33],
+        ])
+    }
+
+    /// A dark purple to teal to yellow scale over the range 0 to 1.
+    pub fn viridis() -> Self {
+        Self::from_rgb_u8(&[
+            [68, 1, 84],
+            [59, 82, 139],
+            [33, 145, 140],
+            [94, 201, 98],
+            [253, 231, 37],
+        ])
+    }
+
+    /// A black to red to yellow to white scale over the range 0 to 1.
+    pub fn hot() -> Self {
+        Self::from_rgb_u8(&[[0, 0, 0], [255, 0, 0], [255, 255, 0], [255, 255, 255]])
+    }
+
+    /// Evenly spaced stops from 0 to 1.
+    fn from_rgb_u8(colors: &[[u8; 3]]) -> Self {
+        let step = 1.0 / (colors.len() - 1) as f32;
+        Self::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, [r, g, b])| (i as f32 * step, Color::rgb_u8(*r, *g, *b)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The color for the given value.
+    ///
+    /// Returns [Color::NONE] if the scale has no stops.
+    pub fn sample(&self, value: f32) -> Color {
+        sample(&self.stops, value)
+    }
+}
+
+fn sample(stops: &[(f32, Color)], value: f32) -> Color {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Color::NONE,
+    };
+    if value.is_nan() || value <= first.0 {
+        return first.1;
+    }
+    if value >= last.0 {
+        return last.1;
+    }
+
+    for pair in stops.windows(2) {
+        let ((v0, c0), (v1, c1)) = (pair[0], pair[1]);
+        if value <= v1 {
+            let t = if v1 > v0 {
+                (value - v0) / (v1 - v0)
+            } else {
+                1.0
+            };
+            let c0 = Vec4::from(c0.as_rgba_f32());
+            let c1 = Vec4::from(c1.as_rgba_f32());
+            return Color::from(c0.lerp(c1, t));
+        }
+    }
+    last.1
+}
+
+impl Terminal {
+    /// Draw a grid of values as tiles, setting every tile to `glyph` with a
+    /// foreground color from `color_scale`.
+    ///
+    /// `glyph` is a code page 437 index, for example 219 for a full block.
+    /// `color_scale` is a list of `(value, color)` stops sorted by value in
+    /// ascending order, see [ColorScale] for some presets.
+    ///
+    /// Values are laid out like terminal tiles: index 0 is the bottom left and
+    /// rows of `width` values go upwards. Values outside the terminal are
+    /// ignored. Returns an error if `data` doesn't have `width` values for every
+    /// row of the terminal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let mut term = Terminal::with_size([10, 5]);
+    /// let costs: Vec<f32> = (0..50).map(|i| i as f32 / 50.0).collect();
+    /// term.draw_heatmap(&costs, 10, &ColorScale::viridis().stops, 219)
+    ///     .unwrap();
+    /// ```
+    pub fn draw_heatmap(
+        &mut self,
+        data: &[f32],
+        width: u32,
+        color_scale: &[(f32, Color)],
+        glyph: u8,
+    ) -> Result<(), String> {
+        let expected = (width * self.height()) as usize;
+        if data.len() != expected {
+            return Err(format!(
+                "draw_heatmap error, expected {} values for width {} and height {}, found {}",
+                expected,
+                width,
+                self.height(),
+                data.len()
+            ));
+        }
+
+        let glyph = code_page_437::index_to_glyph(glyph);
+        for y in 0..self.height() {
+            for x in 0..width.min(self.width()) {
+                let value = data[(y * width + x) as usize];
+                let tile = self.get_tile_mut([x as i32, y as i32]);
+                tile.glyph = glyph;
+                tile.fg_color = sample(color_scale, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heatmap() {
+        let scale = ColorScale::hot();
+        assert_eq!(Color::rgb_u8(0, 0, 0), scale.sample(0.0));
+        assert_eq!(Color::rgb_u8(255, 255, 255), scale.sample(1.0));
+        // Clamped outside the stops
+        assert_eq!(scale.sample(0.0), scale.sample(-5.0));
+        assert_eq!(scale.sample(1.0), scale.sample(5.0));
+        assert_eq!(scale.sample(0.0), scale.sample(f32::NAN));
+        // Exactly on an inner stop
+        assert_eq!(Color::rgb_u8(255, 0, 0), scale.sample(1.0 / 3.0));
+        assert_eq!(
+            Color::rgb_u8(240, 249, 33),
+            ColorScale::plasma().sample(1.0)
+        );
+        assert_eq!(Color::rgb_u8(68, 1, 84), ColorScale::viridis().sample(0.0));
+        assert_eq!(Color::NONE, ColorScale::new([]).sample(0.5));
+
+        let mut term = Terminal::with_size([3, 2]);
+        let stops = [(0.0, Color::BLACK), (10.0, Color::WHITE)];
+        assert!(term.draw_heatmap(&[0.0; 5], 3, &stops, 219).is_err());
+        term.draw_heatmap(&[0.0, 5.0, 10.0, 0.0, 0.0, 20.0], 3, &stops, 219)
+            .unwrap();
+        assert_eq!('█', term.get_tile([0, 0]).glyph);
+        assert_eq!(Color::BLACK, term.get_tile([0, 0]).fg_color);
+        assert_eq!(Color::rgb(0.5, 0.5, 0.5), term.get_tile([1, 0]).fg_color);
+        assert_eq!(Color::WHITE, term.get_tile([2, 0]).fg_color);
+        assert_eq!(Color::WHITE, term.get_tile([2, 1]).fg_color);
+
+        // Data wider than the terminal is clipped
+        term.draw_heatmap(&[10.0; 8], 4, &stops, b'#').unwrap();
+        assert_eq!(Color::WHITE, term.get_tile([0, 0]).fg_color);
+    }
+}
@@ -0,0 +1,147 @@
+//! Elevation based tile shading.
+
+use bevy::prelude::*;
+
+use crate::{renderer::noise::hash, Terminal};
+
+/// A grid of heights from 0 to 1, used to shade terminal tiles by elevation.
+///
+/// Heights are laid out like terminal tiles: index 0 is the bottom left and
+/// rows go upwards.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let map = TerminalHeightMap::from_noise(40, 20, 0.1);
+/// let mut term = Terminal::with_size([40, 20]);
+/// term.apply_height_map(&map, Color::DARK_GREEN, Color::WHITE);
+/// ```
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct TerminalHeightMap {
+    pub heights: Vec<f32>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TerminalHeightMap {
+    /// A flat height map with every height set to 0.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            heights: vec![0.0; (width * height) as usize],
+            width,
+            height,
+        }
+    }
+
+    /// Generate a height map from perlin noise.
+    ///
+    /// `frequency` is the number of noise cells per tile, lower values give
+    /// smoother hills. The same arguments always give the same map.
+    pub fn from_noise(width: u32, height: u32, frequency: f32) -> Self {
+        let mut map = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let n = perlin(x as f32 * frequency, y as f32 * frequency);
+                // Perlin noise in 2d lies within [-sqrt(0.5), sqrt(0.5)]
+                let h = (n * std::f32::consts::SQRT_2 + 1.0) * 0.5;
+                map.heights[(y * width + x) as usize] = h.clamp(0.0, 1.0);
+            }
+        }
+        map
+    }
+
+    /// The height at the given position, or `None` if it's out of bounds.
+    pub fn get(&self, xy: [i32; 2]) -> Option<f32> {
+        let [x, y] = xy;
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        self.heights
+            .get((y as u32 * self.width + x as u32) as usize)
+            .copied()
+    }
+}
+
+impl Terminal {
+    /// Set the foreground color of each tile to a blend between `base_color`
+    /// and `highlight_color`, based on the tile's height in the map.
+    ///
+    /// Only tiles covered by both the terminal and the map are changed.
+    pub fn apply_height_map(
+        &mut self,
+        map: &TerminalHeightMap,
+        base_color: Color,
+        highlight_color: Color,
+    ) {
+        let size = self.size();
+        let base = Vec4::from(base_color.as_rgba_f32());
+        let highlight = Vec4::from(highlight_color.as_rgba_f32());
+        for y in 0..size.y.min(map.height) as i32 {
+            for x in 0..size.x.min(map.width) as i32 {
+                let h = map.get([x, y]).unwrap_or_default().clamp(0.0, 1.0);
+                let color = base.lerp(highlight, h);
+                self.put_fg_color([x, y], Color::from(color));
+            }
+        }
+    }
+}
+
+/// 2d gradient noise, roughly in the range [-0.7, 0.7].
+fn perlin(x: f32, y: f32) -> f32 {
+    let cell = Vec2::new(x.floor(), y.floor());
+    let p = Vec2::new(x, y) - cell;
+    let (cx, cy) = (cell.x as i64, cell.y as i64);
+
+    let dot = |ox: i64, oy: i64| {
+        let angle = (hash(0, (cx + ox) as u64, (cy + oy) as u64) >> 40) as f32
+            / (1u64 << 24) as f32
+            * std::f32::consts::TAU;
+        Vec2::new(angle.cos(), angle.sin()).dot(p - Vec2::new(ox as f32, oy as f32))
+    };
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+
+    let (u, v) = (fade(p.x), fade(p.y));
+    let bottom = dot(0, 0) + (dot(1, 0) - dot(0, 0)) * u;
+    let top = dot(0, 1) + (dot(1, 1) - dot(0, 1)) * u;
+    bottom + (top - bottom) * v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shading() {
+        let mut map = TerminalHeightMap::new(3, 2);
+        map.heights[1] = 1.0;
+        map.heights[2] = 0.5;
+
+        let mut term = Terminal::with_size([4, 4]);
+        term.put_fg_color([3, 3], Color::RED);
+        term.apply_height_map(&map, Color::BLACK, Color::WHITE);
+
+        assert_eq!(Color::BLACK, term.get_tile([0, 0]).fg_color);
+        assert_eq!(Color::WHITE, term.get_tile([1, 0]).fg_color);
+        assert_eq!(Color::rgb(0.5, 0.5, 0.5), term.get_tile([2, 0]).fg_color);
+        // Outside the map
+        assert_eq!(Color::RED, term.get_tile([3, 3]).fg_color);
+    }
+
+    #[test]
+    fn noise() {
+        let map = TerminalHeightMap::from_noise(32, 16, 0.15);
+        assert_eq!(32 * 16, map.heights.len());
+        assert!(map.heights.iter().all(|h| (0.0..=1.0).contains(h)));
+        // Noise is deterministic and not flat
+        assert_eq!(map, TerminalHeightMap::from_noise(32, 16, 0.15));
+        assert!(map
+            .heights
+            .iter()
+            .any(|h| (h - map.heights[0]).abs() > 0.05));
+        // Lattice points are always 0
+        assert_eq!(0.0, perlin(3.0, 5.0));
+    }
+}
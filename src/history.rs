@@ -0,0 +1,147 @@
+//! Undo and redo for terminal draw operations.
+
+use crate::terminal::{Terminal, TerminalSnapshot};
+
+/// A resource which tracks [TerminalSnapshot]s so draw operations on a
+/// terminal can be undone and redone.
+///
+/// Call [TerminalHistory::push_undo] before drawing to the terminal to record
+/// it's current state.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([10, 10]);
+/// let mut history = TerminalHistory::new(50);
+///
+/// history.push_undo(&term);
+/// term.put_char([1, 1], 'a');
+///
+/// history.undo(&mut term);
+/// assert_eq!(' ', term.get_char([1, 1]));
+/// ```
+pub struct TerminalHistory {
+    pub undo_stack: Vec<TerminalSnapshot>,
+    pub redo_stack: Vec<TerminalSnapshot>,
+    /// The maximum number of snapshots kept on the undo stack.
+    pub max_depth: usize,
+}
+
+impl Default for TerminalHistory {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl TerminalHistory {
+    /// Create a history which will keep at most `max_depth` undo snapshots.
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            max_depth,
+        }
+    }
+
+    /// Record the current state of the terminal. Should be called before
+    /// a draw operation.
+    ///
+    /// This will clear the redo stack.
+    pub fn push_undo(&mut self, terminal: &Terminal) {
+        self.redo_stack.clear();
+        self.undo_stack.push(terminal.snapshot());
+        self.prune();
+    }
+
+    /// Restore the terminal to the most recently recorded state.
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self, terminal: &mut Terminal) -> bool {
+        match self.undo_stack.pop() {
+            Some(snapshot) => {
+                self.redo_stack.push(terminal.snapshot());
+                terminal.restore(&snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reapply the most recently undone state.
+    ///
+    /// Returns false if there was nothing to redo.
+    pub fn redo(&mut self, terminal: &mut Terminal) -> bool {
+        match self.redo_stack.pop() {
+            Some(snapshot) => {
+                self.undo_stack.push(terminal.snapshot());
+                self.prune();
+                terminal.restore(&snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clear both the undo and redo stacks.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    fn prune(&mut self) {
+        if self.undo_stack.len() > self.max_depth {
+            let excess = self.undo_stack.len() - self.max_depth;
+            self.undo_stack.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let mut term = Terminal::with_size([10, 10]);
+        let mut history = TerminalHistory::default();
+
+        for (i, ch) in "abc".chars().enumerate() {
+            history.push_undo(&term);
+            term.put_char([i as i32, 0], ch);
+        }
+        assert_eq!("abc", term.get_string([0, 0], 3));
+
+        assert!(history.undo(&mut term));
+        assert_eq!("ab ", term.get_string([0, 0], 3));
+        assert!(history.undo(&mut term));
+        assert_eq!("a  ", term.get_string([0, 0], 3));
+        assert!(history.undo(&mut term));
+        assert_eq!("   ", term.get_string([0, 0], 3));
+        assert!(!history.undo(&mut term));
+
+        assert!(history.redo(&mut term));
+        assert!(history.redo(&mut term));
+        assert!(history.redo(&mut term));
+        assert_eq!("abc", term.get_string([0, 0], 3));
+        assert!(!history.redo(&mut term));
+    }
+
+    #[test]
+    fn max_depth() {
+        let mut term = Terminal::with_size([10, 10]);
+        let mut history = TerminalHistory::new(2);
+
+        for i in 0..5 {
+            history.push_undo(&term);
+            term.put_char([i, 0], 'a');
+        }
+
+        assert_eq!(2, history.undo_stack.len());
+        assert!(history.undo(&mut term));
+        assert!(history.undo(&mut term));
+        assert!(!history.undo(&mut term));
+        assert_eq!("aaa  ", term.get_string([0, 0], 5));
+    }
+}
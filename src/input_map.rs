@@ -0,0 +1,131 @@
+//! Rebindable keyboard input for terminal widgets.
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// An input action used by terminal widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Cancel,
+    NextPage,
+    PrevPage,
+}
+
+/// Resource mapping [TerminalAction]s to the keys which trigger them, used by
+/// widgets such as the [TerminalMenuWidget](crate::TerminalMenuWidget) instead
+/// of hard coded keys.
+///
+/// By default the arrow keys move, `Enter` confirms, `Escape` cancels and
+/// `PageUp`/`PageDown` change pages.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn setup(mut input_map: ResMut<TerminalInputMap>) {
+///     input_map.bind(TerminalAction::Confirm, KeyCode::Space);
+///     input_map.bind(TerminalAction::Up, KeyCode::W);
+///     input_map.bind(TerminalAction::Down, KeyCode::S);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerminalInputMap {
+    pub bindings: HashMap<TerminalAction, Vec<KeyCode>>,
+}
+
+impl Default for TerminalInputMap {
+    fn default() -> Self {
+        let bindings = [
+            (TerminalAction::Up, KeyCode::Up),
+            (TerminalAction::Down, KeyCode::Down),
+            (TerminalAction::Left, KeyCode::Left),
+            (TerminalAction::Right, KeyCode::Right),
+            (TerminalAction::Confirm, KeyCode::Return),
+            (TerminalAction::Cancel, KeyCode::Escape),
+            (TerminalAction::NextPage, KeyCode::PageDown),
+            (TerminalAction::PrevPage, KeyCode::PageUp),
+        ];
+        Self {
+            bindings: bindings
+                .into_iter()
+                .map(|(action, key)| (action, vec![key]))
+                .collect(),
+        }
+    }
+}
+
+impl TerminalInputMap {
+    /// Add a key which triggers the action, in addition to any already bound.
+    pub fn bind(&mut self, action: TerminalAction, key: KeyCode) {
+        let keys = self.bindings.entry(action).or_default();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    /// Remove a key from the action.
+    pub fn unbind(&mut self, action: TerminalAction, key: KeyCode) {
+        if let Some(keys) = self.bindings.get_mut(&action) {
+            keys.retain(|k| *k != key);
+        }
+    }
+
+    /// Remove all keys from the action.
+    pub fn clear(&mut self, action: TerminalAction) {
+        self.bindings.remove(&action);
+    }
+
+    /// The keys bound to the action.
+    pub fn keys(&self, action: TerminalAction) -> &[KeyCode] {
+        self.bindings
+            .get(&action)
+            .map(|keys| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// True if any key bound to the action was pressed this frame.
+    pub fn is_just_pressed(&self, action: TerminalAction, keyboard: &Input<KeyCode>) -> bool {
+        self.keys(action).iter().any(|k| keyboard.just_pressed(*k))
+    }
+
+    /// True if any key bound to the action is held down.
+    pub fn is_pressed(&self, action: TerminalAction, keyboard: &Input<KeyCode>) -> bool {
+        self.keys(action).iter().any(|k| keyboard.pressed(*k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings() {
+        let mut map = TerminalInputMap::default();
+        let mut keys = Input::<KeyCode>::default();
+        keys.press(KeyCode::Space);
+        assert!(!map.is_just_pressed(TerminalAction::Confirm, &keys));
+
+        map.bind(TerminalAction::Confirm, KeyCode::Space);
+        map.bind(TerminalAction::Confirm, KeyCode::Space);
+        assert_eq!(
+            &[KeyCode::Return, KeyCode::Space],
+            map.keys(TerminalAction::Confirm)
+        );
+        assert!(map.is_just_pressed(TerminalAction::Confirm, &keys));
+
+        keys.clear();
+        assert!(!map.is_just_pressed(TerminalAction::Confirm, &keys));
+        assert!(map.is_pressed(TerminalAction::Confirm, &keys));
+
+        map.unbind(TerminalAction::Confirm, KeyCode::Space);
+        assert!(!map.is_pressed(TerminalAction::Confirm, &keys));
+        map.clear(TerminalAction::Up);
+        assert!(map.keys(TerminalAction::Up).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Drawing 3d boxes with an oblique projection.
+
+use bevy::math::{UVec2, UVec3, Vec2, Vec3};
+
+use crate::{Terminal, Tile};
+
+/// Projects 3d box positions to terminal tiles.
+///
+/// Width (x) runs to the right and height (z) runs up the terminal. Depth (y)
+/// runs along `scale`, in tiles per unit of depth. The default of (1,1) draws
+/// depth diagonally up and to the right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsometricTransform {
+    pub scale: Vec2,
+}
+
+impl Default for IsometricTransform {
+    fn default() -> Self {
+        Self { scale: Vec2::ONE }
+    }
+}
+
+impl IsometricTransform {
+    pub fn new(scale: Vec2) -> Self {
+        Self { scale }
+    }
+
+    /// The terminal offset of a 3d position, relative to the projection of
+    /// the origin.
+    pub fn project(&self, pos: Vec3) -> Vec2 {
+        Vec2::new(pos.x, pos.z) + self.scale * pos.y
+    }
+}
+
+/// The tiles used for each visible face of a box drawn with
+/// [Terminal::draw_box_3d].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Box3dSides {
+    pub top: Tile,
+    /// The front face, below the top face.
+    pub left: Tile,
+    /// The side face, to the right of the front face.
+    pub right: Tile,
+}
+
+impl Terminal {
+    /// Draw a 3d box with it's front bottom left corner at `origin`.
+    ///
+    /// `size` is the box's width, depth and height in tiles. The top face is
+    /// `size.x * size.y` tiles of `sides.top`, the front face below it is
+    /// `size.x * size.z` tiles of `sides.left` and the side face to the right
+    /// is `size.y * size.z` tiles of `sides.right`. Any part of the box outside
+    /// the terminal will be skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let mut term = Terminal::with_size([20, 20]);
+    /// let face = |glyph| Tile {
+    ///     glyph,
+    ///     ..Default::default()
+    /// };
+    /// let sides = Box3dSides {
+    ///     top: face('▒'),
+    ///     left: face('█'),
+    ///     right: face('▓'),
+    /// };
+    /// term.draw_box_3d(
+    ///     UVec2::new(2, 2),
+    ///     UVec3::new(6, 3, 4),
+    ///     sides,
+    ///     IsometricTransform::default(),
+    /// );
+    /// ```
+    pub fn draw_box_3d(
+        &mut self,
+        origin: UVec2,
+        size: UVec3,
+        sides: Box3dSides,
+        transform: IsometricTransform,
+    ) {
+        let origin = origin.as_vec2();
+        let mut put = |pos: Vec3, tile: Tile| {
+            let xy = (origin + transform.project(pos)).round().as_ivec2();
+            if self.is_in_bounds(xy.into()) {
+                self.put_tile(xy.into(), tile);
+            }
+        };
+        let [w, d, h] = size.to_array();
+
+        // Back to front, so the faces nearest the viewer are drawn over the others.
+        // The side face of the last column is drawn a step deeper so it's top
+        // edge meets the top face without a gap.
+        for y in 0..d {
+            for z in 0..h {
+                let pos = Vec3::new(w as f32 - 1.0, y as f32 + 1.0, z as f32);
+                put(pos, sides.right);
+            }
+        }
+        for x in 0..w {
+            for z in 0..h {
+                put(Vec3::new(x as f32, 0.0, z as f32), sides.left);
+            }
+        }
+        for x in 0..w {
+            for y in 0..d {
+                put(Vec3::new(x as f32, y as f32, h as f32), sides.top);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::IVec2;
+
+    use super::*;
+
+    #[test]
+    fn box_3d() {
+        let face = |glyph| Tile {
+            glyph,
+            ..Default::default()
+        };
+        let sides = Box3dSides {
+            top: face('t'),
+            left: face('l'),
+            right: face('r'),
+        };
+        let mut term = Terminal::with_size([10, 10]);
+        term.draw_box_3d(
+            UVec2::new(1, 1),
+            UVec3::new(2, 2, 2),
+            sides,
+            IsometricTransform::default(),
+        );
+
+        let glyphs: String = term.iter().map(|t| t.glyph).collect();
+        assert_eq!(4, glyphs.matches('t').count());
+        assert_eq!(4, glyphs.matches('l').count());
+        assert_eq!(4, glyphs.matches('r').count());
+
+        let rows: Vec<String> = (0..6).map(|y| term.get_string([0, y], 6)).collect();
+        assert_eq!(
+            vec![
+                "      ", //
+                " ll   ", //
+                " llr  ", //
+                " ttrr ", //
+                "  ttr ", //
+                "      ",
+            ],
+            rows
+        );
+
+        // The depth axis follows the scale
+        let transform = IsometricTransform::new(Vec2::new(-1.0, 1.0));
+        assert_eq!(
+            Vec2::new(1.0, 5.0),
+            transform.project(Vec3::new(3.0, 2.0, 3.0))
+        );
+        let mut term = Terminal::with_size([10, 10]);
+        term.draw_box_3d(UVec2::new(4, 1), UVec3::new(2, 2, 2), sides, transform);
+        assert_eq!(IVec2::new(4, 3), find(&term, 't'));
+    }
+
+    /// The bottom left most tile with the given glyph.
+    fn find(term: &Terminal, glyph: char) -> IVec2 {
+        let i = term.iter().position(|t| t.glyph == glyph).unwrap();
+        IVec2::new(
+            i as i32 % term.width() as i32,
+            i as i32 / term.width() as i32,
+        )
+    }
+}
@@ -0,0 +1,166 @@
+//! Drawing to stacked layers of a single terminal.
+
+use std::{
+    borrow::Cow,
+    ops::{Deref, DerefMut},
+};
+
+use bevy::prelude::*;
+
+use crate::{Terminal, Tile};
+
+/// A single layer of a terminal, drawn to via [Terminal::draw_layer].
+///
+/// Derefs to a [Terminal] the same size as the one it belongs to, so it has the
+/// same drawing api. Tiles left as [Tile::default] are transparent.
+pub struct TerminalLayerWriter(Terminal);
+
+impl Deref for TerminalLayerWriter {
+    type Target = Terminal;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for TerminalLayerWriter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Terminal {
+    /// Draw to one of the terminal's layers, from 0 at the bottom to 255 at the top.
+    ///
+    /// Layer 0 is the base: once drawn to, it replaces all of the terminal's
+    /// own tiles when it's rendered, including it's default tiles. Higher
+    /// layers are drawn over the base, with a tile replacing the tiles below
+    /// it unless it's a default tile, which is transparent. The terminal's own
+    /// tiles are never modified.
+    ///
+    /// Layers are reset at the start of every frame, so they must be redrawn each
+    /// frame. Systems drawing layers should run before
+    /// [TERMINAL_UPDATE_TILE_DATA](crate::renderer::TERMINAL_UPDATE_TILE_DATA).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let mut term = Terminal::with_size([10, 10]);
+    /// term.put_char([1, 1], '.');
+    /// term.put_char([2, 2], '#');
+    /// term.draw_layer(1, |layer| layer.put_char([1, 1], '@'));
+    /// term.draw_layer(0, |layer| layer.put_char([1, 1], 'x'));
+    ///
+    /// let tiles = term.composited_tiles();
+    /// assert_eq!('@', tiles[term.to_index([1, 1])].glyph);
+    /// // Layer 0 replaces the terminal's tiles
+    /// assert_eq!(' ', tiles[term.to_index([2, 2])].glyph);
+    /// // The terminal itself is unchanged
+    /// assert_eq!('.', term.get_char([1, 1]));
+    /// ```
+    pub fn draw_layer(&mut self, z: u8, f: impl FnOnce(&mut TerminalLayerWriter)) {
+        let size = self.size();
+        let layer = self
+            .layers
+            .entry(z)
+            .or_insert_with(|| TerminalLayerWriter(Terminal::with_size(size.into())));
+        f(layer);
+    }
+
+    /// The terminal's tiles, or layer 0 if it's been drawn to, with all the
+    /// higher layers drawn on top.
+    pub fn composited_tiles(&self) -> Cow<'_, [Tile]> {
+        let tiles = self.tiles.slice(..);
+        if self.layers.is_empty() {
+            return Cow::Borrowed(tiles);
+        }
+
+        let mut composite = match self.layers.get(&0) {
+            Some(base) => base.tiles.slice(..).to_vec(),
+            None => tiles.to_vec(),
+        };
+        let transparent = Tile::default();
+        for layer in self.layers.range(1..).map(|(_, layer)| layer) {
+            for (dst, src) in composite.iter_mut().zip(layer.tiles.slice(..)) {
+                if *src != transparent {
+                    *dst = *src;
+                }
+            }
+        }
+        Cow::Owned(composite)
+    }
+
+    /// Remove all drawn layers.
+    pub fn clear_layers(&mut self) {
+        self.layers.clear();
+    }
+
+    /// True if anything has been drawn to a layer since they were last cleared.
+    pub fn has_layers(&self) -> bool {
+        !self.layers.is_empty()
+    }
+}
+
+/// Reset terminal layers at the start of each frame.
+pub(crate) fn terminal_clear_layers(mut q: Query<&mut Terminal>) {
+    for mut term in q.iter_mut() {
+        // Avoid flagging the terminal as changed if there's nothing to clear
+        if term.has_layers() {
+            term.clear_layers();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite() {
+        let mut term = Terminal::with_size([4, 4]);
+        term.put_char([0, 0], 'a');
+        term.put_char([1, 0], 'b');
+        term.put_char([3, 0], 'g');
+        assert!(matches!(term.composited_tiles(), Cow::Borrowed(_)));
+
+        term.draw_layer(200, |layer| {
+            layer.put_char([0, 0], 'c');
+            layer.put_char([2, 0], 'd');
+        });
+        // Higher layers are drawn over the terminal's tiles
+        assert_eq!('g', term.composited_tiles()[3].glyph);
+        assert_eq!('b', term.composited_tiles()[1].glyph);
+
+        // Layer 0 replaces them
+        term.draw_layer(0, |layer| {
+            layer.put_char([0, 0], 'e');
+            layer.put_char([1, 0], 'f');
+        });
+
+        let tiles = term.composited_tiles();
+        assert_eq!('c', tiles[0].glyph);
+        assert_eq!('f', tiles[1].glyph);
+        assert_eq!('d', tiles[2].glyph);
+        assert_eq!(' ', tiles[3].glyph);
+        assert_eq!("ab g", term.get_string([0, 0], 4));
+
+        term.clear_layers();
+        assert!(!term.has_layers());
+        assert_eq!(
+            "ab g",
+            term.composited_tiles()[..4]
+                .iter()
+                .map(|t| t.glyph)
+                .collect::<String>()
+        );
+
+        // Layers are cleared when the terminal is resized
+        term.draw_layer(3, |_| {});
+        term.resize([2, 2]);
+        assert!(!term.has_layers());
+        term.draw_layer(3, |layer| assert_eq!(UVec2::new(2, 2), layer.size()));
+    }
+}
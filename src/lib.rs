@@ -38,7 +38,7 @@
 //! fn main () {
 //!     App::new()
 //!     .add_plugins(DefaultPlugins)
-//!     .add_plugin(TerminalPlugin)
+//!     .add_plugin(TerminalPlugin::default())
 //!     .add_plugin(TiledCameraPlugin)
 //!     .add_startup_system(setup.system())
 //!     .run();
@@ -46,15 +46,110 @@
 //! ```
 pub mod renderer;
 
+mod accessibility;
+pub mod animation;
+#[cfg(feature = "image")]
+mod ascii_image;
+pub mod ascii_render;
+mod atlas_editor;
+mod auto_resize;
+#[cfg(feature = "terminal_backend")]
+pub mod backend;
+mod batch;
+mod blend;
+mod color_picker;
+mod color_scheme;
+mod console;
+#[cfg(feature = "console_log")]
+mod console_log;
+#[cfg(all(feature = "debug_overlay", debug_assertions))]
+pub mod debug_overlay;
+mod font_picker;
 pub mod formatting;
+mod heatmap;
+mod height_map;
+mod history;
+mod input_map;
+mod isometric;
+mod layers;
+mod lighting;
+mod line;
+#[cfg(feature = "map_gen")]
+mod map_gen;
+mod markup;
+mod menu;
+mod panel;
+mod path;
+pub mod picking;
+mod sprite_map;
 mod terminal;
+pub mod terminal_color256;
+pub mod text_wrap;
+mod tile_codec;
+mod tile_diff;
+mod tile_events;
+mod transition;
+pub mod ui;
+mod vt_parser;
+mod world;
 
-pub use terminal::{BorderGlyphs, Terminal, Tile};
+pub use accessibility::{
+    describe_tile, AccessibilityRole, TerminalAccessibilityEvent, TerminalAccessibilityPlugin,
+    TerminalAccessibilityRegion,
+};
+pub use animation::{
+    AnimationDoneEvent, TerminalAnimationClip, TerminalAnimationClipAssetLoader,
+    TerminalAnimationPlayer,
+};
+#[cfg(feature = "image")]
+pub use ascii_image::AsciiPalette;
+pub use atlas_editor::{
+    TerminalGlyphAtlasEditor, TerminalGlyphAtlasEditorPlugin, TERMINAL_ATLAS_EDITOR_INPUT,
+};
+pub use auto_resize::{ResizeStrategy, TerminalAutoResize, TerminalResizedEvent};
+pub use batch::{DrawCommand, TerminalBatchDrawer};
+pub use blend::TileBlendMode;
+pub use color_picker::{ColorChangedEvent, TerminalColorPicker, TerminalColorPickerPlugin};
+pub use color_scheme::TerminalColorScheme;
+pub use console::{
+    ConsoleCommandHandler, TerminalConsole, TerminalConsolePlugin, TerminalConsoleToggleKey,
+    TERMINAL_CONSOLE_INPUT,
+};
+#[cfg(feature = "console_log")]
+pub use console_log::{TerminalConsoleLogPlugin, TerminalLog, TERMINAL_CONSOLE_LOG_FLUSH};
+pub use font_picker::{FontSelectedEvent, TerminalFontPicker, TerminalFontPickerPlugin};
+pub use heatmap::ColorScale;
+pub use height_map::TerminalHeightMap;
+pub use history::TerminalHistory;
+pub use input_map::{TerminalAction, TerminalInputMap};
+pub use isometric::{Box3dSides, IsometricTransform};
+pub use layers::TerminalLayerWriter;
+pub use lighting::{compute_shadow_volume, ShadowCastingLight};
+pub use line::BresenhamLine;
+#[cfg(feature = "map_gen")]
+pub use map_gen::{BspDungeonGenerator, TerminalMapGenerator};
+pub use markup::{TerminalStringTokenizer, TextToken, TileStyle};
+pub use menu::{TerminalMenuFocus, TerminalMenuPlugin, TerminalMenuSelected, TerminalMenuWidget};
+pub use panel::{TerminalPanel, TerminalPanelRegistry};
+pub use path::TerminalPathRenderer;
+pub use picking::{TerminalMouseDragEvent, TerminalMousePickEvent};
+pub use sprite_map::{TerminalSprite, TerminalSpriteMap};
+pub use terminal::{BorderGlyphs, Terminal, TerminalSnapshot, TerminalTileFlags, Tile};
+pub use tile_codec::{DecodeError, TerminalGlyphDecoder, TerminalGlyphEncoder};
+pub use tile_diff::TerminalTileComparator;
+#[cfg(feature = "simd_tile_diff")]
+pub use tile_diff::TileChunkComparator;
+pub use tile_events::{TerminalEventWriter, TileChangedEvent};
+pub use transition::{TerminalTransition, TransitionDoneEvent, TransitionMode};
+pub use ui::{TerminalLayout, TerminalUI, TerminalWidget};
+pub use vt_parser::TerminalVTParser;
+pub use world::{TerminalWorld, WorldTileProvider};
 
 pub use formatting::{CharFormat, Pivot, StringFormat};
 pub use renderer::code_page_437;
 pub use renderer::material::BuiltInFontHandles;
 pub use renderer::material::TerminalMaterial;
+pub use renderer::{TerminalAppExt, TerminalBackground, TerminalClearColor, TerminalNoise};
 
 use bevy::prelude::*;
 
@@ -84,9 +179,99 @@ impl TerminalBundle {
 }
 
 /// Plugin for terminal rendering and related components and systems.
-pub struct TerminalPlugin;
+#[derive(Default)]
+pub struct TerminalPlugin {
+    render_layer: Option<u8>,
+    asset_directory: Option<&'static str>,
+    pool_size: usize,
+    #[cfg(feature = "terminal_backend")]
+    backend: Option<std::sync::Arc<std::sync::Mutex<dyn backend::TerminalPlatformBackend>>>,
+}
+
+impl TerminalPlugin {
+    pub fn new() -> Self {
+        TerminalPlugin::default()
+    }
+
+    /// Set the default render layer for all terminals.
+    ///
+    /// Individual terminals can override this via [TerminalRenderLayer](renderer::TerminalRenderLayer).
+    /// Layers past the last of bevy's 32 render layers are clamped to it.
+    pub fn with_render_layer(mut self, layer: u8) -> Self {
+        self.render_layer = Some(layer);
+        self
+    }
+
+    /// Set a directory, relative to the bevy asset folder, to discover user fonts in.
+    ///
+    /// Every image in the directory will be loaded on startup and can be retrieved
+    /// from [BuiltInFontHandles] by it's file name once loaded.
+    pub fn with_asset_directory(mut self, path: &'static str) -> Self {
+        self.asset_directory = Some(path);
+        self
+    }
+
+    /// Allocate meshes and materials for `size` terminals on startup.
+    ///
+    /// Terminals spawned with [TerminalRendererBundle::from_pool](renderer::TerminalRendererBundle::from_pool)
+    /// take their assets from the [TerminalAssetPool](renderer::TerminalAssetPool)
+    /// resource instead of allocating new ones.
+    pub fn with_pool_size(mut self, size: usize) -> Self {
+        self.pool_size = size;
+        self
+    }
+
+    /// Write terminals to a platform backend instead of rendering them on the
+    /// gpu. The gpu renderer's systems won't be added.
+    ///
+    /// Only available with the `terminal_backend` feature.
+    #[cfg(feature = "terminal_backend")]
+    pub fn with_backend(mut self, backend: impl backend::TerminalPlatformBackend) -> Self {
+        self.backend = Some(std::sync::Arc::new(std::sync::Mutex::new(backend)));
+        self
+    }
+}
+
 impl Plugin for TerminalPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(renderer::TerminalRendererPlugin);
+        if let Some(layer) = self.render_layer {
+            app.insert_resource(renderer::DefaultTerminalRenderLayer(layer));
+        }
+        app.init_resource::<TerminalColorScheme>()
+            .init_resource::<TerminalPanelRegistry>()
+            .init_resource::<TerminalSpriteMap>()
+            .add_asset::<TerminalAnimationClip>()
+            .init_asset_loader::<TerminalAnimationClipAssetLoader>()
+            .add_event::<AnimationDoneEvent>()
+            .add_event::<TransitionDoneEvent>()
+            .add_event::<TerminalMousePickEvent>()
+            .add_event::<TerminalMouseDragEvent>()
+            .add_event::<TileChangedEvent>()
+            .add_event::<TerminalResizedEvent>()
+            .add_system(animation::terminal_animation_update)
+            .add_system(picking::terminal_mouse_pick)
+            .add_system(picking::terminal_mouse_drag)
+            .add_system(tile_events::terminal_tile_changed_events)
+            .add_system(transition::terminal_update_transitions)
+            .add_system(ui::terminal_ui_draw)
+            .add_system(world::terminal_world_update_chunks)
+            .add_system_to_stage(CoreStage::PostUpdate, auto_resize::terminal_auto_resize);
+
+        #[cfg(feature = "terminal_backend")]
+        if let Some(backend) = &self.backend {
+            // Assets read by terminal systems which the renderer would add
+            if !app.world.contains_resource::<Assets<Image>>() {
+                app.add_asset::<Image>();
+            }
+            app.add_asset::<TerminalMaterial>()
+                .insert_resource(backend::TerminalBackend::new(backend.clone()))
+                .add_system_to_stage(CoreStage::Last, backend::terminal_backend_write);
+            return;
+        }
+        app.insert_resource(renderer::asset_pool::TerminalAssetPoolSize(self.pool_size))
+            .add_plugin(renderer::TerminalRendererPlugin);
+        if let Some(directory) = self.asset_directory {
+            app.add_plugin(renderer::user_fonts::UserFontPlugin { directory });
+        }
     }
 }
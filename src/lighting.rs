@@ -0,0 +1,243 @@
+//! Lighting terminal tiles from point lights which cast shadows.
+
+use bevy::prelude::*;
+
+use crate::{Terminal, Tile};
+
+/// A point light which is blocked by opaque tiles.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([20, 10]);
+/// for tile in term.iter_mut() {
+///     tile.glyph = '.';
+/// }
+/// term.put_char([6, 5], '#');
+///
+/// let torch = ShadowCastingLight::new([5, 5], 8).with_color(Color::ORANGE);
+/// term.apply_light_map(&[torch], 0.1, |t| t.glyph == '#');
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ShadowCastingLight {
+    /// The tile the light is centered on.
+    pub pos: UVec2,
+    /// How many tiles the light reaches.
+    pub radius: u32,
+    pub color: Color,
+    /// The light value at the light's position. It falls off linearly towards
+    /// the light's radius.
+    pub intensity: f32,
+}
+
+impl ShadowCastingLight {
+    /// A white light with an intensity of 1.
+    pub fn new(pos: [u32; 2], radius: u32) -> Self {
+        ShadowCastingLight {
+            pos: UVec2::from(pos),
+            radius,
+            color: Color::WHITE,
+            intensity: 1.0,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+}
+
+/// Transforms from octant space to terminal space for each of the 8 octants,
+/// as `[xx, xy, yx, yy]`.
+const OCTANTS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+/// The light value of every tile of the terminal from a single light, in the
+/// same order as the terminal's tiles.
+///
+/// Uses recursive shadowcasting: tiles for which `opaque_fn` returns true block
+/// the light, but are lit themselves. Tiles outside the terminal are treated as
+/// opaque.
+pub fn compute_shadow_volume(
+    terminal: &Terminal,
+    light: &ShadowCastingLight,
+    opaque_fn: impl Fn(&Tile) -> bool,
+) -> Vec<f32> {
+    let mut caster = ShadowCaster {
+        terminal,
+        light,
+        opaque_fn: &opaque_fn,
+        values: vec![0.0; terminal.iter().len()],
+    };
+
+    let center = light.pos.as_ivec2();
+    if terminal.is_in_bounds(center.into()) {
+        caster.light_tile(center, 0);
+        for octant in OCTANTS {
+            caster.cast(1, 1.0, 0.0, octant);
+        }
+    }
+    caster.values
+}
+
+struct ShadowCaster<'a> {
+    terminal: &'a Terminal,
+    light: &'a ShadowCastingLight,
+    opaque_fn: &'a dyn Fn(&Tile) -> bool,
+    values: Vec<f32>,
+}
+
+impl<'a> ShadowCaster<'a> {
+    fn is_opaque(&self, xy: IVec2) -> bool {
+        !self.terminal.is_in_bounds(xy.into())
+            || (self.opaque_fn)(self.terminal.get_tile(xy.into()))
+    }
+
+    fn light_tile(&mut self, xy: IVec2, distance_sq: i32) {
+        if !self.terminal.is_in_bounds(xy.into()) {
+            return;
+        }
+        let falloff = 1.0 - (distance_sq as f32).sqrt() / (self.light.radius + 1) as f32;
+        let value = self.light.intensity * falloff.max(0.0);
+        let i = self.terminal.to_index(xy.into());
+        self.values[i] = self.values[i].max(value);
+    }
+
+    /// Scan the rows of an octant from `row` outwards, between the `start` and
+    /// `end` slopes.
+    fn cast(&mut self, row: i32, mut start: f32, end: f32, octant: [i32; 4]) {
+        if start < end {
+            return;
+        }
+        let [xx, xy, yx, yy] = octant;
+        let radius = self.light.radius as i32;
+        let center = self.light.pos.as_ivec2();
+
+        let mut next_start = start;
+        for j in row..=radius {
+            let dy = -j;
+            let mut blocked = false;
+            for dx in -j..=0 {
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+
+                let pos = center + IVec2::new(dx * xx + dy * xy, dx * yx + dy * yy);
+                let distance_sq = dx * dx + dy * dy;
+                if distance_sq <= radius * radius {
+                    self.light_tile(pos, distance_sq);
+                }
+
+                let opaque = self.is_opaque(pos);
+                if blocked {
+                    if opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if opaque && j < radius {
+                    blocked = true;
+                    self.cast(j + 1, start, left_slope, octant);
+                    next_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+impl Terminal {
+    /// Light the terminal's tiles from the given lights, multiplying the light
+    /// color into each tile's foreground and background colors.
+    ///
+    /// `ambient` is the light value every tile receives regardless of the lights.
+    /// The light from every source is added together, so a tile lit by several
+    /// lights can be brighter than by any single one. See [compute_shadow_volume].
+    pub fn apply_light_map(
+        &mut self,
+        lights: &[ShadowCastingLight],
+        ambient: f32,
+        opaque_fn: impl Fn(&Tile) -> bool,
+    ) {
+        let mut light_map = vec![Vec3::splat(ambient); self.iter().len()];
+        for light in lights {
+            let color = Vec4::from(light.color.as_rgba_f32()).truncate();
+            let values = compute_shadow_volume(self, light, &opaque_fn);
+            for (total, value) in light_map.iter_mut().zip(values) {
+                *total += color * value;
+            }
+        }
+
+        let shade = |color: Color, light: Vec3| {
+            let [r, g, b, a] = color.as_rgba_f32();
+            let rgb = (Vec3::new(r, g, b) * light).clamp(Vec3::ZERO, Vec3::ONE);
+            Color::rgba(rgb.x, rgb.y, rgb.z, a)
+        };
+        for (tile, light) in self.iter_mut().zip(light_map) {
+            tile.fg_color = shade(tile.fg_color, light);
+            tile.bg_color = shade(tile.bg_color, light);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadows() {
+        let mut term = Terminal::with_size([11, 11]);
+        term.put_char([6, 5], '#');
+        let light = ShadowCastingLight::new([5, 5], 4);
+        let values = compute_shadow_volume(&term, &light, |t| t.glyph == '#');
+        let value = |xy: [i32; 2]| values[term.to_index(xy)];
+
+        assert_eq!(1.0, value([5, 5]));
+        assert_eq!(0.8, value([4, 5]));
+        assert_eq!(0.8, value([5, 6]));
+        // The wall is lit but the tiles behind it aren't
+        assert_eq!(0.8, value([6, 5]));
+        assert_eq!(0.0, value([7, 5]));
+        assert_eq!(0.0, value([9, 5]));
+        assert!(value([7, 7]) > 0.0);
+        // Out of range
+        assert_eq!(0.0, value([0, 5]));
+        assert!(value([1, 5]) > 0.0);
+        // Symmetric without the wall
+        assert_eq!(value([5, 2]), value([5, 8]));
+        assert_eq!(value([2, 3]), value([8, 7]));
+
+        for tile in term.iter_mut() {
+            tile.bg_color = Color::WHITE;
+        }
+        let red = light.with_color(Color::RED);
+        let blue = light.with_color(Color::BLUE).with_intensity(0.5);
+        term.apply_light_map(&[red, blue], 0.25, |t| t.glyph == '#');
+        assert_eq!(Color::rgb(1.0, 0.25, 0.75), term.get_tile([5, 5]).bg_color);
+        assert_eq!(Color::rgb(0.25, 0.25, 0.25), term.get_tile([9, 5]).bg_color);
+    }
+}
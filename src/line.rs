@@ -0,0 +1,192 @@
+//! Line rasterization for drawing to terminals.
+
+use bevy::math::{IVec2, Vec2};
+
+use crate::{Terminal, Tile};
+
+/// An iterator over the points of a line between two positions, using
+/// Bresenham's line algorithm.
+///
+/// Both the start and end points are included.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::BresenhamLine;
+///
+/// let points: Vec<_> = BresenhamLine::new([0, 0], [3, 1]).collect();
+/// assert_eq!(vec![[0, 0], [1, 0], [2, 1], [3, 1]], points);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BresenhamLine {
+    current: IVec2,
+    end: IVec2,
+    delta: IVec2,
+    step: IVec2,
+    error: i32,
+    done: bool,
+}
+
+impl BresenhamLine {
+    pub fn new(start: [i32; 2], end: [i32; 2]) -> Self {
+        let start = IVec2::from(start);
+        let end = IVec2::from(end);
+        let d = end - start;
+        let delta = IVec2::new(d.x.abs(), -d.y.abs());
+        Self {
+            current: start,
+            end,
+            delta,
+            step: d.signum() + IVec2::new((d.x == 0) as i32, (d.y == 0) as i32),
+            error: delta.x + delta.y,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for BresenhamLine {
+    type Item = [i32; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let point = self.current;
+        if point == self.end {
+            self.done = true;
+            return Some(point.into());
+        }
+
+        let e2 = self.error * 2;
+        if e2 >= self.delta.y {
+            self.error += self.delta.y;
+            self.current.x += self.step.x;
+        }
+        if e2 <= self.delta.x {
+            self.error += self.delta.x;
+            self.current.y += self.step.y;
+        }
+
+        Some(point.into())
+    }
+}
+
+impl Terminal {
+    /// Draw a quadratic bezier curve from `p0` to `p2`, bent towards `p1`.
+    ///
+    /// The curve is sampled at `segments` evenly spaced points which are joined
+    /// with [Terminal::draw_line]. Points are in terminal tile space and are
+    /// rounded to the nearest tile. Any part of the curve outside the terminal
+    /// will be skipped.
+    pub fn draw_bezier(&mut self, p0: Vec2, p1: Vec2, p2: Vec2, tile: Tile, segments: u32) {
+        self.draw_curve(segments, tile, |t| {
+            let u = 1.0 - t;
+            p0 * (u * u) + p1 * (2.0 * u * t) + p2 * (t * t)
+        });
+    }
+
+    /// Draw a cubic bezier curve from `p0` to `p3`, bent towards `p1` and `p2`.
+    ///
+    /// See [Terminal::draw_bezier].
+    pub fn draw_bezier_cubic(
+        &mut self,
+        p0: Vec2,
+        p1: Vec2,
+        p2: Vec2,
+        p3: Vec2,
+        tile: Tile,
+        segments: u32,
+    ) {
+        self.draw_curve(segments, tile, |t| {
+            let u = 1.0 - t;
+            p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+        });
+    }
+
+    /// Draw lines between points sampled from a curve at evenly spaced `t`
+    /// values from 0 to 1.
+    fn draw_curve(&mut self, segments: u32, tile: Tile, curve: impl Fn(f32) -> Vec2) {
+        let segments = segments.max(1);
+        let point = |i: u32| curve(i as f32 / segments as f32).round().as_ivec2().into();
+        let mut start = point(0);
+        for i in 1..=segments {
+            let end = point(i);
+            self.draw_line(start, end, tile);
+            start = end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        for end in [[5, 2], [-3, 7], [0, -4], [-6, -6], [0, 0]] {
+            let points: Vec<_> = BresenhamLine::new([0, 0], end).collect();
+            assert_eq!([0, 0], points[0]);
+            assert_eq!(end, *points.last().unwrap());
+
+            let d = IVec2::from(end).abs();
+            assert_eq!(d.x.max(d.y) as usize + 1, points.len());
+        }
+    }
+
+    #[test]
+    fn bezier() {
+        let tile = Tile {
+            glyph: '*',
+            ..Default::default()
+        };
+        let glyphs = |term: &Terminal| term.iter().map(|t| t.glyph).collect::<String>();
+
+        // Collinear control points sampled once per tile draw the same line
+        let lines = [
+            ([1, 2], [9, 2], 8),
+            ([0, 0], [7, 7], 7),
+            ([8, 1], [2, 7], 6),
+        ];
+        for (start, end, segments) in lines {
+            let mut line = Terminal::with_size([10, 10]);
+            line.draw_line(start, end, tile);
+
+            let [p0, p3] = [
+                Vec2::new(start[0] as f32, start[1] as f32),
+                Vec2::new(end[0] as f32, end[1] as f32),
+            ];
+            let mut quad = Terminal::with_size([10, 10]);
+            quad.draw_bezier(p0, p0.lerp(p3, 0.5), p3, tile, segments);
+            assert_eq!(glyphs(&line), glyphs(&quad));
+
+            let mut cubic = Terminal::with_size([10, 10]);
+            cubic.draw_bezier_cubic(
+                p0,
+                p0.lerp(p3, 1.0 / 3.0),
+                p0.lerp(p3, 2.0 / 3.0),
+                p3,
+                tile,
+                segments,
+            );
+            assert_eq!(glyphs(&line), glyphs(&cubic));
+        }
+
+        // A curve bends towards it's control point and stays connected
+        let mut term = Terminal::with_size([10, 10]);
+        term.draw_bezier(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.5, 12.0),
+            Vec2::new(9.0, 0.0),
+            tile,
+            16,
+        );
+        assert_eq!('*', term.get_char([0, 0]));
+        assert_eq!('*', term.get_char([9, 0]));
+        assert_eq!('*', term.get_char([4, 6]));
+        assert_eq!(' ', term.get_char([4, 0]));
+        for x in 0..10 {
+            assert!(term.column_iter(x).any(|t| t.glyph == '*'));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Map generation for terminals.
+//!
+//! Only available with the `map_gen` feature.
+
+use bevy::math::{IVec2, UVec2};
+use rand::Rng;
+
+use crate::{Terminal, Tile};
+
+/// A generator which fills a terminal with a map.
+///
+/// Generators should only use the public drawing API of the [Terminal].
+pub trait TerminalMapGenerator {
+    fn generate(&self, terminal: &mut Terminal, rng: &mut impl Rng);
+}
+
+/// A dungeon generator which uses binary space partitioning to fill a terminal
+/// with connected rooms.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut term = Terminal::with_size([40, 30]);
+/// let mut rng = StdRng::seed_from_u64(1);
+/// BspDungeonGenerator::default().generate(&mut term, &mut rng);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct BspDungeonGenerator {
+    /// The minimum size of a room, not including it's walls.
+    pub min_room_size: UVec2,
+    /// The maximum number of rooms to generate.
+    pub max_rooms: u32,
+    pub wall_tile: Tile,
+    pub floor_tile: Tile,
+}
+
+impl Default for BspDungeonGenerator {
+    fn default() -> Self {
+        Self {
+            min_room_size: UVec2::new(4, 4),
+            max_rooms: 12,
+            wall_tile: Tile {
+                glyph: '#',
+                ..Default::default()
+            },
+            floor_tile: Tile {
+                glyph: '.',
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// An area of the map, in terminal coordinates.
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    min: IVec2,
+    size: IVec2,
+}
+
+impl Rect {
+    fn center(&self) -> IVec2 {
+        self.min + self.size / 2
+    }
+}
+
+impl BspDungeonGenerator {
+    /// The minimum size of a partition which can hold a room and it's walls.
+    fn min_leaf_size(&self) -> IVec2 {
+        self.min_room_size.as_ivec2() + IVec2::splat(2)
+    }
+
+    /// Split a partition in two along it's longest splittable axis.
+    fn split(&self, leaf: Rect, rng: &mut impl Rng) -> Option<(Rect, Rect)> {
+        let min = self.min_leaf_size();
+        let can_split_x = leaf.size.x >= min.x * 2;
+        let can_split_y = leaf.size.y >= min.y * 2;
+
+        let axis = match (can_split_x, can_split_y) {
+            (true, true) if leaf.size.x >= leaf.size.y => 0,
+            (true, true) => 1,
+            (true, false) => 0,
+            (false, true) => 1,
+            (false, false) => return None,
+        };
+
+        let at = rng.gen_range(min[axis]..=leaf.size[axis] - min[axis]);
+        let mut a = leaf;
+        let mut b = leaf;
+        a.size[axis] = at;
+        b.min[axis] += at;
+        b.size[axis] -= at;
+        Some((a, b))
+    }
+
+    /// Pick a random room inside a partition, leaving space for walls.
+    fn room(&self, leaf: Rect, rng: &mut impl Rng) -> Option<Rect> {
+        let max = leaf.size - IVec2::splat(2);
+        if max.x < 1 || max.y < 1 {
+            return None;
+        }
+        let min = self.min_room_size.as_ivec2().min(max).max(IVec2::ONE);
+
+        let size = IVec2::new(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y));
+        let offset = IVec2::new(
+            rng.gen_range(0..=max.x - size.x),
+            rng.gen_range(0..=max.y - size.y),
+        );
+        Some(Rect {
+            min: leaf.min + IVec2::ONE + offset,
+            size,
+        })
+    }
+
+    fn carve(&self, terminal: &mut Terminal, rect: Rect) {
+        for y in rect.min.y..rect.min.y + rect.size.y {
+            for x in rect.min.x..rect.min.x + rect.size.x {
+                if terminal.is_in_bounds([x, y]) {
+                    terminal.put_tile([x, y], self.floor_tile);
+                }
+            }
+        }
+    }
+
+    /// Carve an L-shaped corridor between two points.
+    fn carve_corridor(&self, terminal: &mut Terminal, a: IVec2, b: IVec2, rng: &mut impl Rng) {
+        let corner = if rng.gen::<bool>() {
+            IVec2::new(b.x, a.y)
+        } else {
+            IVec2::new(a.x, b.y)
+        };
+        for (from, to) in [(a, corner), (corner, b)] {
+            let min = from.min(to);
+            let max = from.max(to);
+            self.carve(
+                terminal,
+                Rect {
+                    min,
+                    size: max - min + IVec2::ONE,
+                },
+            );
+        }
+    }
+}
+
+impl TerminalMapGenerator for BspDungeonGenerator {
+    fn generate(&self, terminal: &mut Terminal, rng: &mut impl Rng) {
+        for tile in terminal.iter_mut() {
+            *tile = self.wall_tile;
+        }
+
+        let mut leaves = vec![Rect {
+            min: IVec2::ZERO,
+            size: terminal.size().as_ivec2(),
+        }];
+
+        // Repeatedly split the largest partition until we have enough for every room.
+        while leaves.len() < self.max_rooms as usize {
+            leaves.sort_by_key(|leaf| leaf.size.x * leaf.size.y);
+            let split = (0..leaves.len())
+                .rev()
+                .find_map(|i| self.split(leaves[i], rng).map(|halves| (i, halves)));
+            match split {
+                Some((i, (a, b))) => {
+                    leaves[i] = a;
+                    leaves.push(b);
+                }
+                None => break,
+            }
+        }
+
+        // Order rooms spatially so corridors between consecutive rooms stay short.
+        leaves.sort_by_key(|leaf| (leaf.min.y, leaf.min.x));
+        let rooms: Vec<_> = leaves
+            .into_iter()
+            .filter_map(|leaf| self.room(leaf, rng))
+            .collect();
+
+        for room in rooms.iter() {
+            self.carve(terminal, *room);
+        }
+        for pair in rooms.windows(2) {
+            self.carve_corridor(terminal, pair[0].center(), pair[1].center(), rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn generates_connected_rooms() {
+        let mut term = Terminal::with_size([60, 40]);
+        let mut rng = StdRng::seed_from_u64(1234);
+        let gen = BspDungeonGenerator::default();
+        gen.generate(&mut term, &mut rng);
+
+        let is_floor = |xy: IVec2| {
+            term.is_in_bounds(xy.into()) && term.get_char(xy.into()) == gen.floor_tile.glyph
+        };
+
+        // At least one full room of the minimum size
+        let min = gen.min_room_size.as_ivec2();
+        let has_room = (0..40).any(|y| {
+            (0..60).any(|x| {
+                (0..min.y).all(|ry| (0..min.x).all(|rx| is_floor(IVec2::new(x + rx, y + ry))))
+            })
+        });
+        assert!(has_room);
+
+        // Every floor tile is reachable from every other
+        let floors: Vec<_> = (0..40)
+            .flat_map(|y| (0..60).map(move |x| IVec2::new(x, y)))
+            .filter(|xy| is_floor(*xy))
+            .collect();
+        let mut visited = vec![floors[0]];
+        let mut open = vec![floors[0]];
+        while let Some(p) = open.pop() {
+            for d in [IVec2::X, -IVec2::X, IVec2::Y, -IVec2::Y] {
+                let n = p + d;
+                if is_floor(n) && !visited.contains(&n) {
+                    visited.push(n);
+                    open.push(n);
+                }
+            }
+        }
+        assert_eq!(floors.len(), visited.len());
+    }
+}
@@ -0,0 +1,199 @@
+//! Parsing colors from markup in strings written to the terminal.
+//!
+//! Supported tags:
+//! - `[fg=color]text[/fg]` sets the foreground color of `text`.
+//! - `[bg=color]text[/bg]` sets the background color of `text`.
+//! - `[reset]` resets both colors to the starting style.
+//!
+//! Colors can be a name like `red` or a hex code like `#ff8000`. Tags can be
+//! nested, closing a tag restores the color from before it was opened. Any tag
+//! which can't be parsed is kept as literal text.
+
+use bevy::prelude::*;
+
+/// The colors of a run of text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileStyle {
+    pub fg_color: Color,
+    pub bg_color: Color,
+}
+
+impl Default for TileStyle {
+    fn default() -> Self {
+        Self {
+            fg_color: Color::WHITE,
+            bg_color: Color::BLACK,
+        }
+    }
+}
+
+impl TileStyle {
+    pub fn new(fg_color: Color, bg_color: Color) -> Self {
+        Self { fg_color, bg_color }
+    }
+}
+
+/// A run of text sharing the same style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextToken {
+    pub text: String,
+    pub style: TileStyle,
+}
+
+/// Splits a string with markup into runs of styled text.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let tokens = TerminalStringTokenizer::parse("a[fg=red]b[/fg]c");
+/// assert_eq!(3, tokens.len());
+/// assert_eq!("b", tokens[1].text);
+/// assert_eq!(Color::RED, tokens[1].style.fg_color);
+/// ```
+pub struct TerminalStringTokenizer;
+
+impl TerminalStringTokenizer {
+    /// Parse a string starting from the default style, white on black.
+    pub fn parse(input: &str) -> Vec<TextToken> {
+        Self::parse_with_style(input, TileStyle::default())
+    }
+
+    /// Parse a string starting from the given style. `[reset]` returns to this style.
+    pub fn parse_with_style(input: &str, base: TileStyle) -> Vec<TextToken> {
+        let mut tokens: Vec<TextToken> = Vec::new();
+        let mut fg_stack = Vec::new();
+        let mut bg_stack = Vec::new();
+        let mut style = base;
+
+        let mut push = |text: &str, style: TileStyle| {
+            if text.is_empty() {
+                return;
+            }
+            match tokens.last_mut() {
+                Some(last) if last.style == style => last.text.push_str(text),
+                _ => tokens.push(TextToken {
+                    text: text.to_string(),
+                    style,
+                }),
+            }
+        };
+
+        let mut rest = input;
+        while let Some(open) = rest.find('[') {
+            push(&rest[..open], style);
+            rest = &rest[open..];
+
+            let close = match rest.find(']') {
+                Some(close) => close,
+                None => break,
+            };
+            let tag = &rest[1..close];
+            let applied = match tag {
+                "reset" => {
+                    fg_stack.clear();
+                    bg_stack.clear();
+                    style = base;
+                    true
+                }
+                "/fg" => fg_stack.pop().map(|c| style.fg_color = c).is_some(),
+                "/bg" => bg_stack.pop().map(|c| style.bg_color = c).is_some(),
+                _ => match tag.split_once('=') {
+                    Some(("fg", color)) => parse_color(color)
+                        .map(|c| fg_stack.push(std::mem::replace(&mut style.fg_color, c)))
+                        .is_some(),
+                    Some(("bg", color)) => parse_color(color)
+                        .map(|c| bg_stack.push(std::mem::replace(&mut style.bg_color, c)))
+                        .is_some(),
+                    _ => false,
+                },
+            };
+
+            if applied {
+                rest = &rest[close + 1..];
+            } else {
+                // Keep the '[' as text and keep parsing after it, in case a
+                // valid tag starts inside the malformed one
+                push("[", style);
+                rest = &rest[1..];
+            }
+        }
+        push(rest, style);
+
+        tokens
+    }
+}
+
+/// Parse a color name or a hex code starting with `#`.
+fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        return Color::hex(hex).ok();
+    }
+    let color = match s.to_ascii_lowercase().as_str() {
+        "black" => Color::BLACK,
+        "white" => Color::WHITE,
+        "gray" | "grey" => Color::GRAY,
+        "red" => Color::RED,
+        "maroon" => Color::MAROON,
+        "orange" => Color::ORANGE,
+        "yellow" => Color::YELLOW,
+        "green" => Color::GREEN,
+        "lime" => Color::LIME_GREEN,
+        "cyan" => Color::CYAN,
+        "teal" => Color::TEAL,
+        "blue" => Color::BLUE,
+        "navy" => Color::NAVY,
+        "purple" => Color::PURPLE,
+        "pink" => Color::PINK,
+        "magenta" | "fuchsia" => Color::FUCHSIA,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(tokens: &[TextToken]) -> Vec<&str> {
+        tokens.iter().map(|t| t.text.as_str()).collect()
+    }
+
+    #[test]
+    fn tags() {
+        let tokens = TerminalStringTokenizer::parse("[fg=red]a[bg=#00ff00]b[/fg]c[reset]d");
+        assert_eq!(vec!["a", "b", "c", "d"], texts(&tokens));
+        assert_eq!(TileStyle::new(Color::RED, Color::BLACK), tokens[0].style);
+        assert_eq!(
+            TileStyle::new(Color::RED, Color::rgb(0.0, 1.0, 0.0)),
+            tokens[1].style
+        );
+        assert_eq!(
+            TileStyle::new(Color::WHITE, Color::rgb(0.0, 1.0, 0.0)),
+            tokens[2].style
+        );
+        assert_eq!(TileStyle::default(), tokens[3].style);
+    }
+
+    #[test]
+    fn nested() {
+        let tokens = TerminalStringTokenizer::parse("[fg=red]a[fg=blue]b[/fg]c[/fg]d");
+        assert_eq!(vec!["a", "b", "c", "d"], texts(&tokens));
+        assert_eq!(Color::RED, tokens[2].style.fg_color);
+        assert_eq!(Color::WHITE, tokens[3].style.fg_color);
+    }
+
+    #[test]
+    fn malformed() {
+        let tokens = TerminalStringTokenizer::parse("[fg=nope]a[/bg][b");
+        assert_eq!(vec!["[fg=nope]a[/bg][b"], texts(&tokens));
+
+        let tokens = TerminalStringTokenizer::parse("[[fg=red]a]");
+        assert_eq!(vec!["[", "a]"], texts(&tokens));
+        assert_eq!(Color::RED, tokens[1].style.fg_color);
+
+        assert!(TerminalStringTokenizer::parse("").is_empty());
+    }
+}
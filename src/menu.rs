@@ -0,0 +1,243 @@
+//! A simple widget for drawing a list of selectable options to a terminal.
+
+use bevy::prelude::*;
+
+use crate::{CharFormat, Terminal, TerminalAction, TerminalInputMap};
+
+/// Plugin which routes keyboard input to any [TerminalMenuWidget] with a
+/// [TerminalMenuFocus] component.
+///
+/// [TerminalAction::Up] and [TerminalAction::Down] change the selection and
+/// [TerminalAction::Confirm] sends a [TerminalMenuSelected] event. The keys for
+/// each action are set by the [TerminalInputMap] resource, which is added with
+/// the default bindings if it doesn't exist.
+pub struct TerminalMenuPlugin;
+
+impl Plugin for TerminalMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TerminalMenuSelected>()
+            .init_resource::<TerminalInputMap>()
+            .add_system(terminal_menu_input);
+    }
+}
+
+/// Marker component for the menu which should receive keyboard input.
+#[derive(Component, Default)]
+pub struct TerminalMenuFocus;
+
+/// Event sent when an item is chosen from a focused menu.
+pub struct TerminalMenuSelected {
+    /// The entity with the [TerminalMenuWidget].
+    pub entity: Entity,
+    /// The index of the chosen item.
+    pub index: usize,
+}
+
+/// A list of options drawn one per row, with the selected option highlighted.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([20, 10]);
+/// let mut menu = TerminalMenuWidget::new(["Play", "Options", "Quit"]);
+///
+/// menu.next();
+/// menu.draw(&mut term, [1, 8]);
+/// assert_eq!("Options", menu.selected_item());
+/// ```
+#[derive(Component, Clone)]
+pub struct TerminalMenuWidget {
+    pub items: Vec<String>,
+    pub selected: usize,
+    /// The format of unselected items.
+    pub normal_format: CharFormat,
+    /// The format of the selected item.
+    pub selected_format: CharFormat,
+    /// The width of each row in tiles. Items will be padded or truncated to fit.
+    pub width: u32,
+    /// If true the selection will wrap around when moving past the first or
+    /// last item, otherwise it will stop. Defaults to true.
+    pub wrap: bool,
+}
+
+impl Default for TerminalMenuWidget {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            selected: 0,
+            normal_format: CharFormat::default(),
+            selected_format: CharFormat::new(Color::BLACK, Color::WHITE),
+            width: 0,
+            wrap: true,
+        }
+    }
+}
+
+impl TerminalMenuWidget {
+    /// Create a menu from a list of items. The width will fit the longest item.
+    pub fn new<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        let items: Vec<String> = items.into_iter().map(Into::into).collect();
+        let width = items.iter().map(|s| s.chars().count()).max().unwrap_or(0) as u32;
+        Self {
+            items,
+            width,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_formats(mut self, normal: CharFormat, selected: CharFormat) -> Self {
+        self.normal_format = normal;
+        self.selected_format = selected;
+        self
+    }
+
+    /// Move the selection to the next item.
+    pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        } else if self.wrap {
+            self.selected = 0;
+        }
+    }
+
+    /// Move the selection to the previous item.
+    pub fn prev(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else if self.wrap {
+            self.selected = self.items.len() - 1;
+        }
+    }
+
+    /// The currently selected item, or an empty string if the menu is empty.
+    pub fn selected_item(&self) -> &str {
+        self.items
+            .get(self.selected)
+            .map(|s| s.as_str())
+            .unwrap_or("")
+    }
+
+    /// Draw the menu to the terminal with the first item at `xy`. Following items
+    /// are drawn on the rows below it.
+    pub fn draw(&self, terminal: &mut Terminal, xy: [i32; 2]) {
+        for (i, item) in self.items.iter().enumerate() {
+            let format = if i == self.selected {
+                self.selected_format
+            } else {
+                self.normal_format
+            };
+            let y = xy[1] - i as i32;
+
+            let mut chars = item.chars();
+            for x in 0..self.width as i32 {
+                let pos = [xy[0] + x, y];
+                let ch = chars.next().unwrap_or(' ');
+                if terminal.is_in_bounds(pos) {
+                    terminal.put_tile(pos, format.tile(ch));
+                }
+            }
+        }
+    }
+}
+
+fn terminal_menu_input(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<TerminalInputMap>,
+    mut events: EventWriter<TerminalMenuSelected>,
+    mut q: Query<(Entity, &mut TerminalMenuWidget), With<TerminalMenuFocus>>,
+) {
+    for (entity, mut menu) in q.iter_mut() {
+        if input_map.is_just_pressed(TerminalAction::Down, &keys) {
+            menu.next();
+        }
+        if input_map.is_just_pressed(TerminalAction::Up, &keys) {
+            menu.prev();
+        }
+        if input_map.is_just_pressed(TerminalAction::Confirm, &keys) && !menu.items.is_empty() {
+            events.send(TerminalMenuSelected {
+                entity,
+                index: menu.selected,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_prev() {
+        let mut menu = TerminalMenuWidget::new(["a", "b", "c"]);
+        menu.next();
+        menu.prev();
+        assert_eq!(0, menu.selected);
+
+        menu.prev();
+        assert_eq!("c", menu.selected_item());
+        menu.next();
+        assert_eq!("a", menu.selected_item());
+
+        let mut menu = menu.with_wrap(false);
+        menu.prev();
+        assert_eq!("a", menu.selected_item());
+        menu.selected = 2;
+        menu.next();
+        assert_eq!("c", menu.selected_item());
+    }
+
+    #[test]
+    fn draw() {
+        let mut term = Terminal::with_size([10, 5]);
+        let menu = TerminalMenuWidget::new(["Play", "Quit"]).with_width(6);
+        menu.draw(&mut term, [1, 4]);
+
+        assert_eq!("Play  ", term.get_string([1, 4], 6));
+        assert_eq!("Quit  ", term.get_string([1, 3], 6));
+        assert_eq!(Color::WHITE, term.get_tile([1, 4]).bg_color);
+        assert_eq!(Color::BLACK, term.get_tile([1, 3]).bg_color);
+    }
+
+    #[test]
+    fn rebound_input() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .add_plugin(TerminalMenuPlugin);
+        app.world
+            .get_resource_mut::<TerminalInputMap>()
+            .unwrap()
+            .bind(TerminalAction::Down, KeyCode::S);
+        let menu = app
+            .world
+            .spawn()
+            .insert(TerminalMenuWidget::new(["a", "b", "c"]))
+            .insert(TerminalMenuFocus)
+            .id();
+
+        let mut keys = app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+        keys.press(KeyCode::S);
+        keys.press(KeyCode::Down);
+        app.update();
+        // Both keys trigger the same action, which is only applied once
+        let selected = app.world.get::<TerminalMenuWidget>(menu).unwrap().selected;
+        assert_eq!(1, selected);
+    }
+}
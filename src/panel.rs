@@ -0,0 +1,267 @@
+//! Named rectangular regions of a terminal.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{BorderGlyphs, Terminal};
+
+/// A named rectangular region of a terminal, optionally with a border and title.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([40, 20]);
+/// let log = TerminalPanel::new("log", [0, 0], [40, 5]).with_title("Log");
+///
+/// log.draw_border(&mut term);
+/// assert_eq!("Log", term.get_string([1, 4], 3));
+/// ```
+#[derive(Component, Clone)]
+pub struct TerminalPanel {
+    pub name: String,
+    /// The bottom left corner of the panel.
+    pub xy: [i32; 2],
+    pub size: [u32; 2],
+    /// A title drawn on the top border of the panel.
+    pub title: Option<String>,
+    /// The glyphs used to draw the panel's border, if any.
+    pub border: Option<BorderGlyphs>,
+}
+
+impl TerminalPanel {
+    /// Create a panel with a single line border.
+    pub fn new(name: impl Into<String>, xy: [i32; 2], size: [u32; 2]) -> Self {
+        Self {
+            name: name.into(),
+            xy,
+            size,
+            title: None,
+            border: Some(crate::terminal::SINGLE_LINE_GLYPHS),
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the border glyphs for the panel, or `None` for no border.
+    pub fn with_border(mut self, border: Option<BorderGlyphs>) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// The bottom left corner and size of the panel's interior, not including it's border.
+    pub fn interior(&self) -> ([i32; 2], [u32; 2]) {
+        let [x, y] = self.xy;
+        let [width, height] = self.size;
+        match self.border {
+            Some(_) => (
+                [x + 1, y + 1],
+                [width.saturating_sub(2), height.saturating_sub(2)],
+            ),
+            None => (self.xy, self.size),
+        }
+    }
+
+    /// Draw the panel's border and title to the terminal.
+    ///
+    /// Does nothing if the panel has no border or is too small to have one.
+    pub fn draw_border(&self, terminal: &mut Terminal) {
+        let [width, height] = self.size;
+        let border = match self.border {
+            Some(border) if width >= 2 && height >= 2 => border,
+            _ => return,
+        };
+        terminal.draw_box(self.xy, self.size, border);
+
+        if let Some(title) = &self.title {
+            let [x, y] = self.xy;
+            let top = y + height as i32 - 1;
+            for (i, ch) in title.chars().take(width as usize - 2).enumerate() {
+                terminal.put_char([x + 1 + i as i32, top], ch);
+            }
+        }
+    }
+
+    /// Clear the interior of the panel, leaving the border untouched.
+    pub fn clear(&self, terminal: &mut Terminal) {
+        let (xy, size) = self.interior();
+        terminal.clear_box(xy, size);
+    }
+
+    /// Split the panel into a left and right panel, with the left panel taking
+    /// `ratio` of the columns, rounded down, and the right panel the rest.
+    ///
+    /// Both panels keep this panel's border, so their borders form a separator
+    /// between them. The left panel keeps the title. The panels are named
+    /// after this one with a `_left` or `_right` suffix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let screen = TerminalPanel::new("screen", [0, 0], [80, 40]);
+    /// let (map, sidebar) = screen.split_horizontal(0.7);
+    /// assert_eq!([56, 40], map.size);
+    /// assert_eq!([56, 0], sidebar.xy);
+    /// ```
+    pub fn split_horizontal(&self, ratio: f32) -> (TerminalPanel, TerminalPanel) {
+        let [x, y] = self.xy;
+        let [width, height] = self.size;
+        let left_width = split_len(width, ratio);
+        let left = TerminalPanel {
+            name: format!("{}_left", self.name),
+            size: [left_width, height],
+            ..self.clone()
+        };
+        let right = TerminalPanel {
+            name: format!("{}_right", self.name),
+            xy: [x + left_width as i32, y],
+            size: [width - left_width, height],
+            title: None,
+            ..self.clone()
+        };
+        (left, right)
+    }
+
+    /// Split the panel into a top and bottom panel, with the top panel taking
+    /// `ratio` of the rows, rounded down, and the bottom panel the rest.
+    ///
+    /// Both panels keep this panel's border, so their borders form a separator
+    /// between them. The top panel keeps the title. The panels are named after
+    /// this one with a `_top` or `_bottom` suffix.
+    pub fn split_vertical(&self, ratio: f32) -> (TerminalPanel, TerminalPanel) {
+        let [x, y] = self.xy;
+        let [width, height] = self.size;
+        let top_height = split_len(height, ratio);
+        let bottom_height = height - top_height;
+        let top = TerminalPanel {
+            name: format!("{}_top", self.name),
+            xy: [x, y + bottom_height as i32],
+            size: [width, top_height],
+            ..self.clone()
+        };
+        let bottom = TerminalPanel {
+            name: format!("{}_bottom", self.name),
+            size: [width, bottom_height],
+            title: None,
+            ..self.clone()
+        };
+        (top, bottom)
+    }
+}
+
+/// The length of the first part of `len` split at `ratio`.
+fn split_len(len: u32, ratio: f32) -> u32 {
+    ((len as f32 * ratio.clamp(0.0, 1.0)).floor() as u32).min(len)
+}
+
+/// A resource mapping names to [TerminalPanel]s, so systems can look up
+/// regions of a terminal without hard coding their positions.
+#[derive(Default)]
+pub struct TerminalPanelRegistry {
+    panels: HashMap<String, TerminalPanel>,
+}
+
+impl TerminalPanelRegistry {
+    /// Add a panel to the registry, replacing any existing panel with the same name.
+    pub fn insert(&mut self, panel: TerminalPanel) {
+        self.panels.insert(panel.name.clone(), panel);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TerminalPanel> {
+        self.panels.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut TerminalPanel> {
+        self.panels.get_mut(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<TerminalPanel> {
+        self.panels.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TerminalPanel> {
+        self.panels.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_inside_rect() {
+        let mut term = Terminal::with_size([10, 10]);
+        let panel = TerminalPanel::new("stats", [2, 3], [4, 3]);
+        panel.draw_border(&mut term);
+
+        for y in 0..10 {
+            for x in 0..10 {
+                let inside_rect = (2..6).contains(&x) && (3..6).contains(&y);
+                let interior = (3..5).contains(&x) && y == 4;
+                let drawn = term.get_char([x, y]) != ' ';
+                assert_eq!(inside_rect && !interior, drawn, "at {},{}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn clear_interior() {
+        let mut term = Terminal::with_size([10, 10]);
+        for t in term.iter_mut() {
+            t.glyph = 'x';
+        }
+        let panel = TerminalPanel::new("map", [0, 0], [5, 5]);
+        panel.draw_border(&mut term);
+        panel.clear(&mut term);
+
+        assert_eq!('┌', term.get_char([0, 4]));
+        assert_eq!("   ", term.get_string([1, 2], 3));
+        assert_eq!('│', term.get_char([4, 2]));
+        assert_eq!('x', term.get_char([5, 2]));
+    }
+
+    #[test]
+    fn split() {
+        let panel = TerminalPanel::new("main", [2, 1], [10, 6]).with_title("Main");
+        let (left, right) = panel.split_horizontal(0.7);
+        assert_eq!(
+            ("main_left", [2, 1], [7, 6]),
+            (&left.name[..], left.xy, left.size)
+        );
+        assert_eq!(([9, 1], [3, 6]), (right.xy, right.size));
+        assert_eq!(Some("Main"), left.title.as_deref());
+        assert!(right.title.is_none() && right.border.is_some());
+
+        // Odd widths round the first panel down
+        let panel = TerminalPanel::new("main", [0, 0], [11, 7]);
+        let (left, right) = panel.split_horizontal(0.5);
+        assert_eq!([5, 6], [left.size[0], right.size[0]]);
+        assert_eq!(5, right.xy[0]);
+
+        let (top, bottom) = panel.split_vertical(0.5);
+        assert_eq!([3, 4], [top.size[1], bottom.size[1]]);
+        assert_eq!(([0, 4], [0, 0]), (top.xy, bottom.xy));
+        assert_eq!(7, panel.split_vertical(1.5).0.size[1]);
+
+        // The borders of both halves meet in the middle
+        let mut term = Terminal::with_size([11, 7]);
+        left.draw_border(&mut term);
+        right.draw_border(&mut term);
+        assert_eq!("┐┌", term.get_string([4, 6], 2));
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = TerminalPanelRegistry::default();
+        registry.insert(TerminalPanel::new("log", [0, 0], [10, 4]));
+
+        registry.get_mut("log").unwrap().size = [20, 4];
+        assert_eq!([20, 4], registry.get("log").unwrap().size);
+        assert!(registry.get("map").is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! Drawing paths to terminals, for visualizing pathfinding results.
+
+use bevy::{math::UVec2, prelude::Color};
+
+use crate::{Terminal, Tile};
+
+/// Builds arrow tiles pointing along a path, for use with
+/// [Terminal::draw_path].
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([10, 10]);
+/// let path = [UVec2::new(1, 1), UVec2::new(2, 1), UVec2::new(3, 2)];
+/// let arrows = TerminalPathRenderer::new().with_fg(Color::YELLOW);
+///
+/// term.draw_path(&path, |from, to| arrows.arrow_tile(from, to));
+/// assert_eq!('→', term.get_char([1, 1]));
+/// assert_eq!('↗', term.get_char([2, 1]));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TerminalPathRenderer {
+    pub fg_color: Color,
+    pub bg_color: Color,
+}
+
+impl Default for TerminalPathRenderer {
+    fn default() -> Self {
+        let tile = Tile::default();
+        Self {
+            fg_color: tile.fg_color,
+            bg_color: tile.bg_color,
+        }
+    }
+}
+
+impl TerminalPathRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fg(mut self, color: Color) -> Self {
+        self.fg_color = color;
+        self
+    }
+
+    pub fn with_bg(mut self, color: Color) -> Self {
+        self.bg_color = color;
+        self
+    }
+
+    /// A tile with an arrow pointing from `from` towards `to`.
+    ///
+    /// Up is towards the top of the terminal. The straight arrows are part of
+    /// code page 437 but the diagonal arrows (`↖`, `↗`, `↘` and `↙`) aren't,
+    /// so they need a font which maps them. A step of zero gives a `•`.
+    pub fn arrow_tile(&self, from: UVec2, to: UVec2) -> Tile {
+        let d = to.as_ivec2() - from.as_ivec2();
+        let glyph = match (d.x.signum(), d.y.signum()) {
+            (1, 0) => '→',
+            (-1, 0) => '←',
+            (0, 1) => '↑',
+            (0, -1) => '↓',
+            (1, 1) => '↗',
+            (-1, 1) => '↖',
+            (1, -1) => '↘',
+            (-1, -1) => '↙',
+            _ => '•',
+        };
+        Tile {
+            glyph,
+            fg_color: self.fg_color,
+            bg_color: self.bg_color,
+            ..Default::default()
+        }
+    }
+}
+
+impl Terminal {
+    /// Draw a tile for each step of a path.
+    ///
+    /// `tile_fn` is given each pair of consecutive positions in the path and
+    /// it's tile is drawn at the first of them, so the final position is left
+    /// untouched. Any positions outside the terminal will be skipped.
+    pub fn draw_path(&mut self, path: &[UVec2], tile_fn: impl Fn(UVec2, UVec2) -> Tile) {
+        for step in path.windows(2) {
+            let xy = step[0].as_ivec2().into();
+            if self.is_in_bounds(xy) {
+                self.put_tile(xy, tile_fn(step[0], step[1]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_path() {
+        let mut term = Terminal::with_size([8, 3]);
+        let path: Vec<_> = (0..=5).map(|x| UVec2::new(x, 0)).collect();
+        let arrows = TerminalPathRenderer::new().with_fg(Color::RED);
+        term.draw_path(&path, |from, to| arrows.arrow_tile(from, to));
+
+        assert_eq!("→→→→→ ", term.get_string([0, 0], 6));
+        assert_eq!(Color::RED, term.get_tile([4, 0]).fg_color);
+
+        let dirs = [(1, 1, '↗'), (0, 1, '↑'), (-1, 1, '↖'), (-1, 0, '←')];
+        let center = UVec2::new(1, 1);
+        for (x, y, glyph) in dirs {
+            let to = (center.as_ivec2() + bevy::math::IVec2::new(x, y)).as_uvec2();
+            assert_eq!(glyph, arrows.arrow_tile(center, to).glyph);
+            assert_eq!(glyph, arrows.arrow_tile(center * 3, to * 3).glyph);
+        }
+        assert_eq!('↙', arrows.arrow_tile(center, UVec2::ZERO).glyph);
+        assert_eq!('↓', arrows.arrow_tile(center, UVec2::new(1, 0)).glyph);
+        assert_eq!('↘', arrows.arrow_tile(center, UVec2::new(2, 0)).glyph);
+    }
+}
@@ -0,0 +1,491 @@
+//! Mouse picking for terminals.
+
+use bevy::{prelude::*, render::camera::RenderTarget, ui::entity::CameraUi};
+
+use crate::{
+    renderer::{
+        material::TerminalMaterial, plugin::loaded_tile_size,
+        renderer_vertex_data::TerminalRendererVertexData, TerminalPivot, TerminalViewMatrix,
+        TilePivot, TileScaling,
+    },
+    Terminal,
+};
+
+/// Event sent when a mouse button is pressed while the cursor is over a
+/// terminal tile.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalMousePickEvent {
+    /// The terminal entity which was clicked.
+    pub entity: Entity,
+    /// The position of the clicked tile in the terminal.
+    pub xy: [i32; 2],
+    pub button: MouseButton,
+}
+
+/// Event sent every frame a mouse button is held while the cursor is over a
+/// terminal tile, including the frame it's pressed.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalMouseDragEvent {
+    /// The terminal entity under the cursor.
+    pub entity: Entity,
+    /// The position of the tile under the cursor in the terminal.
+    pub xy: [i32; 2],
+    pub button: MouseButton,
+}
+
+type PickCameras<'w, 's> =
+    Query<'w, 's, (&'static Camera, &'static GlobalTransform), Without<CameraUi>>;
+
+type PickTerminals<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Terminal,
+        &'static GlobalTransform,
+        &'static TerminalRendererVertexData,
+        Option<&'static TerminalViewMatrix>,
+    ),
+>;
+
+pub(crate) fn terminal_mouse_pick(
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    cameras: PickCameras,
+    terminals: PickTerminals,
+    mut events: EventWriter<TerminalMousePickEvent>,
+) {
+    for button in buttons.get_just_pressed() {
+        for (entity, xy) in tiles_under_cursor(&windows, &cameras, &terminals) {
+            events.send(TerminalMousePickEvent {
+                entity,
+                xy,
+                button: *button,
+            });
+        }
+    }
+}
+
+pub(crate) fn terminal_mouse_drag(
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    cameras: PickCameras,
+    terminals: PickTerminals,
+    mut events: EventWriter<TerminalMouseDragEvent>,
+) {
+    for button in buttons.get_pressed() {
+        for (entity, xy) in tiles_under_cursor(&windows, &cameras, &terminals) {
+            events.send(TerminalMouseDragEvent {
+                entity,
+                xy,
+                button: *button,
+            });
+        }
+    }
+}
+
+/// Every terminal tile under the cursor, from every camera.
+fn tiles_under_cursor(
+    windows: &Windows,
+    cameras: &PickCameras,
+    terminals: &PickTerminals,
+) -> Vec<(Entity, [i32; 2])> {
+    let mut tiles = Vec::new();
+    for (camera, camera_transform) in cameras.iter() {
+        let window = match camera.target {
+            RenderTarget::Window(id) => windows.get(id),
+            RenderTarget::Image(_) => None,
+        };
+        let world = match window.and_then(|w| cursor_to_world(w, camera, camera_transform)) {
+            Some(world) => world,
+            None => continue,
+        };
+
+        for (entity, terminal, transform, vert_data, view) in terminals.iter() {
+            let local = transform.compute_matrix().inverse().transform_point3(world);
+            let xy = first_tile(vert_data, view).and_then(|(min, tile_size)| {
+                local_to_tile(min, tile_size, terminal.size(), view, local.truncate())
+            });
+            if let Some(xy) = xy {
+                tiles.push((entity, xy));
+            }
+        }
+    }
+    tiles
+}
+
+/// The world position under the window's cursor, on the camera's near plane.
+fn cursor_to_world(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    screen_to_world(window.cursor_position()?, window, camera, camera_transform)
+}
+
+/// The world position, on the camera's near plane, of a position in the
+/// window in logical pixels from the bottom left. Returns `None` if the
+/// position is outside the window.
+pub fn screen_to_world(
+    screen_pos: Vec2,
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    let window_size = Vec2::new(window.width(), window.height());
+    window_to_world(screen_pos, window_size, camera, camera_transform)
+}
+
+fn window_to_world(
+    screen_pos: Vec2,
+    window_size: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    if screen_pos.cmplt(Vec2::ZERO).any() || screen_pos.cmpgt(window_size).any() {
+        return None;
+    }
+    let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+    let world = ndc_to_world.project_point3(ndc.extend(-1.0));
+    if world.is_nan() {
+        return None;
+    }
+    Some(world)
+}
+
+/// The components of a terminal which decide where it's tiles are drawn, for
+/// [screen_to_tile].
+#[derive(Clone, Copy)]
+pub struct TerminalPickLayout<'a> {
+    pub terminal: &'a Terminal,
+    pub transform: &'a GlobalTransform,
+    pub pivot: &'a TerminalPivot,
+    pub tile_pivot: &'a TilePivot,
+    pub scaling: &'a TileScaling,
+    pub view: Option<&'a TerminalViewMatrix>,
+}
+
+impl TerminalPickLayout<'_> {
+    /// The bottom left corner of the terminal's first tile in it's local
+    /// space, before the view matrix is applied.
+    fn first_tile_min(&self, tile_size: Vec2) -> Vec2 {
+        let size = self.terminal.size().as_vec2();
+        -(size * tile_size * self.pivot.0) - tile_size * self.tile_pivot.0
+    }
+}
+
+/// The tile of a terminal under a position in the window, in logical pixels
+/// from the bottom left, such as [Window::cursor_position].
+///
+/// The size of the terminal's tiles is found from it's [TileScaling] and
+/// `material` the same way the terminal's mesh is built.
+///
+/// Returns `None` if the position is outside the window or doesn't land on
+/// one of the terminal's tiles, or if the tile size depends on the material's
+/// font texture and it isn't loaded.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::{
+///     picking::{self, TerminalPickLayout},
+///     renderer::{material::TerminalMaterial, *},
+///     *,
+/// };
+///
+/// fn hover(
+///     windows: Res<Windows>,
+///     images: Res<Assets<Image>>,
+///     materials: Res<Assets<TerminalMaterial>>,
+///     cameras: Query<(&Camera, &GlobalTransform)>,
+///     terminals: Query<(
+///         &Terminal,
+///         &GlobalTransform,
+///         &TerminalPivot,
+///         &TilePivot,
+///         &TileScaling,
+///         &Handle<TerminalMaterial>,
+///         Option<&TerminalViewMatrix>,
+///     )>,
+/// ) {
+///     let window = windows.get_primary().unwrap();
+///     let cursor = match window.cursor_position() {
+///         Some(cursor) => cursor,
+///         None => return,
+///     };
+///     let (camera, camera_transform) = cameras.single();
+///     for (terminal, transform, pivot, tile_pivot, scaling, material, view) in terminals.iter() {
+///         let material = match materials.get(material) {
+///             Some(material) => material,
+///             None => continue,
+///         };
+///         let layout = TerminalPickLayout {
+///             terminal,
+///             transform,
+///             pivot,
+///             tile_pivot,
+///             scaling,
+///             view,
+///         };
+///         let tile = picking::screen_to_tile(
+///             cursor, window, camera, camera_transform, &layout, material, &images,
+///         );
+///         if let Some(xy) = tile {
+///             info!("Hovering {}", xy);
+///         }
+///     }
+/// }
+/// ```
+pub fn screen_to_tile(
+    screen_pos: Vec2,
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    layout: &TerminalPickLayout,
+    material: &TerminalMaterial,
+    images: &Assets<Image>,
+) -> Option<UVec2> {
+    let world = screen_to_world(screen_pos, window, camera, camera_transform)?;
+    let tile_size = loaded_tile_size(material, layout.scaling, images)?;
+    world_to_tile(world, layout, tile_size)
+}
+
+/// The tile of a terminal at a world position.
+fn world_to_tile(world: Vec3, layout: &TerminalPickLayout, tile_size: Vec2) -> Option<UVec2> {
+    let local = layout
+        .transform
+        .compute_matrix()
+        .inverse()
+        .transform_point3(world);
+    let min = layout.first_tile_min(tile_size);
+    let size = layout.terminal.size();
+    local_to_tile(min, tile_size, size, layout.view, local.truncate())
+        .map(|xy| IVec2::from(xy).as_uvec2())
+}
+
+/// The bottom left corner and size of a terminal's first tile, before the
+/// view matrix is applied, from it's vertex data.
+fn first_tile(
+    vert_data: &TerminalRendererVertexData,
+    view: Option<&TerminalViewMatrix>,
+) -> Option<(Vec2, Vec2)> {
+    // The bottom left and top right verts of the first tile
+    let (min, max) = match vert_data.verts.get(1..3) {
+        Some([min, max]) => (Vec3::from(*min).truncate(), Vec3::from(*max).truncate()),
+        _ => return None,
+    };
+    let tile_size = max - min;
+    let min = match view {
+        Some(view) => view.untransform_point((min + max) * 0.5) - tile_size * 0.5,
+        None => min,
+    };
+    Some((min, tile_size))
+}
+
+/// The tile containing a position in the terminal's local space, if any, for
+/// a terminal whose bottom left tile starts at `min` before it's view matrix
+/// is applied.
+fn local_to_tile(
+    min: Vec2,
+    tile_size: Vec2,
+    size: UVec2,
+    view: Option<&TerminalViewMatrix>,
+    local: Vec2,
+) -> Option<[i32; 2]> {
+    let local = match view {
+        Some(view) => view.untransform_point(local),
+        None => local,
+    };
+    let xy = ((local - min) / tile_size).floor().as_ivec2();
+    if xy.cmplt(IVec2::ZERO).any() || xy.cmpge(size.as_ivec2()).any() {
+        return None;
+    }
+    Some(xy.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::camera::CameraProjection;
+
+    use super::*;
+
+    fn pick_vert_data(
+        vert_data: &TerminalRendererVertexData,
+        size: UVec2,
+        view: Option<&TerminalViewMatrix>,
+        local: Vec2,
+    ) -> Option<[i32; 2]> {
+        let (min, tile_size) = first_tile(vert_data, view)?;
+        local_to_tile(min, tile_size, size, view, local)
+    }
+
+    #[test]
+    fn local_to_tile_pivot() {
+        let size = UVec2::new(4, 2);
+        let mut vert_data = TerminalRendererVertexData::default();
+        vert_data.resize(size, Vec2::splat(0.5), Vec2::ZERO, Vec2::new(2.0, 3.0));
+
+        assert_eq!(
+            Some([0, 0]),
+            pick_vert_data(&vert_data, size, None, Vec2::new(-3.5, -2.5))
+        );
+        assert_eq!(
+            Some([3, 1]),
+            pick_vert_data(&vert_data, size, None, Vec2::new(3.9, 2.9))
+        );
+        assert_eq!(
+            Some([2, 0]),
+            pick_vert_data(&vert_data, size, None, Vec2::new(0.0, -0.1))
+        );
+        assert_eq!(
+            None,
+            pick_vert_data(&vert_data, size, None, Vec2::new(4.1, 0.0))
+        );
+        assert_eq!(
+            None,
+            pick_vert_data(&vert_data, size, None, Vec2::new(0.0, -3.1))
+        );
+    }
+
+    #[test]
+    fn local_to_tile_view() {
+        let size = UVec2::new(4, 2);
+        let mut vert_data = TerminalRendererVertexData::default();
+        vert_data.resize(size, Vec2::splat(0.5), Vec2::ZERO, Vec2::ONE);
+        let view = TerminalViewMatrix::rotate_180();
+        view.apply(&mut vert_data);
+
+        // The first tile is drawn at the top right
+        let pick = |local: Vec2| pick_vert_data(&vert_data, size, Some(&view), local);
+        assert_eq!(Some([0, 0]), pick(Vec2::new(1.5, 0.5)));
+        assert_eq!(Some([3, 1]), pick(Vec2::new(-1.5, -0.5)));
+        assert_eq!(Some([1, 1]), pick(Vec2::new(0.5, -0.9)));
+        assert_eq!(None, pick(Vec2::new(2.1, 0.0)));
+
+        let mut vert_data = TerminalRendererVertexData::default();
+        vert_data.resize(size, Vec2::ZERO, Vec2::ZERO, Vec2::ONE);
+        let view = TerminalViewMatrix::flip_horizontal();
+        view.apply(&mut vert_data);
+        let pick = |local: Vec2| pick_vert_data(&vert_data, size, Some(&view), local);
+        assert_eq!(Some([0, 0]), pick(Vec2::new(-0.5, 0.5)));
+        assert_eq!(Some([3, 1]), pick(Vec2::new(-3.5, 1.5)));
+        assert_eq!(None, pick(Vec2::new(0.5, 0.5)));
+    }
+
+    #[test]
+    fn screen_to_tile() {
+        let window_size = Vec2::new(800.0, 600.0);
+        let mut projection = OrthographicProjection::default();
+        projection.update(window_size.x, window_size.y);
+        let camera = Camera {
+            projection_matrix: projection.get_projection_matrix(),
+            ..Default::default()
+        };
+        let camera_transform = GlobalTransform::from_xyz(0.0, 0.0, 999.9);
+        let terminal = Terminal::with_size([10, 5]);
+        let pick = |screen: Vec2, transform: &GlobalTransform, pivot: Vec2, tile_size: Vec2| {
+            let world = window_to_world(screen, window_size, &camera, &camera_transform)?;
+            let layout = TerminalPickLayout {
+                terminal: &terminal,
+                transform,
+                pivot: &TerminalPivot(pivot),
+                tile_pivot: &TilePivot::default(),
+                scaling: &TileScaling::World,
+                view: None,
+            };
+            world_to_tile(world, &layout, tile_size)
+        };
+        let centered = GlobalTransform::identity();
+
+        // A centered 80x40 pixel terminal, with 8 pixel tiles
+        let pixels = Vec2::splat(8.0);
+        let center = Vec2::new(400.0, 300.0);
+        let half = Vec2::splat(0.5);
+        assert_eq!(
+            Some(UVec2::new(0, 0)),
+            pick(center - Vec2::new(39.0, 19.0), &centered, half, pixels)
+        );
+        assert_eq!(
+            Some(UVec2::new(9, 4)),
+            pick(center + Vec2::new(39.0, 19.0), &centered, half, pixels)
+        );
+        assert_eq!(
+            Some(UVec2::new(5, 2)),
+            pick(center + Vec2::new(1.0, 1.0), &centered, half, pixels)
+        );
+        assert_eq!(
+            None,
+            pick(center + Vec2::new(41.0, 0.0), &centered, half, pixels)
+        );
+        // Outside the window
+        assert_eq!(None, pick(Vec2::new(-1.0, 300.0), &centered, half, pixels));
+
+        // Bottom left pivot, moved and scaled
+        let transform = GlobalTransform::from_xyz(-100.0, -50.0, 0.0).with_scale(Vec3::splat(2.0));
+        assert_eq!(
+            Some(UVec2::new(0, 0)),
+            pick(Vec2::new(301.0, 251.0), &transform, Vec2::ZERO, pixels)
+        );
+        assert_eq!(
+            Some(UVec2::new(1, 1)),
+            pick(Vec2::new(317.0, 267.0), &transform, Vec2::ZERO, pixels)
+        );
+        assert_eq!(
+            None,
+            pick(Vec2::new(299.0, 251.0), &transform, Vec2::ZERO, pixels)
+        );
+
+        // World scaling, where each tile is one unit
+        assert_eq!(
+            Some(UVec2::new(5, 2)),
+            pick(center + Vec2::new(0.5, 0.1), &centered, half, Vec2::ONE)
+        );
+        assert_eq!(
+            None,
+            pick(center + Vec2::new(5.5, 0.0), &centered, half, Vec2::ONE)
+        );
+
+        // Matches the tiles picked from the terminal's mesh
+        let tile_pivot = TilePivot(half);
+        let view = TerminalViewMatrix::rotate_180();
+        let mut vert_data = TerminalRendererVertexData::default();
+        vert_data.resize(terminal.size(), half, tile_pivot.0, pixels);
+        view.apply(&mut vert_data);
+        let layout = TerminalPickLayout {
+            terminal: &terminal,
+            transform: &centered,
+            pivot: &TerminalPivot(half),
+            tile_pivot: &tile_pivot,
+            scaling: &TileScaling::Pixels,
+            view: Some(&view),
+        };
+        let (min, tile_size) = first_tile(&vert_data, Some(&view)).unwrap();
+        for offset in [
+            Vec2::new(-39.0, -19.0),
+            Vec2::new(3.0, 5.0),
+            Vec2::new(43.0, 2.0),
+        ] {
+            let world = window_to_world(center + offset, window_size, &camera, &camera_transform);
+            let world = world.unwrap();
+            let local = world.truncate();
+            let from_mesh = local_to_tile(min, tile_size, terminal.size(), Some(&view), local);
+            assert_eq!(
+                from_mesh.map(|xy| IVec2::from(xy).as_uvec2()),
+                world_to_tile(world, &layout, pixels)
+            );
+        }
+        // The last tile is drawn at the bottom left, offset by the tile pivot
+        let world = window_to_world(
+            center + Vec2::new(-35.0, -15.0),
+            window_size,
+            &camera,
+            &camera_transform,
+        );
+        assert_eq!(
+            Some(UVec2::new(9, 4)),
+            world_to_tile(world.unwrap(), &layout, pixels)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Frame accumulation for motion blur, see [TerminalMaterial::accumulation_strength].
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    },
+};
+
+use super::material::TerminalMaterial;
+
+/// Resource holding a pair of images which terminals with an
+/// [accumulation_strength](TerminalMaterial::accumulation_strength) above 0
+/// are rendered to, one image per frame in turn.
+///
+/// Each frame the images are swapped. Cameras with a
+/// [TerminalAccumulationCamera] render to the current image, while terminal
+/// materials sample the previous image and blend it into their output. The
+/// previous image is sampled at the same screen position as the pixel being
+/// drawn, so the images should be the size of the window.
+///
+/// Rendering to an image requires it's own camera pass, see bevy's
+/// `render_to_texture` example. When no material has an accumulation strength
+/// above 0 the images are never swapped.
+pub struct TerminalAccumulationBuffer {
+    pub textures: [Handle<Image>; 2],
+    /// The index in `textures` of the image being rendered to this frame.
+    pub current: usize,
+}
+
+impl TerminalAccumulationBuffer {
+    /// Create the pair of images, `size` pixels wide and high.
+    pub fn new(size: UVec2, images: &mut Assets<Image>) -> Self {
+        let mut texture = || {
+            let mut image = Image::new_fill(
+                Extent3d {
+                    width: size.x.max(1),
+                    height: size.y.max(1),
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0, 0, 0, 0],
+                TextureFormat::Bgra8UnormSrgb,
+            );
+            image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT;
+            images.add(image)
+        };
+        Self {
+            textures: [texture(), texture()],
+            current: 0,
+        }
+    }
+
+    /// The image being rendered to this frame.
+    pub fn current(&self) -> &Handle<Image> {
+        &self.textures[self.current]
+    }
+
+    /// The image rendered to last frame.
+    pub fn previous(&self) -> &Handle<Image> {
+        &self.textures[1 - self.current]
+    }
+
+    /// Swap the current and previous images.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+}
+
+/// Camera component which points the camera's target at the current image of
+/// the [TerminalAccumulationBuffer] every frame.
+#[derive(Component, Default, Clone, Copy)]
+pub struct TerminalAccumulationCamera;
+
+/// Swap the accumulation images and point cameras and materials at them.
+///
+/// Materials using accumulation are modified every frame, so they're rebuilt
+/// on the gpu every frame.
+pub(crate) fn terminal_accumulation_swap(
+    buffer: Option<ResMut<TerminalAccumulationBuffer>>,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    mut cameras: Query<&mut Camera, With<TerminalAccumulationCamera>>,
+) {
+    let mut buffer = match buffer {
+        Some(buffer) => buffer,
+        None => return,
+    };
+    let accumulating: Vec<_> = materials
+        .iter()
+        .filter(|(_, m)| m.accumulation_strength > 0.0)
+        .map(|(id, _)| id)
+        .collect();
+    if accumulating.is_empty() {
+        return;
+    }
+
+    buffer.swap();
+    for mut camera in cameras.iter_mut() {
+        camera.target = RenderTarget::Image(buffer.current().clone());
+    }
+    for id in accumulating {
+        if let Some(material) = materials.get_mut(id) {
+            material.accumulation_texture = Some(buffer.previous().clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin, window::WindowId};
+
+    use super::*;
+
+    #[test]
+    fn swap() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .add_asset::<TerminalMaterial>()
+            .add_system(terminal_accumulation_swap);
+
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        let buffer = TerminalAccumulationBuffer::new(UVec2::new(64, 32), &mut images);
+        let [first, second] = buffer.textures.clone();
+        app.insert_resource(buffer);
+
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        let material = materials.add(TerminalMaterial::default());
+        let camera = app
+            .world
+            .spawn()
+            .insert(Camera::default())
+            .insert(TerminalAccumulationCamera)
+            .id();
+
+        // Nothing is swapped while no material uses accumulation
+        app.update();
+        let buffer = app
+            .world
+            .get_resource::<TerminalAccumulationBuffer>()
+            .unwrap();
+        assert_eq!(0, buffer.current);
+        let camera_target = |app: &App| app.world.get::<Camera>(camera).unwrap().target.clone();
+        assert_eq!(
+            RenderTarget::Window(WindowId::primary()),
+            camera_target(&app)
+        );
+
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        materials.get_mut(&material).unwrap().accumulation_strength = 0.5;
+        app.update();
+        assert_eq!(RenderTarget::Image(second.clone()), camera_target(&app));
+        let accumulation_texture = |app: &App| {
+            let materials = app
+                .world
+                .get_resource::<Assets<TerminalMaterial>>()
+                .unwrap();
+            materials
+                .get(&material)
+                .unwrap()
+                .accumulation_texture
+                .clone()
+        };
+        assert_eq!(Some(first.clone()), accumulation_texture(&app));
+
+        app.update();
+        assert_eq!(RenderTarget::Image(first), camera_target(&app));
+        assert_eq!(Some(second), accumulation_texture(&app));
+    }
+}
@@ -0,0 +1,172 @@
+//! Parsing of sprite sheet metadata exported from [Aseprite](https://www.aseprite.org).
+//!
+//! Both the "Hash" and "Array" json formats are supported. Each frame's name
+//! determines the glyph it's mapped to:
+//! - A name which is a single character maps to that character, ie: `"A"`.
+//! - Otherwise the last number in the name is used as a code page 437 index,
+//!   ie: `"font 65.aseprite"` maps to `'A'`.
+
+use std::fmt;
+
+use bevy::{math::UVec2, utils::HashMap};
+use serde::Deserialize;
+
+use super::code_page_437;
+
+/// An error produced when parsing Aseprite sprite sheet json.
+#[derive(Debug)]
+pub enum AsepriteLoadError {
+    /// The json was invalid or didn't match the Aseprite format.
+    Json(serde_json::Error),
+    /// A frame name couldn't be mapped to a glyph.
+    InvalidFrameName(String),
+}
+
+impl fmt::Display for AsepriteLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsepriteLoadError::Json(e) => write!(f, "Invalid Aseprite json: {}", e),
+            AsepriteLoadError::InvalidFrameName(name) => {
+                write!(f, "Unable to map Aseprite frame '{}' to a glyph", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsepriteLoadError {}
+
+impl From<serde_json::Error> for AsepriteLoadError {
+    fn from(e: serde_json::Error) -> Self {
+        AsepriteLoadError::Json(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct Sheet {
+    frames: Frames,
+    meta: Meta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Hash(HashMap<String, Frame>),
+    Array(Vec<NamedFrame>),
+}
+
+#[derive(Deserialize)]
+struct NamedFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: Frame,
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    frame: Rect,
+}
+
+#[derive(Deserialize)]
+struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    size: Size,
+}
+
+#[derive(Deserialize)]
+struct Size {
+    w: u32,
+    h: u32,
+}
+
+/// The glyphs and pixel bounds of every frame in an Aseprite sprite sheet.
+pub(crate) struct AsepriteFrames {
+    /// Each frame's glyph and it's bounds as (x, y, width, height).
+    pub frames: Vec<(char, [u32; 4])>,
+    /// The size of the sprite sheet in pixels.
+    pub size: UVec2,
+}
+
+pub(crate) fn parse_frames(json: &str) -> Result<AsepriteFrames, AsepriteLoadError> {
+    let sheet: Sheet = serde_json::from_str(json)?;
+
+    let frames: Vec<(String, Frame)> = match sheet.frames {
+        Frames::Hash(map) => map.into_iter().collect(),
+        Frames::Array(frames) => frames.into_iter().map(|f| (f.filename, f.frame)).collect(),
+    };
+
+    let glyphs = frames
+        .into_iter()
+        .map(|(name, frame)| {
+            let glyph = frame_glyph(&name).ok_or(AsepriteLoadError::InvalidFrameName(name))?;
+            let r = frame.frame;
+            Ok((glyph, [r.x, r.y, r.w, r.h]))
+        })
+        .collect::<Result<_, AsepriteLoadError>>()?;
+
+    Ok(AsepriteFrames {
+        frames: glyphs,
+        size: UVec2::new(sheet.meta.size.w, sheet.meta.size.h),
+    })
+}
+
+fn frame_glyph(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(ch);
+    }
+
+    let digits: String = name
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())?
+        .to_string();
+    let index: u8 = digits.parse().ok()?;
+    Some(code_page_437::index_to_glyph(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_names() {
+        assert_eq!(Some('A'), frame_glyph("A"));
+        assert_eq!(Some('A'), frame_glyph("font 65.aseprite"));
+        assert_eq!(Some('1'), frame_glyph("1"));
+        assert_eq!(Some('☺'), frame_glyph("frame 1"));
+        assert_eq!(None, frame_glyph("font.aseprite"));
+        assert_eq!(None, frame_glyph("font 300.aseprite"));
+    }
+
+    #[test]
+    fn array_format() {
+        let json = r#"{
+            "frames": [
+                { "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 100 },
+                { "filename": "b", "frame": { "x": 8, "y": 0, "w": 6, "h": 8 }, "duration": 100 }
+            ],
+            "meta": { "size": { "w": 16, "h": 8 } }
+        }"#;
+        let sheet = parse_frames(json).unwrap();
+        assert_eq!(UVec2::new(16, 8), sheet.size);
+        assert_eq!(('b', [8, 0, 6, 8]), sheet.frames[1]);
+    }
+
+    #[test]
+    fn invalid_name() {
+        let json = r#"{
+            "frames": { "sheet.aseprite": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } } },
+            "meta": { "size": { "w": 8, "h": 8 } }
+        }"#;
+        assert!(matches!(
+            parse_frames(json),
+            Err(AsepriteLoadError::InvalidFrameName(_))
+        ));
+    }
+}
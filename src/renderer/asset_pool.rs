@@ -0,0 +1,130 @@
+//! Pre-allocated mesh and material assets for spawning many terminals.
+
+use bevy::{prelude::*, render::mesh::PrimitiveTopology};
+
+use super::material::TerminalMaterial;
+
+/// Resource holding mesh and material handles allocated on startup by
+/// [TerminalPlugin::with_pool_size](crate::TerminalPlugin::with_pool_size).
+///
+/// Each pooled material is a copy of the default terminal material, so a
+/// terminal spawned from the pool can change it's material without affecting
+/// other terminals.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::{TerminalAssetPool, TerminalRendererBundle};
+///
+/// fn spawn(mut commands: Commands, mut pool: ResMut<TerminalAssetPool>) {
+///     for _ in 0..10 {
+///         commands.spawn_bundle(TerminalBundle {
+///             renderer: TerminalRendererBundle::from_pool(&mut pool),
+///             ..TerminalBundle::new().with_size([10, 10])
+///         });
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct TerminalAssetPool {
+    meshes: Vec<Handle<Mesh>>,
+    materials: Vec<Handle<TerminalMaterial>>,
+}
+
+/// Resource holding the number of terminals the [TerminalAssetPool] is
+/// allocated for, set by [TerminalPlugin::with_pool_size](crate::TerminalPlugin::with_pool_size)
+/// before the renderer plugin is added.
+pub(crate) struct TerminalAssetPoolSize(pub usize);
+
+impl TerminalAssetPool {
+    /// Allocate `size` empty terminal meshes and copies of the default
+    /// terminal material.
+    pub fn new(
+        size: usize,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<TerminalMaterial>,
+    ) -> Self {
+        let material = materials
+            .get(Handle::<TerminalMaterial>::default())
+            .cloned()
+            .unwrap_or_default();
+        Self {
+            meshes: (0..size)
+                .map(|_| meshes.add(Mesh::new(PrimitiveTopology::TriangleList)))
+                .collect(),
+            materials: (0..size).map(|_| materials.add(material.clone())).collect(),
+        }
+    }
+
+    /// Take a mesh and material handle from the pool, or `None` if it's
+    /// exhausted.
+    pub fn take(&mut self) -> Option<(Handle<Mesh>, Handle<TerminalMaterial>)> {
+        let mesh = self.meshes.pop()?;
+        let material = self.materials.pop()?;
+        Some((mesh, material))
+    }
+
+    /// The number of terminals which can still be spawned from the pool.
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::renderer::TerminalRendererBundle;
+
+    use super::*;
+
+    #[test]
+    fn take() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<TerminalMaterial>();
+        let default_material = TerminalMaterial {
+            clip_color: Color::RED,
+            ..Default::default()
+        };
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        materials.set_untracked(Handle::<TerminalMaterial>::default(), default_material);
+
+        let mut pool = app
+            .world
+            .resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+                let mut materials = world
+                    .get_resource_mut::<Assets<TerminalMaterial>>()
+                    .unwrap();
+                TerminalAssetPool::new(2, &mut meshes, &mut materials)
+            });
+        assert_eq!(2, pool.len());
+
+        let a = TerminalRendererBundle::from_pool(&mut pool);
+        let b = TerminalRendererBundle::from_pool(&mut pool);
+        assert!(pool.is_empty());
+        assert_ne!(a.mesh.0, b.mesh.0);
+        assert_ne!(a.material, b.material);
+        assert_ne!(Handle::default(), a.material);
+
+        let materials = app
+            .world
+            .get_resource::<Assets<TerminalMaterial>>()
+            .unwrap();
+        assert_eq!(Color::RED, materials.get(&a.material).unwrap().clip_color);
+
+        // An exhausted pool falls back to the default handles
+        let c = TerminalRendererBundle::from_pool(&mut pool);
+        assert_eq!(Handle::default(), c.material);
+        assert_eq!(Handle::default(), c.mesh.0);
+    }
+}
@@ -0,0 +1,192 @@
+//! Packing several font textures into a single texture.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension},
+};
+
+use super::font_asset::{FontError, TerminalFontAsset};
+
+/// The maximum width and height of a packed atlas in pixels.
+pub const MAX_ATLAS_SIZE: u32 = 4096;
+
+/// Where a single font ended up in a packed atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasEntry {
+    /// The uv of the top left of the font in the atlas.
+    pub uv_offset: Vec2,
+    /// The size of the whole font in atlas uvs.
+    pub uv_size: Vec2,
+    /// The number of glyphs along each axis of the font.
+    pub tile_count: UVec2,
+}
+
+impl AtlasEntry {
+    /// The uvs of a glyph from the font in the atlas, in the same order as
+    /// [UvMapping::get_grid_uvs](super::uv_mapping::UvMapping::get_grid_uvs).
+    pub fn glyph_uvs(&self, index: usize) -> [[f32; 2]; 4] {
+        let count = self.tile_count.max(UVec2::ONE);
+        let xy = UVec2::new(index as u32 % count.x, index as u32 / count.x).as_vec2();
+        let size = self.uv_size / count.as_vec2();
+        let origin = self.uv_offset + xy * size;
+        [
+            origin.into(),
+            (origin + Vec2::new(0.0, size.y)).into(),
+            (origin + Vec2::new(size.x, 0.0)).into(),
+            (origin + size).into(),
+        ]
+    }
+}
+
+impl TerminalFontAsset {
+    /// Pack the textures of several fonts into a single texture.
+    ///
+    /// Fonts are placed left to right in rows, in the order given. The returned
+    /// entries are in the same order as `fonts` and can be used to build a
+    /// [UvMapping](super::uv_mapping::UvMapping) for each font with
+    /// [UvMapping::from_atlas_entry](super::uv_mapping::UvMapping::from_atlas_entry).
+    ///
+    /// Note the atlas is not a 16x16 code page 437 sheet, so it won't work with
+    /// [TileScaling::Pixels](super::TileScaling::Pixels) or the material outline.
+    pub fn pack_into_atlas(
+        fonts: &[TerminalFontAsset],
+        images: &Assets<Image>,
+    ) -> Result<(Image, Vec<AtlasEntry>), FontError> {
+        let images = fonts
+            .iter()
+            .map(|font| images.get(&font.image).ok_or(FontError::MissingImage))
+            .collect::<Result<Vec<_>, _>>()?;
+        pack_images(&images, fonts.iter().map(|f| f.tile_count))
+    }
+}
+
+fn pack_images(
+    images: &[&Image],
+    tile_counts: impl Iterator<Item = UVec2>,
+) -> Result<(Image, Vec<AtlasEntry>), FontError> {
+    let format = images
+        .first()
+        .map(|image| image.texture_descriptor.format)
+        .unwrap_or(bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb);
+
+    // Find the position of every image
+    let mut positions = Vec::with_capacity(images.len());
+    let mut cursor = UVec2::ZERO;
+    let mut row_height = 0;
+    let mut atlas_size = UVec2::ZERO;
+    for image in images {
+        let size = image_size(image);
+        if image.texture_descriptor.format != format
+            || image.data.len() != (size.x * size.y * 4) as usize
+        {
+            return Err(FontError::UnsupportedFormat);
+        }
+        if cursor.x + size.x > MAX_ATLAS_SIZE {
+            cursor = UVec2::new(0, cursor.y + row_height);
+            row_height = 0;
+        }
+        if size.x > MAX_ATLAS_SIZE || cursor.y + size.y > MAX_ATLAS_SIZE {
+            return Err(FontError::AtlasTooBig);
+        }
+        positions.push(cursor);
+        atlas_size = atlas_size.max(cursor + size);
+        row_height = row_height.max(size.y);
+        cursor.x += size.x;
+    }
+    let atlas_size = atlas_size.max(UVec2::ONE);
+
+    let mut atlas = Image::new_fill(
+        Extent3d {
+            width: atlas_size.x,
+            height: atlas_size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        format,
+    );
+
+    let atlas_row_len = atlas_size.x as usize * 4;
+    let mut entries = Vec::with_capacity(images.len());
+    for ((image, pos), tile_count) in images.iter().zip(positions).zip(tile_counts) {
+        let size = image_size(image);
+        let row_len = size.x as usize * 4;
+        for (row, src) in image.data.chunks_exact(row_len).enumerate() {
+            let start = (pos.y as usize + row) * atlas_row_len + pos.x as usize * 4;
+            atlas.data[start..start + row_len].copy_from_slice(src);
+        }
+        entries.push(AtlasEntry {
+            uv_offset: pos.as_vec2() / atlas_size.as_vec2(),
+            uv_size: size.as_vec2() / atlas_size.as_vec2(),
+            tile_count,
+        });
+    }
+
+    Ok((atlas, entries))
+}
+
+fn image_size(image: &Image) -> UVec2 {
+    let size = image.texture_descriptor.size;
+    UVec2::new(size.width, size.height)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::TextureFormat;
+
+    use super::*;
+
+    fn image(width: u32, height: u32, value: u8) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[value, value, value, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn pack() {
+        let a = image(64, 32, 1);
+        let b = image(32, 64, 2);
+        let counts = [UVec2::new(8, 4), UVec2::new(4, 8)];
+        let (atlas, entries) = pack_images(&[&a, &b], counts.into_iter()).unwrap();
+
+        assert_eq!(UVec2::new(96, 64), image_size(&atlas));
+        assert_eq!(Vec2::ZERO, entries[0].uv_offset);
+        assert_eq!(Vec2::new(64.0 / 96.0, 0.5), entries[0].uv_size);
+        assert_eq!(Vec2::new(64.0 / 96.0, 0.0), entries[1].uv_offset);
+
+        // Pixels from each image are copied to their position in the atlas
+        let pixel = |x: usize, y: usize| atlas.data[(y * 96 + x) * 4];
+        assert_eq!(1, pixel(63, 31));
+        assert_eq!(0, pixel(63, 32));
+        assert_eq!(2, pixel(64, 63));
+
+        // Second glyph of the second font
+        let uvs = entries[1].glyph_uvs(1);
+        assert_eq!([72.0 / 96.0, 0.0], uvs[0]);
+        assert_eq!([80.0 / 96.0, 8.0 / 64.0], uvs[3]);
+    }
+
+    #[test]
+    fn too_big() {
+        let wide = image(MAX_ATLAS_SIZE + 1, 1, 0);
+        let counts = [UVec2::ONE];
+        assert!(matches!(
+            pack_images(&[&wide], counts.into_iter()),
+            Err(FontError::AtlasTooBig)
+        ));
+
+        let tall = image(MAX_ATLAS_SIZE, MAX_ATLAS_SIZE / 2 + 1, 0);
+        let counts = [UVec2::ONE, UVec2::ONE];
+        assert!(matches!(
+            pack_images(&[&tall, &tall], counts.into_iter()),
+            Err(FontError::AtlasTooBig)
+        ));
+    }
+}
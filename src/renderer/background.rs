@@ -0,0 +1,251 @@
+//! A terminal component for tiling a texture behind the terminal mesh.
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::Indices,
+        render_resource::{AddressMode, PrimitiveTopology},
+    },
+    sprite::{ColorMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::Terminal;
+
+use super::renderer_vertex_data::TerminalRendererVertexData;
+
+/// Terminal component which tiles a texture behind the terminal mesh.
+///
+/// The background is visible wherever the terminal's tiles are transparent. It
+/// is sized to the terminal's bounds and will follow the terminal when it's
+/// moved or resized.
+///
+/// The texture's sampler will be set to repeat so it can be tiled.
+#[derive(Component, Clone)]
+pub struct TerminalBackground {
+    pub texture: Handle<Image>,
+    /// The size of a single repetition of the texture, in terminal tiles.
+    pub tile_scale: Vec2,
+}
+
+impl TerminalBackground {
+    pub fn new(texture: Handle<Image>) -> Self {
+        Self {
+            texture,
+            tile_scale: Vec2::ONE,
+        }
+    }
+
+    pub fn with_tile_scale(mut self, tile_scale: Vec2) -> Self {
+        self.tile_scale = tile_scale;
+        self
+    }
+}
+
+/// Points to the entity rendering a terminal's background.
+#[derive(Component)]
+struct TerminalBackgroundQuad(Entity);
+
+pub(crate) struct TerminalBackgroundPlugin;
+
+impl Plugin for TerminalBackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_background_update.after(super::TERMINAL_UPDATE_SIZE))
+            .add_system(terminal_background_removed)
+            .add_system(terminal_background_sampler);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_background_update(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q: Query<
+        (
+            Entity,
+            &Terminal,
+            &TerminalBackground,
+            &TerminalRendererVertexData,
+            Option<&TerminalBackgroundQuad>,
+        ),
+        Or<(
+            Changed<TerminalBackground>,
+            Changed<TerminalRendererVertexData>,
+        )>,
+    >,
+) {
+    for (entity, term, background, vert_data, quad) in q.iter() {
+        if let Some(quad) = quad {
+            // Recursively, so the quad is also removed from the terminal's children
+            commands.entity(quad.0).despawn_recursive();
+        }
+
+        let (min, max) = match mesh_bounds(vert_data) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+
+        let uv_max = background_uv_size(term.size(), background.tile_scale);
+        let mesh = quad_mesh(min, max, Vec2::ZERO, uv_max);
+
+        let quad = commands
+            .spawn_bundle(ColorMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                material: materials.add(background.texture.clone().into()),
+                transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                ..Default::default()
+            })
+            .id();
+
+        commands
+            .entity(entity)
+            .add_child(quad)
+            .insert(TerminalBackgroundQuad(quad));
+    }
+}
+
+fn terminal_background_removed(
+    mut commands: Commands,
+    removed: RemovedComponents<TerminalBackground>,
+    q: Query<&TerminalBackgroundQuad>,
+) {
+    for entity in removed.iter() {
+        if let Ok(quad) = q.get(entity) {
+            commands.entity(quad.0).despawn_recursive();
+            commands.entity(entity).remove::<TerminalBackgroundQuad>();
+        }
+    }
+}
+
+/// Ensure background textures repeat so they can be tiled.
+fn terminal_background_sampler(mut images: ResMut<Assets<Image>>, q: Query<&TerminalBackground>) {
+    for background in q.iter() {
+        set_repeat(&mut images, &background.texture);
+    }
+}
+
+/// Set the texture's sampler to repeat, if it's loaded and doesn't already.
+pub(super) fn set_repeat(images: &mut Assets<Image>, texture: &Handle<Image>) {
+    let repeats = match images.get(texture) {
+        Some(image) => {
+            let sampler = &image.sampler_descriptor;
+            sampler.address_mode_u == AddressMode::Repeat
+                && sampler.address_mode_v == AddressMode::Repeat
+        }
+        None => return,
+    };
+
+    if !repeats {
+        let image = images.get_mut(texture).unwrap();
+        image.sampler_descriptor.address_mode_u = AddressMode::Repeat;
+        image.sampler_descriptor.address_mode_v = AddressMode::Repeat;
+    }
+}
+
+/// The min and max corners of the terminal mesh, or `None` if it's empty.
+pub(super) fn mesh_bounds(vert_data: &TerminalRendererVertexData) -> Option<(Vec2, Vec2)> {
+    if vert_data.verts.is_empty() {
+        return None;
+    }
+    let (min, max) = vert_data.verts.iter().map(|v| Vec3::from(*v)).fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), v| (min.min(v), max.max(v)),
+    );
+    Some((min.truncate(), max.truncate()))
+}
+
+/// The uv extents for a background covering a terminal of the given size.
+fn background_uv_size(term_size: UVec2, tile_scale: Vec2) -> Vec2 {
+    term_size.as_vec2() / tile_scale.max(Vec2::splat(f32::EPSILON))
+}
+
+pub(super) fn quad_mesh(min: Vec2, max: Vec2, uv_min: Vec2, uv_max: Vec2) -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    // 0---2
+    // | / |
+    // 1---3
+    let positions = vec![
+        [min.x, max.y, 0.0],
+        [min.x, min.y, 0.0],
+        [max.x, max.y, 0.0],
+        [max.x, min.y, 0.0],
+    ];
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 4]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, quad_uvs(uv_min, uv_max));
+    mesh.set_indices(Some(Indices::U32(vec![0, 1, 2, 3, 2, 1])));
+    mesh
+}
+
+/// The uvs of each vertex of a [quad_mesh].
+pub(super) fn quad_uvs(uv_min: Vec2, uv_max: Vec2) -> Vec<[f32; 2]> {
+    vec![
+        [uv_min.x, uv_min.y],
+        [uv_min.x, uv_max.y],
+        [uv_max.x, uv_min.y],
+        [uv_max.x, uv_max.y],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin};
+
+    use super::*;
+
+    #[test]
+    fn quad_children() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .add_asset::<Mesh>()
+            .add_asset::<ColorMaterial>()
+            .add_plugin(TerminalBackgroundPlugin);
+
+        let size = UVec2::new(4, 2);
+        let term = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([4, 2]))
+            .insert(TerminalRendererVertexData::with_size(size))
+            .insert(TerminalBackground::new(Handle::default()))
+            .id();
+        app.update();
+
+        let children = |app: &App| {
+            let children = app.world.get::<Children>(term);
+            children.map(|c| c.to_vec()).unwrap_or_default()
+        };
+        let first = children(&app);
+        assert_eq!(1, first.len());
+
+        // Rebuilding the quad replaces the old child
+        app.world
+            .get_mut::<TerminalRendererVertexData>(term)
+            .unwrap()
+            .set_changed();
+        app.update();
+        let second = children(&app);
+        assert_eq!(1, second.len());
+        assert_ne!(first, second);
+        assert!(app.world.get_entity(first[0]).is_none());
+
+        app.world.entity_mut(term).remove::<TerminalBackground>();
+        app.update();
+        assert!(children(&app).is_empty());
+        assert!(app.world.get_entity(second[0]).is_none());
+    }
+
+    #[test]
+    fn uv_size() {
+        assert_eq!(
+            Vec2::new(20.0, 10.0),
+            background_uv_size(UVec2::new(20, 10), Vec2::ONE)
+        );
+        assert_eq!(
+            Vec2::new(5.0, 5.0),
+            background_uv_size(UVec2::new(20, 10), Vec2::new(4.0, 2.0))
+        );
+    }
+}
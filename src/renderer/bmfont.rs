@@ -0,0 +1,193 @@
+//! Exporting fonts to the BMFont format, for use in other engines and tools.
+
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use super::{
+    code_page_437,
+    font_asset::{FontError, TerminalFontAsset},
+};
+
+impl TerminalFontAsset {
+    /// Describe the font as a BMFont text `.fnt` file, returned with a copy of
+    /// the font's image.
+    ///
+    /// The `.fnt` file refers to the image as `font.png`, so the image should
+    /// be saved with that name next to it. Glyphs are numbered row by row from
+    /// the top left and use the unicode character of their code page 437
+    /// glyph as their id. Glyph 0 is given id 0 and glyph 255 the non-breaking
+    /// space, since both are blank like the space. Glyphs past the first 256,
+    /// like the extra glyphs of a [TerminalAtlasBuilder](super::font_builder::TerminalAtlasBuilder)
+    /// atlas, are numbered in order from the start of unicode's Private Use
+    /// Area at U+E000.
+    ///
+    /// Every glyph is a whole tile which advances by the tile width plus the
+    /// font's [kerning](TerminalFontAsset::kerning).
+    pub fn export_to_fnt(&self, images: &Assets<Image>) -> Result<(Vec<u8>, Image), FontError> {
+        let image = images
+            .get(&self.image)
+            .ok_or(FontError::MissingImage)?
+            .clone();
+        let size = image.texture_descriptor.size;
+        let tile = self.tile_size;
+        let count = self.tile_count.x * self.tile_count.y;
+        let base = tile.y.saturating_sub(self.metrics.baseline_offset);
+        let advance = (tile.x as i32 + self.kerning).max(0);
+
+        let mut fnt = String::new();
+        // Writing to a string can't fail
+        let _ = writeln!(
+            fnt,
+            "info face=\"terminal\" size={} bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=0 aa=1 padding=0,0,0,0 spacing=0,0",
+            tile.y
+        );
+        let _ = writeln!(
+            fnt,
+            "common lineHeight={} base={} scaleW={} scaleH={} pages=1 packed=0",
+            tile.y, base, size.width, size.height
+        );
+        let _ = writeln!(fnt, "page id=0 file=\"font.png\"");
+        let _ = writeln!(fnt, "chars count={}", count);
+        for i in 0..count {
+            let x = (i % self.tile_count.x) * tile.x;
+            let y = (i / self.tile_count.x) * tile.y;
+            let _ = writeln!(
+                fnt,
+                "char id={} x={} y={} width={} height={} xoffset=0 yoffset=0 xadvance={} page=0 chnl=15",
+                glyph_id(i),
+                x,
+                y,
+                tile.x,
+                tile.y,
+                advance
+            );
+        }
+        Ok((fnt.into_bytes(), image))
+    }
+}
+
+/// The BMFont id of the glyph at an index in the font image.
+fn glyph_id(index: u32) -> u32 {
+    match index {
+        0 => 0,
+        255 => 0xA0,
+        i if i < 256 => code_page_437::index_to_glyph(i as u8) as u32,
+        i => 0xE000 + (i - 256),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+    use crate::renderer::{font_asset::TerminalFontMetrics, font_builder::TerminalAtlasBuilder};
+
+    #[test]
+    fn export_to_fnt() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Image>();
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: 128,
+                height: 192,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        let font = TerminalFontAsset {
+            image,
+            tile_count: UVec2::new(16, 16),
+            tile_size: UVec2::new(8, 12),
+            clip_color: Color::BLACK,
+            metrics: TerminalFontMetrics::from_tile_height(12),
+            sdf_mode: false,
+            kerning: 1,
+            pixel_density_hint: 1.0,
+        };
+
+        let (fnt, fnt_image) = font.export_to_fnt(&images).unwrap();
+        assert_eq!(128, fnt_image.texture_descriptor.size.width);
+        let fnt = String::from_utf8(fnt).unwrap();
+        assert!(fnt.contains("common lineHeight=12 base=10 scaleW=128 scaleH=192"));
+        assert!(fnt.contains("chars count=256\n"));
+
+        let chars: Vec<_> = fnt.lines().filter(|l| l.starts_with("char ")).collect();
+        assert_eq!(256, chars.len());
+        let field = |line: &str, name: &str| -> u32 {
+            let prefix = format!("{}=", name);
+            let value = line.split(' ').find_map(|f| f.strip_prefix(&prefix[..]));
+            value.unwrap().parse().unwrap()
+        };
+        for (i, line) in chars.iter().enumerate() {
+            assert_eq!((i as u32 % 16) * 8, field(line, "x"));
+            assert_eq!((i as u32 / 16) * 12, field(line, "y"));
+            assert_eq!(8, field(line, "width"));
+            assert_eq!(12, field(line, "height"));
+            assert_eq!(9, field(line, "xadvance"));
+        }
+        let mut ids: Vec<_> = chars.iter().map(|l| field(l, "id")).collect();
+        assert_eq!(['A' as u32, '☺' as u32], [ids[65], ids[1]]);
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(256, ids.len());
+
+        let missing = TerminalFontAsset {
+            image: Handle::default(),
+            ..font
+        };
+        assert!(matches!(
+            missing.export_to_fnt(&images),
+            Err(FontError::MissingImage)
+        ));
+    }
+
+    #[test]
+    fn atlas_ids() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Image>();
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        // The layout of a TerminalAtlasBuilder atlas
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: 128,
+                height: 256,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        let font = TerminalFontAsset {
+            image,
+            tile_count: UVec2::new(16, 32),
+            tile_size: UVec2::new(8, 8),
+            clip_color: Color::BLACK,
+            metrics: TerminalFontMetrics::from_tile_height(8),
+            sdf_mode: false,
+            kerning: 0,
+            pixel_density_hint: 1.0,
+        };
+
+        let (fnt, _) = font.export_to_fnt(&images).unwrap();
+        let fnt = String::from_utf8(fnt).unwrap();
+        let mut ids: Vec<u32> = fnt
+            .lines()
+            .filter_map(|l| l.strip_prefix("char id="))
+            .map(|l| l.split(' ').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(TerminalAtlasBuilder::GLYPH_COUNT, ids.len());
+        assert_eq!([0xE000, 0xE0FF], [ids[256], ids[511]]);
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(TerminalAtlasBuilder::GLYPH_COUNT, ids.len());
+    }
+}
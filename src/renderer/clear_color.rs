@@ -0,0 +1,58 @@
+//! A terminal component for filling the terminal's background with a solid color.
+
+use bevy::prelude::*;
+
+use super::{material::TerminalMaterial, owned_material::TerminalOwnedMaterial};
+
+/// Terminal component which fills any clipped parts of the terminal's font
+/// texture with a solid color, without having to set the background color of
+/// every tile.
+///
+/// Tile background colors are still blended on top of the clear color.
+///
+/// Since the clear color is part of the [TerminalMaterial], it's drawn by the
+/// terminal's [TerminalOwnedMaterial].
+#[derive(Component, Clone, Copy)]
+pub struct TerminalClearColor(pub Color);
+
+pub(crate) struct TerminalClearColorPlugin;
+
+impl Plugin for TerminalClearColorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_clear_color_update)
+            .add_system(terminal_clear_color_removed);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_clear_color_update(
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    q: Query<
+        (
+            &TerminalClearColor,
+            &Handle<TerminalMaterial>,
+            &TerminalOwnedMaterial,
+        ),
+        Or<(Changed<TerminalClearColor>, Changed<TerminalOwnedMaterial>)>,
+    >,
+) {
+    for (clear_color, handle, owned) in q.iter() {
+        if let Some(material) = owned.get_mut(handle, &mut materials) {
+            material.clear_color = Some(clear_color.0);
+        }
+    }
+}
+
+fn terminal_clear_color_removed(
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    removed: RemovedComponents<TerminalClearColor>,
+    q: Query<(&Handle<TerminalMaterial>, &TerminalOwnedMaterial)>,
+) {
+    for entity in removed.iter() {
+        if let Ok((handle, owned)) = q.get(entity) {
+            if let Some(material) = owned.get_mut(handle, &mut materials) {
+                material.clear_color = None;
+            }
+        }
+    }
+}
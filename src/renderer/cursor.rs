@@ -0,0 +1,225 @@
+//! Terminal components for drawing a cursor over part or all of a tile.
+
+use bevy::prelude::*;
+
+use super::{
+    material::{font_tile_size, TerminalMaterial},
+    owned_material::TerminalOwnedMaterial,
+    renderer_vertex_data::TerminalRendererVertexData,
+};
+use crate::Terminal;
+
+/// Terminal component which draws a solid colored cursor over one of it's tiles.
+///
+/// The cursor is drawn by the terminal's shader on top of the tile, so the
+/// terminal's tiles are never modified. It's shape can be changed by adding a
+/// [TerminalCursorShape] to the terminal.
+///
+/// Since the cursor is part of the [TerminalMaterial], it's drawn by the
+/// terminal's [TerminalOwnedMaterial].
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::{TerminalCursor, TerminalCursorShape};
+///
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 5]))
+///         .insert(TerminalCursor::new([0, 0]))
+///         .insert(TerminalCursorShape::Underline(2));
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TerminalCursor {
+    /// The tile the cursor is drawn over. The cursor is hidden if this is out
+    /// of bounds.
+    pub xy: [i32; 2],
+    pub color: Color,
+}
+
+impl TerminalCursor {
+    /// A white cursor at the given position.
+    pub fn new(xy: [i32; 2]) -> Self {
+        TerminalCursor {
+            xy,
+            color: Color::WHITE,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Terminal component which sets the shape of the terminal's [TerminalCursor].
+///
+/// Sizes are in pixels of the terminal's font texture, and are clamped to the
+/// size of a tile. A terminal without a shape uses a [TerminalCursorShape::Block].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalCursorShape {
+    /// Covers the whole tile.
+    Block,
+    /// Covers the bottom of the tile, with the given height.
+    Underline(u32),
+    /// Covers the left side of the tile, with the given width.
+    Bar(u32),
+}
+
+impl Default for TerminalCursorShape {
+    fn default() -> Self {
+        TerminalCursorShape::Block
+    }
+}
+
+pub(crate) struct TerminalCursorPlugin;
+
+impl Plugin for TerminalCursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_cursor_update)
+            .add_system(terminal_cursor_removed);
+    }
+}
+
+/// The cursor rect, as x, y, width and height in the terminal mesh's local
+/// space, for a cursor over the tile at `index`.
+///
+/// `tile_pixels` is the size of a tile in the font texture, required for any
+/// shape other than [TerminalCursorShape::Block].
+fn cursor_rect(
+    shape: TerminalCursorShape,
+    verts: &[[f32; 3]],
+    index: usize,
+    tile_pixels: Option<UVec2>,
+) -> Option<Vec4> {
+    let quad = verts.get(index * 4..index * 4 + 4)?;
+    // See TerminalRendererVertexData::resize for the vertex order
+    let min = Vec2::new(quad[1][0], quad[1][1]);
+    let size = Vec2::new(quad[2][0], quad[2][1]) - min;
+
+    let size = match shape {
+        TerminalCursorShape::Block => size,
+        TerminalCursorShape::Underline(height) => {
+            let pixels = tile_pixels?.y.max(1);
+            Vec2::new(size.x, size.y * height.min(pixels) as f32 / pixels as f32)
+        }
+        TerminalCursorShape::Bar(width) => {
+            let pixels = tile_pixels?.x.max(1);
+            Vec2::new(size.x * width.min(pixels) as f32 / pixels as f32, size.y)
+        }
+    };
+    Some(Vec4::new(min.x, min.y, size.x, size.y))
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_cursor_update(
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    images: Res<Assets<Image>>,
+    q: Query<(
+        &Terminal,
+        &TerminalCursor,
+        Option<&TerminalCursorShape>,
+        &TerminalRendererVertexData,
+        &Handle<TerminalMaterial>,
+        &TerminalOwnedMaterial,
+    )>,
+) {
+    for (term, cursor, shape, vertex_data, handle, owned) in q.iter() {
+        let material = match materials.get(handle) {
+            Some(material) if owned.handle() == handle => material,
+            _ => continue,
+        };
+
+        let rect = if term.is_in_bounds(cursor.xy) {
+            let tile_pixels = material
+                .texture
+                .as_ref()
+                .and_then(|texture| images.get(texture))
+                .map(font_tile_size);
+            cursor_rect(
+                shape.copied().unwrap_or_default(),
+                &vertex_data.verts,
+                term.to_index(cursor.xy),
+                tile_pixels,
+            )
+        } else {
+            None
+        };
+
+        // Avoid re-uploading the material every frame when nothing changed
+        if material.cursor_rect == rect && material.cursor_color == cursor.color {
+            continue;
+        }
+        if let Some(material) = owned.get_mut(handle, &mut materials) {
+            material.cursor_rect = rect;
+            material.cursor_color = cursor.color;
+        }
+    }
+}
+
+fn terminal_cursor_removed(
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    removed: RemovedComponents<TerminalCursor>,
+    q: Query<(&Handle<TerminalMaterial>, &TerminalOwnedMaterial)>,
+) {
+    for entity in removed.iter() {
+        if let Ok((handle, owned)) = q.get(entity) {
+            if let Some(material) = owned.get_mut(handle, &mut materials) {
+                material.cursor_rect = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::material::TerminalMaterialFlags;
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let mut vertex_data = TerminalRendererVertexData::with_size(UVec2::new(4, 2));
+        vertex_data.resize(
+            UVec2::new(4, 2),
+            Vec2::ZERO,
+            Vec2::ZERO,
+            Vec2::new(2.0, 4.0),
+        );
+        let term = Terminal::with_size([4, 2]);
+        let index = term.to_index([1, 1]);
+        let verts = &vertex_data.verts;
+        let tile_pixels = Some(UVec2::new(8, 16));
+
+        let block = cursor_rect(TerminalCursorShape::Block, verts, index, None);
+        assert_eq!(Some(Vec4::new(2.0, 4.0, 2.0, 4.0)), block);
+
+        let underline = cursor_rect(TerminalCursorShape::Underline(4), verts, index, tile_pixels);
+        assert_eq!(Some(Vec4::new(2.0, 4.0, 2.0, 1.0)), underline);
+
+        let bar = cursor_rect(TerminalCursorShape::Bar(2), verts, index, tile_pixels);
+        assert_eq!(Some(Vec4::new(2.0, 4.0, 0.5, 4.0)), bar);
+
+        // Clamped to the size of the tile
+        let bar = cursor_rect(TerminalCursorShape::Bar(100), verts, index, tile_pixels);
+        assert_eq!(Some(Vec4::new(2.0, 4.0, 2.0, 4.0)), bar);
+
+        // Partial shapes can't be sized until the font is loaded
+        assert_eq!(
+            None,
+            cursor_rect(TerminalCursorShape::Bar(2), verts, index, None)
+        );
+        assert_eq!(
+            None,
+            cursor_rect(TerminalCursorShape::Block, verts, 8, None)
+        );
+
+        let material = TerminalMaterial {
+            cursor_rect: block,
+            ..Default::default()
+        };
+        assert!(material.flags().contains(TerminalMaterialFlags::CURSOR));
+    }
+}
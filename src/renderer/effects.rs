@@ -0,0 +1,382 @@
+//! Composable post-processing effects for terminal materials.
+//!
+//! Each [TerminalEffect] sets one or more shader settings on a [TerminalMaterial].
+//! A [TerminalEffectChain] on a terminal entity applies it's effects to the
+//! terminal's material every frame.
+//!
+//! # Example
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_ascii_terminal::*;
+//! use bevy_ascii_terminal::renderer::effects::*;
+//!
+//! fn setup(mut commands: Commands) {
+//!     let bundle = TerminalBundle::new().with_size([40, 20]);
+//!     commands.spawn_bundle(bundle).insert(
+//!         TerminalEffectChain::new()
+//!             .with(ScanlineEffect { strength: 0.3 })
+//!             .with(VignetteEffect { radius: 0.6 }),
+//!     );
+//! }
+//! ```
+
+use bevy::{asset::HandleId, prelude::*};
+
+use super::{
+    material::{ChromaticAberration, TerminalMaterial},
+    owned_material::TerminalOwnedMaterial,
+};
+
+/// An effect which modifies a terminal material's shader settings.
+pub trait TerminalEffect: Send + Sync + 'static {
+    /// Apply the effect to the material. `time` is the time in seconds since
+    /// startup and can be used to animate the effect.
+    fn apply(&self, material: &mut TerminalMaterial, time: f32);
+}
+
+/// Darkens every other row of screen pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct ScanlineEffect {
+    /// How much the rows are darkened, from 0 to 1.
+    pub strength: f32,
+}
+
+impl TerminalEffect for ScanlineEffect {
+    fn apply(&self, material: &mut TerminalMaterial, _time: f32) {
+        material.scanline_strength = self.strength;
+    }
+}
+
+/// Animated per-pixel noise, like film grain or tv static.
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseEffect {
+    pub strength: f32,
+}
+
+impl TerminalEffect for NoiseEffect {
+    fn apply(&self, material: &mut TerminalMaterial, time: f32) {
+        material.noise_strength = self.strength;
+        // Keep the seed small so it doesn't lose precision in the shader
+        material.noise_seed = (time * 60.0).floor() % 1000.0;
+    }
+}
+
+/// Darkens the screen towards it's edges.
+#[derive(Debug, Clone, Copy)]
+pub struct VignetteEffect {
+    /// The distance from the center of the screen where darkening starts,
+    /// where 1 is the edge of the screen.
+    pub radius: f32,
+}
+
+impl TerminalEffect for VignetteEffect {
+    fn apply(&self, material: &mut TerminalMaterial, _time: f32) {
+        material.vignette_radius = Some(self.radius);
+    }
+}
+
+/// Shifts the red and blue channels of the glyphs apart horizontally.
+#[derive(Debug, Clone, Copy)]
+pub struct ChromaticAberrationEffect {
+    /// The offset of each channel in uvs.
+    pub offset: f32,
+}
+
+impl TerminalEffect for ChromaticAberrationEffect {
+    fn apply(&self, material: &mut TerminalMaterial, _time: f32) {
+        material.chromatic_aberration = Some(ChromaticAberration::horizontal(self.offset));
+    }
+}
+
+/// Terminal component holding a list of effects which are applied, in order,
+/// to the terminal's material every frame.
+///
+/// The effects are drawn by the terminal's [TerminalOwnedMaterial], so other
+/// terminals using the same material aren't affected. Each frame the chain is
+/// applied to a copy of the material taken before any effect was applied, so
+/// removing an effect, or the whole chain, restores the settings it changed.
+/// Settings drawn by other terminal components, such as the cursor, clear
+/// color and fonts, are kept.
+///
+/// The material is only modified, and rebuilt on the gpu, on frames where the
+/// effects change it.
+#[derive(Component, Default)]
+pub struct TerminalEffectChain {
+    pub effects: Vec<Box<dyn TerminalEffect>>,
+}
+
+impl TerminalEffectChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an effect to the end of the chain.
+    pub fn with(mut self, effect: impl TerminalEffect) -> Self {
+        self.effects.push(Box::new(effect));
+        self
+    }
+
+    /// Apply every effect in the chain to a material.
+    pub fn apply(&self, material: &mut TerminalMaterial, time: f32) {
+        for effect in self.effects.iter() {
+            effect.apply(material, time);
+        }
+    }
+}
+
+/// Terminal component holding the terminal's owned material as it was before
+/// any effect was applied.
+#[derive(Component)]
+pub(crate) struct TerminalEffectBase {
+    material: HandleId,
+    base: TerminalMaterial,
+}
+
+pub(crate) struct TerminalEffectsPlugin;
+
+impl Plugin for TerminalEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_apply_effects)
+            .add_system(terminal_effects_removed);
+    }
+}
+
+/// Copy the settings drawn by other terminal components from the live
+/// material, so effects don't overwrite them.
+fn keep_component_settings(material: &mut TerminalMaterial, live: &TerminalMaterial) {
+    material.texture = live.texture.clone();
+    material.clear_color = live.clear_color;
+    material.cursor_rect = live.cursor_rect;
+    material.cursor_color = live.cursor_color;
+    material.invert_colors = live.invert_colors;
+    material.accumulation_texture = live.accumulation_texture.clone();
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_apply_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    q: Query<(
+        Entity,
+        &TerminalEffectChain,
+        &Handle<TerminalMaterial>,
+        &TerminalOwnedMaterial,
+        Option<&TerminalEffectBase>,
+    )>,
+) {
+    let t = time.seconds_since_startup() as f32;
+    for (entity, chain, handle, owned, base) in q.iter() {
+        let material = match materials.get(handle) {
+            Some(material) if owned.handle() == handle => material,
+            _ => continue,
+        };
+        let mut applied = match base {
+            Some(base) if base.material == handle.id => base.base.clone(),
+            // A new owned material, take it's settings as the base
+            _ => {
+                commands.entity(entity).insert(TerminalEffectBase {
+                    material: handle.id,
+                    base: material.clone(),
+                });
+                material.clone()
+            }
+        };
+        chain.apply(&mut applied, t);
+        keep_component_settings(&mut applied, material);
+
+        // Modifying the material rebuilds it's gpu data, so skip it if nothing changed
+        if applied == *material {
+            continue;
+        }
+        if let Some(material) = owned.get_mut(handle, &mut materials) {
+            *material = applied;
+        }
+    }
+}
+
+fn terminal_effects_removed(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    removed: RemovedComponents<TerminalEffectChain>,
+    q: Query<(
+        &Handle<TerminalMaterial>,
+        &TerminalOwnedMaterial,
+        &TerminalEffectBase,
+    )>,
+) {
+    for entity in removed.iter() {
+        if let Ok((handle, owned, base)) = q.get(entity) {
+            if base.material == handle.id {
+                if let Some(material) = owned.get_mut(handle, &mut materials) {
+                    let mut restored = base.base.clone();
+                    keep_component_settings(&mut restored, material);
+                    *material = restored;
+                }
+            }
+            commands.entity(entity).remove::<TerminalEffectBase>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin};
+
+    use super::super::{
+        material::TerminalMaterialFlags, owned_material::TerminalOwnedMaterialPlugin,
+    };
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<TerminalMaterial>()
+            .add_plugin(TerminalOwnedMaterialPlugin)
+            .add_plugin(TerminalEffectsPlugin);
+        app
+    }
+
+    fn material(app: &App, entity: Entity) -> TerminalMaterial {
+        let handle = app.world.get::<Handle<TerminalMaterial>>(entity).unwrap();
+        let materials = app
+            .world
+            .get_resource::<Assets<TerminalMaterial>>()
+            .unwrap();
+        materials.get(handle).unwrap().clone()
+    }
+
+    #[test]
+    fn owned_base() {
+        let mut app = app();
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        let shared = materials.add(TerminalMaterial::default());
+        let plain = app.world.spawn().insert(shared.clone()).id();
+        let term = app
+            .world
+            .spawn()
+            .insert(shared.clone())
+            .insert(
+                TerminalEffectChain::new()
+                    .with(ScanlineEffect { strength: 0.5 })
+                    .with(VignetteEffect { radius: 0.6 }),
+            )
+            .id();
+        app.update();
+
+        let applied = material(&app, term);
+        assert_eq!(0.5, applied.scanline_strength);
+        assert_eq!(Some(0.6), applied.vignette_radius);
+        // The shared material is untouched
+        assert_ne!(&shared, app.world.get(term).unwrap());
+        assert_eq!(0.0, material(&app, plain).scanline_strength);
+
+        // Settings drawn by other components are kept
+        let handle = app.world.get::<TerminalOwnedMaterial>(term).unwrap();
+        let handle = handle.handle().clone();
+        app.world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap()
+            .get_mut(&handle)
+            .unwrap()
+            .clear_color = Some(Color::BLUE);
+
+        // Removing an effect restores it's settings
+        app.world
+            .get_mut::<TerminalEffectChain>(term)
+            .unwrap()
+            .effects
+            .pop();
+        app.update();
+        let applied = material(&app, term);
+        assert_eq!(0.5, applied.scanline_strength);
+        assert_eq!(None, applied.vignette_radius);
+        assert_eq!(Some(Color::BLUE), applied.clear_color);
+
+        // As does removing the chain
+        app.world.entity_mut(term).remove::<TerminalEffectChain>();
+        app.update();
+        let restored = material(&app, term);
+        assert_eq!(0.0, restored.scanline_strength);
+        assert_eq!(Some(Color::BLUE), restored.clear_color);
+        assert!(app.world.get::<TerminalEffectBase>(term).is_none());
+    }
+
+    #[test]
+    fn modified_only_on_change() {
+        #[derive(Default)]
+        struct Modified(usize);
+
+        fn count_modified(
+            mut count: ResMut<Modified>,
+            mut events: EventReader<AssetEvent<TerminalMaterial>>,
+        ) {
+            for event in events.iter() {
+                if let AssetEvent::Modified { .. } = event {
+                    count.0 += 1;
+                }
+            }
+        }
+
+        let mut app = app();
+        app.init_resource::<Modified>()
+            .add_system_to_stage(CoreStage::PostUpdate, count_modified);
+
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        let material = materials.add(TerminalMaterial::default());
+        let term = app
+            .world
+            .spawn()
+            .insert(material)
+            .insert(TerminalEffectChain::new().with(ScanlineEffect { strength: 0.5 }))
+            .id();
+        // Asset events are sent at the end of the frame and read on the next
+        let modified = |app: &mut App| {
+            app.update();
+            app.update();
+            app.world.get_resource::<Modified>().unwrap().0
+        };
+
+        assert_eq!(1, modified(&mut app));
+        assert_eq!(1, modified(&mut app));
+
+        app.world
+            .get_mut::<TerminalEffectChain>(term)
+            .unwrap()
+            .effects
+            .push(Box::new(VignetteEffect { radius: 0.6 }));
+        assert_eq!(2, modified(&mut app));
+    }
+
+    #[test]
+    fn chain() {
+        let chain = TerminalEffectChain::new()
+            .with(ScanlineEffect { strength: 0.5 })
+            .with(NoiseEffect { strength: 0.2 })
+            .with(VignetteEffect { radius: 0.7 })
+            .with(ChromaticAberrationEffect { offset: 0.002 });
+
+        let mut material = TerminalMaterial::default();
+        chain.apply(&mut material, 1.0);
+
+        assert_eq!(0.5, material.scanline_strength);
+        assert_eq!(0.2, material.noise_strength);
+        assert_eq!(60.0, material.noise_seed);
+        assert_eq!(Some(0.7), material.vignette_radius);
+        let flags = material.flags();
+        assert!(flags.contains(
+            TerminalMaterialFlags::SCANLINES
+                | TerminalMaterialFlags::NOISE
+                | TerminalMaterialFlags::VIGNETTE
+                | TerminalMaterialFlags::CHROMATIC_ABERRATION
+        ));
+    }
+}
@@ -5,8 +5,8 @@ use bevy::{prelude::*, sprite::Mesh2dHandle};
 use crate::TerminalMaterial;
 
 use super::{
-    renderer_tile_data::TerminalRendererTileData, renderer_vertex_data::TerminalRendererVertexData,
-    uv_mapping::UvMapping,
+    asset_pool::TerminalAssetPool, renderer_tile_data::TerminalRendererTileData,
+    renderer_vertex_data::TerminalRendererVertexData, uv_mapping::UvMapping,
 };
 
 /// Terminal component specifying the origin of the terminal mesh.
@@ -33,6 +33,9 @@ pub enum TileScaling {
     ///
     /// This matches how [TiledCamera](https://crates.io/crates/bevy_tiled_camera) is set up. This setting
     /// will only work with square fonts.
+    ///
+    /// If the terminal's material sets [TerminalMaterial::pixels_per_unit] each tile will instead take
+    /// up its size in pixels divided by that value.
     World,
     /// Scale terminal tiles based on the size of their texture.
     ///
@@ -47,6 +50,27 @@ impl Default for TileScaling {
     }
 }
 
+impl TileScaling {
+    /// The size of a single tile in world units for a terminal with the given
+    /// material.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size depends on the material's font texture and it isn't
+    /// loaded.
+    pub fn tile_size(&self, material: &TerminalMaterial, images: &Assets<Image>) -> Vec2 {
+        super::plugin::terminal_tile_size(material, self, images)
+    }
+}
+
+/// Terminal component specifying which render layer the terminal is drawn on.
+///
+/// If `None` the layer set via [TerminalPlugin::with_render_layer](crate::TerminalPlugin::with_render_layer)
+/// is used, or layer 0 if none was set. Layers past the last of bevy's 32
+/// render layers are clamped to it.
+#[derive(Component, Default, Clone, Copy)]
+pub struct TerminalRenderLayer(pub Option<u8>);
+
 /// A bundle of all the components required to render a terminal.
 ///
 /// Has various functions to help with the construction of a terminal.
@@ -60,6 +84,7 @@ pub struct TerminalRendererBundle {
     pub uv_mapping: UvMapping,
     pub terminal_pivot: TerminalPivot,
     pub tile_pivot: TilePivot,
+    pub render_layer: TerminalRenderLayer,
     pub visibility: Visibility,
     pub computed_visibility: ComputedVisibility,
 }
@@ -69,6 +94,24 @@ impl TerminalRendererBundle {
         TerminalRendererBundle::default()
     }
 
+    /// Create a bundle using a mesh and material from the [TerminalAssetPool].
+    ///
+    /// If the pool is exhausted a warning is logged and the mesh and material
+    /// are allocated as normal.
+    pub fn from_pool(pool: &mut TerminalAssetPool) -> Self {
+        match pool.take() {
+            Some((mesh, material)) => TerminalRendererBundle {
+                mesh: Mesh2dHandle(mesh),
+                material,
+                ..Default::default()
+            },
+            None => {
+                warn!("Terminal asset pool is exhausted, allocating new terminal assets");
+                TerminalRendererBundle::default()
+            }
+        }
+    }
+
     /// Set the terminal pivot value.
     ///
     /// Terminal pivot determines where the origin of the terminal mesh sits, where
@@ -92,4 +135,63 @@ impl TerminalRendererBundle {
         self.scaling = scaling;
         self
     }
+
+    /// Set the render layer for the terminal, overriding the default layer
+    /// set on the [TerminalPlugin](crate::TerminalPlugin).
+    ///
+    /// Layers past the last of bevy's 32 render layers are clamped to it.
+    pub fn with_render_layer(mut self, layer: u8) -> Self {
+        self.render_layer = TerminalRenderLayer(Some(layer));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle_verts(bundle: TerminalRendererBundle, size: UVec2) -> Vec<[f32; 3]> {
+        let mut vert_data = bundle.vert_data;
+        vert_data.resize(
+            size,
+            bundle.terminal_pivot.0,
+            bundle.tile_pivot.0,
+            Vec2::ONE,
+        );
+        vert_data.verts
+    }
+
+    #[test]
+    fn default_pivot() {
+        let verts = bundle_verts(TerminalRendererBundle::new(), UVec2::new(4, 2));
+
+        // Bottom left vert of the first tile
+        assert_eq!([-2.0, -1.0, 0.0], verts[1]);
+    }
+
+    #[test]
+    fn terminal_pivot() {
+        let bundle = TerminalRendererBundle::new().with_terminal_pivot(0.0, 0.0);
+        let verts = bundle_verts(bundle, UVec2::new(4, 2));
+
+        assert_eq!([0.0, 0.0, 0.0], verts[1]);
+        // Top right vert of the last tile
+        assert_eq!([4.0, 2.0, 0.0], verts[verts.len() - 2]);
+    }
+
+    #[test]
+    fn tile_pivot() {
+        let bundle = TerminalRendererBundle::new()
+            .with_terminal_pivot(0.0, 0.0)
+            .with_tile_pivot(0.5, 0.5);
+        let verts = bundle_verts(bundle, UVec2::new(4, 2));
+
+        assert_eq!([-0.5, -0.5, 0.0], verts[1]);
+    }
+
+    #[test]
+    fn tile_scaling() {
+        let bundle = TerminalRendererBundle::new().with_tile_scaling(TileScaling::Pixels);
+        assert!(matches!(bundle.scaling, TileScaling::Pixels));
+    }
 }
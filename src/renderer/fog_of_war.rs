@@ -0,0 +1,289 @@
+//! A terminal component for hiding tiles outside the player's field of view.
+//!
+//! Fog is applied to the terminal's renderer data, the tiles of the [Terminal]
+//! itself are never modified.
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    lighting::{compute_shadow_volume, ShadowCastingLight},
+    Terminal, Tile,
+};
+
+use super::{renderer_tile_data::TerminalRendererTileData, uv_mapping::UvMapping};
+
+/// Terminal component which draws a fog tile over every tile outside of the
+/// `visible` set.
+///
+/// Tiles in neither set are drawn as `unseen_tile`. Tiles which have been
+/// explored but aren't currently visible are drawn as
+/// `seen_but_not_visible_tile`, or as normal if it's `None`.
+///
+/// The sets can be written directly or from a [TerminalFovSource] event.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::{TerminalFogOfWar, TerminalFovSource};
+///
+/// fn setup(mut commands: Commands) {
+///     let remembered = Tile {
+///         glyph: '.',
+///         fg_color: Color::DARK_GRAY,
+///         ..Default::default()
+///     };
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([40, 20]))
+///         .insert(TerminalFogOfWar::new(Tile::default()).with_seen_tile(remembered));
+/// }
+///
+/// fn player_fov(
+///     mut events: EventWriter<TerminalFovSource>,
+///     q: Query<Entity, With<TerminalFogOfWar>>,
+/// ) {
+///     for entity in q.iter() {
+///         events.send(TerminalFovSource {
+///             entity,
+///             light: ShadowCastingLight::new([20, 10], 8),
+///             opaque_fn: |t| t.glyph == '#',
+///         });
+///     }
+/// }
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct TerminalFogOfWar {
+    /// Drawn over tiles which have never been visible.
+    pub unseen_tile: Tile,
+    /// Drawn over explored tiles which aren't currently visible.
+    pub seen_but_not_visible_tile: Option<Tile>,
+    /// The tiles which are currently visible.
+    pub visible: HashSet<UVec2>,
+    /// Every tile which has ever been visible.
+    pub explored: HashSet<UVec2>,
+}
+
+impl TerminalFogOfWar {
+    /// Fog which hides every tile behind `unseen_tile` until it's visible.
+    pub fn new(unseen_tile: Tile) -> Self {
+        Self {
+            unseen_tile,
+            seen_but_not_visible_tile: None,
+            visible: HashSet::default(),
+            explored: HashSet::default(),
+        }
+    }
+
+    /// Draw explored tiles which aren't currently visible as `tile`.
+    pub fn with_seen_tile(mut self, tile: Tile) -> Self {
+        self.seen_but_not_visible_tile = Some(tile);
+        self
+    }
+
+    /// Replace the visible tiles. They're also marked as explored.
+    pub fn set_visible(&mut self, visible: impl IntoIterator<Item = UVec2>) {
+        self.visible.clear();
+        self.visible.extend(visible);
+        self.explored.extend(self.visible.iter().copied());
+    }
+
+    /// The tile drawn over the given position, or `None` if it isn't fogged.
+    pub fn fog_tile(&self, xy: UVec2) -> Option<Tile> {
+        if self.visible.contains(&xy) {
+            None
+        } else if self.explored.contains(&xy) {
+            self.seen_but_not_visible_tile
+        } else {
+            Some(self.unseen_tile)
+        }
+    }
+}
+
+/// Event which sets the visible tiles of a [TerminalFogOfWar] terminal to the
+/// tiles lit by a light, using [compute_shadow_volume] on the terminal's
+/// tiles.
+///
+/// Every event sent to the same terminal in a frame adds to it's visible set,
+/// so a terminal can have several sources.
+#[derive(Clone, Copy)]
+pub struct TerminalFovSource {
+    /// The terminal entity with the [TerminalFogOfWar] component.
+    pub entity: Entity,
+    /// The viewer. It sees every tile the light reaches.
+    pub light: ShadowCastingLight,
+    /// Returns true for tiles which block sight.
+    pub opaque_fn: fn(&Tile) -> bool,
+}
+
+pub(crate) fn terminal_fog_of_war_fov(
+    mut events: EventReader<TerminalFovSource>,
+    mut q: Query<(&Terminal, &mut TerminalFogOfWar)>,
+) {
+    let mut updated = Vec::new();
+    for source in events.iter() {
+        let (term, mut fog) = match q.get_mut(source.entity) {
+            Ok(q) => q,
+            Err(_) => continue,
+        };
+        // The first source this frame replaces the previous frame's visible tiles
+        if !updated.contains(&source.entity) {
+            fog.visible.clear();
+            updated.push(source.entity);
+        }
+        let width = term.width().max(1);
+        let values = compute_shadow_volume(term, &source.light, source.opaque_fn);
+        let visible: Vec<_> = values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value > 0.0)
+            .map(|(i, _)| UVec2::new(i as u32 % width, i as u32 / width))
+            .collect();
+        fog.visible.extend(visible.iter().copied());
+        fog.explored.extend(visible);
+    }
+}
+
+/// Rewrite the tile data of terminals whose fog has changed or been removed,
+/// so fog is applied to unmodified tiles.
+#[allow(clippy::type_complexity)]
+pub(crate) fn terminal_fog_of_war_refresh(
+    removed: RemovedComponents<TerminalFogOfWar>,
+    mut changed: Query<
+        &mut TerminalRendererTileData,
+        (With<TerminalFogOfWar>, Changed<TerminalFogOfWar>),
+    >,
+    mut q: Query<&mut TerminalRendererTileData, Without<TerminalFogOfWar>>,
+) {
+    for mut tile_data in changed.iter_mut() {
+        tile_data.request_refresh();
+    }
+    for entity in removed.iter() {
+        if let Ok(mut tile_data) = q.get_mut(entity) {
+            tile_data.request_refresh();
+        }
+    }
+}
+
+/// Write fog tiles over the tile data of terminals, whenever it's written
+/// from the terminal.
+///
+/// Fogged tiles only change when the fog does, which refreshes the whole
+/// terminal, so a partial update only needs it's own tiles fogged.
+pub(crate) fn terminal_renderer_apply_fog_of_war(
+    mut q: Query<(
+        &Terminal,
+        &TerminalFogOfWar,
+        &UvMapping,
+        &mut TerminalRendererTileData,
+    )>,
+) {
+    for (term, fog, uv_mapping, mut tile_data) in q.iter_mut() {
+        if !tile_data.is_refreshed() {
+            continue;
+        }
+        let width = term.width().max(1);
+        for i in 0..tile_data.uvs.len() / 4 {
+            let xy = UVec2::new(i as u32 % width, i as u32 / width);
+            if let Some(tile) = fog.fog_tile(xy) {
+                tile_data.write_tile(i, &tile, uv_mapping);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+
+    #[test]
+    fn fov() {
+        let mut app = App::new();
+        app.add_event::<TerminalFovSource>()
+            .add_system(terminal_fog_of_war_fov);
+
+        // A wall splitting the terminal in two
+        let mut term = Terminal::with_size([10, 5]);
+        for y in 0..5 {
+            term.put_char([5, y], '#');
+        }
+        let unseen = Tile {
+            glyph: '?',
+            ..Default::default()
+        };
+        let seen = Tile {
+            glyph: '.',
+            ..Default::default()
+        };
+        let fog = TerminalFogOfWar::new(unseen).with_seen_tile(seen);
+        let entity = app.world.spawn().insert(term).insert(fog).id();
+
+        let send = |app: &mut App, pos: [u32; 2]| {
+            app.world
+                .get_resource_mut::<Events<TerminalFovSource>>()
+                .unwrap()
+                .send(TerminalFovSource {
+                    entity,
+                    light: ShadowCastingLight::new(pos, 20),
+                    opaque_fn: |t| t.glyph == '#',
+                });
+            app.update();
+        };
+        send(&mut app, [1, 2]);
+        let fog = app.world.get::<TerminalFogOfWar>(entity).unwrap();
+        assert_eq!(None, fog.fog_tile(UVec2::new(0, 0)));
+        // Walls are seen, but not what's behind them
+        assert_eq!(None, fog.fog_tile(UVec2::new(5, 2)));
+        assert_eq!(Some(unseen), fog.fog_tile(UVec2::new(8, 2)));
+
+        send(&mut app, [8, 2]);
+        let fog = app.world.get::<TerminalFogOfWar>(entity).unwrap();
+        assert_eq!(None, fog.fog_tile(UVec2::new(8, 2)));
+        assert_eq!(Some(seen), fog.fog_tile(UVec2::new(0, 0)));
+
+        assert_eq!(None, fog.fog_tile(UVec2::new(5, 0)));
+    }
+
+    #[test]
+    fn apply_fog() {
+        let mut app = App::new();
+        app.add_system(terminal_renderer_apply_fog_of_war);
+
+        let unseen = Tile {
+            glyph: '?',
+            fg_color: Color::RED,
+            ..Default::default()
+        };
+        let mut fog = TerminalFogOfWar::new(unseen);
+        fog.set_visible([UVec2::new(0, 0)]);
+        let term = Terminal::with_size([2, 1]);
+        let uv_mapping = UvMapping::default();
+        let mut tile_data = TerminalRendererTileData::with_size(UVec2::new(2, 1));
+        tile_data.update_from_tiles(&term.composited_tiles(), &uv_mapping);
+        let entity = app
+            .world
+            .spawn()
+            .insert_bundle((term, fog, uv_mapping, tile_data))
+            .id();
+
+        app.update();
+        let fg = |app: &App, i: usize| {
+            let tile_data = app.world.get::<TerminalRendererTileData>(entity).unwrap();
+            tile_data.fg_colors[i * 4]
+        };
+        assert_eq!(Color::WHITE.as_linear_rgba_f32(), fg(&app, 0));
+        assert_eq!(Color::RED.as_linear_rgba_f32(), fg(&app, 1));
+
+        // Fog is only written over data which was just written from the terminal
+        let mut tile_data = app
+            .world
+            .get_mut::<TerminalRendererTileData>(entity)
+            .unwrap();
+        tile_data.clear_refreshed();
+        tile_data.fg_colors.fill([0.0; 4]);
+        app.update();
+        assert_eq!([0.0; 4], fg(&app, 1));
+    }
+}
@@ -0,0 +1,1087 @@
+//! An asset loader for terminal fonts with optional metadata.
+//!
+//! Any image with the `.font.png` extension will be loaded as a [TerminalFontAsset]
+//! rather than a plain [Image]. Loading happens on bevy's asset task pool so
+//! large fonts won't block the main thread.
+//!
+//! If a `.json` file with the same name exists next to the image it's read as
+//! the font's metadata, so `my_font.font.png` would read `my_font.json`. All
+//! fields are optional:
+//!
+//! ```json
+//! {
+//!     "tile_count": [16, 16],
+//!     "clip_color": [0.0, 0.0, 0.0, 1.0],
+//!     "kerning": 0,
+//!     "pixel_density_hint": 1.0
+//! }
+//! ```
+//!
+//! `tile_count` is the number of glyphs along each axis of the image and
+//! `clip_color` the color treated as background in the image, as linear rgba.
+//! `kerning` is extra horizontal space between glyphs in pixels, and
+//! `pixel_density_hint` is the number of screen pixels per font pixel the font
+//! is designed for.
+//!
+//! The font's [TerminalFontMetrics] are estimated from it's tile height. They can
+//! be overridden by a `.metrics.json` file next to the image, so `my_font.font.png`
+//! would read `my_font.metrics.json`. All fields are optional and measured in pixels:
+//!
+//! ```json
+//! { "cap_height": 7, "x_height": 5, "baseline_offset": 1, "ascender": 7, "descender": 1 }
+//! ```
+//!
+//! A font can also be bundled into a single `.tfont` file, which is json holding
+//! the metadata along with the image as a base64 encoded png:
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "image": "iVBORw0KGgoAAAANSUhEUgAA...",
+//!     "tile_count": [16, 16],
+//!     "clip_color": [0.0, 0.0, 0.0, 1.0],
+//!     "kerning": 0,
+//!     "pixel_density_hint": 1.0
+//! }
+//! ```
+//!
+//! `version` is the version of the format, currently always 1, and `image` is the
+//! font's png image in standard base64. Both are required. The other fields are
+//! optional and work the same as in a font's `.json` file. Metrics can't be
+//! overridden for a `.tfont` file, they're always estimated.
+
+use std::{fmt, io};
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, HandleId, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    render::texture::{CompressedImageFormats, ImageType},
+    utils::HashMap,
+};
+use serde::Deserialize;
+#[cfg(feature = "image")]
+use std::path::Path;
+
+use super::{atlas::MAX_ATLAS_SIZE, material::TerminalMaterial, sdf::glyphs_to_sdf};
+
+/// A font texture along with it's metadata.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::font_asset::TerminalFontAsset;
+///
+/// fn change_font(
+///     fonts: Res<Assets<TerminalFontAsset>>,
+///     asset_server: Res<AssetServer>,
+///     mut materials: ResMut<Assets<TerminalMaterial>>,
+///     q: Query<&Handle<TerminalMaterial>>,
+/// ) {
+///     let font: Handle<TerminalFontAsset> = asset_server.load("my_font.font.png");
+///     if let Some(font) = fonts.get(&font) {
+///         for mat in q.iter() {
+///             *materials.get_mut(mat).unwrap() = font.material();
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "5b4b8f3c-1c3e-4f6e-9a51-0d7c2b1e9f42"]
+pub struct TerminalFontAsset {
+    pub image: Handle<Image>,
+    /// The number of glyphs along each axis of the image.
+    pub tile_count: UVec2,
+    /// The size of a single glyph in pixels.
+    pub tile_size: UVec2,
+    pub clip_color: Color,
+    pub metrics: TerminalFontMetrics,
+    /// If true the image is a signed distance field, see [TerminalFontAsset::generate_sdf].
+    pub sdf_mode: bool,
+    /// Extra horizontal space between glyphs in pixels. Can be negative.
+    pub kerning: i32,
+    /// The number of screen pixels per font pixel the font is designed for.
+    pub pixel_density_hint: f32,
+}
+
+impl TerminalFontAsset {
+    /// Check whether `other` can replace this font without changing the uvs or
+    /// size of any tiles, which requires the same tile count and tile size.
+    pub fn validate_against(&self, other: &TerminalFontAsset) -> Result<(), FontError> {
+        if self.tile_count != other.tile_count {
+            return Err(FontError::TileCountMismatch {
+                expected: self.tile_count,
+                actual: other.tile_count,
+            });
+        }
+        if self.tile_size != other.tile_size {
+            return Err(FontError::TileSizeMismatch {
+                expected: self.tile_size,
+                actual: other.tile_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// A [TerminalMaterial] which renders this font.
+    pub fn material(&self) -> TerminalMaterial {
+        TerminalMaterial {
+            clip_color: self.clip_color,
+            sdf_mode: self.sdf_mode,
+            ..TerminalMaterial::from(self.image.clone())
+        }
+    }
+
+    /// Create a signed distance field version of this font, which stays sharp at
+    /// any scale.
+    ///
+    /// The distance field image is added to `images`, and the returned font's
+    /// [material](TerminalFontAsset::material) renders it in sdf mode. `sigma`
+    /// is the distance in pixels at which the field reaches 0 or 1. See
+    /// [font_to_sdf](super::sdf::font_to_sdf).
+    pub fn generate_sdf(
+        &self,
+        sigma: f32,
+        images: &mut Assets<Image>,
+    ) -> Result<TerminalFontAsset, FontError> {
+        let font_image = self.rgba_image(images)?;
+        let sdf = glyphs_to_sdf(font_image, self.tile_size, self.clip_color, sigma)?;
+        Ok(TerminalFontAsset {
+            image: images.add(sdf),
+            sdf_mode: true,
+            ..self.clone()
+        })
+    }
+
+    /// Create a copy of this font with a drop shadow baked into every glyph.
+    ///
+    /// Every glyph pixel is stamped in `shadow_color` at `offset` pixels from
+    /// it's position, with positive y pointing up like the terminal. Shadows are
+    /// only drawn over background pixels, so they sit under the original glyph,
+    /// and are clipped to the glyph's tile.
+    ///
+    /// The new image is added to `images`, the original font is unchanged.
+    pub fn bake_shadow(
+        &self,
+        offset: IVec2,
+        shadow_color: Color,
+        images: &mut Assets<Image>,
+    ) -> Result<TerminalFontAsset, FontError> {
+        let font_image = self.rgba_image(images)?;
+        let size = font_image.texture_descriptor.size;
+        let width = size.width as usize;
+
+        let clip = color_to_bytes(self.clip_color);
+        let shadow = color_to_bytes(shadow_color);
+        let src = &font_image.data;
+        let inside = |x: usize, y: usize| is_glyph_pixel(&src[(y * width + x) * 4..], clip);
+
+        let mut shadowed = font_image.clone();
+        let tile_size = self.tile_size.as_ivec2();
+        for y in 0..size.height as usize {
+            for x in 0..width {
+                if !inside(x, y) {
+                    continue;
+                }
+                let xy = IVec2::new(x as i32, y as i32);
+                let tile_min = (xy / tile_size) * tile_size;
+                // Image rows go down, terminal rows go up
+                let target = xy + IVec2::new(offset.x, -offset.y);
+                let local = target - tile_min;
+                if local.cmplt(IVec2::ZERO).any() || local.cmpge(tile_size).any() {
+                    continue;
+                }
+
+                let (tx, ty) = (target.x as usize, target.y as usize);
+                if !inside(tx, ty) {
+                    let i = (ty * width + tx) * 4;
+                    shadowed.data[i..i + 4].copy_from_slice(&shadow);
+                }
+            }
+        }
+
+        Ok(TerminalFontAsset {
+            image: images.add(shadowed),
+            ..self.clone()
+        })
+    }
+
+    /// Create a copy of this font with every glyph drawn in a bold, italic or
+    /// underlined style, see [TerminalFontVariant].
+    ///
+    /// The new image is added to `images`, the original font is unchanged. Glyphs
+    /// are clipped to their tile, so glyphs which fill their tile may lose pixels
+    /// at the edges.
+    pub fn synthesize_variant(
+        &self,
+        variant: TerminalFontVariant,
+        images: &mut Assets<Image>,
+    ) -> Result<TerminalFontAsset, FontError> {
+        let font_image = self.rgba_image(images)?;
+        let size = font_image.texture_descriptor.size;
+        let (width, height) = (size.width as usize, size.height as usize);
+
+        let clip = color_to_bytes(self.clip_color);
+        let src = &font_image.data;
+        let inside = |x: usize, y: usize| is_glyph_pixel(&src[(y * width + x) * 4..], clip);
+
+        let mut out = font_image.clone();
+        let (tile_w, tile_h) = (self.tile_size.x as usize, self.tile_size.y as usize);
+        let mut copy = |to: (usize, usize), from: Option<(usize, usize)>| {
+            let i = (to.1 * width + to.0) * 4;
+            match from {
+                Some((x, y)) => {
+                    let j = (y * width + x) * 4;
+                    out.data[i..i + 4].copy_from_slice(&src[j..j + 4]);
+                }
+                None => out.data[i..i + 4].copy_from_slice(&clip),
+            }
+        };
+
+        match variant {
+            TerminalFontVariant::Bold => {
+                for y in 0..height {
+                    for x in 0..width {
+                        // Stay within the glyph's tile
+                        let right = x + 1;
+                        if inside(x, y) && right % tile_w != 0 && !inside(right, y) {
+                            copy((right, y), Some((x, y)));
+                        }
+                    }
+                }
+            }
+            TerminalFontVariant::Italic => {
+                let slant = 15f32.to_radians().tan();
+                for y in 0..height {
+                    // Image rows go down, the baseline is measured up from the
+                    // bottom of the tile
+                    let above_baseline =
+                        (tile_h - 1 - y % tile_h) as f32 - self.metrics.baseline_offset as f32;
+                    let shift = (above_baseline * slant).round() as i32;
+                    for x in 0..width {
+                        let tile_x = (x - x % tile_w) as i32;
+                        let from = x as i32 - shift;
+                        let from = if from >= tile_x && from < tile_x + tile_w as i32 {
+                            Some((from as usize, y))
+                        } else {
+                            None
+                        };
+                        copy((x, y), from);
+                    }
+                }
+            }
+            TerminalFontVariant::Underline => {
+                let underline = [255; 4];
+                // The row just below the baseline
+                let row = tile_h - self.metrics.baseline_offset.clamp(1, tile_h as u32) as usize;
+                for y in (row..height).step_by(tile_h) {
+                    let i = y * width * 4;
+                    for pixel in out.data[i..i + width * 4].chunks_exact_mut(4) {
+                        pixel.copy_from_slice(&underline);
+                    }
+                }
+            }
+        }
+
+        Ok(TerminalFontAsset {
+            image: images.add(out),
+            ..self.clone()
+        })
+    }
+
+    /// The fraction of a glyph's pixels which aren't the clip color, from 0 for
+    /// an empty glyph to 1 for a solid block.
+    ///
+    /// `glyph` is the glyph's index in the font image, counting row by row from
+    /// the top left. Glyphs outside the image have a coverage of 0.
+    pub fn estimate_coverage(&self, glyph: u8, images: &Assets<Image>) -> Result<f32, FontError> {
+        let font_image = self.rgba_image(images)?;
+        Ok(self.glyph_coverage(font_image, glyph))
+    }
+
+    /// The [coverage](TerminalFontAsset::estimate_coverage) of all 256 glyphs,
+    /// indexed by glyph.
+    ///
+    /// Useful for picking glyphs by visual density, for example to build an
+    /// ascii art palette for a specific font.
+    pub fn build_coverage_table(&self, images: &Assets<Image>) -> Result<Vec<f32>, FontError> {
+        let font_image = self.rgba_image(images)?;
+        Ok((0..=255)
+            .map(|glyph| self.glyph_coverage(font_image, glyph))
+            .collect())
+    }
+
+    fn glyph_coverage(&self, font_image: &Image, glyph: u8) -> f32 {
+        let width = font_image.texture_descriptor.size.width as usize;
+        let height = font_image.texture_descriptor.size.height as usize;
+        let (columns, rows) = (self.tile_count.x as usize, self.tile_count.y as usize);
+        let (tile_w, tile_h) = (self.tile_size.x as usize, self.tile_size.y as usize);
+        let glyph = glyph as usize;
+        if columns == 0 || glyph >= columns * rows || tile_w * tile_h == 0 {
+            return 0.0;
+        }
+
+        let clip = color_to_bytes(self.clip_color);
+        let (x0, y0) = ((glyph % columns) * tile_w, (glyph / columns) * tile_h);
+        let mut covered = 0;
+        for y in y0..(y0 + tile_h).min(height) {
+            for x in x0..(x0 + tile_w).min(width) {
+                let i = (y * width + x) * 4;
+                if is_glyph_pixel(&font_image.data[i..], clip) {
+                    covered += 1;
+                }
+            }
+        }
+        covered as f32 / (tile_w * tile_h) as f32
+    }
+
+    /// The font's image, checking it's loaded and uses 4 bytes per pixel.
+    fn rgba_image<'a>(&self, images: &'a Assets<Image>) -> Result<&'a Image, FontError> {
+        let image = images.get(&self.image).ok_or(FontError::MissingImage)?;
+        check_rgba(image)?;
+        Ok(image)
+    }
+
+    /// Typographic measurements of the font's glyphs.
+    pub fn metrics(&self) -> TerminalFontMetrics {
+        self.metrics
+    }
+
+    /// Save the font's image to a png file, for example after modifying it with
+    /// [override_glyph](super::material::override_glyph).
+    ///
+    /// Only available with the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn write_to_png(&self, path: &Path, images: &Assets<Image>) -> Result<(), FontError> {
+        let font_image = self.rgba_image(images)?;
+        let size = font_image.texture_descriptor.size;
+        let file = io::BufWriter::new(std::fs::File::create(path)?);
+        image::png::PngEncoder::new(file)
+            .encode(
+                &font_image.data,
+                size.width,
+                size.height,
+                image::ColorType::Rgba8,
+            )
+            .map_err(|e| FontError::Io(io::Error::new(io::ErrorKind::Other, e)))
+    }
+}
+
+/// A style synthesized from a regular bitmap font by
+/// [TerminalFontAsset::synthesize_variant].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalFontVariant {
+    /// Every glyph pixel is repeated one pixel to the right.
+    Bold,
+    /// Glyphs are sheared 15 degrees to the right around the baseline.
+    Italic,
+    /// A line is drawn along the row below the baseline.
+    Underline,
+}
+
+/// Whether the first pixel of `rgba` is part of a glyph rather than the
+/// background. Matches the clip test in the terminal shader.
+fn is_glyph_pixel(rgba: &[u8], clip: [u8; 4]) -> bool {
+    rgba[..3].iter().zip(clip).any(|(p, c)| *p > c)
+}
+
+fn color_to_bytes(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// Typographic measurements of a font's glyphs, in pixels.
+///
+/// Heights are measured up from the baseline, which sits `baseline_offset` pixels
+/// above the bottom of a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct TerminalFontMetrics {
+    /// The height of capital letters.
+    pub cap_height: u32,
+    /// The height of lowercase letters without ascenders, such as 'x'.
+    pub x_height: u32,
+    /// The distance from the bottom of a tile to the baseline.
+    pub baseline_offset: u32,
+    /// The height of the tallest glyphs.
+    pub ascender: u32,
+    /// How far glyphs can extend below the baseline.
+    pub descender: u32,
+}
+
+impl TerminalFontMetrics {
+    /// Estimate metrics from the height of a tile in pixels.
+    ///
+    /// The baseline sits an eighth of the way up the tile, capitals take up 80%
+    /// of the tile's height and lowercase letters 60%.
+    pub fn from_tile_height(height: u32) -> Self {
+        let percent = |p: u32| (height * p + 50) / 100;
+        let baseline_offset = (height + 4) / 8;
+        Self {
+            cap_height: percent(80).min(height - baseline_offset),
+            x_height: percent(60),
+            baseline_offset,
+            ascender: height - baseline_offset,
+            descender: baseline_offset,
+        }
+    }
+
+    /// How far glyphs should be moved up, in pixels, to center capital letters
+    /// vertically within a tile of the given height. Can be negative.
+    pub fn vertical_center_offset(&self, tile_height: u32) -> i32 {
+        let top_gap = tile_height as i32 - (self.baseline_offset + self.cap_height) as i32;
+        (top_gap - self.baseline_offset as i32) / 2
+    }
+}
+
+/// The contents of a font's `.metrics.json` file.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TerminalFontMetricsOverride {
+    cap_height: Option<u32>,
+    x_height: Option<u32>,
+    baseline_offset: Option<u32>,
+    ascender: Option<u32>,
+    descender: Option<u32>,
+}
+
+impl TerminalFontMetricsOverride {
+    fn apply(&self, metrics: TerminalFontMetrics) -> TerminalFontMetrics {
+        TerminalFontMetrics {
+            cap_height: self.cap_height.unwrap_or(metrics.cap_height),
+            x_height: self.x_height.unwrap_or(metrics.x_height),
+            baseline_offset: self.baseline_offset.unwrap_or(metrics.baseline_offset),
+            ascender: self.ascender.unwrap_or(metrics.ascender),
+            descender: self.descender.unwrap_or(metrics.descender),
+        }
+    }
+}
+
+/// The reason a font operation failed.
+///
+/// Operations which read a font's image fail with [FontError::MissingImage] if
+/// it hasn't been loaded, and [FontError::UnsupportedFormat] if it doesn't use
+/// 4 bytes per pixel.
+#[derive(Debug)]
+pub enum FontError {
+    /// The font's image hasn't been loaded.
+    MissingImage,
+    /// The font's image doesn't use 4 bytes per pixel, or doesn't match the
+    /// format of the other fonts it's combined with.
+    UnsupportedFormat,
+    /// The fonts have a different number of glyphs along each axis.
+    TileCountMismatch { expected: UVec2, actual: UVec2 },
+    /// The font has more glyphs along an axis than it's image has pixels.
+    TileCountTooLarge {
+        tile_count: UVec2,
+        image_size: UVec2,
+    },
+    /// The fonts' glyphs are a different size in pixels.
+    TileSizeMismatch { expected: UVec2, actual: UVec2 },
+    /// The glyph index is past the last glyph of the font.
+    GlyphOutOfRange { index: usize, count: usize },
+    /// The pixel data for a glyph is the wrong length.
+    PixelDataLength { expected: usize, actual: usize },
+    /// No fonts were given to combine.
+    NoFonts,
+    /// The fonts don't fit in a [MAX_ATLAS_SIZE] by [MAX_ATLAS_SIZE] texture.
+    AtlasTooBig,
+    /// The `.tfont` file's format version isn't supported.
+    UnsupportedVersion(u32),
+    /// The `.tfont` file's image isn't valid base64.
+    InvalidBase64(char),
+    /// Writing the font failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::MissingImage => write!(f, "Font image is not loaded"),
+            FontError::UnsupportedFormat => write!(f, "Font texture must use 4 bytes per pixel"),
+            FontError::TileCountMismatch { expected, actual } => write!(
+                f,
+                "Expected {}x{} tiles, found {}x{}",
+                expected.x, expected.y, actual.x, actual.y
+            ),
+            FontError::TileCountTooLarge {
+                tile_count,
+                image_size,
+            } => write!(
+                f,
+                "{}x{} tiles don't fit in a {}x{} pixel image",
+                tile_count.x, tile_count.y, image_size.x, image_size.y
+            ),
+            FontError::TileSizeMismatch { expected, actual } => write!(
+                f,
+                "Expected {}x{} pixel tiles, found {}x{}",
+                expected.x, expected.y, actual.x, actual.y
+            ),
+            FontError::GlyphOutOfRange { index, count } => write!(
+                f,
+                "Glyph index {} is out of range for a font with {} glyphs",
+                index, count
+            ),
+            FontError::PixelDataLength { expected, actual } => write!(
+                f,
+                "Expected {} bytes of pixel data, got {}",
+                expected, actual
+            ),
+            FontError::NoFonts => write!(f, "No fonts"),
+            FontError::AtlasTooBig => write!(
+                f,
+                "Fonts don't fit in a {}x{} atlas",
+                MAX_ATLAS_SIZE, MAX_ATLAS_SIZE
+            ),
+            FontError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported tfont version {}", version)
+            }
+            FontError::InvalidBase64(c) => write!(f, "Invalid base64 character {:?}", c),
+            FontError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+/// Check a font image uses 4 bytes per pixel.
+pub(crate) fn check_rgba(image: &Image) -> Result<(), FontError> {
+    let size = image.texture_descriptor.size;
+    if image.data.len() != (size.width * size.height * 4) as usize {
+        return Err(FontError::UnsupportedFormat);
+    }
+    Ok(())
+}
+
+/// Warn when a terminal material's font is replaced by an incompatible one.
+///
+/// Terminals using the material are marked as changed, so their meshes are
+/// rebuilt for the new tile size rather than stretching the new font over
+/// the old tiles.
+pub(crate) fn terminal_validate_font_change(
+    // The font each material was last seen with
+    mut fonts: Local<HashMap<HandleId, TerminalFontAsset>>,
+    mut events: EventReader<AssetEvent<TerminalMaterial>>,
+    materials: Res<Assets<TerminalMaterial>>,
+    images: Res<Assets<Image>>,
+    mut q: Query<&mut Handle<TerminalMaterial>>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => {
+                fonts.remove(&handle.id);
+                continue;
+            }
+        };
+        let image = materials
+            .get(handle)
+            .and_then(|m| m.texture.as_ref())
+            .and_then(|t| images.get(t));
+        let font = match image {
+            Some(image) => match font_from_meta(image, TerminalFontMeta::default()) {
+                Ok(font) => font,
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+
+        if let Some(Err(e)) = fonts.get(&handle.id).map(|old| old.validate_against(&font)) {
+            warn!("Terminal font changed to an incompatible font: {}", e);
+            for mut material in q.iter_mut().filter(|m| m.id == handle.id) {
+                material.set_changed();
+            }
+        }
+        fonts.insert(handle.id, font);
+    }
+}
+
+/// The contents of a font's `.json` metadata file, which are also part of a
+/// `.tfont` file.
+#[derive(Deserialize)]
+#[serde(default)]
+struct TerminalFontMeta {
+    tile_count: (u32, u32),
+    clip_color: Option<(f32, f32, f32, f32)>,
+    kerning: i32,
+    pixel_density_hint: f32,
+}
+
+impl Default for TerminalFontMeta {
+    fn default() -> Self {
+        Self {
+            tile_count: (16, 16),
+            clip_color: None,
+            kerning: 0,
+            pixel_density_hint: 1.0,
+        }
+    }
+}
+
+/// The contents of a `.tfont` file.
+#[derive(Deserialize)]
+struct TerminalFontFile {
+    version: u32,
+    image: String,
+    #[serde(flatten)]
+    meta: TerminalFontMeta,
+}
+
+/// Loads `.font.png` images with their metadata files, or self contained
+/// `.tfont` files, as a [TerminalFontAsset].
+#[derive(Default)]
+pub struct TerminalFontLoader;
+
+impl AssetLoader for TerminalFontLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            if load_context.path().extension().and_then(|e| e.to_str()) == Some("tfont") {
+                let (image, font) = read_tfont(bytes)?;
+                let image = load_context.set_labeled_asset("image", LoadedAsset::new(image));
+                load_context
+                    .set_default_asset(LoadedAsset::new(TerminalFontAsset { image, ..font }));
+                return Ok(());
+            }
+
+            let image = Image::from_buffer(
+                bytes,
+                ImageType::Extension("png"),
+                CompressedImageFormats::NONE,
+                true,
+            )?;
+
+            // "font.font.png" -> "font.json" and "font.metrics.json"
+            let path = load_context.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let name = name.trim_end_matches(".font.png");
+            let meta_path = path.with_file_name(format!("{}.json", name));
+            let metrics_path = path.with_file_name(format!("{}.metrics.json", name));
+
+            let meta = match load_context.read_asset_bytes(&meta_path).await {
+                Ok(bytes) => serde_json::from_slice::<TerminalFontMeta>(&bytes)?,
+                Err(_) => TerminalFontMeta::default(),
+            };
+            let mut font = font_from_meta(&image, meta)?;
+            if let Ok(bytes) = load_context.read_asset_bytes(&metrics_path).await {
+                let metrics: TerminalFontMetricsOverride = serde_json::from_slice(&bytes)?;
+                font.metrics = metrics.apply(font.metrics);
+            }
+            let image = load_context.set_labeled_asset("image", LoadedAsset::new(image));
+            load_context.set_default_asset(LoadedAsset::new(TerminalFontAsset { image, ..font }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["font.png", "tfont"]
+    }
+}
+
+/// Read a font and it's image from the contents of a `.tfont` file. The font's
+/// image handle is left as default.
+fn read_tfont(bytes: &[u8]) -> Result<(Image, TerminalFontAsset), anyhow::Error> {
+    let file: TerminalFontFile = serde_json::from_slice(bytes)?;
+    if file.version != 1 {
+        return Err(FontError::UnsupportedVersion(file.version).into());
+    }
+    let png = decode_base64(&file.image)?;
+    let image = Image::from_buffer(
+        &png,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )?;
+    let font = font_from_meta(&image, file.meta)?;
+    Ok((image, font))
+}
+
+/// Decode standard base64, ignoring whitespace. Padding is optional.
+fn decode_base64(text: &str) -> Result<Vec<u8>, FontError> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return Err(FontError::InvalidBase64(c as char)),
+        };
+        bits = (bits << 6) | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Build a font from it's metadata. The font's image handle is left as default.
+///
+/// Fails if the image is too small to hold at least one pixel per tile.
+fn font_from_meta(image: &Image, meta: TerminalFontMeta) -> Result<TerminalFontAsset, FontError> {
+    let tile_count = UVec2::from(meta.tile_count).max(UVec2::ONE);
+    let size = image.texture_descriptor.size;
+    let image_size = UVec2::new(size.width, size.height);
+    if tile_count.x > image_size.x || tile_count.y > image_size.y {
+        return Err(FontError::TileCountTooLarge {
+            tile_count,
+            image_size,
+        });
+    }
+    let clip_color = match meta.clip_color {
+        Some((r, g, b, a)) => Color::rgba_linear(r, g, b, a),
+        None => Color::BLACK,
+    };
+    let tile_size = image_size / tile_count;
+    Ok(TerminalFontAsset {
+        image: Handle::default(),
+        tile_count,
+        tile_size,
+        clip_color,
+        metrics: TerminalFontMetrics::from_tile_height(tile_size.y),
+        sdf_mode: false,
+        kerning: meta.kerning,
+        pixel_density_hint: meta.pixel_density_hint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    /// An image `width` by `height` pixels filled with `rgba`.
+    fn fill_image(width: u32, height: u32, rgba: [u8; 4]) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &rgba,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// An app holding `image` and a font drawn from it, with the given
+    /// metadata.
+    fn font_fixture(image: &Image, meta: TerminalFontMeta) -> (App, TerminalFontAsset) {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Image>();
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        let font = TerminalFontAsset {
+            image: images.add(image.clone()),
+            ..font_from_meta(image, meta).unwrap()
+        };
+        (app, font)
+    }
+
+    #[test]
+    fn meta() {
+        let image = fill_image(128, 96, [0, 0, 0, 255]);
+
+        let font = font_from_meta(&image, TerminalFontMeta::default()).unwrap();
+        assert_eq!(UVec2::new(8, 6), font.tile_size);
+
+        let meta: TerminalFontMeta = serde_json::from_str(
+            r#"{ "tile_count": [32, 8], "clip_color": [1.0, 0.0, 1.0, 1.0], "kerning": 2 }"#,
+        )
+        .unwrap();
+        let font = font_from_meta(&image, meta).unwrap();
+        assert_eq!(UVec2::new(32, 8), font.tile_count);
+        assert_eq!(UVec2::new(4, 12), font.tile_size);
+        assert_eq!(Color::rgba_linear(1.0, 0.0, 1.0, 1.0), font.clip_color);
+        assert_eq!(2, font.kerning);
+        assert_eq!(1.0, font.pixel_density_hint);
+
+        let cp437 = font_from_meta(&image, TerminalFontMeta::default()).unwrap();
+        assert!(cp437.validate_against(&cp437.clone()).is_ok());
+        assert!(matches!(
+            cp437.validate_against(&font),
+            Err(FontError::TileCountMismatch { expected, actual })
+                if expected == UVec2::new(16, 16) && actual == UVec2::new(32, 8)
+        ));
+        let font = TerminalFontAsset {
+            tile_size: UVec2::new(8, 8),
+            ..cp437.clone()
+        };
+        assert!(matches!(
+            cp437.validate_against(&font),
+            Err(FontError::TileSizeMismatch { expected, actual })
+                if expected == UVec2::new(8, 6) && actual == UVec2::new(8, 8)
+        ));
+
+        // Tiles can't be smaller than a pixel
+        let meta = TerminalFontMeta {
+            tile_count: (256, 16),
+            ..Default::default()
+        };
+        assert!(matches!(
+            font_from_meta(&image, meta),
+            Err(FontError::TileCountTooLarge { tile_count, image_size })
+                if tile_count == UVec2::new(256, 16) && image_size == UVec2::new(128, 96)
+        ));
+    }
+
+    #[test]
+    fn tfont() {
+        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let png = include_bytes!("builtin/px437_8x8.png");
+        let mut image = String::new();
+        for chunk in png.chunks(3) {
+            let n = chunk.iter().fold(0u32, |n, b| n << 8 | *b as u32) << (8 * (3 - chunk.len()));
+            for i in 0..=chunk.len() {
+                image.push(CHARS[(n >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        image.push_str(&"=".repeat((3 - png.len() % 3) % 3));
+
+        let file = format!(
+            r#"{{ "version": 1, "image": "{}", "tile_count": [16, 16], "kerning": -1 }}"#,
+            image
+        );
+        let (image, font) = read_tfont(file.as_bytes()).unwrap();
+        assert_eq!(UVec2::new(16, 16), font.tile_count);
+        assert_eq!(UVec2::new(8, 8), font.tile_size);
+        assert_eq!(128, image.texture_descriptor.size.width);
+        assert_eq!(-1, font.kerning);
+        assert_eq!(1.0, font.pixel_density_hint);
+        assert_eq!(Color::BLACK, font.clip_color);
+
+        let file = file.replace(r#""version": 1"#, r#""version": 2"#);
+        assert!(read_tfont(file.as_bytes()).is_err());
+        assert_eq!(b"Man".to_vec(), decode_base64("TW\nFu").unwrap());
+        assert_eq!(b"Ma".to_vec(), decode_base64("TWE=").unwrap());
+        assert!(decode_base64("TW!u").is_err());
+    }
+
+    #[test]
+    fn metrics() {
+        let metrics = TerminalFontMetrics::from_tile_height(16);
+        assert_eq!(13, metrics.cap_height);
+        assert_eq!(2, metrics.baseline_offset);
+        assert_eq!(14, metrics.ascender);
+        assert_eq!(0, metrics.vertical_center_offset(16));
+
+        let over: TerminalFontMetricsOverride =
+            serde_json::from_str(r#"{ "cap_height": 10, "baseline_offset": 3 }"#).unwrap();
+        let metrics = over.apply(metrics);
+        assert_eq!(10, metrics.cap_height);
+        assert_eq!(3, metrics.baseline_offset);
+        assert_eq!(14, metrics.ascender);
+        assert_eq!(0, metrics.vertical_center_offset(16));
+        assert_eq!(-2, metrics.vertical_center_offset(12));
+    }
+
+    #[test]
+    fn generate_sdf() {
+        let image = fill_image(64, 32, [255; 4]);
+        let meta: TerminalFontMeta = serde_json::from_str(r#"{ "tile_count": [8, 4] }"#).unwrap();
+        let (mut app, font) = font_fixture(&image, meta);
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+
+        let sdf = font.generate_sdf(2.0, &mut images).unwrap();
+        assert_ne!(font.image, sdf.image);
+        assert_eq!(font.tile_count, sdf.tile_count);
+        assert_eq!(font.tile_size, sdf.tile_size);
+        assert!(sdf.material().sdf_mode);
+
+        let sdf_image = images.get(&sdf.image).unwrap();
+        assert_eq!(
+            image.texture_descriptor.size,
+            sdf_image.texture_descriptor.size
+        );
+        // Every pixel is inside a glyph
+        assert!(sdf_image.data.chunks(4).all(|p| p[0] == 255));
+
+        let missing = TerminalFontAsset {
+            image: Handle::default(),
+            ..font
+        };
+        assert!(missing.generate_sdf(2.0, &mut images).is_err());
+    }
+
+    #[test]
+    fn bake_shadow() {
+        // 2x2 glyphs
+        let mut image = fill_image(32, 32, [0, 0, 0, 255]);
+        // The top left pixel of the first glyph, and the bottom right of the second
+        image.data[0..4].copy_from_slice(&[255; 4]);
+        let i = (32 + 3) * 4;
+        image.data[i..i + 4].copy_from_slice(&[255; 4]);
+        let (mut app, font) = font_fixture(&image, TerminalFontMeta::default());
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+
+        let shadowed = font
+            .bake_shadow(IVec2::new(1, -1), Color::rgb_u8(64, 0, 0), &mut images)
+            .unwrap();
+        assert_eq!(font.tile_count, shadowed.tile_count);
+        assert_eq!(font.tile_size, shadowed.tile_size);
+        assert_eq!(image.data, images.get(&font.image).unwrap().data);
+
+        let shadowed = images.get(&shadowed.image).unwrap();
+        let pixel = |x: usize, y: usize| &shadowed.data[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(&[255; 4], pixel(0, 0));
+        // One pixel right and one pixel down
+        assert_eq!(&[64, 0, 0, 255], pixel(1, 1));
+        assert_eq!(&[0, 0, 0, 255], pixel(1, 0));
+        // Clipped to the tile rather than bleeding into the neighbouring glyphs
+        assert_eq!(&[0, 0, 0, 255], pixel(4, 2));
+        assert_eq!(3, shadowed.data.chunks(4).filter(|p| p[0] > 0).count());
+    }
+
+    #[test]
+    fn coverage() {
+        // 8x8 glyphs
+        let mut image = fill_image(128, 128, [0, 0, 0, 255]);
+        // Half of glyph 1 and all of glyph 17
+        for y in 0..16 {
+            for x in 8..16 {
+                if y >= 8 || x < 12 {
+                    let i = (y * 128 + x) * 4;
+                    image.data[i..i + 4].copy_from_slice(&[255; 4]);
+                }
+            }
+        }
+        let (app, font) = font_fixture(&image, TerminalFontMeta::default());
+        let images = app.world.get_resource::<Assets<Image>>().unwrap();
+
+        assert_eq!(0.0, font.estimate_coverage(0, images).unwrap());
+        assert_eq!(0.5, font.estimate_coverage(1, images).unwrap());
+        assert_eq!(1.0, font.estimate_coverage(17, images).unwrap());
+        let table = font.build_coverage_table(images).unwrap();
+        assert_eq!(256, table.len());
+        assert_eq!(1.5, table.iter().sum::<f32>());
+        assert_eq!(1.0, table[17]);
+
+        let missing = TerminalFontAsset {
+            image: Handle::default(),
+            ..font
+        };
+        assert!(missing.estimate_coverage(0, images).is_err());
+    }
+
+    #[test]
+    fn font_variants() {
+        // 8x8 glyphs
+        let mut image = fill_image(128, 128, [0, 0, 0, 255]);
+        let mut set = |x: usize, y: usize| {
+            let i = (y * 128 + x) * 4;
+            image.data[i..i + 4].copy_from_slice(&[255; 4]);
+        };
+        // A vertical line, a horizontal line and a pixel on the right edge of
+        // the first glyph
+        for y in 1..5 {
+            set(2, y);
+        }
+        for x in 4..7 {
+            set(x, 5);
+        }
+        set(7, 0);
+        let (mut app, font) = font_fixture(&image, TerminalFontMeta::default());
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        let lit = |font: &TerminalFontAsset, images: &Assets<Image>| -> Vec<(usize, usize)> {
+            let data = &images.get(&font.image).unwrap().data;
+            (0..128 * 128)
+                .filter(|i| data[i * 4] > 0)
+                .map(|i| (i % 128, i / 128))
+                .collect()
+        };
+
+        let bold = font
+            .synthesize_variant(TerminalFontVariant::Bold, &mut images)
+            .unwrap();
+        let bold = lit(&bold, &images);
+        // One extra pixel to the right of every run of glyph pixels in a row,
+        // except at the edge of the tile
+        assert_eq!(8 + 4 + 1, bold.len());
+        for y in 1..5 {
+            assert!(bold.contains(&(3, y)));
+        }
+        assert!(bold.contains(&(7, 5)));
+        assert!(!bold.contains(&(8, 0)));
+
+        let italic = font
+            .synthesize_variant(TerminalFontVariant::Italic, &mut images)
+            .unwrap();
+        let italic = lit(&italic, &images);
+        // The pixel on the top row is pushed out of the tile
+        assert_eq!(7, italic.len());
+        for y in 1..5 {
+            assert!(italic.contains(&(3, y)));
+        }
+        // The row above the baseline doesn't move
+        assert!(italic.contains(&(4, 5)));
+        assert!(!italic.iter().any(|(x, _)| *x >= 8));
+
+        let underline = font
+            .synthesize_variant(TerminalFontVariant::Underline, &mut images)
+            .unwrap();
+        let underline = lit(&underline, &images);
+        assert_eq!(8 + 16 * 128, underline.len());
+        assert!(underline.contains(&(0, 7)) && underline.contains(&(127, 127)));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn png_round_trip() {
+        use crate::renderer::material::override_glyph;
+
+        let mut image = fill_image(32, 32, [0, 0, 0, 255]);
+        let glyph: Vec<u8> = (0..2 * 2 * 4).map(|i| i * 16).collect();
+        override_glyph(&mut image, 17, &glyph, 2, 2).unwrap();
+        let (app, font) = font_fixture(&image, TerminalFontMeta::default());
+        let images = app.world.get_resource::<Assets<Image>>().unwrap();
+        let path = std::env::temp_dir().join("bevy_ascii_terminal_png_round_trip.png");
+        font.write_to_png(&path, images).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let loaded = Image::from_buffer(
+            &bytes,
+            ImageType::Extension("png"),
+            CompressedImageFormats::NONE,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            image.texture_descriptor.size,
+            loaded.texture_descriptor.size
+        );
+        assert_eq!(image.data, loaded.data);
+
+        let missing = TerminalFontAsset {
+            image: Handle::default(),
+            ..font
+        };
+        assert!(missing.write_to_png(&path, images).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Building font textures in code.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use super::{
+    code_page_437::CP_437_CHARS,
+    font_asset::{check_rgba, FontError},
+    material::font_tile_size,
+    uv_mapping::UvMapping,
+};
+
+/// Builds a font texture one glyph at a time, for procedural fonts or glyph
+/// editors.
+///
+/// Glyphs are laid out left to right, top to bottom, so a builder with a
+/// `tile_count` of 16x16 produces a code page 437 style texture. Every glyph
+/// starts out opaque black, the default clip color of a [TerminalMaterial](super::material::TerminalMaterial).
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::font_builder::TerminalFontSpriteBuilder;
+///
+/// fn make_font(mut images: ResMut<Assets<Image>>, mut materials: ResMut<Assets<TerminalMaterial>>) {
+///     let mut builder = TerminalFontSpriteBuilder::new(UVec2::new(8, 8), UVec2::new(16, 16));
+///     // A solid block for '█'
+///     builder.fill_glyph(219, Color::WHITE).unwrap();
+///     let image = builder.build(&mut images);
+///     materials.add(TerminalMaterial::from(image));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerminalFontSpriteBuilder {
+    tile_size: UVec2,
+    tile_count: UVec2,
+    data: Vec<u8>,
+}
+
+impl TerminalFontSpriteBuilder {
+    /// Create a builder for a font with `tile_count` glyphs along each axis, each
+    /// `tile_size` pixels.
+    pub fn new(tile_size: UVec2, tile_count: UVec2) -> Self {
+        let pixels = tile_size * tile_count;
+        Self {
+            tile_size,
+            tile_count,
+            data: [0, 0, 0, 255].repeat((pixels.x * pixels.y) as usize),
+        }
+    }
+
+    /// The size of a single glyph in pixels.
+    pub fn tile_size(&self) -> UVec2 {
+        self.tile_size
+    }
+
+    /// The number of glyphs along each axis of the texture.
+    pub fn tile_count(&self) -> UVec2 {
+        self.tile_count
+    }
+
+    /// Set the pixels of a single glyph.
+    ///
+    /// `pixels` must be rgba data for one glyph, with rows going from top to bottom.
+    pub fn set_glyph(&mut self, index: usize, pixels: &[u8]) -> Result<(), FontError> {
+        self.check_index(index)?;
+        let row_len = self.tile_size.x as usize * 4;
+        let expected = row_len * self.tile_size.y as usize;
+        if pixels.len() != expected {
+            return Err(FontError::PixelDataLength {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        let image_row_len = row_len * self.tile_count.x as usize;
+        let x = (index % self.tile_count.x as usize) * row_len;
+        let y = (index / self.tile_count.x as usize) * self.tile_size.y as usize;
+        for (row, src) in pixels.chunks_exact(row_len).enumerate() {
+            let start = (y + row) * image_row_len + x;
+            self.data[start..start + row_len].copy_from_slice(src);
+        }
+        Ok(())
+    }
+
+    /// Set every pixel of a single glyph to one color.
+    pub fn fill_glyph(&mut self, index: usize, color: Color) -> Result<(), FontError> {
+        self.check_index(index)?;
+        let rgba = color
+            .as_rgba_f32()
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let pixels = rgba.repeat((self.tile_size.x * self.tile_size.y) as usize);
+        self.set_glyph(index, &pixels)
+    }
+
+    /// The font texture with every glyph set so far.
+    pub fn image(&self) -> Image {
+        let size = self.tile_size * self.tile_count;
+        Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.data.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// Add the font texture to `images`.
+    pub fn build(&self, images: &mut Assets<Image>) -> Handle<Image> {
+        images.add(self.image())
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), FontError> {
+        let count = (self.tile_count.x * self.tile_count.y) as usize;
+        if index >= count {
+            return Err(FontError::GlyphOutOfRange { index, count });
+        }
+        Ok(())
+    }
+}
+
+/// Builds a 512 glyph atlas from a code page 437 font followed by extra tiles,
+/// for combining your own art with the font's glyphs.
+///
+/// The atlas is 16 tiles wide and 32 tall. The base font fills glyphs 0 to 255
+/// and tiles added with [TerminalAtlasBuilder::add_tiles_from_image] are copied
+/// over the glyphs from their start index, in order. Glyphs that nothing was
+/// copied to are left opaque black. Use [TerminalAtlasBuilder::uv_mapping] to
+/// map chars to the added tiles.
+///
+/// Note the atlas is not a 16x16 code page 437 sheet, so it won't work with
+/// [TileScaling::Pixels](super::TileScaling::Pixels) or the material outline.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::font_builder::TerminalAtlasBuilder;
+///
+/// fn build_atlas(
+///     mut commands: Commands,
+///     server: Res<AssetServer>,
+///     mut images: ResMut<Assets<Image>>,
+///     mut materials: ResMut<Assets<TerminalMaterial>>,
+/// ) {
+///     // Assumes the image is already loaded
+///     let monsters = server.load("monsters.png");
+///     let atlas = TerminalAtlasBuilder::new("px437_8x8.png")
+///         .add_tiles_from_image(monsters, 256)
+///         .build(&mut images)
+///         .unwrap();
+///
+///     let mut bundle = TerminalBundle::new().with_size([20, 10]);
+///     bundle.renderer.material = materials.add(TerminalMaterial::from(atlas));
+///     bundle.renderer.uv_mapping = TerminalAtlasBuilder::uv_mapping("🐀🐉".chars());
+///     commands.spawn_bundle(bundle);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerminalAtlasBuilder {
+    base_font: String,
+    sources: Vec<(Handle<Image>, usize)>,
+}
+
+impl TerminalAtlasBuilder {
+    /// The number of glyphs in the atlas.
+    pub const GLYPH_COUNT: usize = 512;
+
+    /// Create a builder for an atlas starting with the glyphs of `base_font`.
+    ///
+    /// `base_font` can be the name of one of the [BuiltInFontHandles](super::material::BuiltInFontHandles)
+    /// or the asset path of a loaded font.
+    pub fn new(base_font: &str) -> Self {
+        Self {
+            base_font: base_font.to_string(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Copy every tile from an image into the atlas, starting at glyph
+    /// `start_index` and going left to right, top to bottom through the image.
+    ///
+    /// Tiles must be the same size as the base font's glyphs. Tiles past the
+    /// end of the atlas are ignored.
+    pub fn add_tiles_from_image(mut self, image: Handle<Image>, start_index: usize) -> Self {
+        self.sources.push((image, start_index));
+        self
+    }
+
+    /// A uv mapping for the atlas. Code page 437 chars map to the base font and
+    /// the chars from `extra` map to glyphs 256 and up, in order.
+    pub fn uv_mapping(extra: impl IntoIterator<Item = char>) -> UvMapping {
+        // Code page 437's glyph 255 is a non-breaking space
+        let chars = CP_437_CHARS
+            .iter()
+            .cloned()
+            .chain(std::iter::once('\u{A0}'));
+        UvMapping::from_grid([16, 32], chars.chain(extra))
+    }
+
+    /// Build the atlas and add it to `images`.
+    ///
+    /// Returns an error if any of the images isn't loaded or doesn't use 4
+    /// bytes per pixel.
+    pub fn build(&self, images: &mut Assets<Image>) -> Result<Handle<Image>, FontError> {
+        let base = images
+            .get(self.base_font.as_str())
+            .ok_or(FontError::MissingImage)?;
+        let tile_size = font_tile_size(base);
+        let mut builder = TerminalFontSpriteBuilder::new(tile_size, UVec2::new(16, 32));
+        copy_tiles(&mut builder, base, 0)?;
+
+        for (image, start_index) in &self.sources {
+            let image = images.get(image).ok_or(FontError::MissingImage)?;
+            copy_tiles(&mut builder, image, *start_index)?;
+        }
+        Ok(builder.build(images))
+    }
+}
+
+/// Copy every tile of `image` into the builder starting at glyph `start_index`.
+fn copy_tiles(
+    builder: &mut TerminalFontSpriteBuilder,
+    image: &Image,
+    start_index: usize,
+) -> Result<(), FontError> {
+    check_rgba(image)?;
+    let size = image.texture_descriptor.size;
+    let image_row_len = size.width as usize * 4;
+
+    let tile_size = builder.tile_size();
+    let tile_count = UVec2::new(size.width, size.height) / tile_size.max(UVec2::ONE);
+    let row_len = tile_size.x as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * tile_size.y as usize);
+    for i in 0..(tile_count.x * tile_count.y) as usize {
+        let index = start_index + i;
+        if index >= TerminalAtlasBuilder::GLYPH_COUNT {
+            break;
+        }
+        let x = (i % tile_count.x as usize) * row_len;
+        let y = (i / tile_count.x as usize) * tile_size.y as usize;
+        pixels.clear();
+        for row in y..y + tile_size.y as usize {
+            let start = row * image_row_len + x;
+            pixels.extend_from_slice(&image.data[start..start + row_len]);
+        }
+        builder.set_glyph(index, &pixels)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_glyphs() {
+        let mut builder = TerminalFontSpriteBuilder::new(UVec2::new(2, 2), UVec2::new(4, 2));
+
+        assert!(matches!(
+            builder.set_glyph(8, &[0; 16]),
+            Err(FontError::GlyphOutOfRange { index: 8, count: 8 })
+        ));
+        assert!(matches!(
+            builder.set_glyph(1, &[0; 12]),
+            Err(FontError::PixelDataLength {
+                expected: 16,
+                actual: 12
+            })
+        ));
+        assert!(builder.fill_glyph(8, Color::RED).is_err());
+
+        builder.fill_glyph(5, Color::RED).unwrap();
+        let mut glyph = [0; 16];
+        glyph[12..].copy_from_slice(&[1, 2, 3, 4]);
+        builder.set_glyph(2, &glyph).unwrap();
+
+        let image = builder.image();
+        assert_eq!(8, image.texture_descriptor.size.width);
+        assert_eq!(4, image.texture_descriptor.size.height);
+
+        let pixel = |x: usize, y: usize| &image.data[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+        // Glyph 5 is the second glyph of the second row
+        assert_eq!([255, 0, 0, 255], pixel(2, 2));
+        assert_eq!([255, 0, 0, 255], pixel(3, 3));
+        assert_eq!([0, 0, 0, 255], pixel(4, 2));
+        // The last pixel of glyph 2
+        assert_eq!([1, 2, 3, 4], pixel(5, 1));
+        assert_eq!([0, 0, 0, 0], pixel(4, 0));
+    }
+
+    #[test]
+    fn atlas() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Image>();
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+
+        let fill = |size: UVec2, value: u8| {
+            Image::new_fill(
+                Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[value, value, value, 255],
+                TextureFormat::Rgba8UnormSrgb,
+            )
+        };
+        // A 2x2 pixel font and two tiles of art
+        let _base = images.set("base.png", fill(UVec2::new(32, 32), 1));
+        let mut tiles = fill(UVec2::new(4, 2), 2);
+        tiles.data[8..12].copy_from_slice(&[3, 3, 3, 255]);
+        let tiles = images.add(tiles);
+
+        assert!(matches!(
+            TerminalAtlasBuilder::new("missing.png").build(&mut images),
+            Err(FontError::MissingImage)
+        ));
+        let handle = TerminalAtlasBuilder::new("base.png")
+            .add_tiles_from_image(tiles.clone(), 256)
+            .add_tiles_from_image(tiles, 511)
+            .build(&mut images)
+            .unwrap();
+
+        let atlas = images.get(handle).unwrap();
+        assert_eq!(32, atlas.texture_descriptor.size.width);
+        assert_eq!(64, atlas.texture_descriptor.size.height);
+        let pixel = |x: usize, y: usize| atlas.data[(y * 32 + x) * 4];
+        assert_eq!(1, pixel(31, 31));
+        // Glyph 256 starts the second half, glyph 257 starts with the third pixel
+        assert_eq!(2, pixel(0, 32));
+        assert_eq!(3, pixel(2, 32));
+        assert_eq!(0, pixel(4, 32));
+        // Only the first tile of the last image fit
+        assert_eq!(2, pixel(30, 62));
+
+        let mapping = TerminalAtlasBuilder::uv_mapping("xy".chars());
+        let mapping_with_art = TerminalAtlasBuilder::uv_mapping("🐀🐉".chars());
+        assert_eq!([0.0, 0.5], mapping_with_art.uvs_from_glyph('🐀')[0]);
+        assert_eq!([1.0 / 16.0, 0.5], mapping_with_art.uvs_from_glyph('🐉')[0]);
+        assert_eq!(
+            mapping.uvs_from_glyph('A'),
+            mapping_with_art.uvs_from_glyph('A')
+        );
+        assert_eq!(
+            [15.0 / 16.0, 15.0 / 32.0],
+            mapping.uvs_from_glyph('\u{A0}')[0]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! A keyboard shortcut for toggling [TerminalMaterial::invert_colors].
+
+use bevy::prelude::*;
+
+use super::{material::TerminalMaterial, renderer_3d::TerminalMaterial3d};
+
+/// Resource specifying the key which toggles [TerminalMaterial::invert_colors]
+/// on every terminal material, including the materials of 3d terminals.
+/// Defaults to `F11`.
+///
+/// Set to `None` to disable the shortcut.
+#[derive(Clone, Copy)]
+pub struct TerminalInvertColorsKey(pub Option<KeyCode>);
+
+impl Default for TerminalInvertColorsKey {
+    fn default() -> Self {
+        Self(Some(KeyCode::F11))
+    }
+}
+
+pub(crate) struct TerminalInvertColorsPlugin;
+
+impl Plugin for TerminalInvertColorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerminalInvertColorsKey>()
+            .add_system(toggle_invert_colors);
+    }
+}
+
+fn toggle_invert_colors(
+    keys: Option<Res<Input<KeyCode>>>,
+    toggle_key: Res<TerminalInvertColorsKey>,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    materials_3d: Option<ResMut<Assets<TerminalMaterial3d>>>,
+) {
+    let pressed = match (keys, toggle_key.0) {
+        (Some(keys), Some(key)) => keys.just_pressed(key),
+        _ => false,
+    };
+    if !pressed {
+        return;
+    }
+
+    let handles: Vec<_> = materials.ids().collect();
+    for id in handles {
+        if let Some(material) = materials.get_mut(id) {
+            material.invert_colors = !material.invert_colors;
+        }
+    }
+    if let Some(mut materials) = materials_3d {
+        let handles: Vec<_> = materials.ids().collect();
+        for id in handles {
+            if let Some(material) = materials.get_mut(id) {
+                material.0.invert_colors = !material.0.invert_colors;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin};
+
+    use super::*;
+
+    /// Run the shader's `invert_color` function on a color.
+    fn shader_invert_color(color: Color) -> [f32; 4] {
+        use naga::{BinaryOperator, Expression, MathFunction};
+
+        fn eval(
+            module: &naga::Module,
+            function: &naga::Function,
+            expr: naga::Handle<Expression>,
+            arg: &[f32],
+        ) -> Vec<f32> {
+            let eval = |expr| eval(module, function, expr, arg);
+            match &function.expressions[expr] {
+                Expression::FunctionArgument(0) => arg.to_vec(),
+                Expression::Constant(c) => match module.constants[*c].inner {
+                    naga::ConstantInner::Scalar {
+                        value: naga::ScalarValue::Float(value),
+                        ..
+                    } => vec![value as f32],
+                    ref inner => panic!("Unexpected constant {:?}", inner),
+                },
+                Expression::Compose { components, .. } => {
+                    components.iter().flat_map(|c| eval(*c)).collect()
+                }
+                Expression::Swizzle {
+                    size,
+                    vector,
+                    pattern,
+                } => {
+                    let vector = eval(*vector);
+                    let size = *size as usize;
+                    pattern[..size]
+                        .iter()
+                        .map(|c| vector[*c as usize])
+                        .collect()
+                }
+                Expression::AccessIndex { base, index } => vec![eval(*base)[*index as usize]],
+                Expression::Binary {
+                    op: BinaryOperator::Subtract,
+                    left,
+                    right,
+                } => eval(*left)
+                    .iter()
+                    .zip(eval(*right))
+                    .map(|(l, r)| l - r)
+                    .collect(),
+                Expression::Math {
+                    fun: MathFunction::Clamp,
+                    arg,
+                    arg1: Some(min),
+                    arg2: Some(max),
+                    ..
+                } => {
+                    let (min, max) = (eval(*min), eval(*max));
+                    eval(*arg)
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| v.clamp(min[i], max[i]))
+                        .collect()
+                }
+                expr => panic!("Unexpected expression {:?}", expr),
+            }
+        }
+
+        let source = include_str!("terminal_common.wgsl");
+        let start = source.find("// Invert a color's rgb").unwrap();
+        let end = start + source[start..].find("\n}\n").unwrap() + 3;
+        let module = naga::front::wgsl::parse_str(&source[start..end]).unwrap();
+        let (_, function) = module.functions.iter().next().unwrap();
+        let value = function
+            .body
+            .iter()
+            .find_map(|s| match s {
+                naga::Statement::Return { value } => *value,
+                _ => None,
+            })
+            .unwrap();
+        let result = eval(&module, function, value, &color.as_rgba_f32());
+        [result[0], result[1], result[2], result[3]]
+    }
+
+    #[test]
+    fn shader_inverts() {
+        assert_eq!([0.0, 0.0, 0.0, 1.0], shader_invert_color(Color::WHITE));
+        assert_eq!(
+            [1.0, 1.0, 1.0, 0.5],
+            shader_invert_color(Color::rgba(0.0, 0.0, 0.0, 0.5))
+        );
+        assert_eq!([0.0, 1.0, 1.0, 1.0], shader_invert_color(Color::RED));
+    }
+
+    #[test]
+    fn toggle() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<TerminalMaterial>()
+            .add_asset::<TerminalMaterial3d>()
+            .init_resource::<Input<KeyCode>>()
+            .add_plugin(TerminalInvertColorsPlugin);
+
+        let handle = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap()
+            .add(TerminalMaterial::default());
+        let handle_3d = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial3d>>()
+            .unwrap()
+            .add(TerminalMaterial::default().into());
+
+        app.world
+            .get_resource_mut::<Input<KeyCode>>()
+            .unwrap()
+            .press(KeyCode::F11);
+        app.update();
+
+        let materials = app
+            .world
+            .get_resource::<Assets<TerminalMaterial>>()
+            .unwrap();
+        assert!(materials.get(handle).unwrap().invert_colors);
+        let materials = app
+            .world
+            .get_resource::<Assets<TerminalMaterial3d>>()
+            .unwrap();
+        assert!(materials.get(handle_3d).unwrap().0.invert_colors);
+    }
+}
@@ -0,0 +1,117 @@
+//! A terminal component for drawing with fonts which aren't laid out in code
+//! page 437 order.
+
+use bevy::prelude::Component;
+
+use super::code_page_437;
+
+/// Terminal component which remaps code page 437 characters to the index of
+/// the glyph drawn for them on the font's tile sheet.
+///
+/// `map[code]` is the glyph index used for the code page 437 character
+/// `code`. The glyph's uvs are then looked up from the terminal's
+/// [UvMapping](super::uv_mapping::UvMapping) as if it were that character.
+/// Characters outside of code page 437 are drawn unmapped.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::TerminalKeyMap;
+///
+/// fn setup(mut commands: Commands) {
+///     // A font with it's uppercase letters in the lowercase slots and vice versa
+///     let mut key_map = TerminalKeyMap::identity();
+///     for upper in b'A'..=b'Z' {
+///         key_map.swap(upper, upper.to_ascii_lowercase());
+///     }
+///
+///     let mut term = TerminalBundle::new().with_size([10, 1]);
+///     term.terminal.put_string([0, 0], "Hello");
+///     commands.spawn_bundle(term).insert(key_map);
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminalKeyMap {
+    pub map: [u8; 256],
+}
+
+impl Default for TerminalKeyMap {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl TerminalKeyMap {
+    /// A key map which draws every character with the glyph at it's own index.
+    pub fn identity() -> Self {
+        let mut map = [0; 256];
+        for (i, glyph) in map.iter_mut().enumerate() {
+            *glyph = i as u8;
+        }
+        Self { map }
+    }
+
+    /// The key map for fonts laid out in code page 437 order, such as all of the
+    /// built in fonts.
+    ///
+    /// Code page 437 characters are stored by their code page index, so this is
+    /// the same as [TerminalKeyMap::identity].
+    pub fn cp437() -> Self {
+        Self::identity()
+    }
+
+    /// Swap the glyphs drawn for two characters.
+    pub fn swap(&mut self, a: u8, b: u8) {
+        self.map.swap(a as usize, b as usize);
+    }
+
+    /// The character whose uvs are used to draw `ch`.
+    #[inline]
+    pub fn map_glyph(&self, ch: char) -> char {
+        let index = code_page_437::glyph_to_index(ch);
+        if code_page_437::index_to_glyph(index) != ch {
+            return ch;
+        }
+        code_page_437::index_to_glyph(self.map[index as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        renderer::{uv_mapping::UvMapping, TerminalRendererTileData},
+        Terminal,
+    };
+
+    use super::*;
+
+    #[test]
+    fn swapped() {
+        let mut key_map = TerminalKeyMap::cp437();
+        assert_eq!(TerminalKeyMap::identity(), key_map);
+        assert_eq!('A', key_map.map_glyph('A'));
+
+        key_map.swap(b'A', b'B');
+        assert_eq!('B', key_map.map_glyph('A'));
+        assert_eq!('A', key_map.map_glyph('B'));
+        assert_eq!('ア', key_map.map_glyph('ア'));
+
+        let mut term = Terminal::with_size([3, 1]);
+        term.put_string([0, 0], "ABC");
+        let uv_mapping = UvMapping::default();
+        let mut data = TerminalRendererTileData::with_size(term.size());
+        data.set_key_map(&key_map);
+        data.update_from_tiles(&term.composited_tiles(), &uv_mapping);
+
+        assert_eq!(uv_mapping.uvs_from_glyph('B'), &data.uvs[0..4]);
+        assert_eq!(uv_mapping.uvs_from_glyph('A'), &data.uvs[4..8]);
+        assert_eq!(uv_mapping.uvs_from_glyph('C'), &data.uvs[8..12]);
+
+        // Changing the key map rewrites glyphs from the new one
+        data.set_key_map(&TerminalKeyMap::identity());
+        data.update_from_tiles(&term.composited_tiles(), &uv_mapping);
+        assert_eq!(uv_mapping.uvs_from_glyph('A'), &data.uvs[0..4]);
+    }
+}
@@ -35,8 +35,9 @@
 
 use bevy::app::{App, Plugin};
 use bevy::asset::{AssetServer, Assets, Handle, HandleUntyped};
-use bevy::ecs::system::{lifetimeless::SRes, SystemParamItem};
-use bevy::math::Vec4;
+use bevy::core::Time;
+use bevy::ecs::system::{lifetimeless::SRes, Commands, Res, SystemParamItem};
+use bevy::math::{UVec2, Vec2, Vec4};
 use bevy::prelude::Mesh;
 use bevy::reflect::TypeUuid;
 use bevy::render::mesh::MeshVertexBufferLayout;
@@ -49,19 +50,27 @@ use bevy::render::{
         std140::{AsStd140, Std140},
         *,
     },
-    renderer::RenderDevice,
+    renderer::{RenderDevice, RenderQueue},
     texture::Image,
+    RenderApp, RenderStage,
 };
 
 use bevy::sprite::{Material2dPipeline, Material2dPlugin, SpecializedMaterial2d};
 use bevy::utils::HashMap;
 
-use super::plugin::{ATTRIBUTE_COLOR_BG, ATTRIBUTE_COLOR_FG, ATTRIBUTE_UV};
+use super::font_asset::{check_rgba, FontError};
+use super::plugin::{
+    ATTRIBUTE_BG_UV, ATTRIBUTE_COLOR_BG, ATTRIBUTE_COLOR_FG, ATTRIBUTE_TERMINAL_UV, ATTRIBUTE_UV,
+};
 
 /// The default shader handle used by the terminal.
 pub const TERMINAL_MATERIAL_SHADER_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 3142086872234592509);
 
+/// The handle of the shader module shared by the 2d and 3d terminal shaders.
+pub const TERMINAL_COMMON_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 8406732315907265126);
+
 /// The default material handle used by the terminal.
 pub const TERMINAL_DEFAULT_MATERIAL_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 2121056571224552501);
@@ -85,6 +94,10 @@ macro_rules! include_font {
 /// A resource which can be used to retrieve the image handles
 /// for the terminal's built-in fonts.
 ///
+/// Fonts discovered in the directory set via
+/// [TerminalPlugin::with_asset_directory](crate::TerminalPlugin::with_asset_directory)
+/// are also registered here, by file name, once they've finished loading.
+///
 /// # Example
 ///
 /// ```
@@ -105,14 +118,55 @@ macro_rules! include_font {
 /// ```
 pub struct BuiltInFontHandles {
     map: HashMap<String, Handle<Image>>,
+    tile_sizes: HashMap<String, UVec2>,
 }
 
 impl BuiltInFontHandles {
+    pub(crate) fn new() -> Self {
+        Self {
+            map: HashMap::default(),
+            tile_sizes: HashMap::default(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, font_name: &str, handle: Handle<Image>, tile_size: UVec2) {
+        self.map.insert(font_name.to_string(), handle);
+        self.tile_sizes.insert(font_name.to_string(), tile_size);
+    }
+
     /// Retrieve a built-in font handle by it's name. Must include ".png" the extension.
     pub fn get(&self, font_name: &str) -> Option<&Handle<Image>> {
         self.map.get(font_name)
     }
 
+    /// Retrieve the size of a single tile in pixels for a built-in font.
+    pub fn tile_size(&self, font_name: &str) -> Option<UVec2> {
+        self.tile_sizes.get(font_name).copied()
+    }
+
+    /// Retrieve the name of the built-in font whose tile size in pixels is closest
+    /// to `target`.
+    ///
+    /// If multiple fonts are equally close the name that sorts first is returned.
+    pub fn nearest_to_pixel_size(&self, target: UVec2) -> Option<&str> {
+        let target = target.as_ivec2();
+        self.tile_sizes
+            .iter()
+            .map(|(name, size)| {
+                let d = size.as_ivec2() - target;
+                (name, d.x * d.x + d.y * d.y)
+            })
+            .min_by(|(a_name, a), (b_name, b)| a.cmp(b).then_with(|| a_name.cmp(b_name)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The names of every font, sorted alphabetically.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.map.keys().map(|s| s.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
     /// An iterator over the name-value-pairs of the built in font handles
     /// for the terminal.
     ///
@@ -123,6 +177,72 @@ impl BuiltInFontHandles {
     }
 }
 
+/// The size of a single tile in pixels for a code page 437 font texture.
+pub(crate) fn font_tile_size(image: &Image) -> UVec2 {
+    let size = image.texture_descriptor.size;
+    UVec2::new(size.width, size.height) / UVec2::new(16, 16)
+}
+
+/// Replace the pixels of a single glyph in a code page 437 font texture.
+///
+/// `pixels` must be rgba data for a `width` by `height` glyph, which must match the
+/// tile size of the font. The texture on the gpu will be updated if the image was
+/// retrieved via [`Assets::get_mut`].
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::material::override_glyph;
+///
+/// fn add_icon(fonts: Res<BuiltInFontHandles>, mut images: ResMut<Assets<Image>>) {
+///     let handle = fonts.get("px437_8x8.png").unwrap();
+///     let image = images.get_mut(handle).unwrap();
+///     let icon = [255; 8 * 8 * 4];
+///     override_glyph(image, 1, &icon, 8, 8).unwrap();
+/// }
+/// ```
+pub fn override_glyph(
+    image: &mut Image,
+    index: usize,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), FontError> {
+    if index >= 256 {
+        return Err(FontError::GlyphOutOfRange { index, count: 256 });
+    }
+
+    let tile_size = font_tile_size(image);
+    if tile_size != UVec2::new(width, height) {
+        return Err(FontError::TileSizeMismatch {
+            expected: tile_size,
+            actual: UVec2::new(width, height),
+        });
+    }
+
+    let row_len = width as usize * 4;
+    if pixels.len() != row_len * height as usize {
+        return Err(FontError::PixelDataLength {
+            expected: row_len * height as usize,
+            actual: pixels.len(),
+        });
+    }
+
+    check_rgba(image)?;
+    let image_row_len = image.texture_descriptor.size.width as usize * 4;
+
+    let x = (index % 16) * width as usize;
+    let y = (index / 16) * height as usize;
+    for (row, src) in pixels.chunks_exact(row_len).enumerate() {
+        let start = (y + row) * image_row_len + x * 4;
+        image.data[start..start + row_len].copy_from_slice(src);
+    }
+
+    Ok(())
+}
+
 /// Plugin for the terminal renderer. Initializes resources and systems related to rendering.
 #[derive(Default)]
 pub struct TerminalMaterialPlugin;
@@ -131,16 +251,27 @@ impl Plugin for TerminalMaterialPlugin {
     fn build(&self, app: &mut App) {
         let mut shaders = app.world.get_resource_mut::<Assets<Shader>>().unwrap();
 
+        shaders.set_untracked(
+            TERMINAL_COMMON_SHADER_HANDLE,
+            Shader::from_wgsl(include_str!("terminal_common.wgsl")),
+        );
         shaders.set_untracked(
             TERMINAL_MATERIAL_SHADER_HANDLE,
             Shader::from_wgsl(include_str!("terminal.wgsl")),
         );
         app.add_plugin(Material2dPlugin::<TerminalMaterial>::default());
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_system_to_stage(RenderStage::Extract, extract_terminal_material_time)
+                // After materials are prepared, so a material rebuilt this frame is updated too
+                .add_system_to_stage(
+                    RenderStage::Queue,
+                    write_terminal_material_time::<TerminalMaterial>,
+                );
+        }
 
-        let mut fonts = BuiltInFontHandles {
-            map: HashMap::default(),
-        };
-        let font_map = &mut fonts.map;
+        let mut fonts = BuiltInFontHandles::new();
+        let font_map = &mut fonts;
 
         let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
 
@@ -171,15 +302,61 @@ impl Plugin for TerminalMaterialPlugin {
 fn add_font_resource(
     font: (&str, Image),
     images: &mut Assets<Image>,
-    font_map: &mut HashMap<String, Handle<Image>>,
+    font_map: &mut BuiltInFontHandles,
 ) -> Handle<Image> {
+    let tile_size = font_tile_size(&font.1);
     let handle = images.set(font.0, font.1);
-    font_map.insert(font.0.to_string(), handle.clone());
+    font_map.insert(font.0, handle.clone(), tile_size);
     handle
 }
 
+/// How the font texture is sampled when a terminal is scaled up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpscaleMode {
+    /// Sample the texture directly.
+    None,
+    /// Snap uvs to the center of the nearest texel for crisp pixels at any scale.
+    Integer,
+    /// Blend nearest and bilinear sampling, only filtering across texel edges.
+    ///
+    /// Requires the font texture to use a linear sampler.
+    SharpBilinear,
+}
+
+impl Default for UpscaleMode {
+    fn default() -> Self {
+        UpscaleMode::None
+    }
+}
+
+/// The shape a terminal is cut to by [TerminalMaterial::mask_shape].
+///
+/// Sizes are in the terminal's uvs, where (0,0) is the bottom left of the
+/// terminal and (1,1) is the top right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaskShape {
+    /// Draw the whole terminal.
+    None,
+    /// A circle with the given radius.
+    Circle(f32),
+    /// An ellipse with the given half width and half height.
+    Ellipse(Vec2),
+}
+
+impl MaskShape {
+    /// The half width and half height of the shape, or `None` if the whole
+    /// terminal is drawn.
+    pub fn half_extents(&self) -> Option<Vec2> {
+        match *self {
+            MaskShape::None => None,
+            MaskShape::Circle(radius) => Some(Vec2::splat(radius)),
+            MaskShape::Ellipse(half_extents) => Some(half_extents),
+        }
+    }
+}
+
 /// The material for rendering a terminal.
-#[derive(Debug, Clone, TypeUuid)]
+#[derive(Debug, Clone, PartialEq, TypeUuid)]
 #[uuid = "e228a534-e3ca-2e1e-ab9d-4d8bc1ad8c19"]
 pub struct TerminalMaterial {
     /// The clip color for the active font texture.
@@ -190,6 +367,143 @@ pub struct TerminalMaterial {
 
     /// The font texture rendered by the terminal.
     pub texture: Option<Handle<Image>>,
+
+    /// How the font texture is sampled when the terminal is scaled up.
+    pub upscale_mode: UpscaleMode,
+
+    /// A solid color drawn behind the terminal's background colors.
+    ///
+    /// If `None` clipped parts of the texture are only drawn with the
+    /// tile's background color, which may be transparent.
+    pub clear_color: Option<Color>,
+
+    /// If set, a one pixel outline of this color is drawn around the
+    /// foreground of every glyph.
+    pub outline_color: Option<Color>,
+
+    /// Overrides how many pixels of the font texture make up one unit of world
+    /// space when using [TileScaling::World](super::TileScaling::World).
+    ///
+    /// If `None` each tile takes up exactly one world unit.
+    pub pixels_per_unit: Option<u32>,
+
+    /// If true the final color of every pixel is inverted, leaving alpha as is.
+    pub invert_colors: bool,
+
+    /// The strength of ordered dithering applied to the final color, from 0 to 1.
+    ///
+    /// When above 0 each color channel is reduced to 8 levels and a 4x4 bayer
+    /// matrix, scaled by this value, decides whether a pixel rounds up or down.
+    pub dithering_strength: f32,
+
+    /// How much every other row of screen pixels is darkened, from 0 to 1.
+    pub scanline_strength: f32,
+
+    /// The strength of random per-pixel noise added to the final color, from 0 to 1.
+    pub noise_strength: f32,
+
+    /// Seeds the per-pixel noise. Change it every frame to animate the noise.
+    pub noise_seed: f32,
+
+    /// If set, the screen darkens towards it's edges starting at this distance
+    /// from the center, where 1 is the edge of the screen.
+    pub vignette_radius: Option<f32>,
+
+    /// If set, how far the red, green and blue channels of the font texture are
+    /// shifted apart. See [ChromaticAberration].
+    pub chromatic_aberration: Option<ChromaticAberration>,
+
+    /// If set, gamma, contrast and saturation adjustments applied to the final color.
+    pub color_correction: Option<ColorCorrection>,
+
+    /// If true the font texture is treated as a signed distance field, which keeps
+    /// glyph edges sharp at any scale. The distance is read from the red channel.
+    ///
+    /// SDF textures can be made from regular fonts with [font_to_sdf](super::sdf::font_to_sdf).
+    pub sdf_mode: bool,
+
+    /// The distance field value at the edge of a glyph.
+    pub sdf_threshold: f32,
+
+    /// How far from [TerminalMaterial::sdf_threshold] glyph edges are smoothed.
+    pub sdf_smoothing: f32,
+
+    /// If above 0, blocks of this many font texture pixels along each axis are
+    /// drawn as a single solid color. 2 doubles the size of every pixel.
+    ///
+    /// Pixelation replaces the [TerminalMaterial::upscale_mode].
+    pub pixelation_size: f32,
+
+    /// If set, the rectangle drawn as the terminal's cursor, as x, y, width
+    /// and height in the terminal mesh's local space. x and y are the bottom
+    /// left corner.
+    ///
+    /// This is normally set by a [TerminalCursor](super::cursor::TerminalCursor).
+    pub cursor_rect: Option<Vec4>,
+
+    /// The color the cursor is drawn with.
+    pub cursor_color: Color,
+
+    /// A sprite sheet of 16x16 background tiles, drawn behind the glyphs of tiles
+    /// with a [bg_index](crate::Tile::bg_index) instead of their background color.
+    ///
+    /// Sprites are numbered row by row from the top left, like the glyphs of a
+    /// code page 437 font.
+    pub background_texture: Option<Handle<Image>>,
+
+    /// If set, glyphs with a foreground color brighter than
+    /// [TerminalMaterial::glow_threshold] get a halo of this color.
+    ///
+    /// The halo is a 3x3 box blur of the glyph within it's own tile, a cheap
+    /// approximation of bloom. For accurate bloom use post processing instead.
+    pub glow_color: Option<Color>,
+
+    /// The luminance, from 0 to 1, a glyph's foreground color must exceed to glow.
+    pub glow_threshold: f32,
+
+    /// How far, in uvs, glyphs are shifted sideways by an animated wave.
+    ///
+    /// Each row of pixels is offset by
+    /// `sin(y * wave_frequency + time) * wave_distortion`, where `y` is the
+    /// pixel's position in the terminal mesh's local space. Small values such
+    /// as 0.01 keep glyphs readable.
+    pub wave_distortion: f32,
+
+    /// How many radians the wave advances per unit of the terminal mesh's
+    /// local space.
+    pub wave_frequency: f32,
+
+    /// Group every `mosaic_tiles` x `mosaic_tiles` block of tiles into a single
+    /// block of their average color. 1 disables the effect.
+    ///
+    /// Each tile's color is it's foreground and background colors mixed by how
+    /// much of it's glyph is drawn. Blocks are averaged into the terminal's
+    /// renderer data, the terminal's tiles aren't changed.
+    pub mosaic_tiles: u32,
+
+    /// The shape the terminal is cut to. Pixels outside the shape are discarded.
+    pub mask_shape: MaskShape,
+
+    /// The center of the [TerminalMaterial::mask_shape] in the terminal's uvs.
+    pub mask_center: Vec2,
+
+    /// The width, in the terminal's uvs, of the edge over which the mask fades
+    /// out. 0 gives a hard edge.
+    pub mask_feather: f32,
+
+    /// How much of the previous frame, from 0 to 1, is blended into the
+    /// terminal's output for a motion blur effect. 0 disables the effect.
+    ///
+    /// Requires a [TerminalAccumulationBuffer](super::accumulation::TerminalAccumulationBuffer)
+    /// which the terminal is rendered to.
+    pub accumulation_strength: f32,
+
+    /// The previous frame, sampled when [TerminalMaterial::accumulation_strength]
+    /// is above 0.
+    ///
+    /// This is normally set every frame from the
+    /// [TerminalAccumulationBuffer](super::accumulation::TerminalAccumulationBuffer).
+    pub accumulation_texture: Option<Handle<Image>>,
 }
 
 impl Default for TerminalMaterial {
@@ -197,7 +511,243 @@ impl Default for TerminalMaterial {
         TerminalMaterial {
             clip_color: Color::BLACK,
             texture: None,
+            upscale_mode: UpscaleMode::None,
+            clear_color: None,
+            outline_color: None,
+            pixels_per_unit: None,
+            invert_colors: false,
+            dithering_strength: 0.0,
+            scanline_strength: 0.0,
+            noise_strength: 0.0,
+            noise_seed: 0.0,
+            vignette_radius: None,
+            chromatic_aberration: None,
+            color_correction: None,
+            sdf_mode: false,
+            sdf_threshold: 0.5,
+            sdf_smoothing: 0.05,
+            pixelation_size: 0.0,
+            cursor_rect: None,
+            cursor_color: Color::WHITE,
+            background_texture: None,
+            glow_color: None,
+            glow_threshold: 0.9,
+            wave_distortion: 0.0,
+            wave_frequency: 5.0,
+            mosaic_tiles: 1,
+            mask_shape: MaskShape::None,
+            mask_center: Vec2::splat(0.5),
+            mask_feather: 0.0,
+            accumulation_strength: 0.0,
+            accumulation_texture: None,
+        }
+    }
+}
+
+/// Color adjustments applied to every pixel of a terminal, for monitor calibration
+/// or to set the mood of a scene.
+///
+/// A value of 1 for any setting leaves the color unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, AsStd140)]
+pub struct ColorCorrection {
+    /// Each color channel is raised to the power of `1 / gamma`.
+    pub gamma: f32,
+    /// Scales the distance of each channel from 0.5.
+    pub contrast: f32,
+    /// Scales the distance of each color from a gray of the same luminance.
+    /// 0 is fully grayscale.
+    pub saturation: f32,
+}
+
+impl Default for ColorCorrection {
+    fn default() -> Self {
+        ColorCorrection {
+            gamma: 1.0,
+            contrast: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+/// Offsets, in uvs, at which each color channel of a glyph is sampled from the
+/// font texture, for a retro CRT look.
+///
+/// Offsets are clamped so a channel never samples a neighbouring glyph. Small
+/// values such as 0.002 work best.
+#[derive(Debug, Clone, Copy, Default, PartialEq, AsStd140)]
+pub struct ChromaticAberration {
+    pub r_offset: Vec2,
+    pub g_offset: Vec2,
+    pub b_offset: Vec2,
+}
+
+impl ChromaticAberration {
+    /// Shift the red and blue channels apart horizontally by `offset` each,
+    /// leaving the green channel in place.
+    pub fn horizontal(offset: f32) -> Self {
+        Self {
+            r_offset: Vec2::new(-offset, 0.0),
+            g_offset: Vec2::ZERO,
+            b_offset: Vec2::new(offset, 0.0),
+        }
+    }
+
+    /// Whether every channel is sampled in place, which leaves glyphs unchanged.
+    pub fn is_zero(&self) -> bool {
+        self.r_offset == Vec2::ZERO && self.g_offset == Vec2::ZERO && self.b_offset == Vec2::ZERO
+    }
+}
+
+impl TerminalMaterial {
+    /// Override the pixels per unit for world space tile sizing.
+    pub fn with_pixels_per_unit(mut self, ppu: u32) -> Self {
+        self.pixels_per_unit = Some(ppu);
+        self
+    }
+
+    /// Set the strength of ordered dithering, from 0 to 1.
+    pub fn with_dithering_strength(mut self, strength: f32) -> Self {
+        self.dithering_strength = strength;
+        self
+    }
+
+    /// Set the size of pixelation blocks, in font texture pixels.
+    pub fn with_pixelation_size(mut self, size: f32) -> Self {
+        self.pixelation_size = size;
+        self
+    }
+
+    /// Enable glow for glyphs brighter than [TerminalMaterial::glow_threshold].
+    pub fn with_glow_color(mut self, color: Color) -> Self {
+        self.glow_color = Some(color);
+        self
+    }
+
+    /// Animate glyphs with a sideways wave of the given strength and frequency.
+    /// See [TerminalMaterial::wave_distortion].
+    pub fn with_wave_distortion(mut self, distortion: f32, frequency: f32) -> Self {
+        self.wave_distortion = distortion;
+        self.wave_frequency = frequency;
+        self
+    }
+
+    /// Group blocks of tiles into a single color. See [TerminalMaterial::mosaic_tiles].
+    pub fn with_mosaic_tiles(mut self, tiles: u32) -> Self {
+        self.mosaic_tiles = tiles;
+        self
+    }
+
+    /// Cut the terminal to a shape with an edge which fades out over `feather`.
+    /// See [TerminalMaterial::mask_shape].
+    pub fn with_mask(mut self, shape: MaskShape, feather: f32) -> Self {
+        self.mask_shape = shape;
+        self.mask_feather = feather;
+        self
+    }
+
+    /// Sample each color channel of the font texture at an offset. See
+    /// [ChromaticAberration].
+    pub fn with_chromatic_aberration(
+        mut self,
+        r_offset: Vec2,
+        g_offset: Vec2,
+        b_offset: Vec2,
+    ) -> Self {
+        self.chromatic_aberration = Some(ChromaticAberration {
+            r_offset,
+            g_offset,
+            b_offset,
+        });
+        self
+    }
+
+    /// Blend `strength` of the previous frame into the terminal's output. See
+    /// [TerminalMaterial::accumulation_strength].
+    pub fn with_accumulation_strength(mut self, strength: f32) -> Self {
+        self.accumulation_strength = strength;
+        self
+    }
+
+    /// Enable color correction with the given settings. See [ColorCorrection].
+    pub fn set_color_correction(&mut self, gamma: f32, contrast: f32, saturation: f32) {
+        self.color_correction = Some(ColorCorrection {
+            gamma,
+            contrast,
+            saturation,
+        });
+    }
+
+    /// Disable color correction.
+    pub fn reset_color_correction(&mut self) {
+        self.color_correction = None;
+    }
+
+    /// The shader flags for the material's current settings.
+    pub fn flags(&self) -> TerminalMaterialFlags {
+        let mut flags = TerminalMaterialFlags::NONE;
+        if self.texture.is_some() {
+            flags |= TerminalMaterialFlags::TEXTURE;
+        }
+        match self.upscale_mode {
+            UpscaleMode::None => {}
+            UpscaleMode::Integer => flags |= TerminalMaterialFlags::UPSCALE_INTEGER,
+            UpscaleMode::SharpBilinear => flags |= TerminalMaterialFlags::UPSCALE_SHARP_BILINEAR,
+        }
+        if self.clear_color.is_some() {
+            flags |= TerminalMaterialFlags::CLEAR_COLOR;
+        }
+        if self.outline_color.is_some() {
+            flags |= TerminalMaterialFlags::OUTLINE;
+        }
+        if self.invert_colors {
+            flags |= TerminalMaterialFlags::INVERT_COLORS;
+        }
+        if self.dithering_strength > 0.0 {
+            flags |= TerminalMaterialFlags::DITHERING;
+        }
+        if self.scanline_strength > 0.0 {
+            flags |= TerminalMaterialFlags::SCANLINES;
         }
+        if self.noise_strength > 0.0 {
+            flags |= TerminalMaterialFlags::NOISE;
+        }
+        if self.vignette_radius.is_some() {
+            flags |= TerminalMaterialFlags::VIGNETTE;
+        }
+        if matches!(self.chromatic_aberration, Some(c) if !c.is_zero()) {
+            flags |= TerminalMaterialFlags::CHROMATIC_ABERRATION;
+        }
+        if self.color_correction.is_some() {
+            flags |= TerminalMaterialFlags::COLOR_CORRECTION;
+        }
+        if self.sdf_mode {
+            flags |= TerminalMaterialFlags::SDF_MODE;
+        }
+        if self.pixelation_size > 0.0 {
+            flags |= TerminalMaterialFlags::PIXELATION;
+        }
+        if self.cursor_rect.is_some() {
+            flags |= TerminalMaterialFlags::CURSOR;
+        }
+        if self.background_texture.is_some() {
+            flags |= TerminalMaterialFlags::BACKGROUND_TEXTURE;
+        }
+        if self.glow_color.is_some() {
+            flags |= TerminalMaterialFlags::GLOW;
+        }
+        if self.wave_distortion != 0.0 {
+            flags |= TerminalMaterialFlags::WAVE_DISTORTION;
+        }
+        if self.mosaic_tiles > 1 {
+            flags |= TerminalMaterialFlags::MOSAIC;
+        }
+        if self.mask_shape != MaskShape::None {
+            flags |= TerminalMaterialFlags::CIRCULAR_MASK;
+        }
+        if self.accumulation_strength > 0.0 && self.accumulation_texture.is_some() {
+            flags |= TerminalMaterialFlags::ACCUMULATION;
+        }
+        flags
     }
 }
 
@@ -205,26 +755,174 @@ impl From<Handle<Image>> for TerminalMaterial {
     fn from(texture: Handle<Image>) -> Self {
         TerminalMaterial {
             texture: Some(texture),
-            clip_color: Color::BLACK,
+            ..Default::default()
         }
     }
 }
 
-// NOTE: These must match the bit flags in shader.wgsl!
+// NOTE: These must match the bit flags in terminal_common.wgsl!
 bitflags::bitflags! {
     #[repr(transparent)]
     pub struct TerminalMaterialFlags: u32 {
-        const TEXTURE           = (1 << 0);
-        const NONE              = 0;
-        const UNINITIALIZED     = 0xFFFF;
+        const TEXTURE                = (1 << 0);
+        const UPSCALE_INTEGER        = (1 << 1);
+        const UPSCALE_SHARP_BILINEAR = (1 << 2);
+        const CLEAR_COLOR            = (1 << 3);
+        const OUTLINE                = (1 << 4);
+        const INVERT_COLORS          = (1 << 5);
+        const DITHERING              = (1 << 6);
+        const SCANLINES              = (1 << 7);
+        const NOISE                  = (1 << 8);
+        const VIGNETTE               = (1 << 9);
+        const CHROMATIC_ABERRATION   = (1 << 10);
+        const COLOR_CORRECTION       = (1 << 11);
+        const SDF_MODE               = (1 << 12);
+        const PIXELATION             = (1 << 13);
+        const CURSOR                 = (1 << 14);
+        const BACKGROUND_TEXTURE     = (1 << 15);
+        const GLOW                   = (1 << 16);
+        const WAVE_DISTORTION        = (1 << 17);
+        const MOSAIC                 = (1 << 18);
+        const CIRCULAR_MASK          = (1 << 19);
+        const ACCUMULATION           = (1 << 20);
+        const NONE                   = 0;
+        const UNINITIALIZED          = 0xFFFF_FFFF;
     }
 }
 
+// NOTE: The layout must match the TerminalMaterial struct in terminal_common.wgsl!
 /// The GPU representation of the uniform data of a [`TerminalMaterial`].
 #[derive(Clone, Default, AsStd140)]
 struct TerminalMaterialUniformData {
     pub color: Vec4,
+    pub clear_color: Vec4,
+    pub outline_color: Vec4,
     pub flags: u32,
+    pub dithering_strength: f32,
+    pub scanline_strength: f32,
+    pub noise_strength: f32,
+    pub noise_seed: f32,
+    pub vignette_radius: f32,
+    pub color_correction: ColorCorrection,
+    pub sdf_threshold: f32,
+    pub sdf_smoothing: f32,
+    pub pixelation_size: f32,
+    pub cursor_color: Vec4,
+    pub cursor_rect: Vec4,
+    pub glow_color: Vec4,
+    pub glow_threshold: f32,
+    pub effects: EffectsUniformData,
+}
+
+/// Uniform data for effects which need more than a single value. These are
+/// nested rather than added to [TerminalMaterialUniformData] directly, which
+/// keeps the uniform's derived std140 layout cheap to compile.
+#[derive(Clone, Default, AsStd140)]
+struct EffectsUniformData {
+    pub wave: WaveUniformData,
+    pub mask: MaskUniformData,
+    pub accumulation_strength: f32,
+    pub chromatic_aberration: ChromaticAberration,
+}
+
+/// The GPU representation of a [`TerminalMaterial`]'s wave distortion.
+#[derive(Clone, Default, AsStd140)]
+struct WaveUniformData {
+    /// Written every frame by [write_terminal_material_time].
+    pub time: f32,
+    pub distortion: f32,
+    pub frequency: f32,
+}
+
+/// The GPU representation of a [`TerminalMaterial`]'s mask.
+#[derive(Clone, Default, AsStd140)]
+struct MaskUniformData {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    pub feather: f32,
+}
+
+impl From<&TerminalMaterial> for TerminalMaterialUniformData {
+    fn from(material: &TerminalMaterial) -> Self {
+        TerminalMaterialUniformData {
+            color: material.clip_color.as_linear_rgba_f32().into(),
+            clear_color: material
+                .clear_color
+                .unwrap_or(Color::NONE)
+                .as_linear_rgba_f32()
+                .into(),
+            outline_color: material
+                .outline_color
+                .unwrap_or(Color::NONE)
+                .as_linear_rgba_f32()
+                .into(),
+            flags: material.flags().bits(),
+            dithering_strength: material.dithering_strength.clamp(0.0, 1.0),
+            scanline_strength: material.scanline_strength.clamp(0.0, 1.0),
+            noise_strength: material.noise_strength.clamp(0.0, 1.0),
+            noise_seed: material.noise_seed,
+            vignette_radius: material.vignette_radius.unwrap_or(0.0),
+            color_correction: material.color_correction.unwrap_or_default(),
+            sdf_threshold: material.sdf_threshold,
+            sdf_smoothing: material.sdf_smoothing,
+            pixelation_size: material.pixelation_size.max(0.0),
+            cursor_color: material.cursor_color.as_linear_rgba_f32().into(),
+            cursor_rect: material.cursor_rect.unwrap_or(Vec4::ZERO),
+            glow_color: material
+                .glow_color
+                .unwrap_or(Color::NONE)
+                .as_linear_rgba_f32()
+                .into(),
+            glow_threshold: material.glow_threshold,
+            effects: EffectsUniformData {
+                wave: WaveUniformData {
+                    time: 0.0,
+                    distortion: material.wave_distortion,
+                    frequency: material.wave_frequency,
+                },
+                mask: MaskUniformData {
+                    center: material.mask_center,
+                    half_extents: material.mask_shape.half_extents().unwrap_or(Vec2::ZERO),
+                    feather: material.mask_feather,
+                },
+                accumulation_strength: material.accumulation_strength.clamp(0.0, 1.0),
+                chromatic_aberration: material.chromatic_aberration.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// The offset of [WaveUniformData::time] in the uniform buffer.
+const TIME_UNIFORM_OFFSET: u64 = 176;
+
+/// The time in seconds since startup, extracted to the render world for
+/// animated material effects.
+pub(crate) struct ExtractedTerminalMaterialTime(pub f32);
+
+pub(crate) fn extract_terminal_material_time(mut commands: Commands, time: Res<Time>) {
+    // Wrap at a multiple of 2 pi so the time keeps it's precision in the shader
+    // without the wave jumping
+    let t = time.seconds_since_startup() % (std::f64::consts::TAU * 1000.0);
+    commands.insert_resource(ExtractedTerminalMaterialTime(t as f32));
+}
+
+/// Write the current time to the uniform buffer of every prepared material
+/// with animated effects.
+pub(crate) fn write_terminal_material_time<M>(
+    time: Res<ExtractedTerminalMaterialTime>,
+    materials: Res<RenderAssets<M>>,
+    queue: Res<RenderQueue>,
+) where
+    M: RenderAsset<PreparedAsset = GpuTerminalMaterial>,
+{
+    for material in materials.values() {
+        if material
+            .flags
+            .contains(TerminalMaterialFlags::WAVE_DISTORTION)
+        {
+            queue.write_buffer(&material.buffer, TIME_UNIFORM_OFFSET, &time.0.to_ne_bytes());
+        }
+    }
 }
 
 // The data from our material that gets copied to the gpu
@@ -263,41 +961,38 @@ impl RenderAsset for TerminalMaterial {
         } else {
             return Err(PrepareAssetError::RetryNextUpdate(material));
         };
-
-        let mut flags = TerminalMaterialFlags::NONE;
-        if material.texture.is_some() {
-            flags |= TerminalMaterialFlags::TEXTURE;
-        }
-
-        let value = TerminalMaterialUniformData {
-            color: material.clip_color.as_linear_rgba_f32().into(),
-            flags: flags.bits(),
+        // Without a background texture the font texture is bound in it's place
+        let background = match &material.background_texture {
+            Some(_) => match pipeline
+                .mesh2d_pipeline
+                .get_image_texture(gpu_images, &material.background_texture)
+            {
+                Some(result) => result,
+                None => return Err(PrepareAssetError::RetryNextUpdate(material)),
+            },
+            None => (texture_view, sampler),
+        };
+        // Without a previous frame the font texture is bound in it's place
+        let accumulation = match &material.accumulation_texture {
+            Some(_) => match pipeline
+                .mesh2d_pipeline
+                .get_image_texture(gpu_images, &material.accumulation_texture)
+            {
+                Some(result) => result,
+                None => return Err(PrepareAssetError::RetryNextUpdate(material)),
+            },
+            None => (texture_view, sampler),
         };
-        let value_std140 = value.as_std140();
 
-        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-            label: Some("terminal_material_uniform_buffer"),
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            contents: value_std140.as_bytes(),
-        });
-        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: buffer.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(texture_view),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::Sampler(sampler),
-                },
-            ],
-            label: Some("terminal_material_bind_group"),
-            layout: &pipeline.material2d_layout,
-        });
+        let flags = material.flags();
+        let (buffer, bind_group) = create_terminal_bind_group(
+            render_device,
+            &pipeline.material2d_layout,
+            &material,
+            (texture_view, sampler),
+            background,
+            accumulation,
+        );
 
         Ok(GpuTerminalMaterial {
             buffer,
@@ -308,6 +1003,62 @@ impl RenderAsset for TerminalMaterial {
     }
 }
 
+/// Write a material's uniform data to a new buffer and create it's bind group.
+///
+/// Shared by the 2d and 3d terminal materials, which use the same bind group layout.
+pub(crate) fn create_terminal_bind_group(
+    render_device: &RenderDevice,
+    layout: &BindGroupLayout,
+    material: &TerminalMaterial,
+    (texture_view, sampler): (&TextureView, &Sampler),
+    (background_view, background_sampler): (&TextureView, &Sampler),
+    (accumulation_view, accumulation_sampler): (&TextureView, &Sampler),
+) -> (Buffer, BindGroup) {
+    let value_std140 = TerminalMaterialUniformData::from(material).as_std140();
+
+    let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("terminal_material_uniform_buffer"),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        contents: value_std140.as_bytes(),
+    });
+    let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::TextureView(texture_view),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: BindingResource::Sampler(sampler),
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: BindingResource::TextureView(background_view),
+            },
+            BindGroupEntry {
+                binding: 4,
+                resource: BindingResource::Sampler(background_sampler),
+            },
+            BindGroupEntry {
+                binding: 5,
+                resource: BindingResource::TextureView(accumulation_view),
+            },
+            BindGroupEntry {
+                binding: 6,
+                resource: BindingResource::Sampler(accumulation_sampler),
+            },
+        ],
+        label: Some("terminal_material_bind_group"),
+        layout,
+    });
+
+    (buffer, bind_group)
+}
+
 impl SpecializedMaterial2d for TerminalMaterial {
     fn fragment_shader(_asset_server: &AssetServer) -> Option<Handle<Shader>> {
         Some(TERMINAL_MATERIAL_SHADER_HANDLE.typed())
@@ -323,41 +1074,7 @@ impl SpecializedMaterial2d for TerminalMaterial {
     }
 
     fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
-        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            entries: &[
-                BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: BufferSize::new(
-                            TerminalMaterialUniformData::std140_size_static() as u64,
-                        ),
-                    },
-                    count: None,
-                },
-                // Texture
-                BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Texture {
-                        multisampled: false,
-                        sample_type: TextureSampleType::Float { filterable: true },
-                        view_dimension: TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
-                // Texture Sampler
-                BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-            label: Some("color_material_layout"),
-        })
+        terminal_bind_group_layout(render_device)
     }
 
     type Key = ();
@@ -373,17 +1090,531 @@ impl SpecializedMaterial2d for TerminalMaterial {
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayout,
     ) -> Result<(), SpecializedMeshPipelineError> {
-        let formats = vec![
-            Mesh::ATTRIBUTE_POSITION.format,
-            ATTRIBUTE_UV.format,
-            ATTRIBUTE_COLOR_BG.format,
-            ATTRIBUTE_COLOR_FG.format,
-        ];
-
-        let vertex_layout =
-            VertexBufferLayout::from_vertex_formats(VertexStepMode::Vertex, formats);
-        descriptor.vertex.buffers = vec![vertex_layout];
+        descriptor.vertex.buffers = vec![terminal_vertex_layout()];
 
         Ok(())
     }
 }
+
+/// The bind group layout shared by the 2d and 3d terminal materials.
+pub(crate) fn terminal_bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(
+                        TerminalMaterialUniformData::std140_size_static() as u64,
+                    ),
+                },
+                count: None,
+            },
+            // Texture
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // Texture Sampler
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+            // Background Texture
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // Background Texture Sampler
+            BindGroupLayoutEntry {
+                binding: 4,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+            // Accumulation Texture
+            BindGroupLayoutEntry {
+                binding: 5,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            // Accumulation Texture Sampler
+            BindGroupLayoutEntry {
+                binding: 6,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("color_material_layout"),
+    })
+}
+
+/// The vertex layout of a terminal mesh.
+pub(crate) fn terminal_vertex_layout() -> VertexBufferLayout {
+    let formats = vec![
+        Mesh::ATTRIBUTE_POSITION.format,
+        ATTRIBUTE_UV.format,
+        ATTRIBUTE_COLOR_BG.format,
+        ATTRIBUTE_COLOR_FG.format,
+        ATTRIBUTE_BG_UV.format,
+        ATTRIBUTE_TERMINAL_UV.format,
+    ];
+    VertexBufferLayout::from_vertex_formats(VertexStepMode::Vertex, formats)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+
+    use super::*;
+
+    #[test]
+    fn nearest_font() {
+        let mut fonts = BuiltInFontHandles::new();
+        for (name, image) in [
+            include_font!("jt_curses_12x12.png"),
+            include_font!("pastiche_8x8.png"),
+            include_font!("px437_8x8.png"),
+            include_font!("taffer_10x10.png"),
+            include_font!("zx_evolution_8x8.png"),
+        ] {
+            fonts.insert(name, Handle::default(), font_tile_size(&image));
+        }
+
+        assert_eq!(
+            Some(UVec2::new(12, 12)),
+            fonts.tile_size("jt_curses_12x12.png")
+        );
+        assert_eq!(
+            Some("pastiche_8x8.png"),
+            fonts.nearest_to_pixel_size(UVec2::new(8, 8))
+        );
+        assert_eq!(
+            Some("taffer_10x10.png"),
+            fonts.nearest_to_pixel_size(UVec2::new(10, 11))
+        );
+        assert_eq!(
+            Some("jt_curses_12x12.png"),
+            fonts.nearest_to_pixel_size(UVec2::new(64, 64))
+        );
+    }
+
+    #[test]
+    fn upscale_flags() {
+        let mut material = TerminalMaterial::from(Handle::<Image>::default());
+        assert_eq!(TerminalMaterialFlags::TEXTURE, material.flags());
+
+        material.upscale_mode = UpscaleMode::Integer;
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::UPSCALE_INTEGER));
+
+        material.upscale_mode = UpscaleMode::SharpBilinear;
+        let flags = material.flags();
+        assert!(flags.contains(TerminalMaterialFlags::UPSCALE_SHARP_BILINEAR));
+        assert!(!flags.contains(TerminalMaterialFlags::UPSCALE_INTEGER));
+    }
+
+    #[test]
+    fn glyph_override() {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 32,
+                height: 32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let glyph = [255; 2 * 2 * 4];
+
+        assert!(matches!(
+            override_glyph(&mut image, 256, &glyph, 2, 2),
+            Err(FontError::GlyphOutOfRange { index: 256, .. })
+        ));
+        assert!(matches!(
+            override_glyph(&mut image, 17, &glyph, 4, 1),
+            Err(FontError::TileSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            override_glyph(&mut image, 17, &glyph[..8], 2, 2),
+            Err(FontError::PixelDataLength {
+                expected: 16,
+                actual: 8
+            })
+        ));
+        assert!(override_glyph(&mut image, 17, &glyph, 2, 2).is_ok());
+
+        // Glyph 17 is the second tile of the second row, at pixel (2,2)
+        let pixel = |x: usize, y: usize| &image.data[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!([255; 4], pixel(2, 2));
+        assert_eq!([255; 4], pixel(3, 3));
+        assert_eq!([0, 0, 0, 255], pixel(1, 2));
+        assert_eq!([0, 0, 0, 255], pixel(4, 3));
+    }
+
+    #[test]
+    fn clear_color_flag() {
+        let mut material = TerminalMaterial::from(Handle::<Image>::default());
+        assert!(!material
+            .flags()
+            .contains(TerminalMaterialFlags::CLEAR_COLOR));
+
+        material.clear_color = Some(Color::BLUE);
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::CLEAR_COLOR));
+
+        material.outline_color = Some(Color::BLACK);
+        assert!(material.flags().contains(TerminalMaterialFlags::OUTLINE));
+
+        material.invert_colors = true;
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::INVERT_COLORS));
+
+        assert!(!material.flags().contains(TerminalMaterialFlags::DITHERING));
+        let mut material = material.with_dithering_strength(0.5);
+        assert!(material.flags().contains(TerminalMaterialFlags::DITHERING));
+
+        material.sdf_mode = true;
+        assert!(material.flags().contains(TerminalMaterialFlags::SDF_MODE));
+    }
+
+    #[test]
+    fn color_correction() {
+        let mut material = TerminalMaterial::default();
+        material.set_color_correction(2.2, 1.1, 0.5);
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::COLOR_CORRECTION));
+        assert_eq!(Some(0.5), material.color_correction.map(|c| c.saturation));
+
+        material.reset_color_correction();
+        assert!(!material
+            .flags()
+            .contains(TerminalMaterialFlags::COLOR_CORRECTION));
+    }
+
+    /// The offset of every scalar in the shader's `TerminalMaterial` struct,
+    /// in declaration order, as laid out by naga.
+    fn shader_uniform_offsets() -> (Vec<u32>, u32) {
+        fn leaves(
+            module: &naga::Module,
+            ty: naga::Handle<naga::Type>,
+            base: u32,
+            out: &mut Vec<u32>,
+        ) {
+            match &module.types[ty].inner {
+                naga::TypeInner::Struct { members, .. } => {
+                    for member in members {
+                        leaves(module, member.ty, base + member.offset, out);
+                    }
+                }
+                naga::TypeInner::Vector { size, width, .. } => {
+                    out.extend((0..*size as u32).map(|i| base + i * *width as u32));
+                }
+                naga::TypeInner::Scalar { .. } => out.push(base),
+                inner => panic!("Unexpected uniform type {:?}", inner),
+            }
+        }
+
+        // The structs are declared before anything which needs the importing shader
+        let source = include_str!("terminal_common.wgsl");
+        let start = source.find("struct Wave").unwrap();
+        let end = source.find("struct TerminalMaterial").unwrap();
+        let end = end + source[end..].find("};").unwrap() + 2;
+        let module = naga::front::wgsl::parse_str(&source[start..end]).unwrap();
+        let (ty, material) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("TerminalMaterial"))
+            .unwrap();
+        let span = match material.inner {
+            naga::TypeInner::Struct { span, .. } => span,
+            _ => unreachable!(),
+        };
+        let mut offsets = Vec::new();
+        leaves(&module, ty, 0, &mut offsets);
+        (offsets, span)
+    }
+
+    #[test]
+    fn uniform_layout() {
+        // Give every scalar a different value, in declaration order
+        let n = std::cell::Cell::new(0.0);
+        let next = || {
+            n.set(n.get() + 1.0);
+            n.get()
+        };
+        let vec2 = || Vec2::new(next(), next());
+        let time;
+        let data = TerminalMaterialUniformData {
+            color: Vec4::new(next(), next(), next(), next()),
+            clear_color: Vec4::new(next(), next(), next(), next()),
+            outline_color: Vec4::new(next(), next(), next(), next()),
+            flags: f32::to_bits(next()),
+            dithering_strength: next(),
+            scanline_strength: next(),
+            noise_strength: next(),
+            noise_seed: next(),
+            vignette_radius: next(),
+            color_correction: ColorCorrection {
+                gamma: next(),
+                contrast: next(),
+                saturation: next(),
+            },
+            sdf_threshold: next(),
+            sdf_smoothing: next(),
+            pixelation_size: next(),
+            cursor_color: Vec4::new(next(), next(), next(), next()),
+            cursor_rect: Vec4::new(next(), next(), next(), next()),
+            glow_color: Vec4::new(next(), next(), next(), next()),
+            glow_threshold: next(),
+            effects: EffectsUniformData {
+                wave: WaveUniformData {
+                    time: {
+                        time = next();
+                        time
+                    },
+                    distortion: next(),
+                    frequency: next(),
+                },
+                mask: MaskUniformData {
+                    center: vec2(),
+                    half_extents: vec2(),
+                    feather: next(),
+                },
+                accumulation_strength: next(),
+                chromatic_aberration: ChromaticAberration {
+                    r_offset: vec2(),
+                    g_offset: vec2(),
+                    b_offset: vec2(),
+                },
+            },
+        };
+        let std140 = data.as_std140();
+        let bytes = std140.as_bytes();
+
+        // The shader reads each value from the bytes it was written to
+        let (offsets, span) = shader_uniform_offsets();
+        let read = |offset: u32| {
+            let offset = offset as usize;
+            f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+        };
+        let values: Vec<_> = offsets.iter().map(|&offset| read(offset)).collect();
+        let expected: Vec<_> = (1..=values.len()).map(|i| i as f32).collect();
+        assert_eq!(expected, values);
+        assert_eq!(expected.len() as f32, n.get());
+        assert_eq!(bytes.len() as u32, span);
+        assert_eq!(TIME_UNIFORM_OFFSET as u32, offsets[time as usize - 1]);
+    }
+
+    #[test]
+    fn chromatic_aberration() {
+        // Zero offsets sample every channel in place, so the effect is skipped
+        let material = TerminalMaterial::default().with_chromatic_aberration(
+            Vec2::ZERO,
+            Vec2::ZERO,
+            Vec2::ZERO,
+        );
+        assert!(material.chromatic_aberration.unwrap().is_zero());
+        assert!(!material
+            .flags()
+            .contains(TerminalMaterialFlags::CHROMATIC_ABERRATION));
+
+        let material = TerminalMaterial::default().with_chromatic_aberration(
+            Vec2::new(-0.002, 0.0),
+            Vec2::ZERO,
+            Vec2::new(0.002, 0.0),
+        );
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::CHROMATIC_ABERRATION));
+        assert_eq!(
+            Some(ChromaticAberration::horizontal(0.002)),
+            material.chromatic_aberration
+        );
+    }
+
+    #[test]
+    fn glow() {
+        let material = TerminalMaterial::default();
+        assert!(!material.flags().contains(TerminalMaterialFlags::GLOW));
+        let data = TerminalMaterialUniformData::from(&material);
+        assert_eq!(Vec4::ZERO, data.glow_color);
+        assert_eq!(0.9, data.glow_threshold);
+
+        let mut material = material.with_glow_color(Color::YELLOW);
+        material.glow_threshold = 0.5;
+        assert!(material.flags().contains(TerminalMaterialFlags::GLOW));
+        let data = TerminalMaterialUniformData::from(&material);
+        assert_eq!(material.flags().bits(), data.flags);
+        assert_eq!(
+            Vec4::from(Color::YELLOW.as_linear_rgba_f32()),
+            data.glow_color
+        );
+        assert_eq!(0.5, data.glow_threshold);
+    }
+
+    #[test]
+    fn wave_distortion() {
+        let material = TerminalMaterial::default();
+        assert_eq!(5.0, material.wave_frequency);
+        assert!(!material
+            .flags()
+            .contains(TerminalMaterialFlags::WAVE_DISTORTION));
+        let material = material.with_wave_distortion(0.01, 2.0);
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::WAVE_DISTORTION));
+
+        // The time is written straight into the buffer at it's offset
+        let data = TerminalMaterialUniformData {
+            effects: EffectsUniformData {
+                wave: WaveUniformData {
+                    time: 12.5,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let std140 = data.as_std140();
+        let start = TIME_UNIFORM_OFFSET as usize;
+        assert_eq!(&12.5f32.to_ne_bytes(), &std140.as_bytes()[start..start + 4]);
+    }
+
+    #[test]
+    fn mask() {
+        let material = TerminalMaterial::default();
+        assert!(!material
+            .flags()
+            .contains(TerminalMaterialFlags::CIRCULAR_MASK));
+        let mask = TerminalMaterialUniformData::from(&material).effects.mask;
+        assert_eq!(Vec2::ZERO, mask.half_extents);
+
+        let material = material.with_mask(MaskShape::Circle(0.25), 0.0);
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::CIRCULAR_MASK));
+        let mask = TerminalMaterialUniformData::from(&material).effects.mask;
+        assert_eq!(Vec2::splat(0.5), mask.center);
+        assert_eq!(Vec2::splat(0.25), mask.half_extents);
+
+        let mut material = material.with_mask(MaskShape::Ellipse(Vec2::new(0.5, 0.25)), 0.1);
+        material.mask_center = Vec2::new(0.3, 0.6);
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::CIRCULAR_MASK));
+        let mask = TerminalMaterialUniformData::from(&material).effects.mask;
+        assert_eq!(Vec2::new(0.3, 0.6), mask.center);
+        assert_eq!(Vec2::new(0.5, 0.25), mask.half_extents);
+        assert_eq!(0.1, mask.feather);
+    }
+
+    #[test]
+    fn dithering() {
+        let material = TerminalMaterial::default().with_dithering_strength(2.0);
+        assert_eq!(
+            1.0,
+            TerminalMaterialUniformData::from(&material).dithering_strength
+        );
+
+        // The shader's constants, so the expected pattern follows the shader
+        let source = include_str!("terminal_common.wgsl");
+        let start = source.find("// 4x4 bayer matrix").unwrap();
+        let end = start + source[start..].find("[[group(1), binding(0)]]").unwrap();
+        let module = naga::front::wgsl::parse_str(&source[start..end]).unwrap();
+        let constant = |name: &str| {
+            let (_, constant) = module
+                .constants
+                .iter()
+                .find(|(_, c)| c.name.as_deref() == Some(name))
+                .unwrap();
+            constant
+        };
+        let float = |constant: &naga::Constant| match constant.inner {
+            naga::ConstantInner::Scalar {
+                value: naga::ScalarValue::Float(value),
+                ..
+            } => value as f32,
+            _ => panic!("Expected a float constant"),
+        };
+        let bayer: Vec<f32> = match &constant("BAYER_4X4").inner {
+            naga::ConstantInner::Composite { components, .. } => components
+                .iter()
+                .map(|c| float(&module.constants[*c]))
+                .collect(),
+            _ => panic!("Expected an array constant"),
+        };
+        let steps = float(constant("DITHER_LEVELS")) - 1.0;
+
+        // The level 'dither' picks for a gray value at full strength
+        let dither = |value: f32, x: usize, y: usize| {
+            let threshold = (bayer[(y % 4) * 4 + x % 4] + 0.5) / 16.0 - 0.5;
+            (value * steps + 0.5 + threshold).floor() as u32
+        };
+        // A gradient from black to white over 8 pixels, with every value
+        // between two levels
+        let levels: Vec<Vec<u32>> = (0..4)
+            .map(|y| {
+                (0..8)
+                    .map(|x| dither((x as f32 + 0.5) / 8.0, x, y))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0, 1, 2, 3, 3, 5, 5, 7],
+                vec![1, 1, 3, 3, 4, 5, 6, 6],
+                vec![0, 2, 2, 3, 4, 5, 5, 7],
+                vec![1, 1, 3, 3, 4, 5, 6, 6],
+            ],
+            levels
+        );
+    }
+
+    #[test]
+    fn pixelation() {
+        let material = TerminalMaterial::default();
+        assert!(!material.flags().contains(TerminalMaterialFlags::PIXELATION));
+        let material = material.with_pixelation_size(2.0);
+        assert!(material.flags().contains(TerminalMaterialFlags::PIXELATION));
+
+        let data = TerminalMaterialUniformData::from(&material);
+        assert_eq!(material.flags().bits(), data.flags);
+        assert_eq!(2.0, data.pixelation_size);
+
+        // Negative sizes turn pixelation off rather than reaching the shader
+        let material = material.with_pixelation_size(-1.0);
+        assert!(!material.flags().contains(TerminalMaterialFlags::PIXELATION));
+        assert_eq!(
+            0.0,
+            TerminalMaterialUniformData::from(&material).pixelation_size
+        );
+    }
+}
@@ -1,19 +1,42 @@
 //! Handles mesh construction and rendering for the terminal.
 
+pub mod accumulation;
+pub mod aseprite;
+pub mod asset_pool;
+pub mod atlas;
+pub mod background;
+pub mod bmfont;
+pub mod clear_color;
+pub mod cursor;
+pub mod effects;
 pub mod entity;
+pub mod fog_of_war;
+pub mod font_asset;
+pub mod font_builder;
+pub mod font_cache;
+pub mod invert_colors;
+pub mod key_map;
 pub mod material;
+pub mod mosaic;
+pub mod multi_font;
+pub mod noise;
+pub mod owned_material;
+pub mod parallax;
+pub mod partial_upload;
 pub mod plugin;
+pub mod renderer_3d;
+pub mod sdf;
+pub mod throttle;
+pub mod view;
 
 pub mod code_page_437;
+pub(crate) mod user_fonts;
 pub mod uv_mapping;
 
 pub(crate) mod renderer_tile_data;
 pub(crate) mod renderer_vertex_data;
 
-use self::{
-    material::TerminalMaterial, renderer_tile_data::TerminalRendererTileData,
-    renderer_vertex_data::TerminalRendererVertexData,
-};
+use self::{material::TerminalMaterial, renderer_vertex_data::TerminalRendererVertexData};
 use crate::terminal::Terminal;
 
 /// System label for the terminal mesh initialization function.
@@ -24,6 +47,34 @@ pub const TERMINAL_UPDATE_SIZE: &str = "terminal_update_size";
 pub const TERMINAL_UPDATE_TILE_DATA: &str = "terminal_update_tile_data";
 /// System label for the terminal mesh update function.
 pub const TERMINAL_UPDATE_MESH: &str = "terminal_update_mesh";
+/// System label for systems which modify terminal tile data after it's been
+/// updated from the terminal, but before it's written to the mesh.
+///
+/// See [TerminalAppExt::add_terminal_pre_render_system].
+pub const TERMINAL_PRE_RENDER: &str = "terminal_pre_render";
+/// System label for the function collecting fog of war sources.
+pub const TERMINAL_FOG_OF_WAR_FOV: &str = "terminal_fog_of_war_fov";
+/// System label for the pre-render function writing fog of war over the tile data.
+pub const TERMINAL_APPLY_FOG_OF_WAR: &str = "terminal_apply_fog_of_war";
+/// System label for the pre-render function averaging tiles into mosaic blocks.
+pub const TERMINAL_APPLY_MOSAIC: &str = "terminal_apply_mosaic";
 
+pub use accumulation::{TerminalAccumulationBuffer, TerminalAccumulationCamera};
+pub use asset_pool::TerminalAssetPool;
+pub use background::TerminalBackground;
+pub use clear_color::TerminalClearColor;
+pub use cursor::{TerminalCursor, TerminalCursorShape};
 pub use entity::*;
-pub use plugin::TerminalRendererPlugin;
+pub use fog_of_war::{TerminalFogOfWar, TerminalFovSource};
+pub use font_cache::TerminalFontCache;
+pub use invert_colors::TerminalInvertColorsKey;
+pub use key_map::TerminalKeyMap;
+pub use multi_font::TerminalMultiFont;
+pub use noise::TerminalNoise;
+pub use owned_material::TerminalOwnedMaterial;
+pub use parallax::{ParallaxLayer, TerminalParallaxBackground};
+pub use partial_upload::TerminalPartialUpload;
+pub use plugin::{DefaultTerminalRenderLayer, TerminalAppExt, TerminalRendererPlugin};
+pub use renderer_tile_data::TerminalRendererTileData;
+pub use throttle::TerminalThrottledRenderer;
+pub use view::TerminalViewMatrix;
@@ -0,0 +1,199 @@
+//! Grouping terminal tiles into blocks of a single averaged color.
+//!
+//! Blocks are averaged into the terminal's renderer data, the tiles of the
+//! [Terminal] itself are never modified.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::Terminal;
+
+use super::{material::TerminalMaterial, renderer_tile_data::TerminalRendererTileData};
+
+/// Replace the colors of every `group` x `group` block of tiles with the
+/// block's average color.
+///
+/// Each tile's color is it's background and foreground colors mixed by the
+/// fraction of it's glyph which is drawn, given by `coverage` for each tile
+/// index. Blocks on the right and top edges of the terminal may be smaller.
+pub(crate) fn apply_mosaic(
+    tile_data: &mut TerminalRendererTileData,
+    size: UVec2,
+    group: u32,
+    coverage: impl Fn(usize) -> f32,
+) {
+    let group = group.max(1);
+    let (width, height) = (size.x as usize, size.y as usize);
+    if tile_data.fg_colors.len() < width * height * 4 {
+        return;
+    }
+    let tile_color = |data: &TerminalRendererTileData, i: usize| {
+        let fg = Vec4::from(data.fg_colors[i * 4]);
+        let bg = Vec4::from(data.bg_colors[i * 4]);
+        bg.lerp(fg, coverage(i).clamp(0.0, 1.0))
+    };
+
+    for gy in (0..height).step_by(group as usize) {
+        for gx in (0..width).step_by(group as usize) {
+            let xs = gx..(gx + group as usize).min(width);
+            let ys = gy..(gy + group as usize).min(height);
+            let indices = || {
+                ys.clone()
+                    .flat_map(|y| xs.clone().map(move |x| y * width + x))
+            };
+
+            let (sum, count) = indices().fold((Vec4::ZERO, 0.0), |(sum, count), i| {
+                (sum + tile_color(tile_data, i), count + 1.0)
+            });
+            let average = (sum / count).into();
+            for i in indices() {
+                tile_data.fg_colors[i * 4..i * 4 + 4].fill(average);
+                tile_data.bg_colors[i * 4..i * 4 + 4].fill(average);
+            }
+        }
+    }
+}
+
+/// The fraction of pixels within a glyph's uvs which aren't the clip color.
+///
+/// Returns 0.5 for images which don't use 4 bytes per pixel.
+fn glyph_coverage(image: &Image, uvs: &[[f32; 2]], clip_color: Color) -> f32 {
+    let size = image.texture_descriptor.size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    if image.data.len() != width * height * 4 {
+        return 0.5;
+    }
+    let (min, max) = uvs.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), uv| (min.min(Vec2::from(*uv)), max.max(Vec2::from(*uv))),
+    );
+    let image_size = Vec2::new(width as f32, height as f32);
+    let min = (min * image_size).round().as_uvec2();
+    let max = (max * image_size)
+        .round()
+        .as_uvec2()
+        .min(UVec2::new(size.width, size.height));
+
+    let clip = clip_color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    let mut covered = 0;
+    let mut total = 0;
+    for y in min.y as usize..max.y as usize {
+        for x in min.x as usize..max.x as usize {
+            let i = (y * width + x) * 4;
+            // Matches the clip test in the terminal shader
+            if image.data[i..i + 3].iter().zip(clip).any(|(p, c)| *p > c) {
+                covered += 1;
+            }
+            total += 1;
+        }
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    covered as f32 / total as f32
+}
+
+/// Rewrite the tile data of terminals whose material has changed, so a new
+/// mosaic size is applied to unaveraged tiles.
+#[allow(clippy::type_complexity)]
+pub(crate) fn terminal_mosaic_refresh(
+    mut events: EventReader<AssetEvent<TerminalMaterial>>,
+    mut q: Query<(
+        &Handle<TerminalMaterial>,
+        ChangeTrackers<Handle<TerminalMaterial>>,
+        &mut TerminalRendererTileData,
+    )>,
+) {
+    let modified: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.id),
+            _ => None,
+        })
+        .collect();
+    for (material, tracker, mut tile_data) in q.iter_mut() {
+        if tracker.is_changed() || modified.contains(&material.id) {
+            tile_data.request_refresh();
+        }
+    }
+}
+
+/// Average the tile data of terminals into blocks, whenever it's written from
+/// the terminal.
+pub(crate) fn terminal_renderer_apply_mosaic(
+    materials: Res<Assets<TerminalMaterial>>,
+    images: Res<Assets<Image>>,
+    mut q: Query<(
+        &Terminal,
+        &Handle<TerminalMaterial>,
+        &mut TerminalRendererTileData,
+    )>,
+) {
+    for (term, material, mut tile_data) in q.iter_mut() {
+        let material = match materials.get(material) {
+            Some(material) if material.mosaic_tiles > 1 => material,
+            _ => continue,
+        };
+        if !tile_data.is_refreshed() {
+            continue;
+        }
+
+        let image = material.texture.as_ref().and_then(|t| images.get(t));
+        // Glyphs are found by their uvs, which include any drawn by earlier effects
+        let mut glyphs = HashMap::default();
+        let coverage: Vec<f32> = tile_data
+            .uvs
+            .chunks_exact(4)
+            .map(|uvs| match image {
+                Some(image) => {
+                    let key: Vec<u32> = uvs.iter().flatten().map(|f| f.to_bits()).collect();
+                    *glyphs
+                        .entry(key)
+                        .or_insert_with(|| glyph_coverage(image, uvs, material.clip_color))
+                }
+                None => 1.0,
+            })
+            .collect();
+        apply_mosaic(&mut tile_data, term.size(), material.mosaic_tiles, |i| {
+            coverage[i]
+        });
+        tile_data.mark_all_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mosaic() {
+        let size = UVec2::new(4, 4);
+        let mut data = TerminalRendererTileData::with_size(size);
+        for i in 0..16 {
+            let color = [i as f32 / 15.0, 0.0, 0.0, 1.0];
+            data.fg_colors[i * 4..i * 4 + 4].fill(color);
+            data.bg_colors[i * 4..i * 4 + 4].fill([0.0, 0.0, 0.0, 1.0]);
+        }
+        apply_mosaic(&mut data, size, 2, |_| 1.0);
+
+        // Every 2x2 block is one color, so 16 tiles show 4 colors
+        let red = |x: usize, y: usize| data.fg_colors[(y * 4 + x) * 4][0];
+        let mut colors: Vec<_> = (0..16).map(|i| red(i % 4, i / 4)).collect();
+        colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        colors.dedup();
+        assert_eq!(4, colors.len());
+        assert_eq!(red(0, 0), red(1, 1));
+        assert_eq!((0.0 + 1.0 + 4.0 + 5.0) / 4.0 / 15.0, red(0, 0));
+        assert_eq!(data.fg_colors, data.bg_colors);
+
+        // Uncovered glyphs show only their background
+        let mut data = TerminalRendererTileData::with_size(UVec2::new(3, 1));
+        data.fg_colors.fill([1.0, 1.0, 1.0, 1.0]);
+        data.bg_colors.fill([0.0, 0.0, 1.0, 1.0]);
+        apply_mosaic(&mut data, UVec2::new(3, 1), 2, |i| (i == 0) as u32 as f32);
+        assert_eq!([0.5, 0.5, 1.0, 1.0], data.fg_colors[0]);
+        // The last, partial block
+        assert_eq!([0.0, 0.0, 1.0, 1.0], data.fg_colors[8]);
+    }
+}
@@ -0,0 +1,257 @@
+//! A terminal component for rendering different glyphs with different fonts.
+
+use std::ops::RangeInclusive;
+
+use bevy::{asset::HandleId, prelude::*};
+
+use super::{
+    font_asset::{check_rgba, FontError},
+    material::{font_tile_size, TerminalMaterial},
+    owned_material::TerminalOwnedMaterial,
+};
+
+/// Terminal component which renders ranges of glyph indices with different
+/// code page 437 font textures, for example to mix text with icon tiles.
+///
+/// The glyphs from every font are copied into a single combined font texture,
+/// so the terminal is still rendered in one draw call. Glyphs from fonts with a
+/// different tile size than the base font are scaled to fit. All fonts should
+/// use the material's clip color as their background.
+///
+/// Since the combined texture is part of the [TerminalMaterial], it's drawn by
+/// the terminal's [TerminalOwnedMaterial].
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::TerminalMultiFont;
+///
+/// fn setup(mut commands: Commands, fonts: Res<BuiltInFontHandles>) {
+///     let text = fonts.get("px437_8x8.png").unwrap().clone();
+///     let icons = fonts.get("zx_evolution_8x8.png").unwrap().clone();
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 5]))
+///         .insert(TerminalMultiFont::new_with_base(text).with_range(1..=31, icons));
+/// }
+/// ```
+#[derive(Component, Debug, Clone)]
+pub struct TerminalMultiFont {
+    /// Glyph index ranges and the font texture they're drawn from. Later
+    /// entries override earlier ones.
+    pub fonts: Vec<(RangeInclusive<u8>, Handle<Image>)>,
+}
+
+impl TerminalMultiFont {
+    /// Render all 256 glyphs with the given font.
+    pub fn new_with_base(base_font: Handle<Image>) -> Self {
+        TerminalMultiFont {
+            fonts: vec![(0..=255, base_font)],
+        }
+    }
+
+    /// Render the glyphs in `range` with the given font, overriding any earlier
+    /// fonts for that range.
+    pub fn push_range(&mut self, range: RangeInclusive<u8>, font: Handle<Image>) {
+        self.fonts.push((range, font));
+    }
+
+    pub fn with_range(mut self, range: RangeInclusive<u8>, font: Handle<Image>) -> Self {
+        self.push_range(range, font);
+        self
+    }
+
+    /// The font texture the given glyph index is drawn from.
+    pub fn font_for(&self, glyph_index: u8) -> Option<&Handle<Image>> {
+        self.fonts
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&glyph_index))
+            .map(|(_, font)| font)
+    }
+
+    /// Combine the glyphs of every font into a single font texture, with the
+    /// size, format and sampler of the first font.
+    pub fn build_atlas(&self, images: &Assets<Image>) -> Result<Image, FontError> {
+        let get = |handle: &Handle<Image>| -> Result<&Image, FontError> {
+            let image = images.get(handle).ok_or(FontError::MissingImage)?;
+            check_rgba(image)?;
+            Ok(image)
+        };
+        let base = match self.fonts.first() {
+            Some((_, handle)) => get(handle)?,
+            None => return Err(FontError::NoFonts),
+        };
+        let mut atlas = base.clone();
+        let dst_tile = font_tile_size(&atlas);
+        let dst_width = atlas.texture_descriptor.size.width as usize;
+
+        for index in 0..=255u8 {
+            let handle = match self.font_for(index) {
+                Some(handle) => handle,
+                None => continue,
+            };
+            let src = get(handle)?;
+            let src_tile = font_tile_size(src);
+            let src_width = src.texture_descriptor.size.width as usize;
+
+            let cell = UVec2::new(index as u32 % 16, index as u32 / 16);
+            let (src_min, dst_min) = (cell * src_tile, cell * dst_tile);
+            for y in 0..dst_tile.y {
+                for x in 0..dst_tile.x {
+                    // Nearest neighbor sampling
+                    let sx = (src_min.x + x * src_tile.x / dst_tile.x) as usize;
+                    let sy = (src_min.y + y * src_tile.y / dst_tile.y) as usize;
+                    let si = (sy * src_width + sx) * 4;
+                    let (dx, dy) = ((dst_min.x + x) as usize, (dst_min.y + y) as usize);
+                    let di = (dy * dst_width + dx) * 4;
+                    atlas.data[di..di + 4].copy_from_slice(&src.data[si..si + 4]);
+                }
+            }
+        }
+        Ok(atlas)
+    }
+}
+
+/// The fonts a terminal's combined font texture was built from, and the
+/// material it was built for.
+#[derive(Component)]
+struct MultiFontAtlas {
+    material: HandleId,
+    fonts: Vec<(RangeInclusive<u8>, Handle<Image>)>,
+}
+
+pub(crate) struct TerminalMultiFontPlugin;
+
+impl Plugin for TerminalMultiFontPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_multi_font_update)
+            .add_system(terminal_multi_font_removed);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_multi_font_update(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    q: Query<(
+        Entity,
+        &TerminalMultiFont,
+        &Handle<TerminalMaterial>,
+        &TerminalOwnedMaterial,
+        Option<&MultiFontAtlas>,
+    )>,
+) {
+    for (entity, multi_font, handle, owned, atlas) in q.iter() {
+        if owned.handle() != handle {
+            continue;
+        }
+        if let Some(atlas) = atlas {
+            if atlas.material == handle.id && atlas.fonts == multi_font.fonts {
+                continue;
+            }
+        }
+
+        // Wait until every font is loaded
+        if multi_font
+            .fonts
+            .iter()
+            .any(|(_, font)| images.get(font).is_none())
+        {
+            continue;
+        }
+        let atlas = match multi_font.build_atlas(&images) {
+            Ok(atlas) => atlas,
+            Err(_) => continue,
+        };
+        if let Some(material) = owned.get_mut(handle, &mut materials) {
+            material.texture = Some(images.add(atlas));
+        }
+        commands.entity(entity).insert(MultiFontAtlas {
+            material: handle.id,
+            fonts: multi_font.fonts.clone(),
+        });
+    }
+}
+
+fn terminal_multi_font_removed(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    removed: RemovedComponents<TerminalMultiFont>,
+    q: Query<(
+        &Handle<TerminalMaterial>,
+        &TerminalOwnedMaterial,
+        &MultiFontAtlas,
+    )>,
+) {
+    for entity in removed.iter() {
+        if let Ok((handle, owned, atlas)) = q.get(entity) {
+            if atlas.material == handle.id {
+                if let Some(material) = owned.get_mut(handle, &mut materials) {
+                    // Fall back to the base font
+                    material.texture = atlas.fonts.first().map(|(_, font)| font.clone());
+                }
+            }
+            commands.entity(entity).remove::<MultiFontAtlas>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    fn font(tile_size: u32, value: u8) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: tile_size * 16,
+                height: tile_size * 16,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[value, value, value, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn atlas() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Image>();
+        let mut images = app.world.get_resource_mut::<Assets<Image>>().unwrap();
+        let base = images.add(font(4, 10));
+        let icons = images.add(font(8, 20));
+        let symbols = images.add(font(4, 30));
+
+        let multi = TerminalMultiFont::new_with_base(base.clone())
+            .with_range(1..=31, icons.clone())
+            .with_range(16..=16, symbols.clone());
+        assert_eq!(Some(&base), multi.font_for(0));
+        assert_eq!(Some(&icons), multi.font_for(1));
+        assert_eq!(Some(&symbols), multi.font_for(16));
+        assert_eq!(Some(&icons), multi.font_for(31));
+        assert_eq!(Some(&base), multi.font_for(255));
+
+        let atlas = multi.build_atlas(&images).unwrap();
+        assert_eq!(
+            images.get(&base).unwrap().texture_descriptor.size,
+            atlas.texture_descriptor.size
+        );
+        let pixel = |x: usize, y: usize| atlas.data[(y * 64 + x) * 4];
+        assert_eq!(10, pixel(0, 0));
+        assert_eq!(20, pixel(4, 0));
+        assert_eq!(20, pixel(7, 3));
+        assert_eq!(30, pixel(0, 4));
+        assert_eq!(20, pixel(4, 4));
+        assert_eq!(10, pixel(63, 63));
+
+        let missing = multi.with_range(0..=0, Handle::default());
+        assert!(missing.build_atlas(&images).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! A terminal component for adding a CRT-like noise/static effect.
+//!
+//! Noise is applied to the terminal's renderer data each frame, the tiles of
+//! the [Terminal](crate::Terminal) itself are never modified.
+
+use bevy::prelude::*;
+
+use super::{renderer_tile_data::TerminalRendererTileData, uv_mapping::UvMapping};
+
+/// Terminal component which randomly perturbs a fraction of the terminal's
+/// tiles every frame.
+///
+/// The noise is deterministic for a given `seed`.
+#[derive(Component, Clone, Copy)]
+pub struct TerminalNoise {
+    /// The fraction of tiles affected each frame, from 0 to 1.
+    pub strength: f32,
+    /// If true, affected tiles will have their glyph replaced with a random one.
+    pub affected_glyphs: bool,
+    /// If true, affected tiles will have their colors slightly randomized.
+    pub affected_colors: bool,
+    pub seed: u64,
+}
+
+impl Default for TerminalNoise {
+    fn default() -> Self {
+        Self {
+            strength: 0.05,
+            affected_glyphs: true,
+            affected_colors: true,
+            seed: 0,
+        }
+    }
+}
+
+impl TerminalNoise {
+    pub fn new(strength: f32, seed: u64) -> Self {
+        Self {
+            strength,
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Apply noise for the given frame to the renderer tile data.
+    pub(crate) fn apply(
+        &self,
+        frame: u64,
+        tile_data: &mut TerminalRendererTileData,
+        uv_mapping: &UvMapping,
+    ) {
+        let tile_count = tile_data.uvs.len() / 4;
+        let color_range = 0.25 * self.strength.clamp(0.0, 1.0);
+
+        for i in 0..tile_count {
+            let r = hash(self.seed, frame, i as u64);
+            if unit(r) >= self.strength {
+                continue;
+            }
+
+            let vi = i * 4;
+            if self.affected_glyphs {
+                let uvs = uv_mapping.uvs_from_index((r >> 32) as u8);
+                tile_data.uvs[vi..vi + 4].copy_from_slice(uvs);
+            }
+
+            if self.affected_colors {
+                let r = hash(r, frame, i as u64);
+                let fg = perturb(tile_data.fg_colors[vi], r, color_range);
+                let bg = perturb(tile_data.bg_colors[vi], r.rotate_left(32), color_range);
+                tile_data.fg_colors[vi..vi + 4].fill(fg);
+                tile_data.bg_colors[vi..vi + 4].fill(bg);
+            }
+        }
+    }
+}
+
+/// A fast, deterministic 64 bit hash based on SplitMix64.
+pub(crate) fn hash(seed: u64, frame: u64, i: u64) -> u64 {
+    let mut z = seed
+        .wrapping_add(frame.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(i.wrapping_mul(0xD1B5_4A32_D192_ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Map a random value to the range [0,1).
+pub(crate) fn unit(r: u64) -> f32 {
+    (r >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Randomly offset each color channel by up to `range`. Alpha is left as is.
+fn perturb(color: [f32; 4], r: u64, range: f32) -> [f32; 4] {
+    let mut color = color;
+    for (channel, c) in color.iter_mut().take(3).enumerate() {
+        let offset = unit(r.rotate_left(channel as u32 * 8)) * 2.0 - 1.0;
+        *c = (*c + offset * range).clamp(0.0, 1.0);
+    }
+    color
+}
+
+/// Rewrite the tile data of terminals with noise every frame, so the noise
+/// changes, and once more when the noise is removed.
+pub(crate) fn terminal_noise_refresh(
+    removed: RemovedComponents<TerminalNoise>,
+    mut noisy: Query<(
+        &TerminalNoise,
+        ChangeTrackers<TerminalNoise>,
+        &mut TerminalRendererTileData,
+    )>,
+    mut q: Query<&mut TerminalRendererTileData, Without<TerminalNoise>>,
+) {
+    for (noise, tracker, mut tile_data) in noisy.iter_mut() {
+        if noise.strength > 0.0 || tracker.is_changed() {
+            tile_data.request_refresh();
+        }
+    }
+    for entity in removed.iter() {
+        if let Ok(mut tile_data) = q.get_mut(entity) {
+            tile_data.request_refresh();
+        }
+    }
+}
+
+pub(crate) fn terminal_renderer_apply_noise(
+    mut frame: Local<u64>,
+    mut q: Query<(&TerminalNoise, &UvMapping, &mut TerminalRendererTileData)>,
+) {
+    *frame = frame.wrapping_add(1);
+    for (noise, uv_mapping, mut tile_data) in q.iter_mut() {
+        if noise.strength <= 0.0 || !tile_data.is_refreshed() {
+            continue;
+        }
+        noise.apply(*frame, &mut tile_data, uv_mapping);
+        tile_data.mark_all_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::UVec2;
+
+    use crate::Tile;
+
+    use super::*;
+
+    fn noisy_data(noise: &TerminalNoise, frame: u64) -> TerminalRendererTileData {
+        let tiles = vec![Tile::default(); 100];
+        let uv_mapping = UvMapping::default();
+        let mut data = TerminalRendererTileData::with_size(UVec2::new(10, 10));
+        data.update_from_tiles(&tiles, &uv_mapping);
+        noise.apply(frame, &mut data, &uv_mapping);
+        data
+    }
+
+    #[test]
+    fn deterministic() {
+        let noise = TerminalNoise::new(0.5, 1234);
+        let a = noisy_data(&noise, 3);
+        let b = noisy_data(&noise, 3);
+        assert_eq!(a.uvs, b.uvs);
+        assert_eq!(a.fg_colors, b.fg_colors);
+
+        let c = noisy_data(&noise, 4);
+        assert_ne!(a.uvs, c.uvs);
+    }
+
+    #[test]
+    fn zero_strength() {
+        let noise = TerminalNoise::new(0.0, 1234);
+        let clean = noisy_data(&TerminalNoise::new(0.0, 0), 0);
+        let data = noisy_data(&noise, 1);
+        assert_eq!(clean.uvs, data.uvs);
+        assert_eq!(clean.bg_colors, data.bg_colors);
+    }
+}
@@ -0,0 +1,164 @@
+//! Giving terminals a copy of their material which only they use.
+
+use bevy::prelude::*;
+
+use super::{
+    clear_color::TerminalClearColor, cursor::TerminalCursor, effects::TerminalEffectChain,
+    material::TerminalMaterial, multi_font::TerminalMultiFont,
+};
+
+/// Terminal component holding the terminal's own copy of it's material.
+///
+/// Added to terminals with a [TerminalCursor], [TerminalClearColor],
+/// [TerminalMultiFont] or [TerminalEffectChain], which are all drawn by the
+/// material. The copy is made
+/// once from the terminal's current material and shared by every one of these
+/// components, so they can modify it without affecting other terminals using
+/// the same material.
+///
+/// If the terminal's material handle is replaced, a new copy is made from the
+/// new material.
+#[derive(Component, Debug, Clone)]
+pub struct TerminalOwnedMaterial(Handle<TerminalMaterial>);
+
+impl TerminalOwnedMaterial {
+    /// The terminal's copy of it's material.
+    pub fn handle(&self) -> &Handle<TerminalMaterial> {
+        &self.0
+    }
+
+    /// The terminal's copy of it's material, if it's still the terminal's
+    /// current material.
+    pub(crate) fn get_mut<'a>(
+        &self,
+        current: &Handle<TerminalMaterial>,
+        materials: &'a mut Assets<TerminalMaterial>,
+    ) -> Option<&'a mut TerminalMaterial> {
+        if self.0 != *current {
+            return None;
+        }
+        materials.get_mut(&self.0)
+    }
+}
+
+pub(crate) struct TerminalOwnedMaterialPlugin;
+
+impl Plugin for TerminalOwnedMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        // Copies are made in an earlier stage, so they exist before any
+        // system which modifies them runs
+        app.add_system_to_stage(CoreStage::PreUpdate, terminal_owned_material_init);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_owned_material_init(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<TerminalMaterial>>,
+    mut q: Query<
+        (
+            Entity,
+            &mut Handle<TerminalMaterial>,
+            Option<&TerminalOwnedMaterial>,
+        ),
+        Or<(
+            With<TerminalCursor>,
+            With<TerminalClearColor>,
+            With<TerminalMultiFont>,
+            With<TerminalEffectChain>,
+        )>,
+    >,
+) {
+    for (entity, mut handle, owned) in q.iter_mut() {
+        if let Some(owned) = owned {
+            if owned.0 == *handle {
+                continue;
+            }
+        }
+        let material = match materials.get(&*handle) {
+            Some(material) => material.clone(),
+            None => continue,
+        };
+        let new_handle = materials.add(material);
+        *handle = new_handle.clone();
+        commands
+            .entity(entity)
+            .insert(TerminalOwnedMaterial(new_handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin};
+
+    use super::*;
+    use crate::{
+        renderer::{
+            clear_color::TerminalClearColorPlugin, cursor::TerminalCursorPlugin,
+            renderer_vertex_data::TerminalRendererVertexData,
+        },
+        Terminal,
+    };
+
+    #[test]
+    fn shared_copy() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .add_asset::<TerminalMaterial>()
+            .add_plugin(TerminalOwnedMaterialPlugin)
+            .add_plugin(TerminalClearColorPlugin)
+            .add_plugin(TerminalCursorPlugin);
+
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        let shared = materials.add(TerminalMaterial::default());
+        let plain = app.world.spawn().insert(shared.clone()).id();
+        let term = app
+            .world
+            .spawn()
+            .insert(shared.clone())
+            .insert(Terminal::with_size([2, 2]))
+            .insert(TerminalRendererVertexData::default())
+            .insert(TerminalClearColor(Color::BLUE))
+            .insert(TerminalCursor::new([5, 5]).with_color(Color::RED))
+            .id();
+        app.update();
+        app.update();
+
+        let material = |app: &App, entity: Entity| {
+            let handle = app.world.get::<Handle<TerminalMaterial>>(entity).unwrap();
+            let materials = app
+                .world
+                .get_resource::<Assets<TerminalMaterial>>()
+                .unwrap();
+            materials.get(handle).unwrap().clone()
+        };
+        // Both components modify the same copy
+        let owned = app.world.get::<TerminalOwnedMaterial>(term).unwrap();
+        assert_eq!(owned.handle(), app.world.get(term).unwrap());
+        assert_eq!(
+            2,
+            app.world
+                .get_resource::<Assets<TerminalMaterial>>()
+                .unwrap()
+                .len()
+        );
+        let copy = material(&app, term);
+        assert_eq!(Some(Color::BLUE), copy.clear_color);
+        assert_eq!(Color::RED, copy.cursor_color);
+
+        // The original material is untouched
+        assert_eq!(&shared, app.world.get(plain).unwrap());
+        assert_eq!(None, material(&app, plain).clear_color);
+
+        app.world.entity_mut(term).remove::<TerminalClearColor>();
+        app.update();
+        let copy = material(&app, term);
+        assert_eq!(None, copy.clear_color);
+        assert_eq!(Color::RED, copy.cursor_color);
+    }
+}
@@ -0,0 +1,218 @@
+//! A terminal component for scrolling layered textures behind the terminal mesh.
+
+use bevy::{
+    prelude::*,
+    sprite::{ColorMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    background::{mesh_bounds, quad_mesh, quad_uvs, set_repeat},
+    renderer_vertex_data::TerminalRendererVertexData,
+};
+
+/// A single scrolling texture of a [TerminalParallaxBackground].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParallaxLayer {
+    pub texture: Handle<Image>,
+    /// How far the texture scrolls per second, in texture repetitions.
+    pub speed: Vec2,
+    /// How far the texture is currently scrolled, in texture repetitions.
+    /// Wraps around between 0 and 1.
+    pub offset: Vec2,
+}
+
+/// Terminal component which draws scrolling textures behind the terminal mesh,
+/// from the first layer at the back to the last layer at the front.
+///
+/// The layers are visible wherever the terminal's tiles are transparent. Each
+/// layer is stretched over the terminal's bounds and follows the terminal when
+/// it's moved or resized. Layers are drawn behind a [TerminalBackground](super::TerminalBackground).
+///
+/// Layer textures should have transparent areas so the layers behind them show
+/// through. Their samplers will be set to repeat so they can wrap while scrolling.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::TerminalParallaxBackground;
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let background = TerminalParallaxBackground::default()
+///         .add_layer(asset_server.load("sky.png"), Vec2::new(0.01, 0.0))
+///         .add_layer(asset_server.load("hills.png"), Vec2::new(0.05, 0.0));
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([40, 20]))
+///         .insert(background);
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Default)]
+pub struct TerminalParallaxBackground {
+    pub layers: Vec<ParallaxLayer>,
+}
+
+impl TerminalParallaxBackground {
+    /// Add a layer in front of the existing layers.
+    pub fn add_layer(mut self, texture: Handle<Image>, speed: Vec2) -> Self {
+        self.layers.push(ParallaxLayer {
+            texture,
+            speed,
+            offset: Vec2::ZERO,
+        });
+        self
+    }
+}
+
+/// The entities and meshes rendering a terminal's parallax layers, along with
+/// the textures they were created for.
+#[derive(Component)]
+struct ParallaxQuads {
+    quads: Vec<(Entity, Handle<Mesh>)>,
+    textures: Vec<Handle<Image>>,
+}
+
+/// The depth of the back layer, relative to the terminal.
+const PARALLAX_DEPTH: f32 = -2.0;
+
+pub(crate) struct TerminalParallaxPlugin;
+
+impl Plugin for TerminalParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_parallax_update.after(super::TERMINAL_UPDATE_SIZE))
+            .add_system(terminal_parallax_removed)
+            .add_system(terminal_parallax_sampler);
+    }
+}
+
+/// Scroll an offset by `speed` over `delta` seconds, wrapping it between 0 and 1.
+fn scroll_offset(offset: Vec2, speed: Vec2, delta: f32) -> Vec2 {
+    let offset = offset + speed * delta;
+    Vec2::new(offset.x.rem_euclid(1.0), offset.y.rem_euclid(1.0))
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_parallax_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut q: Query<(
+        Entity,
+        &mut TerminalParallaxBackground,
+        &TerminalRendererVertexData,
+        ChangeTrackers<TerminalRendererVertexData>,
+        Option<&ParallaxQuads>,
+    )>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut background, vert_data, vert_tracker, quads) in q.iter_mut() {
+        // Avoid flagging the background as changed if nothing is scrolling
+        if background.layers.iter().any(|l| l.speed != Vec2::ZERO) {
+            for layer in background.layers.iter_mut() {
+                layer.offset = scroll_offset(layer.offset, layer.speed, delta);
+            }
+        }
+
+        let textures: Vec<_> = background
+            .layers
+            .iter()
+            .map(|l| l.texture.clone())
+            .collect();
+        let rebuild = match quads {
+            Some(quads) => quads.textures != textures || vert_tracker.is_changed(),
+            None => true,
+        };
+
+        if !rebuild {
+            if background.is_changed() {
+                let layers = background.layers.iter();
+                for (layer, (_, mesh)) in layers.zip(quads.unwrap().quads.iter()) {
+                    if let Some(mesh) = meshes.get_mut(mesh) {
+                        let uvs = quad_uvs(layer.offset, layer.offset + Vec2::ONE);
+                        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(quads) = quads {
+            for (quad, _) in quads.quads.iter() {
+                commands.entity(*quad).despawn_recursive();
+            }
+        }
+        let (min, max) = match mesh_bounds(vert_data) {
+            Some(bounds) => bounds,
+            None => {
+                commands.entity(entity).remove::<ParallaxQuads>();
+                continue;
+            }
+        };
+
+        let mut spawned = Vec::with_capacity(background.layers.len());
+        for (i, layer) in background.layers.iter().enumerate() {
+            let mesh = meshes.add(quad_mesh(min, max, layer.offset, layer.offset + Vec2::ONE));
+            let quad = commands
+                .spawn_bundle(ColorMesh2dBundle {
+                    mesh: Mesh2dHandle(mesh.clone()),
+                    material: materials.add(layer.texture.clone().into()),
+                    transform: Transform::from_xyz(0.0, 0.0, PARALLAX_DEPTH + i as f32 * 0.01),
+                    ..Default::default()
+                })
+                .id();
+            commands.entity(entity).add_child(quad);
+            spawned.push((quad, mesh));
+        }
+        commands.entity(entity).insert(ParallaxQuads {
+            quads: spawned,
+            textures,
+        });
+    }
+}
+
+fn terminal_parallax_removed(
+    mut commands: Commands,
+    removed: RemovedComponents<TerminalParallaxBackground>,
+    q: Query<&ParallaxQuads>,
+) {
+    for entity in removed.iter() {
+        if let Ok(quads) = q.get(entity) {
+            for (quad, _) in quads.quads.iter() {
+                commands.entity(*quad).despawn_recursive();
+            }
+            commands.entity(entity).remove::<ParallaxQuads>();
+        }
+    }
+}
+
+/// Ensure layer textures repeat so they can wrap while scrolling.
+fn terminal_parallax_sampler(
+    mut images: ResMut<Assets<Image>>,
+    q: Query<&TerminalParallaxBackground>,
+) {
+    for background in q.iter() {
+        for layer in background.layers.iter() {
+            set_repeat(&mut images, &layer.texture);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolling() {
+        let offset = scroll_offset(Vec2::ZERO, Vec2::new(0.5, -0.25), 0.5);
+        assert_eq!(Vec2::new(0.25, 0.875), offset);
+        let offset = scroll_offset(offset, Vec2::new(0.5, -0.25), 2.0);
+        assert_eq!(Vec2::new(0.25, 0.375), offset);
+
+        let background = TerminalParallaxBackground::default()
+            .add_layer(Handle::default(), Vec2::X)
+            .add_layer(Handle::default(), Vec2::Y);
+        assert_eq!(2, background.layers.len());
+        assert_eq!(Vec2::Y, background.layers[1].speed);
+    }
+}
@@ -0,0 +1,112 @@
+//! Writing small terminal changes straight to the gpu.
+//!
+//! Normally any change to a terminal rewrites it's whole mesh, which bevy then
+//! copies into a brand new vertex buffer. For terminals with a
+//! [TerminalPartialUpload] component, updates touching only a few tiles skip
+//! the mesh and write just those tiles into the existing vertex buffer, with
+//! one write for each run of neighbouring tiles.
+//!
+//! On a 200x60 terminal with 10% of it's tiles changed, a partial upload sends
+//! a tenth of the bytes of a full upload in 30 to 1200 writes, depending on
+//! how spread out the changes are, and takes around a tenth of the cpu time to
+//! prepare. See the `upload_cost` test in `renderer_tile_data.rs`.
+
+use bevy::{
+    prelude::*,
+    render::{render_asset::RenderAssets, renderer::RenderQueue, RenderApp, RenderStage},
+    sprite::Mesh2dHandle,
+};
+
+use super::{
+    renderer_tile_data::{TerminalRendererTileData, VertexWrite},
+    renderer_vertex_data::TerminalRendererVertexData,
+    throttle::TerminalThrottledRenderer,
+};
+
+/// Terminal component which writes small changes directly to the terminal
+/// mesh's vertex buffer on the gpu, instead of rebuilding the whole mesh.
+///
+/// Systems added with [add_terminal_pre_render_system](super::TerminalAppExt::add_terminal_pre_render_system)
+/// which change tiles other than the ones written by the terminal must call
+/// [TerminalRendererTileData::mark_all_changed], otherwise their changes
+/// won't be uploaded. Has no effect on terminals with a [TerminalThrottledRenderer].
+#[derive(Component, Default, Clone, Copy)]
+pub struct TerminalPartialUpload;
+
+/// The vertices written by a partial update, extracted to the render world.
+#[derive(Component)]
+struct ExtractedTileUpload {
+    mesh: Handle<Mesh>,
+    writes: Vec<VertexWrite>,
+}
+
+pub(crate) struct TerminalPartialUploadPlugin;
+
+impl Plugin for TerminalPartialUploadPlugin {
+    fn build(&self, app: &mut App) {
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_system_to_stage(RenderStage::Extract, extract_terminal_uploads)
+                // After meshes are prepared, so a mesh rebuilt this frame is patched too
+                .add_system_to_stage(RenderStage::Queue, upload_terminal_tiles);
+        }
+    }
+}
+
+/// True if the terminal's latest tile data will be uploaded directly to the gpu,
+/// so it's mesh shouldn't be rewritten.
+pub(crate) fn is_partial_upload(
+    data: &TerminalRendererTileData,
+    partial: Option<&TerminalPartialUpload>,
+    throttle: Option<&TerminalThrottledRenderer>,
+) -> bool {
+    partial.is_some() && throttle.is_none() && data.partial_update().is_some()
+}
+
+#[allow(clippy::type_complexity)]
+fn extract_terminal_uploads(
+    mut commands: Commands,
+    q: Query<
+        (
+            Option<&Mesh2dHandle>,
+            Option<&Handle<Mesh>>,
+            &TerminalRendererTileData,
+            &TerminalRendererVertexData,
+        ),
+        (
+            With<TerminalPartialUpload>,
+            Without<TerminalThrottledRenderer>,
+            Changed<TerminalRendererTileData>,
+        ),
+    >,
+) {
+    for (mesh_2d, mesh_3d, data, vertex_data) in q.iter() {
+        let changed = match data.partial_update() {
+            Some(changed) => changed,
+            None => continue,
+        };
+        let mesh = match (mesh_2d, mesh_3d) {
+            (Some(mesh), _) => mesh.0.clone_weak(),
+            (None, Some(mesh)) => mesh.clone_weak(),
+            _ => continue,
+        };
+        commands.spawn().insert(ExtractedTileUpload {
+            mesh,
+            writes: data.vertex_writes(changed, vertex_data),
+        });
+    }
+}
+
+fn upload_terminal_tiles(
+    queue: Res<RenderQueue>,
+    meshes: Res<RenderAssets<Mesh>>,
+    q: Query<&ExtractedTileUpload>,
+) {
+    for upload in q.iter() {
+        if let Some(mesh) = meshes.get(&upload.mesh) {
+            for (offset, bytes) in &upload.writes {
+                queue.write_buffer(&mesh.vertex_buffer, *offset, bytes);
+            }
+        }
+    }
+}
@@ -1,15 +1,43 @@
 //! Plugin for rendering related resources and systems.
 
 use bevy::{
+    ecs::schedule::ParallelSystemDescriptorCoercion,
     prelude::*,
     render::{
         mesh::{Indices, MeshVertexAttribute},
         render_resource::{PrimitiveTopology, VertexFormat},
+        view::RenderLayers,
     },
     sprite::Mesh2dHandle,
 };
 
-use super::{material::TerminalMaterialPlugin, uv_mapping::UvMapping, *};
+use crate::layers::terminal_clear_layers;
+
+use super::{
+    accumulation::terminal_accumulation_swap,
+    asset_pool::{TerminalAssetPool, TerminalAssetPoolSize},
+    background::TerminalBackgroundPlugin,
+    clear_color::TerminalClearColorPlugin,
+    cursor::TerminalCursorPlugin,
+    effects::TerminalEffectsPlugin,
+    fog_of_war::{
+        terminal_fog_of_war_fov, terminal_fog_of_war_refresh, terminal_renderer_apply_fog_of_war,
+    },
+    font_asset::{terminal_validate_font_change, TerminalFontAsset, TerminalFontLoader},
+    invert_colors::TerminalInvertColorsPlugin,
+    material::{font_tile_size, TerminalMaterialPlugin},
+    mosaic::{terminal_mosaic_refresh, terminal_renderer_apply_mosaic},
+    multi_font::TerminalMultiFontPlugin,
+    noise::{terminal_noise_refresh, terminal_renderer_apply_noise},
+    owned_material::TerminalOwnedMaterialPlugin,
+    parallax::TerminalParallaxPlugin,
+    partial_upload::{is_partial_upload, TerminalPartialUpload, TerminalPartialUploadPlugin},
+    renderer_tile_data::PARTIAL_UPDATE_THRESHOLD,
+    throttle::TerminalThrottledRenderer,
+    uv_mapping::UvMapping,
+    view::TerminalViewMatrixPlugin,
+    *,
+};
 
 pub const ATTRIBUTE_UV: MeshVertexAttribute =
     MeshVertexAttribute::new("Vertex_Uv", 1, VertexFormat::Float32x2);
@@ -17,14 +45,56 @@ pub const ATTRIBUTE_COLOR_BG: MeshVertexAttribute =
     MeshVertexAttribute::new("Vertex_Color_Bg", 2, VertexFormat::Float32x4);
 pub const ATTRIBUTE_COLOR_FG: MeshVertexAttribute =
     MeshVertexAttribute::new("Vertex_Color_Fg", 3, VertexFormat::Float32x4);
+pub const ATTRIBUTE_BG_UV: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Bg_Uv", 4, VertexFormat::Float32x2);
+/// The position of each vertex within the whole terminal, from (0,0) at the
+/// bottom left to (1,1) at the top right.
+pub const ATTRIBUTE_TERMINAL_UV: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Terminal_Uv", 5, VertexFormat::Float32x2);
+
+/// Resource specifying the render layer used by terminals which don't
+/// specify their own [TerminalRenderLayer].
+///
+/// Layers past the last of bevy's 32 render layers are clamped to it.
+#[derive(Clone, Copy)]
+pub struct DefaultTerminalRenderLayer(pub u8);
 
 pub struct TerminalRendererPlugin;
 
 impl Plugin for TerminalRendererPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(TerminalMaterialPlugin);
+        app.add_plugin(TerminalMaterialPlugin)
+            .add_plugin(TerminalBackgroundPlugin)
+            .add_plugin(TerminalClearColorPlugin)
+            .add_plugin(TerminalCursorPlugin)
+            .add_plugin(TerminalEffectsPlugin)
+            .add_plugin(TerminalMultiFontPlugin)
+            .add_plugin(TerminalOwnedMaterialPlugin)
+            .add_plugin(TerminalParallaxPlugin)
+            .add_plugin(TerminalViewMatrixPlugin)
+            .add_plugin(TerminalInvertColorsPlugin)
+            .add_plugin(TerminalPartialUploadPlugin)
+            .add_asset::<TerminalFontAsset>()
+            .init_asset_loader::<TerminalFontLoader>()
+            .add_event::<TerminalFovSource>();
+
+        // After the material plugin, so pooled materials copy the default material
+        let pool = app
+            .world
+            .resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+                let size = world
+                    .get_resource::<TerminalAssetPoolSize>()
+                    .map_or(0, |size| size.0);
+                let mut materials = world
+                    .get_resource_mut::<Assets<TerminalMaterial>>()
+                    .unwrap();
+                TerminalAssetPool::new(size, &mut meshes, &mut materials)
+            });
+        app.insert_resource(pool);
 
         app.add_system(terminal_renderer_init.label(TERMINAL_INIT))
+            .add_system(terminal_validate_font_change.before(TERMINAL_UPDATE_SIZE))
+            .add_system(terminal_material_created::<TerminalMaterial>.before(TERMINAL_UPDATE_SIZE))
             .add_system(
                 terminal_renderer_update_size
                     .after(TERMINAL_INIT)
@@ -35,12 +105,128 @@ impl Plugin for TerminalRendererPlugin {
                     .after(TERMINAL_UPDATE_SIZE)
                     .label(TERMINAL_UPDATE_TILE_DATA),
             )
+            .add_system(terminal_fog_of_war_fov.label(TERMINAL_FOG_OF_WAR_FOV))
+            .add_system(
+                terminal_fog_of_war_refresh
+                    .after(TERMINAL_FOG_OF_WAR_FOV)
+                    .before(TERMINAL_UPDATE_TILE_DATA),
+            )
+            .add_system(terminal_mosaic_refresh.before(TERMINAL_UPDATE_TILE_DATA))
+            .add_system(terminal_noise_refresh.before(TERMINAL_UPDATE_TILE_DATA))
+            // Fogged tiles are averaged into mosaic blocks, and noise is drawn
+            // over both
+            .add_terminal_pre_render_system(
+                terminal_renderer_apply_fog_of_war.label(TERMINAL_APPLY_FOG_OF_WAR),
+            )
+            .add_terminal_pre_render_system(
+                terminal_renderer_apply_mosaic
+                    .after(TERMINAL_APPLY_FOG_OF_WAR)
+                    .label(TERMINAL_APPLY_MOSAIC),
+            )
+            .add_terminal_pre_render_system(
+                terminal_renderer_apply_noise.after(TERMINAL_APPLY_MOSAIC),
+            )
             .add_system(
                 terminal_renderer_update_mesh
                     .after(TERMINAL_UPDATE_TILE_DATA)
                     .label(TERMINAL_UPDATE_MESH),
-            );
+            )
+            .add_system(terminal_renderer_update_render_layer)
+            .add_system(terminal_accumulation_swap)
+            .add_system_to_stage(CoreStage::First, terminal_clear_layers);
+    }
+}
+
+/// Extension methods for adding terminal systems to an [App].
+pub trait TerminalAppExt {
+    /// Add a system which runs after terminal tile data is updated from the
+    /// terminal, but before it's written to the mesh.
+    ///
+    /// Systems added this way can modify [TerminalRendererTileData] to apply
+    /// effects like blur or shadows without changing the terminal itself. They
+    /// are labeled [TERMINAL_PRE_RENDER], so they can be ordered relative to
+    /// each other.
+    ///
+    /// Changes are made in place and kept until the data is next written from
+    /// the terminal, so effects should only be applied when
+    /// [TerminalRendererTileData::is_refreshed] is true. The data is always
+    /// fully rewritten after it's been modified, so effects are never applied
+    /// on top of themselves. An effect which changes can call
+    /// [TerminalRendererTileData::request_refresh] to be applied again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    /// use bevy_ascii_terminal::renderer::TerminalRendererTileData;
+    ///
+    /// fn dim(mut q: Query<&mut TerminalRendererTileData>) {
+    ///     for mut data in q.iter_mut() {
+    ///         if !data.is_refreshed() {
+    ///             continue;
+    ///         }
+    ///         for color in data.fg_colors.iter_mut() {
+    ///             color[0] *= 0.5;
+    ///             color[1] *= 0.5;
+    ///             color[2] *= 0.5;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn build(app: &mut App) {
+    ///     app.add_plugin(TerminalPlugin::default())
+    ///         .add_terminal_pre_render_system(dim);
+    /// }
+    /// ```
+    fn add_terminal_pre_render_system<Params>(
+        &mut self,
+        system: impl ParallelSystemDescriptorCoercion<Params>,
+    ) -> &mut Self;
+}
+
+impl TerminalAppExt for App {
+    fn add_terminal_pre_render_system<Params>(
+        &mut self,
+        system: impl ParallelSystemDescriptorCoercion<Params>,
+    ) -> &mut Self {
+        self.add_system(
+            system
+                .label(TERMINAL_PRE_RENDER)
+                .after(TERMINAL_UPDATE_TILE_DATA)
+                .before(TERMINAL_UPDATE_MESH),
+        )
+    }
+}
+
+fn terminal_renderer_update_render_layer(
+    mut commands: Commands,
+    default_layer: Option<Res<DefaultTerminalRenderLayer>>,
+    q: Query<(Entity, &TerminalRenderLayer), Changed<TerminalRenderLayer>>,
+) {
+    for (entity, layer) in q.iter() {
+        let layer = layer.0.or_else(|| default_layer.as_ref().map(|l| l.0));
+        match layer {
+            Some(layer) => commands
+                .entity(entity)
+                .insert(RenderLayers::layer(clamp_render_layer(layer))),
+            None => commands.entity(entity).remove::<RenderLayers>(),
+        };
+    }
+}
+
+/// Clamp a layer to the last of bevy's render layers, since
+/// [RenderLayers::layer] panics past it.
+fn clamp_render_layer(layer: u8) -> u8 {
+    let last = (RenderLayers::TOTAL_LAYERS - 1) as u8;
+    if layer > last {
+        warn!(
+            "Terminal render layer {} is out of range, using layer {} instead",
+            layer, last
+        );
+        return last;
     }
+    layer
 }
 
 #[allow(clippy::type_complexity)]
@@ -49,6 +235,10 @@ fn terminal_renderer_init(
     mut q: Query<&mut Mesh2dHandle, (Added<Mesh2dHandle>, With<TerminalRendererVertexData>)>,
 ) {
     for mut mesh in q.iter_mut() {
+        // Meshes from a TerminalAssetPool are already allocated
+        if mesh.0 != Handle::default() {
+            continue;
+        }
         //info!("Initializing ascii terminal mesh");
         let new_mesh = Mesh::new(PrimitiveTopology::TriangleList);
         *mesh = Mesh2dHandle(meshes.add(new_mesh));
@@ -70,30 +260,40 @@ fn terminal_renderer_update_size(
             &mut Mesh2dHandle,
             &mut TerminalRendererVertexData,
             &mut TerminalRendererTileData,
+            Option<&TerminalViewMatrix>,
         ),
         Or<(
             Changed<Handle<Mesh>>,
             Changed<TileScaling>,
             Changed<Handle<TerminalMaterial>>,
+            Changed<TerminalViewMatrix>,
         )>,
     >,
 ) {
-    for (terminal, material, scaling, term_pivot, tile_pivot, mesh, mut vert_data, mut tile_data) in
-        q.iter_mut()
+    for (
+        terminal,
+        material,
+        scaling,
+        term_pivot,
+        tile_pivot,
+        mesh,
+        mut vert_data,
+        mut tile_data,
+        view,
+    ) in q.iter_mut()
     {
-        let mut tile_size = UVec2::ONE;
-        if let TileScaling::Pixels = scaling {
-            let material = materials.get(material).unwrap();
-            let image = images.get(material.texture.clone().unwrap()).unwrap();
-            let size = image.texture_descriptor.size;
-            // TODO: This will need to assignable for graphical terminals, can't necessarily
-            // be derived from the texture for a non-uniform-grid tilesheet.
-            let font_size = UVec2::new(size.width, size.height) / UVec2::new(16, 16);
-            tile_size *= font_size;
-        }
+        // Sized again by terminal_material_created once the material exists
+        let material = match materials.get(material) {
+            Some(material) => material,
+            None => continue,
+        };
+        let tile_size = terminal_tile_size(material, scaling, &images);
 
         let size = terminal.size();
         vert_data.resize(size, term_pivot.0, tile_pivot.0, tile_size);
+        if let Some(view) = view {
+            view.apply(&mut vert_data);
+        }
         tile_data.resize(size);
 
         let mesh = meshes
@@ -103,26 +303,190 @@ fn terminal_renderer_update_size(
         //info!("Changing mesh size size: {}, Length: {}", size, vert_data.indices.len());
         //info!("First 4 verts: {:?}", &vert_data.verts[0..4]);
         //info!("First 6 indices: {:?}", &vert_data.indices[0..6]);
-        mesh.set_indices(Some(Indices::U32(vert_data.indices.clone())));
-        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vert_data.verts.clone());
+        write_mesh_verts(mesh, &vert_data);
+        // Partial uploads skip the mesh, so it may not have the latest tile data
+        write_mesh_tile_data(mesh, &tile_data);
+    }
+}
+
+/// Mark the material of every terminal using a newly created material as
+/// changed, so terminals which couldn't be sized without it are sized.
+pub(crate) fn terminal_material_created<M: bevy::asset::Asset>(
+    mut events: EventReader<AssetEvent<M>>,
+    mut q: Query<&mut Handle<M>>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } = event {
+            for mut material in q.iter_mut().filter(|m| m.id == handle.id) {
+                material.set_changed();
+            }
+        }
+    }
+}
+
+/// The size of a single tile in world units for a terminal's material and scaling.
+pub(crate) fn terminal_tile_size(
+    material: &TerminalMaterial,
+    scaling: &TileScaling,
+    images: &Assets<Image>,
+) -> Vec2 {
+    loaded_tile_size(material, scaling, images).expect("Terminal font texture is not loaded")
+}
+
+/// The size of a single tile in world units for a terminal's material and
+/// scaling, or `None` if it depends on the material's font texture and it
+/// isn't loaded.
+pub(crate) fn loaded_tile_size(
+    material: &TerminalMaterial,
+    scaling: &TileScaling,
+    images: &Assets<Image>,
+) -> Option<Vec2> {
+    let image = || images.get(material.texture.as_ref()?);
+    match scaling {
+        TileScaling::World => match material.pixels_per_unit {
+            Some(ppu) => Some(world_tile_size(font_tile_size(image()?), ppu)),
+            None => Some(Vec2::ONE),
+        },
+        // TODO: This will need to assignable for graphical terminals, can't necessarily
+        // be derived from the texture for a non-uniform-grid tilesheet.
+        TileScaling::Pixels => Some(font_tile_size(image()?).as_vec2()),
+    }
+}
+
+/// Write the positions and indices of a terminal's vertex data to it's mesh.
+pub(crate) fn write_mesh_verts(mesh: &mut Mesh, vert_data: &TerminalRendererVertexData) {
+    mesh.set_indices(Some(Indices::U32(vert_data.indices.clone())));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vert_data.verts.clone());
+    mesh.insert_attribute(ATTRIBUTE_TERMINAL_UV, terminal_uvs(&vert_data.verts));
+}
+
+/// The position of each vert within the bounds of all the verts.
+fn terminal_uvs(verts: &[[f32; 3]]) -> Vec<[f32; 2]> {
+    let bounds = terminal_uv_bounds(verts);
+    verts.iter().map(|v| terminal_uv(*v, bounds)).collect()
+}
+
+/// The bottom left corner and size of the bounds of all the verts.
+pub(crate) fn terminal_uv_bounds(verts: &[[f32; 3]]) -> (Vec2, Vec2) {
+    let (min, max) = verts.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), v| {
+            let v = Vec3::from(*v).truncate();
+            (min.min(v), max.max(v))
+        },
+    );
+    (min, (max - min).max(Vec2::splat(f32::EPSILON)))
+}
+
+/// The position of a vert within the bounds from [terminal_uv_bounds].
+pub(crate) fn terminal_uv(vert: [f32; 3], (min, size): (Vec2, Vec2)) -> [f32; 2] {
+    ((Vec3::from(vert).truncate() - min) / size).into()
+}
+
+/// Write the colors and uvs of a terminal's tile data to it's mesh.
+pub(crate) fn write_mesh_tile_data(mesh: &mut Mesh, tile_data: &TerminalRendererTileData) {
+    mesh.insert_attribute(ATTRIBUTE_COLOR_BG, tile_data.bg_colors.clone());
+    mesh.insert_attribute(ATTRIBUTE_COLOR_FG, tile_data.fg_colors.clone());
+    mesh.insert_attribute(ATTRIBUTE_UV, tile_data.uvs.clone());
+    mesh.insert_attribute(ATTRIBUTE_BG_UV, tile_data.bg_uvs.clone());
+}
+
+/// The size of a tile in world units for a font with the given tile size in pixels.
+fn world_tile_size(tile_pixels: UVec2, ppu: u32) -> Vec2 {
+    if ppu > tile_pixels.y {
+        warn!(
+            "Terminal pixels per unit ({}) is larger than the font's tile height ({})",
+            ppu, tile_pixels.y
+        );
     }
+    tile_pixels.as_vec2() / ppu.max(1) as f32
 }
 
+#[allow(clippy::type_complexity)]
 fn terminal_renderer_update_tile_data(
-    mut q: Query<(&Terminal, &mut TerminalRendererTileData, &UvMapping), Changed<Terminal>>,
+    mut q: Query<(
+        &Terminal,
+        ChangeTrackers<Terminal>,
+        &mut TerminalRendererTileData,
+        ChangeTrackers<UvMapping>,
+        &UvMapping,
+        Option<&TerminalKeyMap>,
+    )>,
 ) {
-    for (term, mut data, uv_mapping) in q.iter_mut() {
+    for (term, term_tracker, mut data, uv_tracker, uv_mapping, key_map) in q.iter_mut() {
+        // Any tile may have been changed by pre-render systems since the data
+        // was last written, so only a full update removes their changes
+        if data.is_changed() {
+            data.set_modified();
+        }
+        data.clear_refreshed();
+        let key_map = key_map.copied().unwrap_or_default();
+        // Every tile must be rewritten with a new key map
+        let key_map_changed = data.key_map() != &key_map;
+        if !term_tracker.is_changed()
+            && !uv_tracker.is_changed()
+            && !key_map_changed
+            && !data.is_refresh_requested()
+        {
+            continue;
+        }
+        if uv_tracker.is_changed() {
+            data.invalidate_uv_cache();
+        }
+        data.set_key_map(&key_map);
         //info!("Renderer update tile data (colors)!");
         //info!("First tiles: {:?}", &term.tiles[0..4]);
-        data.update_from_tiles(term.tiles.slice(..), uv_mapping);
+        let tiles = term.composited_tiles();
+        let tiles = &tiles[..];
+        let threshold = (tiles.len() as f32 * PARTIAL_UPDATE_THRESHOLD) as usize;
+        let changed = match data.changed_tiles(tiles) {
+            Some(changed)
+                if !data.is_modified()
+                    && !data.is_refresh_requested()
+                    && !uv_tracker.is_changed()
+                    && !key_map_changed
+                    && changed.len() < threshold =>
+            {
+                changed
+            }
+            _ => {
+                data.update_from_tiles(tiles, uv_mapping);
+                term.reset_change_counter();
+                continue;
+            }
+        };
+        data.regenerate_partial(&changed, uv_mapping);
+        term.reset_change_counter();
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn terminal_renderer_update_mesh(
+    time: Res<Time>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut q: Query<(&TerminalRendererTileData, &Mesh2dHandle), Changed<TerminalRendererTileData>>,
+    mut q: Query<
+        (
+            &TerminalRendererTileData,
+            ChangeTrackers<TerminalRendererTileData>,
+            &Mesh2dHandle,
+            Option<&mut TerminalThrottledRenderer>,
+            Option<&TerminalPartialUpload>,
+        ),
+        Or<(
+            Changed<TerminalRendererTileData>,
+            With<TerminalThrottledRenderer>,
+        )>,
+    >,
 ) {
-    for (tile_data, mesh) in q.iter_mut() {
+    for (tile_data, tracker, mesh, mut throttle, partial) in q.iter_mut() {
+        if is_partial_upload(tile_data, partial, throttle.as_deref()) {
+            continue;
+        }
+        if let Some(throttle) = &mut throttle {
+            if !throttle.tick(time.delta_seconds(), tracker.is_changed()) {
+                continue;
+            }
+        }
         let mesh = meshes
             .get_mut(&mesh.0)
             .expect("Error accessing terminal mesh");
@@ -133,8 +497,225 @@ fn terminal_renderer_update_mesh(
 
         //mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, tile_data.fg_colors.clone());
 
-        mesh.insert_attribute(ATTRIBUTE_COLOR_BG, tile_data.bg_colors.clone());
-        mesh.insert_attribute(ATTRIBUTE_COLOR_FG, tile_data.fg_colors.clone());
-        mesh.insert_attribute(ATTRIBUTE_UV, tile_data.uvs.clone());
+        write_mesh_tile_data(mesh, tile_data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::schedule::{Stage, SystemStage};
+
+    use super::*;
+    use crate::{
+        renderer::{TerminalFogOfWar, TerminalNoise},
+        Tile,
+    };
+
+    fn run_render_layer_system(world: &mut World) {
+        let mut stage = SystemStage::parallel();
+        stage.add_system(terminal_renderer_update_render_layer);
+        stage.run(world);
+    }
+
+    #[test]
+    fn pre_render_system() {
+        fn dim(mut q: Query<&mut TerminalRendererTileData>) {
+            for mut data in q.iter_mut() {
+                if data.is_refreshed() {
+                    data.fg_colors.iter_mut().for_each(|c| c[0] *= 0.5);
+                }
+            }
+        }
+
+        let mut app = App::new();
+        app.add_system(terminal_renderer_update_tile_data.label(TERMINAL_UPDATE_TILE_DATA))
+            .add_terminal_pre_render_system(dim);
+        let term = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([10, 10]))
+            .insert(TerminalRendererTileData::with_size(UVec2::new(10, 10)))
+            .insert(UvMapping::default())
+            .id();
+        let red = |app: &App, i: usize| {
+            let data = app.world.get::<TerminalRendererTileData>(term).unwrap();
+            data.fg_colors[i * 4][0]
+        };
+
+        app.update();
+        assert_eq!(0.5, red(&app, 0));
+        // The effect is kept, but not applied again, while nothing changes
+        app.update();
+        assert_eq!(0.5, red(&app, 0));
+
+        // A change to a single tile rewrites every tile the effect changed
+        let mut terminal = app.world.get_mut::<Terminal>(term).unwrap();
+        terminal.put_char([5, 5], 'a');
+        app.update();
+        assert_eq!(0.5, red(&app, 0));
+        assert_eq!(0.5, red(&app, 55));
+
+        app.world
+            .get_mut::<TerminalRendererTileData>(term)
+            .unwrap()
+            .request_refresh();
+        app.update();
+        assert_eq!(0.5, red(&app, 0));
+    }
+
+    #[test]
+    fn fog_and_noise() {
+        let mut app = App::new();
+        app.add_system(terminal_renderer_update_tile_data.label(TERMINAL_UPDATE_TILE_DATA))
+            .add_system(terminal_fog_of_war_refresh.before(TERMINAL_UPDATE_TILE_DATA))
+            .add_system(terminal_noise_refresh.before(TERMINAL_UPDATE_TILE_DATA))
+            .add_terminal_pre_render_system(terminal_renderer_apply_fog_of_war.label("fog"))
+            .add_terminal_pre_render_system(terminal_renderer_apply_noise.after("fog"));
+        let unseen = Tile {
+            fg_color: Color::RED,
+            ..Default::default()
+        };
+        let term = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([2, 1]))
+            .insert(TerminalRendererTileData::with_size(UVec2::new(2, 1)))
+            .insert(UvMapping::default())
+            .insert(TerminalFogOfWar::new(unseen))
+            .insert(TerminalNoise::new(0.0, 0))
+            .id();
+        let fg = |app: &App| {
+            let data = app.world.get::<TerminalRendererTileData>(term).unwrap();
+            data.fg_colors[0]
+        };
+        let red = Color::RED.as_linear_rgba_f32();
+        let white = Color::WHITE.as_linear_rgba_f32();
+
+        app.update();
+        assert_eq!(red, fg(&app));
+        // The fog is kept while the terminal is rewritten
+        let mut terminal = app.world.get_mut::<Terminal>(term).unwrap();
+        terminal.put_char([1, 0], 'a');
+        app.update();
+        assert_eq!(red, fg(&app));
+
+        // Changing or removing the fog rewrites the terminal
+        let mut fog = app.world.get_mut::<TerminalFogOfWar>(term).unwrap();
+        fog.set_visible([UVec2::new(0, 0)]);
+        app.update();
+        assert_eq!(white, fg(&app));
+        let mut fog = app.world.get_mut::<TerminalFogOfWar>(term).unwrap();
+        fog.visible.clear();
+        fog.explored.clear();
+        app.update();
+        assert_eq!(red, fg(&app));
+        app.world.entity_mut(term).remove::<TerminalFogOfWar>();
+        app.update();
+        assert_eq!(white, fg(&app));
+
+        // Noise refreshes the terminal every frame
+        app.world.get_mut::<TerminalNoise>(term).unwrap().strength = 1.0;
+        app.update();
+        let first = fg(&app);
+        app.update();
+        assert_ne!(first, fg(&app));
+        app.world.entity_mut(term).remove::<TerminalNoise>();
+        app.update();
+        assert_eq!(white, fg(&app));
+    }
+
+    #[test]
+    fn missing_material() {
+        let mut app = App::new();
+        app.add_plugin(bevy::core::CorePlugin)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<Image>()
+            .add_asset::<TerminalMaterial>()
+            .add_system(terminal_material_created::<TerminalMaterial>.before(TERMINAL_UPDATE_SIZE))
+            .add_system(terminal_renderer_update_size.label(TERMINAL_UPDATE_SIZE));
+        let mesh = app
+            .world
+            .get_resource_mut::<Assets<Mesh>>()
+            .unwrap()
+            .add(Mesh::new(PrimitiveTopology::TriangleList));
+        let material =
+            Handle::<TerminalMaterial>::weak(bevy::asset::HandleId::random::<TerminalMaterial>());
+        let term = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([2, 2]))
+            .insert(material.clone())
+            .insert(TileScaling::World)
+            .insert(TerminalPivot(Vec2::ZERO))
+            .insert(TilePivot::default())
+            .insert(Mesh2dHandle(mesh))
+            .insert(TerminalRendererVertexData::default())
+            .insert(TerminalRendererTileData::default())
+            .id();
+        let vert_count = |app: &App| {
+            let vert_data = app.world.get::<TerminalRendererVertexData>(term).unwrap();
+            vert_data.verts.len()
+        };
+
+        // Terminals aren't sized until their material exists
+        app.update();
+        assert_eq!(0, vert_count(&app));
+        let mut materials = app
+            .world
+            .get_resource_mut::<Assets<TerminalMaterial>>()
+            .unwrap();
+        let _ = materials.set(material, TerminalMaterial::default());
+        app.update();
+        app.update();
+        assert_eq!(16, vert_count(&app));
+    }
+
+    #[test]
+    fn pixels_per_unit() {
+        assert_eq!(Vec2::ONE, world_tile_size(UVec2::new(8, 8), 8));
+        assert_eq!(Vec2::splat(1.5), world_tile_size(UVec2::new(12, 12), 8));
+        assert_eq!(Vec2::new(0.5, 1.0), world_tile_size(UVec2::new(8, 16), 16));
+    }
+
+    #[test]
+    fn render_layer() {
+        let mut world = World::new();
+        let term = world.spawn().insert(TerminalRenderLayer(Some(1))).id();
+
+        run_render_layer_system(&mut world);
+
+        let layers = world.get::<RenderLayers>(term).unwrap();
+        assert!(!layers.intersects(&RenderLayers::layer(0)));
+        assert!(layers.intersects(&RenderLayers::layer(1)));
+    }
+
+    #[test]
+    fn default_render_layer() {
+        let mut world = World::new();
+        world.insert_resource(DefaultTerminalRenderLayer(2));
+        let term = world.spawn().insert(TerminalRenderLayer(None)).id();
+        let term_override = world.spawn().insert(TerminalRenderLayer(Some(1))).id();
+
+        run_render_layer_system(&mut world);
+
+        let layers = world.get::<RenderLayers>(term).unwrap();
+        assert!(layers.intersects(&RenderLayers::layer(2)));
+        let layers = world.get::<RenderLayers>(term_override).unwrap();
+        assert!(!layers.intersects(&RenderLayers::layer(2)));
+    }
+
+    #[test]
+    fn render_layer_out_of_range() {
+        let mut world = World::new();
+        world.insert_resource(DefaultTerminalRenderLayer(40));
+        let term = world.spawn().insert(TerminalRenderLayer(None)).id();
+        let term_override = world.spawn().insert(TerminalRenderLayer(Some(255))).id();
+
+        run_render_layer_system(&mut world);
+
+        let last = RenderLayers::layer((RenderLayers::TOTAL_LAYERS - 1) as u8);
+        assert_eq!(&last, world.get::<RenderLayers>(term).unwrap());
+        assert_eq!(&last, world.get::<RenderLayers>(term_override).unwrap());
     }
 }
@@ -0,0 +1,512 @@
+//! Rendering terminals as a plane in 3d space, for example as an in-game computer screen.
+//!
+//! Add the [TerminalRendering3dPlugin] after the [TerminalPlugin](crate::TerminalPlugin)
+//! and spawn a [Terminal3dBundle]. The terminal is drawn on the xy plane of it's
+//! transform, facing +z, and is unaffected by lighting.
+//!
+//! A [TerminalMaterial3d] uses the same shader code and settings as a 2d
+//! [TerminalMaterial]. The components which change a terminal's material for
+//! it only work with 2d terminals though: [TerminalCursor](super::TerminalCursor),
+//! [TerminalClearColor](super::TerminalClearColor), [TerminalEffectChain](super::effects::TerminalEffectChain),
+//! [TerminalOwnedMaterial](super::TerminalOwnedMaterial) and the
+//! [TerminalAccumulationBuffer](super::TerminalAccumulationBuffer). For 3d
+//! terminals set the matching fields of the material directly.
+//!
+//! # Example
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_ascii_terminal::*;
+//! use bevy_ascii_terminal::renderer::renderer_3d::*;
+//!
+//! fn setup(
+//!     mut commands: Commands,
+//!     fonts: Res<BuiltInFontHandles>,
+//!     mut materials: ResMut<Assets<TerminalMaterial3d>>,
+//! ) {
+//!     let font = fonts.get("px437_8x8.png").unwrap().clone();
+//!     let mut bundle = Terminal3dBundle::new().with_size([20, 10]);
+//!     bundle.renderer.material = materials.add(TerminalMaterial::from(font).into());
+//!     // Each tile is one unit in size, so scale the screen down
+//!     bundle.transform = Transform::from_xyz(0.0, 1.0, -2.0).with_scale(Vec3::splat(0.05));
+//!     bundle.terminal.put_string([0, 9], "READY.");
+//!
+//!     commands.spawn_bundle(bundle);
+//! }
+//! ```
+
+use bevy::{
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    pbr::{AlphaMode, MaterialPipeline, MaterialPlugin, SpecializedMaterial},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        primitives::Aabb,
+        render_asset::{PrepareAssetError, RenderAsset, RenderAssets},
+        render_resource::{
+            BindGroup, BindGroupLayout, PrimitiveTopology, RenderPipelineDescriptor,
+            SpecializedMeshPipelineError,
+        },
+        renderer::RenderDevice,
+        RenderApp, RenderStage,
+    },
+};
+
+use crate::Terminal;
+
+use super::{
+    material::{
+        create_terminal_bind_group, terminal_bind_group_layout, terminal_vertex_layout,
+        write_terminal_material_time, GpuTerminalMaterial, TerminalMaterial,
+    },
+    partial_upload::{is_partial_upload, TerminalPartialUpload},
+    plugin::{
+        terminal_material_created, terminal_tile_size, write_mesh_tile_data, write_mesh_verts,
+    },
+    renderer_tile_data::TerminalRendererTileData,
+    renderer_vertex_data::TerminalRendererVertexData,
+    throttle::TerminalThrottledRenderer,
+    uv_mapping::UvMapping,
+    view::TerminalViewMatrix,
+    TerminalPivot, TilePivot, TileScaling, TERMINAL_INIT, TERMINAL_UPDATE_MESH,
+    TERMINAL_UPDATE_SIZE, TERMINAL_UPDATE_TILE_DATA,
+};
+
+/// The shader handle used by 3d terminals.
+pub const TERMINAL_MATERIAL_3D_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 5731869012876125480);
+
+/// A [TerminalMaterial] for terminals rendered in 3d.
+///
+/// Supports all the same settings as the 2d material, but see the
+/// [module docs](self) for the components which don't work with it.
+#[derive(Debug, Clone, Default, TypeUuid)]
+#[uuid = "0b6f2d1c-5e1a-4a9b-8f2e-6c3d7a4b9e10"]
+pub struct TerminalMaterial3d(pub TerminalMaterial);
+
+impl From<TerminalMaterial> for TerminalMaterial3d {
+    fn from(material: TerminalMaterial) -> Self {
+        TerminalMaterial3d(material)
+    }
+}
+
+impl RenderAsset for TerminalMaterial3d {
+    type ExtractedAsset = TerminalMaterial3d;
+    type PreparedAsset = GpuTerminalMaterial;
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<MaterialPipeline<TerminalMaterial3d>>,
+        SRes<RenderAssets<Image>>,
+    );
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, pipeline, gpu_images): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let (texture_view, sampler) = if let Some(result) = pipeline
+            .mesh_pipeline
+            .get_image_texture(gpu_images, &material.0.texture)
+        {
+            result
+        } else {
+            return Err(PrepareAssetError::RetryNextUpdate(material));
+        };
+        // Without a background texture the font texture is bound in it's place
+        let background = match &material.0.background_texture {
+            Some(_) => match pipeline
+                .mesh_pipeline
+                .get_image_texture(gpu_images, &material.0.background_texture)
+            {
+                Some(result) => result,
+                None => return Err(PrepareAssetError::RetryNextUpdate(material)),
+            },
+            None => (texture_view, sampler),
+        };
+        // Without a previous frame the font texture is bound in it's place
+        let accumulation = match &material.0.accumulation_texture {
+            Some(_) => match pipeline
+                .mesh_pipeline
+                .get_image_texture(gpu_images, &material.0.accumulation_texture)
+            {
+                Some(result) => result,
+                None => return Err(PrepareAssetError::RetryNextUpdate(material)),
+            },
+            None => (texture_view, sampler),
+        };
+
+        let (buffer, bind_group) = create_terminal_bind_group(
+            render_device,
+            &pipeline.material_layout,
+            &material.0,
+            (texture_view, sampler),
+            background,
+            accumulation,
+        );
+
+        Ok(GpuTerminalMaterial {
+            buffer,
+            bind_group,
+            flags: material.0.flags(),
+            texture: material.0.texture,
+        })
+    }
+}
+
+impl SpecializedMaterial for TerminalMaterial3d {
+    type Key = ();
+
+    fn key(_material: &<Self as RenderAsset>::PreparedAsset) -> Self::Key {}
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _key: Self::Key,
+        _layout: &MeshVertexBufferLayout,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.vertex.buffers = vec![terminal_vertex_layout()];
+        Ok(())
+    }
+
+    #[inline]
+    fn bind_group(material: &<Self as RenderAsset>::PreparedAsset) -> &BindGroup {
+        &material.bind_group
+    }
+
+    fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        terminal_bind_group_layout(render_device)
+    }
+
+    fn vertex_shader(_asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(TERMINAL_MATERIAL_3D_SHADER_HANDLE.typed())
+    }
+
+    fn fragment_shader(_asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(TERMINAL_MATERIAL_3D_SHADER_HANDLE.typed())
+    }
+
+    fn alpha_mode(_material: &<Self as RenderAsset>::PreparedAsset) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+/// A bundle of all the components required to render a terminal in 3d.
+///
+/// The 3d equivalent of [TerminalRendererBundle](super::TerminalRendererBundle).
+#[derive(Default, Bundle)]
+pub struct TerminalRenderer3dBundle {
+    pub vert_data: TerminalRendererVertexData,
+    pub tile_data: TerminalRendererTileData,
+    pub scaling: TileScaling,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<TerminalMaterial3d>,
+    pub uv_mapping: UvMapping,
+    pub terminal_pivot: TerminalPivot,
+    pub tile_pivot: TilePivot,
+    pub visibility: Visibility,
+    pub computed_visibility: ComputedVisibility,
+}
+
+impl TerminalRenderer3dBundle {
+    pub fn new() -> Self {
+        TerminalRenderer3dBundle::default()
+    }
+
+    /// Set the terminal pivot value.
+    ///
+    /// Terminal pivot determines where the origin of the terminal mesh sits, where
+    /// (0,0) is the bottom left. Defaults to centered (0.5,0.5).
+    pub fn with_terminal_pivot(mut self, x: f32, y: f32) -> Self {
+        self.terminal_pivot.0 = (x, y).into();
+        self
+    }
+
+    /// Set the tile pivot value.
+    ///
+    /// Tile pivot determines where the origin of a tile sits within the mesh, where
+    /// (0,0) is the bottom left. Defaults to bottom left (0,0).
+    pub fn with_tile_pivot(mut self, x: f32, y: f32) -> Self {
+        self.tile_pivot.0 = (x, y).into();
+        self
+    }
+
+    /// Sets the [TileScaling] for the terminal.
+    pub fn with_tile_scaling(mut self, scaling: TileScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+}
+
+/// A bundle with all the required components for a terminal rendered in 3d.
+#[derive(Bundle, Default)]
+pub struct Terminal3dBundle {
+    pub terminal: Terminal,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+
+    #[bundle]
+    pub renderer: TerminalRenderer3dBundle,
+}
+
+impl Terminal3dBundle {
+    pub fn new() -> Self {
+        Terminal3dBundle::default()
+    }
+
+    /// Set the initial size of the terminal.
+    pub fn with_size(mut self, size: [u32; 2]) -> Self {
+        self.terminal.resize(size);
+        self
+    }
+}
+
+/// Plugin for rendering terminals in 3d. Requires the [TerminalPlugin](crate::TerminalPlugin).
+///
+/// Tile data is shared with 2d terminals, only building and drawing the mesh is
+/// handled separately.
+pub struct TerminalRendering3dPlugin;
+
+impl Plugin for TerminalRendering3dPlugin {
+    fn build(&self, app: &mut App) {
+        let mut shaders = app.world.get_resource_mut::<Assets<Shader>>().unwrap();
+        shaders.set_untracked(
+            TERMINAL_MATERIAL_3D_SHADER_HANDLE,
+            Shader::from_wgsl(include_str!("terminal_3d.wgsl")),
+        );
+        app.add_plugin(MaterialPlugin::<TerminalMaterial3d>::default());
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            // The time itself is extracted by the 2d material plugin
+            render_app.add_system_to_stage(
+                RenderStage::Queue,
+                write_terminal_material_time::<TerminalMaterial3d>,
+            );
+        }
+
+        // Use the default 2d material, with the default font, as the default 3d material
+        let default_material = app
+            .world
+            .get_resource::<Assets<TerminalMaterial>>()
+            .and_then(|materials| materials.get(Handle::<TerminalMaterial>::default()))
+            .cloned()
+            .unwrap_or_default();
+        app.world
+            .get_resource_mut::<Assets<TerminalMaterial3d>>()
+            .unwrap()
+            .set_untracked(
+                Handle::<TerminalMaterial3d>::default(),
+                default_material.into(),
+            );
+
+        app.add_system(terminal_renderer_init_3d.label(TERMINAL_INIT))
+            .add_system(
+                terminal_material_created::<TerminalMaterial3d>.before(TERMINAL_UPDATE_SIZE),
+            )
+            .add_system(
+                terminal_renderer_update_size_3d
+                    .after(TERMINAL_INIT)
+                    .label(TERMINAL_UPDATE_SIZE),
+            )
+            .add_system(
+                terminal_renderer_update_mesh_3d
+                    .after(TERMINAL_UPDATE_TILE_DATA)
+                    .label(TERMINAL_UPDATE_MESH),
+            );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_renderer_init_3d(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q: Query<&mut Handle<Mesh>, (Added<Handle<Mesh>>, With<TerminalRendererVertexData>)>,
+) {
+    for mut mesh in q.iter_mut() {
+        *mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_renderer_update_size_3d(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+    materials: Res<Assets<TerminalMaterial3d>>,
+    mut q: Query<
+        (
+            Entity,
+            &Terminal,
+            &Handle<TerminalMaterial3d>,
+            &TileScaling,
+            &TerminalPivot,
+            &TilePivot,
+            &Handle<Mesh>,
+            &mut TerminalRendererVertexData,
+            &mut TerminalRendererTileData,
+            Option<&TerminalViewMatrix>,
+        ),
+        Or<(
+            Changed<Handle<Mesh>>,
+            Changed<TileScaling>,
+            Changed<Handle<TerminalMaterial3d>>,
+            Changed<TerminalViewMatrix>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        terminal,
+        material,
+        scaling,
+        term_pivot,
+        tile_pivot,
+        mesh,
+        mut vert_data,
+        mut tile_data,
+        view,
+    ) in q.iter_mut()
+    {
+        // Sized again by terminal_material_created once the material exists
+        let material = match materials.get(material) {
+            Some(material) => &material.0,
+            None => continue,
+        };
+        let tile_size = terminal_tile_size(material, scaling, &images);
+
+        let size = terminal.size();
+        vert_data.resize(size, term_pivot.0, tile_pivot.0, tile_size);
+        if let Some(view) = view {
+            view.apply(&mut vert_data);
+        }
+        tile_data.resize(size);
+
+        let mesh = meshes
+            .get_mut(mesh)
+            .expect("Error retrieving mesh from terminal renderer");
+        write_mesh_verts(mesh, &vert_data);
+        // Partial uploads skip the mesh, so it may not have the latest tile data
+        write_mesh_tile_data(mesh, &tile_data);
+
+        // Bevy only calculates bounds for meshes without them, force it to update
+        commands.entity(entity).remove::<Aabb>();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn terminal_renderer_update_mesh_3d(
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q: Query<
+        (
+            &TerminalRendererTileData,
+            ChangeTrackers<TerminalRendererTileData>,
+            &Handle<Mesh>,
+            Option<&mut TerminalThrottledRenderer>,
+            Option<&TerminalPartialUpload>,
+        ),
+        Or<(
+            Changed<TerminalRendererTileData>,
+            With<TerminalThrottledRenderer>,
+        )>,
+    >,
+) {
+    for (tile_data, tracker, mesh, mut throttle, partial) in q.iter_mut() {
+        if is_partial_upload(tile_data, partial, throttle.as_deref()) {
+            continue;
+        }
+        if let Some(throttle) = &mut throttle {
+            if !throttle.tick(time.delta_seconds(), tracker.is_changed()) {
+                continue;
+            }
+        }
+        let mesh = meshes.get_mut(mesh).expect("Error accessing terminal mesh");
+        write_mesh_tile_data(mesh, tile_data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::AssetPlugin, core::CorePlugin, input::InputPlugin,
+        render::mesh::VertexAttributeValues, sprite::ColorMaterial, window::WindowPlugin,
+    };
+
+    use super::*;
+    use crate::{
+        renderer::{material::TerminalMaterialFlags, plugin::ATTRIBUTE_COLOR_FG},
+        TerminalPlugin,
+    };
+
+    #[test]
+    fn render_3d() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(InputPlugin)
+            .add_asset::<Shader>()
+            .add_asset::<Mesh>()
+            .add_asset::<Image>()
+            .add_asset::<ColorMaterial>()
+            .add_plugin(TerminalPlugin::default())
+            .add_plugin(TerminalRendering3dPlugin);
+
+        let material = TerminalMaterial {
+            invert_colors: true,
+            ..Default::default()
+        };
+        let material = app
+            .world
+            .resource_mut::<Assets<TerminalMaterial3d>>()
+            .add(material.into());
+        let mut bundle = Terminal3dBundle::new().with_size([2, 1]);
+        bundle.renderer.material = material.clone();
+        bundle.terminal.put_tile(
+            [1, 0],
+            crate::Tile {
+                glyph: 'a',
+                fg_color: Color::RED,
+                ..Default::default()
+            },
+        );
+        let entity = app.world.spawn().insert_bundle(bundle).id();
+        app.update();
+        app.update();
+
+        // The mesh is built from the terminal's tiles
+        let mesh = app.world.get::<Handle<Mesh>>(entity).unwrap();
+        let mesh = app.world.resource::<Assets<Mesh>>().get(mesh).unwrap();
+        assert_eq!(Some(2 * 6), mesh.indices().map(|i| i.len()));
+        let fg = match mesh.attribute(ATTRIBUTE_COLOR_FG) {
+            Some(VertexAttributeValues::Float32x4(fg)) => fg,
+            _ => panic!("Missing foreground colors"),
+        };
+        assert_eq!(Color::WHITE.as_linear_rgba_f32(), fg[0]);
+        assert_eq!(Color::RED.as_linear_rgba_f32(), fg[4]);
+
+        // The material's settings reach the shader the same way as a 2d material's
+        let material = &app
+            .world
+            .resource::<Assets<TerminalMaterial3d>>()
+            .get(material)
+            .unwrap()
+            .0;
+        assert!(material
+            .flags()
+            .contains(TerminalMaterialFlags::INVERT_COLORS));
+    }
+
+    #[test]
+    fn bundle() {
+        let bundle = Terminal3dBundle::new().with_size([20, 10]);
+        assert_eq!(UVec2::new(20, 10), bundle.terminal.size());
+
+        let renderer = TerminalRenderer3dBundle::new()
+            .with_terminal_pivot(0.0, 1.0)
+            .with_tile_scaling(TileScaling::Pixels);
+        assert_eq!(Vec2::new(0.0, 1.0), renderer.terminal_pivot.0);
+        assert!(matches!(renderer.scaling, TileScaling::Pixels));
+    }
+}
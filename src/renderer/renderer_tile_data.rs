@@ -1,14 +1,67 @@
-use bevy::{math::UVec2, prelude::Component};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::terminal::Tile;
+use bevy::{
+    math::{UVec2, Vec2},
+    prelude::Component,
+    render::{render_resource::Buffer, renderer::RenderQueue},
+};
 
-use super::uv_mapping::UvMapping;
+use crate::{
+    terminal::{TerminalTileFlags, Tile},
+    TerminalTileComparator,
+};
+
+use super::{
+    font_cache::TerminalFontCache,
+    key_map::TerminalKeyMap,
+    plugin::{terminal_uv, terminal_uv_bounds},
+    renderer_vertex_data::TerminalRendererVertexData,
+    uv_mapping::UvMapping,
+};
+
+/// The background uv of tiles without a background sprite. The shader draws
+/// their background color instead.
+pub(crate) const NO_BG_UV: [f32; 2] = [-1.0, -1.0];
+
+/// If fewer than this fraction of tiles changed they will be written
+/// individually rather than rebuilding all tile data.
+pub(crate) const PARTIAL_UPDATE_THRESHOLD: f32 = 0.1;
+
+/// The size in bytes of a single vertex in a terminal mesh's vertex buffer.
+///
+/// Bevy interleaves mesh attributes in order of their id: position, uv,
+/// background color, foreground color, background uv and terminal uv.
+pub(crate) const VERTEX_STRIDE: u64 = 12 + VERTEX_TILE_DATA_SIZE as u64 + 8;
+/// The size of a vertex's uvs and colors, which follow it's position.
+const VERTEX_TILE_DATA_SIZE: usize = 8 + 16 + 16 + 8;
+
+/// The offset of a run of whole vertices in a vertex buffer, and their bytes.
+pub(crate) type VertexWrite = (u64, Vec<u8>);
 
 #[derive(Component, Default)]
 pub struct TerminalRendererTileData {
     pub fg_colors: Vec<[f32; 4]>,
     pub bg_colors: Vec<[f32; 4]>,
     pub uvs: Vec<[f32; 2]>,
+    /// Uvs into the material's background texture, or [NO_BG_UV] for tiles
+    /// without a background sprite.
+    pub bg_uvs: Vec<[f32; 2]>,
+    /// The tiles the data was last written from.
+    tiles: Vec<Tile>,
+    /// The tiles written by the last update, if it was a partial one.
+    partial: Option<Vec<(usize, Tile)>>,
+    /// Glyph uvs from the uv mapping the data is written with.
+    uv_cache: TerminalFontCache,
+    /// Remaps glyphs before their uvs are looked up.
+    key_map: TerminalKeyMap,
+    /// Whether the data was written from tiles this frame. Cleared by the
+    /// renderer, which only has shared access to terminals it doesn't update.
+    refreshed: AtomicBool,
+    /// Set by [TerminalRendererTileData::request_refresh].
+    refresh_requested: bool,
+    /// Whether the data may have been changed by pre-render systems since it
+    /// was last written.
+    modified: bool,
 }
 
 impl TerminalRendererTileData {
@@ -24,36 +77,255 @@ impl TerminalRendererTileData {
         self.fg_colors.resize(len * 4, Default::default());
         self.bg_colors.resize(len * 4, Default::default());
         self.uvs.resize(len * 4, Default::default());
+        self.bg_uvs.resize(len * 4, NO_BG_UV);
+    }
+
+    /// Clear the cached glyph uvs. This must be called before writing tiles
+    /// with a different or modified [UvMapping].
+    pub fn invalidate_uv_cache(&mut self) {
+        self.uv_cache.invalidate();
+    }
+
+    /// Set the [TerminalKeyMap] glyphs are remapped with. Tiles already
+    /// written aren't changed until they're written again.
+    pub fn set_key_map(&mut self, key_map: &TerminalKeyMap) {
+        self.key_map = *key_map;
+    }
+
+    /// The [TerminalKeyMap] glyphs are remapped with.
+    pub fn key_map(&self) -> &TerminalKeyMap {
+        &self.key_map
     }
 
     pub fn update_from_tiles(&mut self, tiles: &[Tile], uv_mapping: &UvMapping) {
         for (i, tile) in tiles.iter().enumerate() {
-            let glyph = tile.glyph;
+            self.write_tile(i, tile, uv_mapping);
+        }
+        self.tiles.clear();
+        self.tiles.extend_from_slice(tiles);
+        self.partial = None;
+        self.set_refreshed();
+    }
 
-            let vi = i * 4;
-            let uvs = &mut self.uvs;
+    /// Write only the given tiles, leaving the data for all other tiles unchanged.
+    pub fn regenerate_partial(&mut self, changed_tiles: &[(usize, Tile)], uv_mapping: &UvMapping) {
+        for (i, tile) in changed_tiles {
+            self.write_tile(*i, tile, uv_mapping);
+            if let Some(t) = self.tiles.get_mut(*i) {
+                *t = *tile;
+            }
+        }
+        self.partial = Some(changed_tiles.to_vec());
+        self.set_refreshed();
+    }
 
-            let glyph_uvs = uv_mapping.uvs_from_glyph(glyph);
+    /// Whether the data was written from the terminal's tiles this frame.
+    ///
+    /// Changes made by [pre-render systems](super::TerminalAppExt::add_terminal_pre_render_system)
+    /// are kept until the data is next written, so they should only apply
+    /// their effects when this is true.
+    pub fn is_refreshed(&self) -> bool {
+        self.refreshed.load(Ordering::Relaxed)
+    }
 
-            for (a, b) in uvs[vi..vi + 4].iter_mut().zip(glyph_uvs) {
-                *a = *b;
-            }
+    /// Write every tile from the terminal next frame, even if the terminal
+    /// hasn't changed.
+    ///
+    /// For pre-render systems whose effect has changed, so it can be applied
+    /// again to unmodified data.
+    pub fn request_refresh(&mut self) {
+        self.refresh_requested = true;
+    }
+
+    /// Whether [TerminalRendererTileData::request_refresh] was called since
+    /// the data was last written.
+    pub(crate) fn is_refresh_requested(&self) -> bool {
+        self.refresh_requested
+    }
+
+    /// Reset [TerminalRendererTileData::is_refreshed] at the start of a frame.
+    pub(crate) fn clear_refreshed(&self) {
+        self.refreshed.store(false, Ordering::Relaxed);
+    }
+
+    /// Record that the data may have been changed by pre-render systems, so
+    /// the next update must rewrite every tile.
+    pub(crate) fn set_modified(&mut self) {
+        self.modified = true;
+    }
+
+    /// Whether [TerminalRendererTileData::set_modified] was called since the
+    /// data was last written.
+    pub(crate) fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    fn set_refreshed(&mut self) {
+        *self.refreshed.get_mut() = true;
+        self.refresh_requested = false;
+        self.modified = false;
+    }
+
+    /// Mark every tile as written, so the whole mesh is rewritten rather than
+    /// only the tiles of a partial update.
+    pub fn mark_all_changed(&mut self) {
+        self.partial = None;
+    }
+
+    /// The tiles written by the last update, or `None` if every tile was written.
+    pub fn partial_update(&self) -> Option<&[(usize, Tile)]> {
+        self.partial.as_deref()
+    }
+
+    /// Write the data for the changed tiles directly into a terminal mesh's
+    /// vertex buffer on the gpu, leaving every other tile as it is.
+    ///
+    /// Each run of neighbouring changed tiles is written with a single
+    /// `write_buffer` call at it's offset in the buffer, without building the
+    /// rest of the buffer on the cpu. The tiles' vertices are written whole, so
+    /// `vertex_data` must be the data the mesh was built from.
+    pub fn upload_to_buffer(
+        &self,
+        queue: &RenderQueue,
+        buffer: &Buffer,
+        changed: &[(usize, Tile)],
+        vertex_data: &TerminalRendererVertexData,
+    ) {
+        for (offset, bytes) in self.vertex_writes(changed, vertex_data) {
+            queue.write_buffer(buffer, offset, &bytes);
+        }
+    }
+
+    /// The offset in a terminal mesh's vertex buffer and the bytes of the
+    /// vertices of each run of neighbouring changed tiles.
+    pub(crate) fn vertex_writes(
+        &self,
+        changed: &[(usize, Tile)],
+        vertex_data: &TerminalRendererVertexData,
+    ) -> Vec<VertexWrite> {
+        let bounds = terminal_uv_bounds(&vertex_data.verts);
+        let len = self.uvs.len().min(vertex_data.verts.len());
+        let mut writes = Vec::new();
+        let mut run: Option<(usize, usize)> = None;
+        for (i, _) in changed {
+            run = match run {
+                Some((start, end)) if end == *i => Some((start, end + 1)),
+                _ => {
+                    if let Some(run) = run {
+                        writes.push(self.run_write(run, vertex_data, bounds, len));
+                    }
+                    Some((*i, i + 1))
+                }
+            };
+        }
+        if let Some(run) = run {
+            writes.push(self.run_write(run, vertex_data, bounds, len));
+        }
+        writes
+    }
+
+    /// The write for the vertices of the tiles from `start` up to `end`.
+    fn run_write(
+        &self,
+        (start, end): (usize, usize),
+        vertex_data: &TerminalRendererVertexData,
+        bounds: (Vec2, Vec2),
+        len: usize,
+    ) -> VertexWrite {
+        let verts = (start * 4).min(len)..(end * 4).min(len);
+        let mut bytes = Vec::with_capacity(verts.len() * VERTEX_STRIDE as usize);
+        for vi in verts {
+            let vert = vertex_data.verts[vi];
+            bytes.extend(vert.iter().flat_map(|f| f.to_ne_bytes()));
+            bytes.extend_from_slice(&self.vertex_bytes(vi));
+            let uv = terminal_uv(vert, bounds);
+            bytes.extend(uv.iter().flat_map(|f| f.to_ne_bytes()));
+        }
+        ((start * 4) as u64 * VERTEX_STRIDE, bytes)
+    }
 
-            for j in vi..vi + 4 {
-                self.fg_colors[j] = tile.fg_color.as_linear_rgba_f32();
-                self.bg_colors[j] = tile.bg_color.as_linear_rgba_f32();
+    /// The uvs and colors of a single vertex as they're laid out in the vertex buffer.
+    fn vertex_bytes(&self, vi: usize) -> [u8; VERTEX_TILE_DATA_SIZE] {
+        let mut bytes = [0; VERTEX_TILE_DATA_SIZE];
+        let floats = self.uvs[vi]
+            .iter()
+            .chain(&self.bg_colors[vi])
+            .chain(&self.fg_colors[vi])
+            .chain(&self.bg_uvs[vi]);
+        for (dst, f) in bytes.chunks_exact_mut(4).zip(floats) {
+            dst.copy_from_slice(&f.to_ne_bytes());
+        }
+        bytes
+    }
+
+    /// The tiles which differ from the ones the data was last written from, or
+    /// `None` if the number of tiles has changed.
+    pub fn changed_tiles(&self, tiles: &[Tile]) -> Option<Vec<(usize, Tile)>> {
+        if tiles.len() != self.tiles.len() {
+            return None;
+        }
+        Some(
+            TerminalTileComparator::diff(&self.tiles, tiles)
+                .map(|(i, tile)| (i, *tile))
+                .collect(),
+        )
+    }
+
+    /// Write the data of the tile at index `i`, without recording it as one of
+    /// the tiles the data was written from.
+    pub fn write_tile(&mut self, i: usize, tile: &Tile, uv_mapping: &UvMapping) {
+        let vi = i * 4;
+        let glyph = self.key_map.map_glyph(tile.glyph);
+        let glyph_uvs = orient_uvs(&self.uv_cache.uvs(glyph, uv_mapping), tile.flags);
+        self.uvs[vi..vi + 4].copy_from_slice(&glyph_uvs);
+
+        self.fg_colors[vi..vi + 4].fill(tile.fg_color.as_linear_rgba_f32());
+        self.bg_colors[vi..vi + 4].fill(tile.bg_color.as_linear_rgba_f32());
+
+        match tile.bg_index {
+            Some(index) => {
+                let xy = [index as u32 % 16, index as u32 / 16];
+                let bg_uvs = UvMapping::get_grid_uvs(xy, [16, 16]);
+                self.bg_uvs[vi..vi + 4].copy_from_slice(&bg_uvs);
             }
+            None => self.bg_uvs[vi..vi + 4].fill(NO_BG_UV),
         }
     }
 }
+/// Reorder a tile's uvs to flip and rotate it's glyph.
+///
+/// Uvs are in the same order as a tile's verts: top left, bottom left, top right,
+/// bottom right.
+fn orient_uvs(uvs: &[[f32; 2]; 4], flags: TerminalTileFlags) -> [[f32; 2]; 4] {
+    let mut uvs = *uvs;
+    if flags.contains(TerminalTileFlags::FLIP_H) {
+        uvs = [uvs[2], uvs[3], uvs[0], uvs[1]];
+    }
+    if flags.contains(TerminalTileFlags::FLIP_V) {
+        uvs = [uvs[1], uvs[0], uvs[3], uvs[2]];
+    }
+    if flags.contains(TerminalTileFlags::ROTATE_90) {
+        uvs = [uvs[1], uvs[3], uvs[0], uvs[2]];
+    }
+    uvs
+}
 
 #[cfg(test)]
 mod tests {
     use bevy::math::UVec2;
     use bevy::prelude::Color;
 
+    use bevy::render::{mesh::Mesh, render_resource::PrimitiveTopology};
+
     use crate::renderer::uv_mapping::UvMapping;
-    use crate::{renderer::renderer_tile_data::TerminalRendererTileData, terminal::Tile};
+    use crate::{
+        renderer::{
+            plugin::{write_mesh_tile_data, write_mesh_verts},
+            renderer_tile_data::*,
+            renderer_vertex_data::TerminalRendererVertexData,
+        },
+        terminal::{TerminalTileFlags, Tile},
+    };
 
     #[test]
     fn resize_test() {
@@ -72,4 +344,185 @@ mod tests {
 
         assert_eq!([0.0, 0.0, 1.0, 1.0], colors.fg_colors[0]);
     }
+
+    #[test]
+    fn tile_flags() {
+        let mapping = UvMapping::default();
+        let [tl, bl, tr, br] = *mapping.uvs_from_glyph('a');
+        let uvs = |flags: TerminalTileFlags| {
+            let mut data = TerminalRendererTileData::with_size(UVec2::new(1, 1));
+            let tile = Tile {
+                glyph: 'a',
+                flags,
+                ..Default::default()
+            };
+            data.update_from_tiles(&[tile], &mapping);
+            data.uvs
+        };
+
+        assert_eq!(vec![tl, bl, tr, br], uvs(TerminalTileFlags::NONE));
+        assert_eq!(vec![tr, br, tl, bl], uvs(TerminalTileFlags::FLIP_H));
+        assert_eq!(vec![bl, tl, br, tr], uvs(TerminalTileFlags::FLIP_V));
+        assert_eq!(vec![bl, br, tl, tr], uvs(TerminalTileFlags::ROTATE_90));
+        // Flipping both ways is a 180 degree rotation
+        assert_eq!(
+            vec![br, tr, bl, tl],
+            uvs(TerminalTileFlags::FLIP_H | TerminalTileFlags::FLIP_V)
+        );
+    }
+
+    #[test]
+    fn partial_update() {
+        let mut tiles: Vec<Tile> = vec![Tile::default(); 16];
+        let mut data = TerminalRendererTileData::with_size(UVec2::new(4, 4));
+        data.update_from_tiles(&tiles, &UvMapping::default());
+
+        tiles[5].fg_color = Color::RED;
+        tiles[9].glyph = 'a';
+        let changed = data.changed_tiles(&tiles).unwrap();
+        assert_eq!(
+            vec![5, 9],
+            changed.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+
+        data.regenerate_partial(&changed, &UvMapping::default());
+        assert_eq!([1.0, 0.0, 0.0, 1.0], data.fg_colors[5 * 4 + 3]);
+        assert_eq!([1.0, 1.0, 1.0, 1.0], data.fg_colors[4 * 4]);
+        assert_eq!(
+            UvMapping::default().uvs_from_glyph('a'),
+            &data.uvs[9 * 4..9 * 4 + 4]
+        );
+        assert!(data.changed_tiles(&tiles).unwrap().is_empty());
+        assert!(data.changed_tiles(&tiles[..4]).is_none());
+        assert_eq!(Some(&changed[..]), data.partial_update());
+
+        data.update_from_tiles(&tiles, &UvMapping::default());
+        assert!(data.partial_update().is_none());
+    }
+
+    #[test]
+    fn vertex_layout() {
+        let size = UVec2::new(3, 2);
+        let mut tiles = vec![Tile::default(); 6];
+        tiles[4] = Tile {
+            glyph: 'a',
+            fg_color: Color::RED,
+            bg_color: Color::BLUE,
+            bg_index: Some(17),
+            ..Default::default()
+        };
+        let mut data = TerminalRendererTileData::with_size(size);
+        data.update_from_tiles(&tiles, &UvMapping::default());
+        assert_eq!(NO_BG_UV, data.bg_uvs[0]);
+        assert_eq!([1.0 / 16.0, 1.0 / 16.0], data.bg_uvs[4 * 4]);
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        write_mesh_verts(&mut mesh, &TerminalRendererVertexData::with_size(size));
+        write_mesh_tile_data(&mut mesh, &data);
+        let buffer = mesh.get_vertex_buffer_data();
+
+        // The bytes written for each vertex match bevy's own vertex buffer
+        assert_eq!(
+            VERTEX_STRIDE,
+            mesh.get_mesh_vertex_buffer_layout().layout().array_stride
+        );
+        for vi in 0..data.uvs.len() {
+            // After the vertex's position
+            let start = vi * VERTEX_STRIDE as usize + 12;
+            assert_eq!(
+                &buffer[start..start + VERTEX_TILE_DATA_SIZE],
+                &data.vertex_bytes(vi)
+            );
+        }
+
+        // Only the vertices of changed tiles are written for a partial update,
+        // with one write for each run of neighbouring tiles
+        let vertex_data = TerminalRendererVertexData::with_size(size);
+        let writes = data.vertex_writes(&[(4, tiles[4])], &vertex_data);
+        assert_eq!(1, writes.len());
+        assert_eq!(4 * VERTEX_STRIDE as usize, writes[0].1.len());
+        let changed = [(0, tiles[0]), (1, tiles[1]), (2, tiles[2]), (4, tiles[4])];
+        let writes = data.vertex_writes(&changed, &vertex_data);
+        assert_eq!(
+            vec![
+                (0, 12 * VERTEX_STRIDE as usize),
+                (16, 4 * VERTEX_STRIDE as usize)
+            ],
+            writes
+                .iter()
+                .map(|(offset, bytes)| ((*offset / VERTEX_STRIDE) as usize, bytes.len()))
+                .collect::<Vec<_>>()
+        );
+        for (offset, bytes) in writes {
+            let offset = offset as usize;
+            assert_eq!(&buffer[offset..offset + bytes.len()], &bytes);
+        }
+    }
+
+    /// Compares a partial upload to the full upload it replaces on a 200x60
+    /// terminal with 10% of it's tiles changed. Only the cpu side is timed,
+    /// the number of writes and bytes stand in for the cost on the gpu.
+    ///
+    /// Run with `cargo test --release upload_cost -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn upload_cost() {
+        use std::time::{Duration, Instant};
+
+        let size = UVec2::new(200, 60);
+        let count = (size.x * size.y) as usize;
+        let tiles = vec![Tile::default(); count];
+        let mut data = TerminalRendererTileData::with_size(size);
+        data.update_from_tiles(&tiles, &UvMapping::default());
+        let vertex_data = TerminalRendererVertexData::with_size(size);
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        write_mesh_verts(&mut mesh, &vertex_data);
+
+        fn median(mut f: impl FnMut() -> (usize, usize)) -> ((usize, usize), Duration) {
+            let mut times = Vec::new();
+            let mut result = (0, 0);
+            for _ in 0..51 {
+                let start = Instant::now();
+                result = f();
+                times.push(start.elapsed());
+            }
+            times.sort();
+            (result, times[times.len() / 2])
+        }
+
+        // What bevy does for a full upload: rebuild the mesh's tile data and
+        // interleave every vertex into a new buffer
+        let full = median(|| {
+            write_mesh_tile_data(&mut mesh, &data);
+            let bytes = mesh.get_vertex_buffer_data();
+            (1, bytes.len())
+        });
+        // Every tenth tile, the worst case for coalescing
+        let scattered: Vec<_> = (0..count).step_by(10).map(|i| (i, tiles[i])).collect();
+        // A 40x30 block of tiles, like a window drawn over the terminal
+        let block: Vec<_> = (0..30)
+            .flat_map(|y| (0..40).map(move |x| y * 200 + x))
+            .map(|i| (i, tiles[i]))
+            .collect();
+        let partial = |changed: &[(usize, Tile)]| {
+            median(|| {
+                let writes = data.vertex_writes(changed, &vertex_data);
+                (writes.len(), writes.iter().map(|(_, b)| b.len()).sum())
+            })
+        };
+        let scattered = partial(&scattered);
+        let block = partial(&block);
+
+        for (name, ((writes, bytes), time)) in
+            [("full", full), ("scattered", scattered), ("block", block)]
+        {
+            println!(
+                "{:>9}: {:>5} writes, {:>8} bytes, {:?}",
+                name, writes, bytes, time
+            );
+        }
+        assert_eq!(1200, scattered.0 .0);
+        assert_eq!(30, block.0 .0);
+        assert!(scattered.0 .1 * 10 == full.0 .1);
+    }
 }
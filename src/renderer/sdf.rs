@@ -0,0 +1,142 @@
+//! Converting bitmap fonts to signed distance fields.
+//!
+//! A signed distance field font stores, for every pixel, how far it is from the
+//! edge of it's glyph. Rendered with [TerminalMaterial::sdf_mode](super::material::TerminalMaterial::sdf_mode)
+//! the glyphs stay sharp no matter how far the terminal is scaled up.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, FilterMode, TextureDimension, TextureFormat},
+};
+
+use super::{
+    font_asset::{check_rgba, FontError},
+    material::font_tile_size,
+};
+
+/// Convert a code page 437 font texture to a signed distance field.
+///
+/// Pixels which don't match `clip_color` are treated as the inside of a glyph.
+/// Every glyph is converted separately so distances never bleed into neighbouring
+/// glyphs. `spread` is the distance in pixels at which the field reaches 0 or 1,
+/// the edge of a glyph sits at 0.5.
+///
+/// The distance is written to the red, green and blue channels of a linear,
+/// bilinearly filtered texture.
+pub fn font_to_sdf(image: &Image, clip_color: Color, spread: f32) -> Result<Image, FontError> {
+    glyphs_to_sdf(image, font_tile_size(image), clip_color, spread)
+}
+
+/// Convert a font texture made of `tile_size` glyphs to a signed distance field.
+/// See [font_to_sdf].
+pub(crate) fn glyphs_to_sdf(
+    image: &Image,
+    tile_size: UVec2,
+    clip_color: Color,
+    spread: f32,
+) -> Result<Image, FontError> {
+    check_rgba(image)?;
+    let size = image.texture_descriptor.size;
+
+    let tile_size = tile_size.max(UVec2::ONE);
+    let clip = clip_color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    let width = size.width as usize;
+    // Matches the clip test in the terminal shader
+    let inside = |x: usize, y: usize| {
+        let i = (y * width + x) * 4;
+        image.data[i..i + 3].iter().zip(clip).any(|(p, c)| *p > c)
+    };
+
+    let spread = spread.max(1.0);
+    let reach = spread.ceil() as i32;
+    let mut data = vec![255; image.data.len()];
+    for y in 0..size.height as usize {
+        for x in 0..width {
+            let tile_min = (UVec2::new(x as u32, y as u32) / tile_size) * tile_size;
+            let tile_max = tile_min + tile_size - UVec2::ONE;
+            let is_inside = inside(x, y);
+
+            // Find the nearest pixel on the other side of the glyph's edge
+            let mut nearest = f32::MAX;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let (ox, oy) = (x as i32 + dx, y as i32 + dy);
+                    if ox < tile_min.x as i32
+                        || oy < tile_min.y as i32
+                        || ox > tile_max.x as i32
+                        || oy > tile_max.y as i32
+                    {
+                        continue;
+                    }
+                    if inside(ox as usize, oy as usize) != is_inside {
+                        nearest = nearest.min((dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+
+            // The edge lies halfway between the two pixel centers
+            let distance = (nearest.sqrt() - 0.5).clamp(0.0, spread);
+            let signed = if is_inside { distance } else { -distance };
+            let value = (0.5 + signed / (2.0 * spread)).clamp(0.0, 1.0);
+            let byte = (value * 255.0).round() as u8;
+            let i = (y * width + x) * 4;
+            data[i..i + 3].fill(byte);
+        }
+    }
+
+    let mut sdf = Image::new(
+        Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+    );
+    sdf.sampler_descriptor.mag_filter = FilterMode::Linear;
+    sdf.sampler_descriptor.min_filter = FilterMode::Linear;
+    Ok(sdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_distances() {
+        // 4x4 pixel glyphs
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        // Fill the left half of the second glyph
+        for y in 0..4 {
+            for x in 4..6 {
+                let i = (y * 64 + x) * 4;
+                image.data[i..i + 4].copy_from_slice(&[255; 4]);
+            }
+        }
+
+        let sdf = font_to_sdf(&image, Color::BLACK, 2.0).unwrap();
+        assert_eq!(image.texture_descriptor.size, sdf.texture_descriptor.size);
+
+        let value = |x: usize, y: usize| sdf.data[(y * 64 + x) * 4];
+        // Half a pixel from the edge on either side
+        assert_eq!(159, value(5, 1));
+        assert_eq!(96, value(6, 1));
+        assert_eq!(32, value(7, 1));
+        assert_eq!(223, value(4, 1));
+        // The empty first glyph doesn't see it's neighbour
+        assert_eq!(0, value(3, 1));
+        assert!(sdf.data.chunks(4).all(|p| p[3] == 255));
+    }
+}
@@ -0,0 +1,28 @@
+#import bevy_pbr::mesh_view_bind_group
+#import bevy_pbr::mesh_struct
+#import bevy_ascii_terminal::terminal_common
+
+[[group(2), binding(0)]]
+var<uniform> mesh: Mesh;
+
+/// Entry point for the vertex shader
+[[stage(vertex)]]
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    var world_position = mesh.model * vec4<f32>(vertex.position, 1.0);
+    out.world_position = world_position;
+    // Project the world position of the mesh into screen position
+    out.clip_position = view.view_proj * world_position;
+    out.uv = vertex.uv;
+    out.fg_color = vertex.fg_color;
+    out.bg_color = vertex.bg_color;
+    out.local_position = vertex.position.xy;
+    out.bg_uv = vertex.bg_uv;
+    out.terminal_uv = vertex.terminal_uv;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fragment(in: FragmentInput) -> [[location(0)]] vec4<f32> {
+    return terminal_fragment(in);
+}
@@ -0,0 +1,382 @@
+#define_import_path bevy_ascii_terminal::terminal_common
+
+// Shared by the 2d and 3d terminal shaders. The importing shader must declare
+// the 'view' uniform before importing it.
+struct Wave {
+    // Seconds since startup
+    time: f32;
+    distortion: f32;
+    frequency: f32;
+};
+
+struct Mask {
+    center: vec2<f32>;
+    half_extents: vec2<f32>;
+    feather: f32;
+};
+
+// Offsets in uvs
+struct ChromaticAberration {
+    r_offset: vec2<f32>;
+    g_offset: vec2<f32>;
+    b_offset: vec2<f32>;
+};
+
+struct Effects {
+    [[size(16)]] wave: Wave;
+    [[size(32)]] mask: Mask;
+    accumulation_strength: f32;
+    [[align(16), size(32)]] chromatic_aberration: ChromaticAberration;
+};
+
+struct ColorCorrection {
+    gamma: f32;
+    contrast: f32;
+    saturation: f32;
+};
+
+// The rust side is laid out with std140 rules, which round the alignment and
+// size of nested structs up to 16 bytes. Naga doesn't, so every nested struct
+// is given it's std140 alignment and size here.
+struct TerminalMaterial {
+    clip_color: vec4<f32>;
+    clear_color: vec4<f32>;
+    outline_color: vec4<f32>;
+    // 'flags' is a bit field indicating various options. u32 is 32 bits so we have up to 32 options.
+    flags: u32;
+    dithering_strength: f32;
+    scanline_strength: f32;
+    noise_strength: f32;
+    noise_seed: f32;
+    vignette_radius: f32;
+    [[align(16), size(16)]] color_correction: ColorCorrection;
+    sdf_threshold: f32;
+    sdf_smoothing: f32;
+    pixelation_size: f32;
+    cursor_color: vec4<f32>;
+    // x, y, width, height in the mesh's local space
+    cursor_rect: vec4<f32>;
+    glow_color: vec4<f32>;
+    glow_threshold: f32;
+    [[align(16)]] effects: Effects;
+};
+let TERMINAL_MATERIAL_FLAGS_TEXTURE_BIT: u32 = 1u;
+let TERMINAL_MATERIAL_FLAGS_UPSCALE_INTEGER_BIT: u32 = 2u;
+let TERMINAL_MATERIAL_FLAGS_UPSCALE_SHARP_BILINEAR_BIT: u32 = 4u;
+let TERMINAL_MATERIAL_FLAGS_CLEAR_COLOR_BIT: u32 = 8u;
+let TERMINAL_MATERIAL_FLAGS_OUTLINE_BIT: u32 = 16u;
+let TERMINAL_MATERIAL_FLAGS_INVERT_COLORS_BIT: u32 = 32u;
+let TERMINAL_MATERIAL_FLAGS_DITHERING_BIT: u32 = 64u;
+let TERMINAL_MATERIAL_FLAGS_SCANLINES_BIT: u32 = 128u;
+let TERMINAL_MATERIAL_FLAGS_NOISE_BIT: u32 = 256u;
+let TERMINAL_MATERIAL_FLAGS_VIGNETTE_BIT: u32 = 512u;
+let TERMINAL_MATERIAL_FLAGS_CHROMATIC_ABERRATION_BIT: u32 = 1024u;
+let TERMINAL_MATERIAL_FLAGS_COLOR_CORRECTION_BIT: u32 = 2048u;
+let TERMINAL_MATERIAL_FLAGS_SDF_MODE_BIT: u32 = 4096u;
+let TERMINAL_MATERIAL_FLAGS_PIXELATION_BIT: u32 = 8192u;
+let TERMINAL_MATERIAL_FLAGS_CURSOR_BIT: u32 = 16384u;
+let TERMINAL_MATERIAL_FLAGS_BACKGROUND_TEXTURE_BIT: u32 = 32768u;
+let TERMINAL_MATERIAL_FLAGS_GLOW_BIT: u32 = 65536u;
+let TERMINAL_MATERIAL_FLAGS_WAVE_DISTORTION_BIT: u32 = 131072u;
+let TERMINAL_MATERIAL_FLAGS_MOSAIC_BIT: u32 = 262144u;
+let TERMINAL_MATERIAL_FLAGS_CIRCULAR_MASK_BIT: u32 = 524288u;
+let TERMINAL_MATERIAL_FLAGS_ACCUMULATION_BIT: u32 = 1048576u;
+
+// 4x4 bayer matrix, row major
+let BAYER_4X4: array<f32, 16> = array<f32, 16>(
+    0.0, 8.0, 2.0, 10.0,
+    12.0, 4.0, 14.0, 6.0,
+    3.0, 11.0, 1.0, 9.0,
+    15.0, 7.0, 13.0, 5.0
+);
+// The number of levels each color channel is reduced to when dithering
+let DITHER_LEVELS: f32 = 8.0;
+
+[[group(1), binding(0)]]
+var<uniform> material: TerminalMaterial;
+[[group(1), binding(1)]]
+var texture: texture_2d<f32>;
+[[group(1), binding(2)]]
+var texture_sampler: sampler;
+// The font texture if the material has no background texture
+[[group(1), binding(3)]]
+var background_texture: texture_2d<f32>;
+[[group(1), binding(4)]]
+var background_sampler: sampler;
+// The previous frame, or the font texture if the material doesn't accumulate
+[[group(1), binding(5)]]
+var accumulation_texture: texture_2d<f32>;
+[[group(1), binding(6)]]
+var accumulation_sampler: sampler;
+
+struct Vertex {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] bg_color: vec4<f32>;
+    [[location(3)]] fg_color: vec4<f32>;
+    [[location(4)]] bg_uv: vec2<f32>;
+    [[location(5)]] terminal_uv: vec2<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] world_position: vec4<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] bg_color: vec4<f32>;
+    [[location(3)]] fg_color: vec4<f32>;
+    [[location(4)]] local_position: vec2<f32>;
+    [[location(5)]] bg_uv: vec2<f32>;
+    [[location(6)]] terminal_uv: vec2<f32>;
+};
+
+struct FragmentInput {
+    [[builtin(position)]] frag_coord: vec4<f32>;
+    [[builtin(front_facing)]] is_front: bool;
+    [[location(0)]] world_position: vec4<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] bg_color: vec4<f32>;
+    [[location(3)]] fg_color: vec4<f32>;
+    [[location(4)]] local_position: vec2<f32>;
+    [[location(5)]] bg_uv: vec2<f32>;
+    [[location(6)]] terminal_uv: vec2<f32>;
+};
+
+// Snap uvs to the corner of the pixelation block containing them.
+fn pixelate_uv(uv: vec2<f32>) -> vec2<f32> {
+    let tex_size = vec2<f32>(textureDimensions(texture));
+    let size = material.pixelation_size;
+    return floor(uv * tex_size / size) * size / tex_size;
+}
+
+// Adjust uvs according to the material's upscale mode.
+fn upscale_uv(uv: vec2<f32>) -> vec2<f32> {
+    let tex_size = vec2<f32>(textureDimensions(texture));
+    let texel = uv * tex_size;
+
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_UPSCALE_INTEGER_BIT) != 0u) {
+        return (floor(texel) + 0.5) / tex_size;
+    }
+
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_UPSCALE_SHARP_BILINEAR_BIT) != 0u) {
+        // Only filter within one screen pixel of a texel edge
+        let texels_per_pixel = max(fwidth(texel), vec2<f32>(0.0001, 0.0001));
+        let seam = floor(texel + 0.5);
+        let offset = clamp((texel - seam) / texels_per_pixel, vec2<f32>(-0.5, -0.5), vec2<f32>(0.5, 0.5));
+        return (seam + offset) / tex_size;
+    }
+
+    return uv;
+}
+
+fn is_clipped(color: vec4<f32>) -> bool {
+    return all(color.rgb - material.clip_color.rgb < vec3<f32>(0.001, 0.001, 0.001));
+}
+
+// Clamp 'sample_uv' to the texel centers of the glyph containing 'uv' in the 16x16 sheet.
+fn clamp_to_glyph(uv: vec2<f32>, sample_uv: vec2<f32>) -> vec2<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(texture));
+    let cell_size = vec2<f32>(1.0 / 16.0, 1.0 / 16.0);
+    let cell_min = floor(uv / cell_size) * cell_size + texel * 0.5;
+    let cell_max = cell_min + cell_size - texel;
+    return clamp(sample_uv, cell_min, cell_max);
+}
+
+// True if any texel adjacent to 'uv' within the same glyph is a foreground texel.
+fn is_outline(uv: vec2<f32>) -> bool {
+    let texel = 1.0 / vec2<f32>(textureDimensions(texture));
+
+    let left = clamp_to_glyph(uv, uv - vec2<f32>(texel.x, 0.0));
+    let right = clamp_to_glyph(uv, uv + vec2<f32>(texel.x, 0.0));
+    let down = clamp_to_glyph(uv, uv - vec2<f32>(0.0, texel.y));
+    let up = clamp_to_glyph(uv, uv + vec2<f32>(0.0, texel.y));
+
+    return !is_clipped(textureSampleLevel(texture, texture_sampler, left, 0.0))
+        || !is_clipped(textureSampleLevel(texture, texture_sampler, right, 0.0))
+        || !is_clipped(textureSampleLevel(texture, texture_sampler, down, 0.0))
+        || !is_clipped(textureSampleLevel(texture, texture_sampler, up, 0.0));
+}
+
+// A halo around glyphs with a foreground color brighter than the glow threshold,
+// from a 3x3 box blur of the glyph's foreground texels.
+fn glow(uv: vec2<f32>, fg_color: vec4<f32>) -> vec3<f32> {
+    let luminance = dot(fg_color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    if (luminance <= material.glow_threshold) {
+        return vec3<f32>(0.0, 0.0, 0.0);
+    }
+    let texel = 1.0 / vec2<f32>(textureDimensions(texture));
+    var coverage = 0.0;
+    for (var y: i32 = -1; y <= 1; y = y + 1) {
+        for (var x: i32 = -1; x <= 1; x = x + 1) {
+            let sample_uv = clamp_to_glyph(uv, uv + vec2<f32>(f32(x), f32(y)) * texel);
+            if (!is_clipped(textureSampleLevel(texture, texture_sampler, sample_uv, 0.0))) {
+                coverage = coverage + 1.0;
+            }
+        }
+    }
+    return material.glow_color.rgb * material.glow_color.a * coverage / 9.0;
+}
+
+// Quantize a color using ordered dithering based on it's screen position.
+fn dither(color: vec3<f32>, frag_coord: vec2<f32>) -> vec3<f32> {
+    var bayer = BAYER_4X4;
+    let xy = vec2<u32>(frag_coord) % vec2<u32>(4u, 4u);
+    // Threshold in the range [-0.5, 0.5)
+    let threshold = (bayer[xy.y * 4u + xy.x] + 0.5) / 16.0 - 0.5;
+    let steps = DITHER_LEVELS - 1.0;
+    let offset = threshold * material.dithering_strength;
+    let quantized = floor(color * steps + 0.5 + vec3<f32>(offset, offset, offset)) / steps;
+    return clamp(quantized, vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0));
+}
+
+// A pseudo random value in [0, 1) for a screen position.
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+// Invert a color's rgb, leaving alpha as is.
+fn invert_color(color: vec4<f32>) -> vec4<f32> {
+    let inverted = clamp(vec3<f32>(1.0, 1.0, 1.0) - color.rgb, vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0));
+    return vec4<f32>(inverted, color.a);
+}
+
+// Apply the material's gamma, contrast and saturation adjustments.
+fn color_correct(color: vec3<f32>) -> vec3<f32> {
+    let cc = material.color_correction;
+    let g = 1.0 / cc.gamma;
+    var rgb = pow(max(color, vec3<f32>(0.0, 0.0, 0.0)), vec3<f32>(g, g, g));
+    rgb = (rgb - vec3<f32>(0.5, 0.5, 0.5)) * cc.contrast + vec3<f32>(0.5, 0.5, 0.5);
+    let luminance = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    rgb = mix(vec3<f32>(luminance, luminance, luminance), rgb, cc.saturation);
+    return clamp(rgb, vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0));
+}
+
+// A tile's background color, blended over the clear color if there is one.
+fn background_color(bg_color: vec4<f32>) -> vec4<f32> {
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_CLEAR_COLOR_BIT) != 0u) {
+        let clear = material.clear_color;
+        let a = bg_color.a + clear.a * (1.0 - bg_color.a);
+        return vec4<f32>(mix(clear.rgb, bg_color.rgb, bg_color.a), a);
+    }
+    return bg_color;
+}
+
+// The color of a tile with the given colors at 'uv' in the font texture.
+fn tile_color(uv: vec2<f32>, fg_color: vec4<f32>, bg_color: vec4<f32>) -> vec4<f32> {
+    var clip_color: vec4<f32> = material.clip_color;
+    var out_color = fg_color;
+
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_TEXTURE_BIT) != 0u) {
+        var tex_color = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+
+        if ((material.flags & TERMINAL_MATERIAL_FLAGS_SDF_MODE_BIT) != 0u) {
+            // The red channel holds the distance to the glyph's edge
+            let edge0 = material.sdf_threshold - material.sdf_smoothing;
+            let edge1 = material.sdf_threshold + material.sdf_smoothing;
+            let t = clamp((tex_color.r - edge0) / max(edge1 - edge0, 0.0001), 0.0, 1.0);
+            let coverage = t * t * (3.0 - 2.0 * t);
+            return mix(background_color(bg_color), fg_color, coverage);
+        }
+
+        let tex_rgb = vec3<f32>(tex_color.rgb);
+        let clip_rgb = vec3<f32>(clip_color.rgb);
+        
+        if( all(tex_rgb - clip_rgb < vec3<f32>(0.001, 0.001, 0.001)) ) {
+            out_color = background_color(bg_color);
+            if ((material.flags & TERMINAL_MATERIAL_FLAGS_OUTLINE_BIT) != 0u && is_outline(uv)) {
+                out_color = material.outline_color;
+            }
+        } else {
+            out_color = vec4<f32>(tex_color.rgb * fg_color.rgb, fg_color.a);
+        }
+    }
+    return out_color;
+}
+
+// The final color of a terminal pixel.
+fn terminal_fragment(in: FragmentInput) -> vec4<f32> {
+    var mask_alpha = 1.0;
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_CIRCULAR_MASK_BIT) != 0u) {
+        let half_extents = max(material.effects.mask.half_extents, vec2<f32>(0.00001, 0.00001));
+        let d = length((in.terminal_uv - material.effects.mask.center) / half_extents);
+        // The distance inside the edge of the mask, in terminal uvs
+        let edge = (1.0 - d) * min(half_extents.x, half_extents.y);
+        if (edge <= 0.0) {
+            discard;
+        }
+        mask_alpha = clamp(edge / max(material.effects.mask.feather, 0.00001), 0.0, 1.0);
+    }
+    var uv = upscale_uv(in.uv);
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_PIXELATION_BIT) != 0u) {
+        uv = pixelate_uv(in.uv);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_WAVE_DISTORTION_BIT) != 0u) {
+        let w = material.effects.wave;
+        let offset = sin(in.local_position.y * w.frequency + w.time) * w.distortion;
+        uv = clamp_to_glyph(uv, uv + vec2<f32>(offset, 0.0));
+    }
+    // Tiles without a background sprite have negative background uvs
+    var bg_color = in.bg_color;
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_BACKGROUND_TEXTURE_BIT) != 0u && in.bg_uv.x >= 0.0) {
+        bg_color = textureSampleLevel(background_texture, background_sampler, in.bg_uv, 0.0);
+    }
+    var out_color = tile_color(uv, in.fg_color, bg_color);
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_MOSAIC_BIT) != 0u) {
+        // Each tile's colors were replaced with it's block's average color
+        out_color = in.fg_color;
+    }
+
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_CHROMATIC_ABERRATION_BIT) != 0u) {
+        let ca = material.effects.chromatic_aberration;
+        let r = tile_color(clamp_to_glyph(uv, uv + ca.r_offset), in.fg_color, bg_color).r;
+        let g = tile_color(clamp_to_glyph(uv, uv + ca.g_offset), in.fg_color, bg_color).g;
+        let b = tile_color(clamp_to_glyph(uv, uv + ca.b_offset), in.fg_color, bg_color).b;
+        out_color = vec4<f32>(r, g, b, out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_GLOW_BIT) != 0u
+        && (material.flags & TERMINAL_MATERIAL_FLAGS_TEXTURE_BIT) != 0u) {
+        let rgb = out_color.rgb + glow(uv, in.fg_color);
+        out_color = vec4<f32>(min(rgb, vec3<f32>(1.0, 1.0, 1.0)), out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_CURSOR_BIT) != 0u) {
+        let rect_min = material.cursor_rect.xy;
+        let rect_max = rect_min + material.cursor_rect.zw;
+        if (all(in.local_position >= rect_min) && all(in.local_position < rect_max)) {
+            out_color = material.cursor_color;
+        }
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_INVERT_COLORS_BIT) != 0u) {
+        out_color = invert_color(out_color);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_SCANLINES_BIT) != 0u) {
+        if (u32(in.frag_coord.y) % 2u == 1u) {
+            out_color = vec4<f32>(out_color.rgb * (1.0 - material.scanline_strength), out_color.a);
+        }
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_NOISE_BIT) != 0u) {
+        let n = hash(in.frag_coord.xy + vec2<f32>(material.noise_seed, material.noise_seed)) - 0.5;
+        let rgb = out_color.rgb + vec3<f32>(n, n, n) * material.noise_strength;
+        out_color = vec4<f32>(clamp(rgb, vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0)), out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_VIGNETTE_BIT) != 0u) {
+        // 0 at the center of the screen, 1 at the middle of each edge
+        let screen_uv = in.frag_coord.xy / vec2<f32>(view.width, view.height);
+        let d = length(screen_uv - vec2<f32>(0.5, 0.5)) * 2.0;
+        let t = clamp((d - material.vignette_radius) / 0.5, 0.0, 1.0);
+        let darken = t * t * (3.0 - 2.0 * t);
+        out_color = vec4<f32>(out_color.rgb * (1.0 - darken), out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_COLOR_CORRECTION_BIT) != 0u) {
+        out_color = vec4<f32>(color_correct(out_color.rgb), out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_DITHERING_BIT) != 0u) {
+        out_color = vec4<f32>(dither(out_color.rgb, in.frag_coord.xy), out_color.a);
+    }
+    if ((material.flags & TERMINAL_MATERIAL_FLAGS_ACCUMULATION_BIT) != 0u) {
+        // The previous frame at the same screen position
+        let size = vec2<f32>(textureDimensions(accumulation_texture));
+        let previous = textureSampleLevel(accumulation_texture, accumulation_sampler, in.frag_coord.xy / size, 0.0);
+        let strength = material.effects.accumulation_strength;
+        out_color = out_color * (1.0 - strength) + previous * strength;
+    }
+    return vec4<f32>(out_color.rgb, out_color.a * mask_alpha);
+}
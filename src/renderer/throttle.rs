@@ -0,0 +1,90 @@
+//! A terminal component for limiting how often a terminal's mesh is updated.
+//!
+//! Writing to the terminal is never throttled, only uploading the changes to
+//! the terminal's mesh. Changes made between uploads are never lost, they're
+//! uploaded together once enough time has passed.
+
+use bevy::prelude::*;
+
+/// Terminal component which limits how many times per second the terminal's
+/// mesh is updated, to reduce gpu bandwidth for terminals which don't need to
+/// update every frame.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::TerminalThrottledRenderer;
+///
+/// fn setup(mut commands: Commands) {
+///     // A status display which only needs to update twice per second
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 3]))
+///         .insert(TerminalThrottledRenderer::new(2.0));
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TerminalThrottledRenderer {
+    /// The maximum number of mesh updates per second. 0 disables throttling.
+    pub max_fps: f32,
+    /// Time in seconds since the last mesh update.
+    pub accumulator: f32,
+    /// True if there are changes which haven't been uploaded yet.
+    pending: bool,
+}
+
+impl TerminalThrottledRenderer {
+    pub fn new(max_fps: f32) -> Self {
+        Self {
+            max_fps,
+            ..Default::default()
+        }
+    }
+
+    /// Advance the throttle by `delta` seconds. Returns true if the mesh should
+    /// be updated this frame.
+    pub(crate) fn tick(&mut self, delta: f32, changed: bool) -> bool {
+        if self.max_fps <= 0.0 {
+            return changed;
+        }
+
+        let interval = 1.0 / self.max_fps;
+        // Clamped so a long idle period doesn't let a burst of updates through
+        self.accumulator = (self.accumulator + delta).min(interval);
+        self.pending |= changed;
+        if !self.pending || self.accumulator < interval {
+            return false;
+        }
+
+        self.accumulator = 0.0;
+        self.pending = false;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttled() {
+        let mut throttle = TerminalThrottledRenderer::new(2.0);
+        assert!(!throttle.tick(0.25, true));
+        // The change is still pending
+        assert!(throttle.tick(0.25, false));
+        assert!(!throttle.tick(0.25, false));
+        assert!(!throttle.tick(0.25, false));
+        // Enough time has passed, but nothing changed
+        assert!(!throttle.tick(0.25, false));
+        assert!(throttle.tick(0.0, true));
+    }
+
+    #[test]
+    fn unthrottled() {
+        let mut throttle = TerminalThrottledRenderer::new(0.0);
+        assert!(throttle.tick(0.0, true));
+        assert!(throttle.tick(0.0, true));
+        assert!(!throttle.tick(1.0, false));
+    }
+}
@@ -0,0 +1,182 @@
+//! Discovery of user fonts placed in the asset directory.
+//!
+//! When [TerminalPlugin::with_asset_directory](crate::TerminalPlugin::with_asset_directory)
+//! is set every image in that directory is loaded on startup and registered in
+//! [BuiltInFontHandles] by it's file name once it has finished loading.
+//!
+//! Fonts with the `.font.png` extension are loaded as a [TerminalFontAsset], so their
+//! metadata is used to determine their tile size.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use super::{
+    font_asset::TerminalFontAsset,
+    material::{font_tile_size, BuiltInFontHandles},
+};
+
+/// The directory user fonts are loaded from, relative to the bevy asset folder.
+pub(crate) struct TerminalFontDirectory(pub &'static str);
+
+/// Keeps user fonts loaded so they can be retrieved from [BuiltInFontHandles].
+#[derive(Default)]
+struct UserFontHandles(Vec<HandleUntyped>);
+
+pub(crate) struct UserFontPlugin {
+    pub directory: &'static str,
+}
+
+impl Plugin for UserFontPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TerminalFontDirectory(self.directory))
+            .init_resource::<UserFontHandles>()
+            .add_startup_system(terminal_load_fonts)
+            .add_system(terminal_register_fonts)
+            .add_system(terminal_register_font_assets);
+    }
+}
+
+fn terminal_load_fonts(
+    dir: Res<TerminalFontDirectory>,
+    asset_server: Res<AssetServer>,
+    mut user_fonts: ResMut<UserFontHandles>,
+) {
+    match asset_server.load_folder(dir.0) {
+        Ok(handles) => user_fonts.0 = handles,
+        Err(e) => warn!("Unable to load terminal fonts from '{}': {:?}", dir.0, e),
+    }
+}
+
+fn terminal_register_fonts(
+    mut events: EventReader<AssetEvent<Image>>,
+    user_fonts: Res<UserFontHandles>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut fonts: ResMut<BuiltInFontHandles>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } => handle,
+            _ => continue,
+        };
+
+        let handle = match user_fonts.0.iter().find(|h| h.id == handle.id) {
+            Some(handle) => handle.clone().typed::<Image>(),
+            None => continue,
+        };
+
+        let name = asset_server
+            .get_handle_path(&handle)
+            .and_then(|path| font_name(path.path()));
+
+        if let (Some(name), Some(image)) = (name, images.get(&handle)) {
+            let tile_size = font_tile_size(image);
+            fonts.insert(&name, handle, tile_size);
+        }
+    }
+}
+
+fn terminal_register_font_assets(
+    mut events: EventReader<AssetEvent<TerminalFontAsset>>,
+    user_fonts: Res<UserFontHandles>,
+    asset_server: Res<AssetServer>,
+    font_assets: Res<Assets<TerminalFontAsset>>,
+    mut fonts: ResMut<BuiltInFontHandles>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } => handle,
+            _ => continue,
+        };
+
+        if !user_fonts.0.iter().any(|h| h.id == handle.id) {
+            continue;
+        }
+
+        let name = asset_server
+            .get_handle_path(handle)
+            .and_then(|path| font_name(path.path()));
+
+        if let (Some(name), Some(font)) = (name, font_assets.get(handle)) {
+            fonts.insert(&name, font.image.clone(), font.tile_size);
+        }
+    }
+}
+
+/// The name a user font is registered under, which is it's file name.
+fn font_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        asset::{AssetPlugin, AssetServerSettings},
+        core::CorePlugin,
+        render::texture::ImagePlugin,
+    };
+
+    use super::*;
+    use crate::renderer::font_asset::TerminalFontLoader;
+
+    #[test]
+    fn discover_fonts() {
+        let root = std::env::temp_dir().join("bevy_ascii_terminal_user_fonts");
+        let dir = root.join("fonts");
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = include_bytes!("builtin/taffer_10x10.png");
+        std::fs::write(dir.join("my_font.png"), image).unwrap();
+        std::fs::write(dir.join("wide.font.png"), image).unwrap();
+        std::fs::write(dir.join("wide.json"), r#"{ "tile_count": [8, 32] }"#).unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(AssetServerSettings {
+            asset_folder: root.to_string_lossy().to_string(),
+            watch_for_changes: false,
+        })
+        .add_plugin(CorePlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(ImagePlugin)
+        .add_asset::<TerminalFontAsset>()
+        .init_asset_loader::<TerminalFontLoader>()
+        .insert_resource(BuiltInFontHandles::new())
+        .add_plugin(UserFontPlugin { directory: "fonts" });
+
+        // Fonts are loaded on another thread
+        for _ in 0..500 {
+            app.update();
+            if app.world.resource::<BuiltInFontHandles>().names().len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let fonts = app.world.resource::<BuiltInFontHandles>();
+        assert_eq!(vec!["my_font.png", "wide.font.png"], fonts.names());
+        assert_eq!(Some(UVec2::new(10, 10)), fonts.tile_size("my_font.png"));
+        // The sidecar is used for fonts with the `.font.png` extension
+        assert_eq!(Some(UVec2::new(20, 5)), fonts.tile_size("wide.font.png"));
+        let images = app.world.resource::<Assets<Image>>();
+        assert!(images.get(fonts.get("my_font.png").unwrap()).is_some());
+        assert!(images.get(fonts.get("wide.font.png").unwrap()).is_some());
+    }
+
+    #[test]
+    fn name_from_path() {
+        assert_eq!(
+            Some("my_font_8x8.png".to_string()),
+            font_name(Path::new("fonts/terminal/my_font_8x8.png"))
+        );
+        assert_eq!(
+            Some("font.png".to_string()),
+            font_name(Path::new("font.png"))
+        );
+        assert_eq!(None, font_name(Path::new("fonts/..")));
+    }
+}
@@ -5,11 +5,16 @@ use bevy::{prelude::*, utils::HashMap};
 
 use crate::code_page_437;
 
-use super::code_page_437::CP_437_CHARS;
+use super::{
+    aseprite::{self, AsepriteLoadError},
+    atlas::AtlasEntry,
+    code_page_437::CP_437_CHARS,
+};
 
 #[derive(Component)]
 pub struct UvMapping {
     uv_map: HashMap<char, [[f32; 2]; 4]>,
+    index_map: HashMap<char, usize>,
 }
 
 impl UvMapping {
@@ -21,15 +26,99 @@ impl UvMapping {
     /// uvs on a 2d tile sheet in sequential order.
     pub fn from_grid(tile_count: [u32; 2], iter: impl Iterator<Item = char>) -> Self {
         let mut uv_map = HashMap::default();
+        let mut index_map = HashMap::default();
 
         for (i, ch) in iter.enumerate() {
             let x = i as u32 % tile_count[0];
             let y = i as u32 / tile_count[0];
             let uvs = Self::get_grid_uvs([x, y], tile_count);
             uv_map.insert(ch, uvs);
+            index_map.insert(ch, i);
         }
 
-        Self { uv_map }
+        Self { uv_map, index_map }
+    }
+
+    /// Create a uv mapping which only uses a rectangular region of a larger tile sheet.
+    ///
+    /// `tile_count` is the size of the whole sheet in tiles. Keys from the iterator are
+    /// mapped in sequential order to the `size` tiles starting at `start`, where
+    /// (0,0) is the top left tile of the sheet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy_ascii_terminal::renderer::{code_page_437::CP_437_CHARS, uv_mapping::UvMapping};
+    ///
+    /// // Use the bottom half of a 16x32 sheet as a code page 437 font
+    /// let chars = CP_437_CHARS.iter().cloned();
+    /// let mapping = UvMapping::from_grid_slice([16, 32], [0, 16], [16, 16], chars);
+    /// assert_eq!([0.0625, 0.5], mapping.uvs_from_glyph('☺')[0]);
+    /// ```
+    pub fn from_grid_slice(
+        tile_count: [u32; 2],
+        start: [u32; 2],
+        size: [u32; 2],
+        iter: impl Iterator<Item = char>,
+    ) -> Self {
+        let mut uv_map = HashMap::default();
+        let mut index_map = HashMap::default();
+
+        let len = (size[0] * size[1]) as usize;
+        for (i, ch) in iter.take(len).enumerate() {
+            let x = start[0] + i as u32 % size[0];
+            let y = start[1] + i as u32 / size[0];
+            let uvs = Self::get_grid_uvs([x, y], tile_count);
+            uv_map.insert(ch, uvs);
+            index_map.insert(ch, i);
+        }
+
+        Self { uv_map, index_map }
+    }
+
+    /// Create a uv mapping for one font packed into an atlas by
+    /// [TerminalFontAsset::pack_into_atlas](super::font_asset::TerminalFontAsset::pack_into_atlas).
+    /// Keys from the iterator are mapped to the font's glyphs in sequential order.
+    pub fn from_atlas_entry(entry: &AtlasEntry, iter: impl Iterator<Item = char>) -> Self {
+        let mut uv_map = HashMap::default();
+        let mut index_map = HashMap::default();
+
+        let len = (entry.tile_count.x * entry.tile_count.y) as usize;
+        for (i, ch) in iter.take(len).enumerate() {
+            uv_map.insert(ch, entry.glyph_uvs(i));
+            index_map.insert(ch, i);
+        }
+
+        Self { uv_map, index_map }
+    }
+
+    /// Create a uv mapping from the json metadata of a sprite sheet exported from
+    /// Aseprite. Glyphs can be different sizes, they will be stretched to fit
+    /// a terminal tile.
+    ///
+    /// See the [aseprite](super::aseprite) module for how frame names are mapped to glyphs.
+    pub fn from_aseprite_json(json: &str) -> Result<Self, AsepriteLoadError> {
+        let sheet = aseprite::parse_frames(json)?;
+        let size = sheet.size.as_vec2().max(Vec2::ONE);
+
+        let mut uv_map = HashMap::default();
+        let mut index_map = HashMap::default();
+        for (ch, [x, y, w, h]) in sheet.frames {
+            let min = Vec2::new(x as f32, y as f32) / size;
+            let max = Vec2::new((x + w) as f32, (y + h) as f32) / size;
+            uv_map.insert(
+                ch,
+                [
+                    [min.x, min.y],
+                    [min.x, max.y],
+                    [max.x, min.y],
+                    [max.x, max.y],
+                ],
+            );
+            index_map.insert(ch, code_page_437::glyph_to_index(ch) as usize);
+        }
+
+        Ok(Self { uv_map, index_map })
     }
 
     pub fn get_grid_uvs(xy: [u32; 2], tile_count: [u32; 2]) -> [[f32; 2]; 4] {
@@ -54,6 +143,15 @@ impl UvMapping {
         let char = code_page_437::index_to_glyph(index);
         self.uvs_from_glyph(char)
     }
+
+    /// The index of a glyph on the tile sheet.
+    ///
+    /// Returns `None` if the glyph isn't mapped or if it's index is outside the
+    /// range of a code page 437 font.
+    #[inline]
+    pub fn char_to_glyph_index(&self, ch: char) -> Option<u8> {
+        self.index_map.get(&ch).and_then(|i| u8::try_from(*i).ok())
+    }
 }
 
 impl Default for UvMapping {
@@ -61,3 +159,51 @@ impl Default for UvMapping {
         Self::code_page_437()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aseprite() {
+        let json = r#"{
+            "frames": {
+                "font 65.aseprite": { "frame": { "x": 8, "y": 0, "w": 8, "h": 16 } }
+            },
+            "meta": { "size": { "w": 32, "h": 16 } }
+        }"#;
+        let mapping = UvMapping::from_aseprite_json(json).unwrap();
+        assert_eq!(
+            &[[0.25, 0.0], [0.25, 1.0], [0.5, 0.0], [0.5, 1.0]],
+            mapping.uvs_from_glyph('A')
+        );
+        assert_eq!(Some(65), mapping.char_to_glyph_index('A'));
+    }
+
+    #[test]
+    fn grid_slice() {
+        let mapping = UvMapping::from_grid_slice([8, 4], [2, 1], [2, 2], "abcde".chars());
+        assert_eq!(
+            &[[0.25, 0.5], [0.25, 0.75], [0.375, 0.5], [0.375, 0.75]],
+            mapping.uvs_from_glyph('c')
+        );
+        assert_eq!(Some(3), mapping.char_to_glyph_index('d'));
+        // Only 4 tiles fit in the slice
+        assert_eq!(None, mapping.char_to_glyph_index('e'));
+    }
+
+    #[test]
+    fn glyph_index() {
+        let mapping = UvMapping::default();
+        assert_eq!(Some(1), mapping.char_to_glyph_index('☺'));
+        assert_eq!(Some(65), mapping.char_to_glyph_index('A'));
+        assert_eq!(None, mapping.char_to_glyph_index('ア'));
+
+        let mapping = UvMapping::from_grid(
+            [32, 32],
+            (0..300).map(|i| char::from_u32(0x4E00 + i).unwrap()),
+        );
+        assert_eq!(Some(255), mapping.char_to_glyph_index('\u{4EFF}'));
+        assert_eq!(None, mapping.char_to_glyph_index('\u{4F00}'));
+    }
+}
@@ -0,0 +1,170 @@
+//! A terminal component for rotating or mirroring how a terminal's tiles are
+//! laid out when rendered.
+
+use bevy::prelude::*;
+
+use super::{renderer_vertex_data::TerminalRendererVertexData, TileScaling};
+
+/// Terminal component which rotates or mirrors the positions of the terminal's
+/// tiles when it's rendered, without changing the terminal's tile coordinates
+/// or it's entity's transform.
+///
+/// Only the layout of the tiles changes, every glyph stays upright. This makes
+/// it useful for split screen games where each player sees the same map from
+/// their own side, with text still readable.
+///
+/// The transform is applied around the terminal's [TerminalPivot](super::TerminalPivot).
+/// Rotations by 90 or 270 degrees should only be used with square tiles,
+/// otherwise neighbouring tiles will overlap or leave gaps.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::renderer::TerminalViewMatrix;
+///
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 20]))
+///         .insert(TerminalViewMatrix::rotate_180());
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct TerminalViewMatrix {
+    /// A 2d transform applied to the position of every tile, in the terminal
+    /// mesh's local space.
+    pub transform: Mat3,
+}
+
+impl Default for TerminalViewMatrix {
+    fn default() -> Self {
+        Self {
+            transform: Mat3::IDENTITY,
+        }
+    }
+}
+
+impl TerminalViewMatrix {
+    /// Rotate the layout 90 degrees clockwise.
+    pub fn rotate_90() -> Self {
+        Self::from_axes(Vec2::new(0.0, -1.0), Vec2::new(1.0, 0.0))
+    }
+
+    /// Rotate the layout 180 degrees.
+    pub fn rotate_180() -> Self {
+        Self::from_axes(Vec2::new(-1.0, 0.0), Vec2::new(0.0, -1.0))
+    }
+
+    /// Rotate the layout 270 degrees clockwise.
+    pub fn rotate_270() -> Self {
+        Self::from_axes(Vec2::new(0.0, 1.0), Vec2::new(-1.0, 0.0))
+    }
+
+    /// Mirror the layout so the left edge of the terminal is drawn on the right.
+    pub fn flip_horizontal() -> Self {
+        Self::from_axes(Vec2::new(-1.0, 0.0), Vec2::new(0.0, 1.0))
+    }
+
+    /// Mirror the layout so the bottom edge of the terminal is drawn at the top.
+    pub fn flip_vertical() -> Self {
+        Self::from_axes(Vec2::new(1.0, 0.0), Vec2::new(0.0, -1.0))
+    }
+
+    fn from_axes(x_axis: Vec2, y_axis: Vec2) -> Self {
+        Self {
+            transform: Mat3::from_cols(x_axis.extend(0.0), y_axis.extend(0.0), Vec3::Z),
+        }
+    }
+
+    /// Move every tile quad of the vertex data to it's transformed position,
+    /// keeping the quads themselves upright.
+    pub(crate) fn apply(&self, vert_data: &mut TerminalRendererVertexData) {
+        for quad in vert_data.verts.chunks_exact_mut(4) {
+            // See TerminalRendererVertexData::resize for the vertex order
+            let min = Vec3::from(quad[1]).truncate();
+            let max = Vec3::from(quad[2]).truncate();
+            let center = (min + max) * 0.5;
+            let offset = self.transform.transform_point2(center) - center;
+            for vert in quad.iter_mut() {
+                vert[0] += offset.x;
+                vert[1] += offset.y;
+            }
+        }
+    }
+
+    /// The position in the terminal's untransformed layout which is drawn at
+    /// `p`, in the terminal mesh's local space.
+    ///
+    /// Quads stay upright, so this only maps the whole area of a tile back to
+    /// it's original tile when tiles are square or the transform doesn't rotate
+    /// by 90 degrees.
+    pub(crate) fn untransform_point(&self, p: Vec2) -> Vec2 {
+        self.transform.inverse().transform_point2(p)
+    }
+}
+
+pub(crate) struct TerminalViewMatrixPlugin;
+
+impl Plugin for TerminalViewMatrixPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(terminal_view_matrix_removed.before(super::TERMINAL_UPDATE_SIZE));
+    }
+}
+
+/// Rebuild the vertex data of terminals which no longer have a view matrix.
+fn terminal_view_matrix_removed(
+    removed: RemovedComponents<TerminalViewMatrix>,
+    mut q: Query<&mut TileScaling>,
+) {
+    for entity in removed.iter() {
+        if let Ok(mut scaling) = q.get_mut(entity) {
+            scaling.set_changed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn centers(vert_data: &TerminalRendererVertexData) -> Vec<Vec2> {
+        vert_data
+            .verts
+            .chunks_exact(4)
+            .map(|q| (Vec3::from(q[1]) + Vec3::from(q[2])).truncate() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn layout() {
+        // 3x1 tiles centered on the origin
+        let vert_data = TerminalRendererVertexData::with_size(UVec2::new(3, 1));
+        let original = centers(&vert_data);
+        assert_eq!(Vec2::new(-1.0, 0.0), original[0]);
+
+        let mut rotated = TerminalRendererVertexData::with_size(UVec2::new(3, 1));
+        TerminalViewMatrix::rotate_90().apply(&mut rotated);
+        let centers = centers(&rotated);
+        assert_eq!(Vec2::new(0.0, 1.0), centers[0]);
+        assert_eq!(Vec2::new(0.0, 0.0), centers[1]);
+        assert_eq!(Vec2::new(0.0, -1.0), centers[2]);
+        // Quads stay upright
+        let quad = &rotated.verts[0..4];
+        assert_eq!(quad[0][0], quad[1][0]);
+        assert_eq!(quad[1][1], quad[3][1]);
+
+        let mut flipped = TerminalRendererVertexData::with_size(UVec2::new(3, 1));
+        TerminalViewMatrix::flip_horizontal().apply(&mut flipped);
+        assert_eq!(flipped.verts[0..4], vert_data.verts[8..12]);
+
+        let full_turn =
+            TerminalViewMatrix::rotate_90().transform * TerminalViewMatrix::rotate_270().transform;
+        assert_eq!(Mat3::IDENTITY, full_turn);
+        assert_eq!(
+            TerminalViewMatrix::rotate_180().transform,
+            TerminalViewMatrix::flip_horizontal().transform
+                * TerminalViewMatrix::flip_vertical().transform
+        );
+    }
+}
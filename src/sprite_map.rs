@@ -0,0 +1,123 @@
+//! Mapping game types to the tiles they're drawn with.
+
+use std::any::TypeId;
+
+use bevy::utils::HashMap;
+
+use crate::Tile;
+
+/// A type with a default tile, which can be added to a [TerminalSpriteMap]
+/// with [TerminalSpriteMap::register_sprite].
+pub trait TerminalSprite: 'static {
+    fn sprite() -> Tile;
+}
+
+/// A resource mapping game types to the tiles they're drawn with, so each
+/// type's appearance is defined in one place.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// #[derive(Component)]
+/// struct Player;
+///
+/// #[derive(Component)]
+/// struct Goblin;
+///
+/// impl TerminalSprite for Goblin {
+///     fn sprite() -> Tile {
+///         Tile {
+///             glyph: 'g',
+///             fg_color: Color::GREEN,
+///             ..Default::default()
+///         }
+///     }
+/// }
+///
+/// fn setup(mut sprites: ResMut<TerminalSpriteMap>) {
+///     sprites.register::<Player>(Tile {
+///         glyph: '@',
+///         ..Default::default()
+///     });
+///     sprites.register_sprite::<Goblin>();
+/// }
+///
+/// fn draw(
+///     sprites: Res<TerminalSpriteMap>,
+///     players: Query<&Transform, With<Player>>,
+///     mut q: Query<&mut Terminal>,
+/// ) {
+///     let tile = sprites.get::<Player>().unwrap_or_default();
+///     for mut term in q.iter_mut() {
+///         for transform in players.iter() {
+///             let xy = transform.translation.truncate().as_ivec2();
+///             term.put_tile(xy.into(), tile);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerminalSpriteMap {
+    pub entries: HashMap<TypeId, Tile>,
+}
+
+impl TerminalSpriteMap {
+    /// Set the tile for a type, replacing any previous one.
+    pub fn register<T: 'static>(&mut self, tile: Tile) {
+        self.entries.insert(TypeId::of::<T>(), tile);
+    }
+
+    /// Set the tile for a type to it's [TerminalSprite::sprite].
+    pub fn register_sprite<T: TerminalSprite>(&mut self) {
+        self.register::<T>(T::sprite());
+    }
+
+    /// The tile for a type, if one has been registered.
+    pub fn get<T: 'static>(&self) -> Option<Tile> {
+        self.get_by_type_id(TypeId::of::<T>())
+    }
+
+    /// The tile for a type, if one has been registered.
+    pub fn get_by_type_id(&self, id: TypeId) -> Option<Tile> {
+        self.entries.get(&id).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Color;
+
+    use super::*;
+
+    struct Player;
+    struct Wall;
+
+    impl TerminalSprite for Wall {
+        fn sprite() -> Tile {
+            Tile {
+                glyph: '#',
+                fg_color: Color::GRAY,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn register() {
+        let mut sprites = TerminalSpriteMap::default();
+        assert_eq!(None, sprites.get::<Player>());
+
+        let player = Tile {
+            glyph: '@',
+            ..Default::default()
+        };
+        sprites.register::<Player>(player);
+        sprites.register_sprite::<Wall>();
+        assert_eq!(Some(player), sprites.get::<Player>());
+        assert_eq!(Some(player), sprites.get_by_type_id(TypeId::of::<Player>()));
+        assert_eq!(Some('#'), sprites.get::<Wall>().map(|t| t.glyph));
+    }
+}
@@ -1,19 +1,24 @@
+use std::collections::BTreeMap;
 use std::iter::FromIterator;
 use std::iter::StepBy;
 use std::slice::Iter;
 use std::slice::IterMut;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use bevy::prelude::*;
 
 use crate::formatting::CharFormat;
 use crate::formatting::StringFormat;
+use crate::layers::TerminalLayerWriter;
+use crate::line::BresenhamLine;
+use crate::markup::{TerminalStringTokenizer, TileStyle};
 
 use sark_grids::Grid;
 
 /// A single tile of the terminal.
 ///
 /// Defaults to a blank glyph with a black background and a white foreground.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Tile {
     /// The glyph for the tile. Glyphs are mapped to sprites via the
     /// terminal's [UvMapping](super::renderer::uv_mapping::UvMapping)
@@ -22,6 +27,28 @@ pub struct Tile {
     pub fg_color: Color,
     /// The background color for the tile.
     pub bg_color: Color,
+    /// How the tile's glyph is flipped and rotated when rendered.
+    pub flags: TerminalTileFlags,
+    /// If set, the sprite of the material's
+    /// [background_texture](super::TerminalMaterial::background_texture) drawn
+    /// behind the glyph instead of the background color.
+    pub bg_index: Option<u8>,
+}
+
+bitflags::bitflags! {
+    /// Flags controlling how a [Tile]'s glyph is oriented when it's rendered.
+    ///
+    /// Flips are applied before rotation.
+    #[derive(Default)]
+    pub struct TerminalTileFlags: u8 {
+        /// Mirror the glyph horizontally.
+        const FLIP_H    = (1 << 0);
+        /// Mirror the glyph vertically.
+        const FLIP_V    = (1 << 1);
+        /// Rotate the glyph 90 degrees clockwise.
+        const ROTATE_90 = (1 << 2);
+        const NONE      = 0;
+    }
 }
 
 /// A simple terminal for writing text in a readable grid.
@@ -44,6 +71,10 @@ pub struct Tile {
 pub struct Terminal {
     pub tiles: Grid<Tile>,
     size: UVec2,
+    /// The number of tile writes since the renderer last read the terminal.
+    changed_count: AtomicU32,
+    /// Layers drawn on top of the terminal's tiles, see [Terminal::draw_layer].
+    pub(crate) layers: BTreeMap<u8, TerminalLayerWriter>,
 }
 
 impl Default for Tile {
@@ -52,10 +83,33 @@ impl Default for Tile {
             glyph: ' ',
             fg_color: Color::WHITE,
             bg_color: Color::BLACK,
+            flags: TerminalTileFlags::NONE,
+            bg_index: None,
         }
     }
 }
 
+/// A copy of a terminal's tiles at a single point in time.
+///
+/// Can be retrieved via [Terminal::snapshot] and applied with [Terminal::restore].
+#[derive(Clone, Debug)]
+pub struct TerminalSnapshot {
+    pub(crate) tiles: Vec<Tile>,
+    pub(crate) size: UVec2,
+}
+
+impl TerminalSnapshot {
+    /// The size of the terminal the snapshot was taken from.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The tiles of the snapshot.
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+}
+
 /// Border glyphs used in box drawing functions.
 ///
 /// Specifies the style of lines to use along the border of the box.
@@ -101,6 +155,8 @@ impl Terminal {
         Terminal {
             tiles: Grid::default(size),
             size: UVec2::from(size),
+            changed_count: AtomicU32::new(0),
+            layers: BTreeMap::new(),
         }
     }
 
@@ -108,6 +164,30 @@ impl Terminal {
     pub fn resize(&mut self, size: [u32; 2]) {
         self.tiles = Grid::default(size);
         self.size = UVec2::from(size);
+        self.layers.clear();
+        self.count_changes(self.tiles.len());
+    }
+
+    /// The number of tile writes since the terminal was last rendered, or since
+    /// [Terminal::reset_change_counter] was called.
+    ///
+    /// Every write made through the terminal's drawing functions is counted,
+    /// so writing the same tile twice counts twice. Tiles modified directly
+    /// through [Terminal::tiles] or the mutable iterators aren't counted.
+    pub fn cells_changed_since_last_frame(&self) -> u32 {
+        self.changed_count.load(Ordering::Relaxed)
+    }
+
+    /// Reset the count returned by [Terminal::cells_changed_since_last_frame].
+    ///
+    /// This is called by the renderer each time it updates from the terminal.
+    pub fn reset_change_counter(&self) {
+        self.changed_count.store(0, Ordering::Relaxed);
+    }
+
+    fn count_changes(&mut self, count: usize) {
+        let changed = self.changed_count.get_mut();
+        *changed = changed.saturating_add(count as u32);
     }
 
     pub fn width(&self) -> u32 {
@@ -188,23 +268,168 @@ impl Terminal {
         let tiles = self.tiles.slice_mut(i..).iter_mut().take(string.len());
         let chars = string.chars().take(tiles.len());
 
-        for (char, mut t) in chars.zip(tiles) {
+        let mut count = 0;
+        for (char, t) in chars.zip(tiles) {
             t.glyph = char;
+            count += 1;
         }
+        self.count_changes(count);
     }
 
     /// Write a string to the terminal with colors.
     ///
+    /// The string can contain color markup like `[fg=red]text[/fg]`, see
+    /// [TerminalStringTokenizer] for the supported tags. Colors not set by markup
+    /// come from `format`.
+    ///
     /// The string will move to the next line if it reaches the edge
     /// and will truncate at the end of the terminal.
     pub fn put_string_formatted(&mut self, xy: [i32; 2], string: &str, format: StringFormat) {
-        let xy = format.get_string_position(xy, self.size.into(), string);
+        let base = TileStyle::new(format.fg_color, format.bg_color);
+        let tokens = TerminalStringTokenizer::parse_with_style(string, base);
+        let text: String = tokens.iter().map(|t| t.text.as_str()).collect();
+        if text.is_empty() {
+            return;
+        }
+
+        let xy = format.get_string_position(xy, self.size.into(), &text);
         let i = self.to_index(xy.into());
-        let tiles = self.tiles.slice_mut(i..).iter_mut().take(string.len());
-        let chars = string.chars().take(tiles.len());
+        let tiles = self.tiles.slice_mut(i..).iter_mut().take(text.len());
+        let chars = tokens
+            .iter()
+            .flat_map(|t| t.text.chars().map(move |c| (c, t.style)))
+            .take(tiles.len());
+
+        let mut count = 0;
+        for ((char, style), t) in chars.zip(tiles) {
+            *t = StringFormat::colors(style.fg_color, style.bg_color).tile(char);
+            count += 1;
+        }
+        self.count_changes(count);
+    }
 
-        for (char, t) in chars.zip(tiles) {
-            *t = format.tile(char);
+    /// Write a string to the terminal, moving down a row for each `\n`.
+    ///
+    /// Tabs expand to the next 4 column tab stop. Lines are truncated at the edge
+    /// of the terminal and lines below the bottom of the terminal are skipped.
+    ///
+    /// Returns the number of lines drawn.
+    pub fn put_string_multiline(&mut self, xy: [i32; 2], text: &str, fg: Color, bg: Color) -> u32 {
+        self.put_string_multiline_tabbed(xy, text, fg, bg, 4)
+    }
+
+    /// Write a string to the terminal, moving down a row for each `\n`.
+    ///
+    /// Tabs expand to the next multiple of `tab_width` columns. Lines are truncated
+    /// at the edge of the terminal and lines below the bottom of the terminal are skipped.
+    ///
+    /// Returns the number of lines drawn.
+    pub fn put_string_multiline_tabbed(
+        &mut self,
+        xy: [i32; 2],
+        text: &str,
+        fg: Color,
+        bg: Color,
+        tab_width: u32,
+    ) -> u32 {
+        let [x, y] = xy;
+        let tab_width = tab_width.max(1) as i32;
+        let mut count = 0;
+
+        for (i, line) in text.lines().enumerate() {
+            let y = y - i as i32;
+            if y < 0 {
+                break;
+            }
+            if y >= self.height() as i32 {
+                continue;
+            }
+
+            let mut column = 0;
+            for ch in line.chars() {
+                if ch == '\t' {
+                    column += tab_width - column % tab_width;
+                    continue;
+                }
+                let xy = [x + column, y];
+                if self.is_in_bounds(xy) {
+                    self.put_tile(
+                        xy,
+                        Tile {
+                            glyph: ch,
+                            fg_color: fg,
+                            bg_color: bg,
+                            ..Default::default()
+                        },
+                    );
+                }
+                column += 1;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Write a string with a drop shadow, for titles and other styled text.
+    ///
+    /// The string is drawn in `shadow_fg` at `xy + shadow_offset` first, then in
+    /// `fg` at `xy`, so the shadow never covers the text itself. Both use `bg`
+    /// as their background color.
+    ///
+    /// Unlike [Terminal::put_string] the string doesn't wrap, any tiles outside
+    /// the terminal are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_ascii_terminal::*;
+    ///
+    /// let mut term = Terminal::with_size([20, 5]);
+    /// let (fg, bg, shadow) = (Color::YELLOW, Color::BLACK, Color::MAROON);
+    /// term.put_string_shadow([2, 3], "TITLE", fg, bg, shadow, IVec2::new(1, -1));
+    /// assert_eq!("TITLE", term.get_string([2, 3], 5));
+    /// assert_eq!(Color::MAROON, term.get_tile([7, 2]).fg_color);
+    /// ```
+    pub fn put_string_shadow(
+        &mut self,
+        xy: [i32; 2],
+        string: &str,
+        fg: Color,
+        bg: Color,
+        shadow_fg: Color,
+        shadow_offset: IVec2,
+    ) {
+        let shadow_xy = IVec2::from(xy) + shadow_offset;
+        self.put_string_clipped(shadow_xy.into(), string, shadow_fg, bg);
+        self.put_string_clipped(xy, string, fg, bg);
+    }
+
+    /// Write a single line of colored text, skipping any tiles outside the terminal.
+    fn put_string_clipped(&mut self, xy: [i32; 2], string: &str, fg: Color, bg: Color) {
+        let [x, y] = xy;
+        for (i, ch) in string.chars().enumerate() {
+            let xy = [x + i as i32, y];
+            if self.is_in_bounds(xy) {
+                self.put_tile(
+                    xy,
+                    Tile {
+                        glyph: ch,
+                        fg_color: fg,
+                        bg_color: bg,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    /// Write a list of tiles to the terminal, skipping any outside the terminal.
+    pub fn apply_tiles(&mut self, tiles: impl IntoIterator<Item = ([i32; 2], Tile)>) {
+        for (xy, tile) in tiles {
+            if self.is_in_bounds(xy) {
+                self.put_tile(xy, tile);
+            }
         }
     }
 
@@ -222,6 +447,13 @@ impl Terminal {
         self.get_tile_mut(xy).bg_color = col;
     }
 
+    /// Set the background sprite of a tile, see [Tile::bg_index].
+    ///
+    /// The existing colors and glyph of the tile will remain.
+    pub fn put_bg_index(&mut self, xy: [i32; 2], index: Option<u8>) {
+        self.get_tile_mut(xy).bg_index = index;
+    }
+
     /// Retrieve the char from a tile.
     pub fn get_char(&self, xy: [i32; 2]) -> char {
         self.get_tile(xy).glyph
@@ -246,8 +478,11 @@ impl Terminal {
     }
 
     /// Retrieve a mutable reference to a tile in the terminal.
+    ///
+    /// Counts as a single write for [Terminal::cells_changed_since_last_frame].
     pub fn get_tile_mut(&mut self, xy: [i32; 2]) -> &mut Tile {
         let i = self.to_index(xy);
+        self.count_changes(1);
         &mut self.tiles[i]
     }
 
@@ -412,12 +647,65 @@ impl Terminal {
         }
     }
 
+    /// Draw a line of tiles between two points, inclusive.
+    ///
+    /// Any part of the line outside the terminal will be skipped.
+    pub fn draw_line(&mut self, start: [i32; 2], end: [i32; 2], tile: Tile) {
+        for xy in BresenhamLine::new(start, end) {
+            if self.is_in_bounds(xy) {
+                self.put_tile(xy, tile);
+            }
+        }
+    }
+
+    /// Draw a line of tiles between two points with the given thickness.
+    ///
+    /// The line is thickened along whichever axis is perpendicular to it's
+    /// major axis. Any part of the line outside the terminal will be skipped.
+    pub fn draw_thick_line(&mut self, start: [i32; 2], end: [i32; 2], thickness: u32, tile: Tile) {
+        let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+        let offset_axis = if dx.abs() >= dy.abs() { [0, 1] } else { [1, 0] };
+        let thickness = thickness as i32;
+
+        for i in 0..thickness {
+            let o = i - (thickness - 1) / 2;
+            let [ox, oy] = [offset_axis[0] * o, offset_axis[1] * o];
+            self.draw_line(
+                [start[0] + ox, start[1] + oy],
+                [end[0] + ox, end[1] + oy],
+                tile,
+            );
+        }
+    }
+
     /// Clear the terminal tiles to default - empty tiles with
     /// a black background
     pub fn clear(&mut self) {
         for t in self.tiles.iter_mut() {
             *t = Tile::default()
         }
+        self.count_changes(self.tiles.len());
+    }
+
+    /// Take a snapshot of the current state of the terminal.
+    pub fn snapshot(&self) -> TerminalSnapshot {
+        TerminalSnapshot {
+            tiles: self.tiles.iter().cloned().collect(),
+            size: self.size,
+        }
+    }
+
+    /// Restore the terminal to the state of the given [TerminalSnapshot].
+    ///
+    /// The terminal will be resized if it's size doesn't match the snapshot.
+    pub fn restore(&mut self, snapshot: &TerminalSnapshot) {
+        if self.size != snapshot.size {
+            self.resize(snapshot.size.into());
+        }
+        for (t, s) in self.tiles.iter_mut().zip(snapshot.tiles.iter()) {
+            *t = *s;
+        }
+        self.count_changes(self.tiles.len());
     }
 
     /// Returns true if the given position is inside the bounds of the terminal.
@@ -479,8 +767,51 @@ impl Terminal {
 
 #[cfg(test)]
 mod tests {
+    use crate::formatting::Pivot;
+
     use super::*;
 
+    #[test]
+    fn put_string_multiline() {
+        let mut term = Terminal::with_size([10, 3]);
+
+        let lines =
+            term.put_string_multiline([0, 2], "ab\n\tc\nde\nfg", Color::WHITE, Color::BLACK);
+        assert_eq!(3, lines);
+        assert_eq!("ab", term.get_string([0, 2], 2));
+        assert_eq!("    c", term.get_string([0, 1], 5));
+        assert_eq!("de", term.get_string([0, 0], 2));
+
+        term.clear();
+        term.put_string_multiline_tabbed([1, 0], "a\tb", Color::WHITE, Color::BLACK, 3);
+        assert_eq!(" a  b", term.get_string([0, 0], 5));
+    }
+
+    #[test]
+    fn draw_line() {
+        let mut term = Terminal::with_size([10, 10]);
+        let tile = Tile {
+            glyph: '*',
+            ..Default::default()
+        };
+
+        term.draw_line([1, 1], [8, 4], tile);
+        assert_eq!('*', term.get_char([1, 1]));
+        assert_eq!('*', term.get_char([8, 4]));
+
+        // Out of bounds lines are clipped
+        term.draw_line([-5, -5], [15, 15], tile);
+        assert_eq!('*', term.get_char([0, 0]));
+        assert_eq!('*', term.get_char([9, 9]));
+
+        term.clear();
+        term.draw_thick_line([0, 5], [9, 5], 3, tile);
+        assert_eq!("*", term.get_string([0, 4], 1));
+        assert_eq!("*", term.get_string([9, 6], 1));
+        assert_eq!(' ', term.get_char([0, 3]));
+        assert_eq!(' ', term.get_char([0, 7]));
+    }
+
     #[test]
     fn put_char() {
         let mut term = Terminal::with_size([20, 20]);
@@ -500,6 +831,56 @@ mod tests {
         assert_eq!("He", term.get_string([18, 19], 2));
     }
 
+    #[test]
+    fn put_string_markup() {
+        let mut term = Terminal::with_size([20, 20]);
+        let format = StringFormat::colors(Color::GRAY, Color::NAVY);
+        term.put_string_formatted([0, 0], "a[fg=red]b[/fg]c", format);
+        assert_eq!("abc", term.get_string([0, 0], 3));
+        assert_eq!(Color::GRAY, term.get_tile([0, 0]).fg_color);
+        assert_eq!(Color::RED, term.get_tile([1, 0]).fg_color);
+        assert_eq!(Color::NAVY, term.get_tile([1, 0]).bg_color);
+        assert_eq!(Color::GRAY, term.get_tile([2, 0]).fg_color);
+
+        // Alignment uses the length without markup
+        let format = StringFormat::pivot(Pivot::TopRight);
+        term.put_string_formatted([0, 0], "[bg=blue]xy[/bg]", format);
+        assert_eq!("xy", term.get_string([18, 19], 2));
+    }
+
+    #[test]
+    fn put_string_shadow() {
+        let mut term = Terminal::with_size([10, 5]);
+        term.put_string_shadow(
+            [1, 2],
+            "abc",
+            Color::WHITE,
+            Color::BLUE,
+            Color::GRAY,
+            IVec2::new(1, 0),
+        );
+        // The shadow is hidden under the text apart from it's last tile
+        assert_eq!("abcc", term.get_string([1, 2], 4));
+        assert_eq!(Color::WHITE, term.get_tile([2, 2]).fg_color);
+        assert_eq!(Color::WHITE, term.get_tile([3, 2]).fg_color);
+        assert_eq!(Color::GRAY, term.get_tile([4, 2]).fg_color);
+        assert_eq!(Color::BLUE, term.get_tile([4, 2]).bg_color);
+
+        // Shadows outside the terminal are clipped
+        term.clear();
+        term.put_string_shadow(
+            [8, 0],
+            "xyz",
+            Color::WHITE,
+            Color::BLACK,
+            Color::GRAY,
+            IVec2::new(1, -1),
+        );
+        assert_eq!("xy", term.get_string([8, 0], 2));
+        assert_eq!(' ', term.get_char([0, 1]));
+        assert_eq!(Color::WHITE, term.get_tile([9, 0]).fg_color);
+    }
+
     #[test]
     fn edges() {
         let mut term = Terminal::with_size([25, 20]);
@@ -545,4 +926,26 @@ mod tests {
         assert_eq!(term.get_char([4, 4]), SINGLE_LINE_GLYPHS.top_right);
         assert_eq!(term.get_char([4, 0]), SINGLE_LINE_GLYPHS.bottom_right);
     }
+
+    #[test]
+    fn change_counter() {
+        let mut term = Terminal::with_size([10, 10]);
+        assert_eq!(0, term.cells_changed_since_last_frame());
+
+        term.put_char([0, 0], 'a');
+        term.put_char([0, 0], 'b');
+        term.put_fg_color([1, 0], Color::RED);
+        term.put_tile([2, 0], Tile::default());
+        assert_eq!(4, term.cells_changed_since_last_frame());
+
+        // Only the characters that fit are counted
+        term.put_string([7, 9], "Hello");
+        assert_eq!(7, term.cells_changed_since_last_frame());
+
+        term.reset_change_counter();
+        assert_eq!(0, term.cells_changed_since_last_frame());
+
+        term.clear();
+        assert_eq!(100, term.cells_changed_since_last_frame());
+    }
 }
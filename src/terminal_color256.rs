@@ -0,0 +1,127 @@
+//! Conversions between xterm's 256 color palette and bevy colors.
+//!
+//! - `0`-`15` are the standard ANSI colors, normal followed by bright.
+//! - `16`-`231` are a 6x6x6 color cube.
+//! - `232`-`255` are a grayscale ramp from dark to light, not including black
+//!   or white.
+//!
+//! # Example
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_ascii_terminal::terminal_color256;
+//!
+//! assert_eq!(Color::rgb_u8(255, 0, 0), terminal_color256::from_index(196));
+//! assert_eq!(196, terminal_color256::nearest(Color::rgb(1.0, 0.05, 0.0)));
+//! ```
+
+use bevy::prelude::*;
+
+/// The 16 standard ANSI colors, normal followed by bright.
+pub(crate) const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (170, 0, 0),
+    (0, 170, 0),
+    (170, 85, 0),
+    (0, 0, 170),
+    (170, 0, 170),
+    (0, 170, 170),
+    (170, 170, 170),
+    (85, 85, 85),
+    (255, 85, 85),
+    (85, 255, 85),
+    (255, 255, 85),
+    (85, 85, 255),
+    (255, 85, 255),
+    (85, 255, 255),
+    (255, 255, 255),
+];
+
+/// The intensity of each of the 6 steps along an axis of the color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+const PALETTE: [(u8, u8, u8); 256] = build_palette();
+
+const fn build_palette() -> [(u8, u8, u8); 256] {
+    let mut palette = [(0, 0, 0); 256];
+    let mut i = 0;
+    while i < 16 {
+        palette[i] = ANSI_COLORS[i];
+        i += 1;
+    }
+    while i < 232 {
+        let cube = i - 16;
+        palette[i] = (
+            CUBE_LEVELS[cube / 36],
+            CUBE_LEVELS[(cube / 6) % 6],
+            CUBE_LEVELS[cube % 6],
+        );
+        i += 1;
+    }
+    while i < 256 {
+        let gray = 8 + (i - 232) as u8 * 10;
+        palette[i] = (gray, gray, gray);
+        i += 1;
+    }
+    palette
+}
+
+/// The color of the given entry in the 256 color palette.
+pub fn from_index(index: u8) -> Color {
+    let (r, g, b) = PALETTE[index as usize];
+    Color::rgb_u8(r, g, b)
+}
+
+/// The index of the palette entry closest to `color`, measured by euclidean
+/// distance in rgb space. Alpha is ignored.
+///
+/// If several entries are equally close the lowest index is returned.
+pub fn nearest(color: Color) -> u8 {
+    let [r, g, b, _] = color.as_rgba_f32();
+    let target = [r, g, b].map(|c| c.clamp(0.0, 1.0) * 255.0);
+    let distance = |&(r, g, b): &(u8, u8, u8)| {
+        [r, g, b]
+            .iter()
+            .zip(target)
+            .map(|(c, t)| (*c as f32 - t).powi(2))
+            .sum::<f32>()
+    };
+
+    let mut best = 0;
+    let mut best_distance = f32::MAX;
+    for (i, entry) in PALETTE.iter().enumerate() {
+        let d = distance(entry);
+        if d < best_distance {
+            best = i;
+            best_distance = d;
+        }
+    }
+    best as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette() {
+        assert_eq!(Color::rgb_u8(170, 0, 0), from_index(1));
+        assert_eq!(Color::rgb_u8(0, 0, 0), from_index(16));
+        assert_eq!(Color::rgb_u8(95, 135, 175), from_index(16 + 36 + 12 + 3));
+        assert_eq!(Color::rgb_u8(255, 255, 255), from_index(231));
+        assert_eq!(Color::rgb_u8(8, 8, 8), from_index(232));
+        assert_eq!(Color::rgb_u8(238, 238, 238), from_index(255));
+    }
+
+    #[test]
+    fn nearest_index() {
+        // Every entry maps back to itself, unless an earlier entry is identical
+        for i in 0..=255 {
+            let found = nearest(from_index(i));
+            assert_eq!(PALETTE[i as usize], PALETTE[found as usize]);
+            assert!(found <= i);
+        }
+        assert_eq!(0, nearest(Color::BLACK));
+        assert_eq!(244, nearest(Color::rgb_u8(130, 128, 129)));
+    }
+}
@@ -0,0 +1,159 @@
+//! Word wrapping for text drawn to a terminal.
+
+/// Split `text` into lines no wider than `width` characters.
+///
+/// Lines are broken at spaces where possible and at every `\n`. Tabs are
+/// expanded to the next multiple of `tab_width` columns. A word wider than
+/// `width` is split across lines, with a hyphen at the end of each line it's
+/// broken on.
+///
+/// Leading whitespace of each line in `text` is kept as indentation, while
+/// whitespace at a line break is dropped. An empty string gives no lines.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::text_wrap::wrap;
+///
+/// let lines = wrap("The quick brown fox\njumps", 10, 4);
+/// assert_eq!(vec!["The quick", "brown fox", "jumps"], lines);
+/// ```
+pub fn wrap(text: &str, width: u32, tab_width: u32) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let width = width.max(1) as usize;
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+        wrap_paragraph(&expand_tabs(paragraph, tab_width), width, &mut lines);
+    }
+    lines
+}
+
+/// Replace each tab with spaces up to the next multiple of `tab_width`
+/// columns. A `tab_width` of 0 removes tabs.
+fn expand_tabs(line: &str, tab_width: u32) -> String {
+    let tab_width = tab_width as usize;
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            if tab_width == 0 {
+                continue;
+            }
+            let spaces = tab_width - column % tab_width;
+            expanded.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+/// Wrap a single line with no newlines or tabs.
+fn wrap_paragraph(paragraph: &str, width: usize, lines: &mut Vec<String>) {
+    let mut line = String::new();
+    let mut line_len = 0;
+    // Whitespace before the next word, which is dropped at a line break
+    let mut space = String::new();
+    let mut chars = paragraph.chars().peekable();
+
+    while chars.peek().is_some() {
+        while let Some(c) = chars.next_if(|c| c.is_whitespace()) {
+            space.push(c);
+        }
+        let word: Vec<char> =
+            std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect();
+        if word.is_empty() {
+            break;
+        }
+
+        let space_len = space.chars().count();
+        if line_len + space_len + word.len() <= width {
+            line.push_str(&space);
+            line.extend(word.iter());
+            line_len += space_len + word.len();
+        } else if word.len() <= width {
+            lines.push(std::mem::take(&mut line));
+            line.extend(word.iter());
+            line_len = word.len();
+        } else {
+            if line_len > 0 {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Leave room for the hyphen, unless there's only room for the letter
+            let piece_len = if width > 1 { width - 1 } else { 1 };
+            let mut pieces = word.chunks(piece_len).peekable();
+            while let Some(piece) = pieces.next() {
+                line = piece.iter().collect();
+                line_len = piece.len();
+                if pieces.peek().is_some() {
+                    if width > 1 {
+                        line.push('-');
+                    }
+                    lines.push(std::mem::take(&mut line));
+                }
+            }
+        }
+        space.clear();
+    }
+    lines.push(line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert!(wrap("", 10, 4).is_empty());
+        assert_eq!(vec![""], wrap("   ", 10, 4));
+        assert_eq!(vec!["", ""], wrap("\n", 10, 4));
+    }
+
+    #[test]
+    fn shorter_than_width() {
+        assert_eq!(vec!["Hello"], wrap("Hello", 10, 4));
+        assert_eq!(vec!["Hello you"], wrap("Hello you   ", 9, 4));
+    }
+
+    #[test]
+    fn words() {
+        let lines = wrap("The quick brown fox jumps over the lazy dog", 10, 4);
+        assert_eq!(
+            vec!["The quick", "brown fox", "jumps over", "the lazy", "dog"],
+            lines
+        );
+        assert!(lines.iter().all(|l| l.chars().count() <= 10));
+    }
+
+    #[test]
+    fn newlines() {
+        assert_eq!(
+            vec!["one", "", "two three", "four"],
+            wrap("one\n\ntwo three four", 9, 4)
+        );
+        assert_eq!(vec!["a", "b"], wrap("a\r\nb", 9, 4));
+    }
+
+    #[test]
+    fn tabs() {
+        assert_eq!(vec!["    a   b"], wrap("\ta\tb", 20, 4));
+        assert_eq!(vec!["ab"], wrap("a\tb", 20, 0));
+        // Indentation is kept, but whitespace at a break is dropped
+        assert_eq!(vec!["  one", "two"], wrap("  one    two", 6, 4));
+    }
+
+    #[test]
+    fn long_words() {
+        assert_eq!(vec!["abcd-", "efgh-", "ij"], wrap("abcdefghij", 5, 4));
+        assert_eq!(vec!["to", "abcd-", "efg"], wrap("to abcdefg", 5, 4));
+        // The end of a broken word is followed by the next word if it fits
+        assert_eq!(vec!["abcd-", "ef gh"], wrap("abcdef gh", 5, 4));
+        // There's no room for a hyphen in a single column
+        assert_eq!(vec!["a", "b", "c"], wrap("abc", 1, 4));
+    }
+}
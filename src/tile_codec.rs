@@ -0,0 +1,302 @@
+//! A compact binary encoding of terminal tiles, for example to send a terminal
+//! over the network.
+//!
+//! The format starts with a 7 byte header, all integers are little endian:
+//!
+//! | Bytes | Contents                  |
+//! |-------|---------------------------|
+//! | 2     | Magic number, `b"TG"`     |
+//! | 1     | Format version, currently 2 |
+//! | 2     | Width in tiles            |
+//! | 2     | Height in tiles           |
+//!
+//! Followed by runs of identical tiles, 12 bytes each:
+//!
+//! | Bytes | Contents                                  |
+//! |-------|-------------------------------------------|
+//! | 2     | Number of tiles in the run in the low 12 bits, up to 4095 |
+//! | 1     | Code page 437 glyph index                 |
+//! | 4     | Foreground color, 8 bit rgba              |
+//! | 4     | Background color, 8 bit rgba              |
+//! | 1     | [TerminalTileFlags](crate::TerminalTileFlags) in the low 3 bits, the highest bit is set if the tile has a background index |
+//!
+//! A tile's [background index](crate::Tile::bg_index) is split between the
+//! unused bits: the high 4 bits are the top 4 bits of the run's count, the low
+//! 4 bits are bits 3 to 6 of the flags byte.
+
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{code_page_437, TerminalSnapshot, TerminalTileFlags, Tile};
+
+const MAGIC: [u8; 2] = *b"TG";
+const VERSION: u8 = 2;
+const HEADER_SIZE: usize = 7;
+const RUN_SIZE: usize = 12;
+/// The longest run, the rest of the count's bits hold part of the background index.
+const MAX_RUN: u16 = 0x0FFF;
+/// Set in a run's flags byte if the tile has a background index.
+const HAS_BG_INDEX: u8 = 1 << 7;
+/// The position of the background index's low 4 bits in a run's flags byte.
+const BG_INDEX_SHIFT: u8 = 3;
+
+/// Encodes tiles in a compact run length encoded format, see the
+/// [module docs](self) for the layout.
+///
+/// Glyphs are stored as code page 437 indices and colors with 8 bits per
+/// channel, so tiles only survive encoding unchanged if their glyph is in code
+/// page 437 and their colors are representable in 8 bit srgb.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([100, 40]);
+/// term.draw_border_single();
+///
+/// let bytes = TerminalGlyphEncoder::encode(term.tiles.slice(..), term.size());
+/// let snapshot = TerminalGlyphDecoder::decode(&bytes).unwrap();
+///
+/// let mut copy = Terminal::default();
+/// copy.restore(&snapshot);
+/// assert_eq!(term.tiles.slice(..), copy.tiles.slice(..));
+/// ```
+pub struct TerminalGlyphEncoder;
+
+impl TerminalGlyphEncoder {
+    /// Encode the tiles of a terminal of the given size.
+    ///
+    /// Panics if the number of tiles doesn't match the size, or if the size
+    /// doesn't fit in 16 bits along either axis.
+    pub fn encode(tiles: &[Tile], size: UVec2) -> Vec<u8> {
+        assert_eq!(
+            (size.x * size.y) as usize,
+            tiles.len(),
+            "Tile count doesn't match the terminal size"
+        );
+        let width = u16::try_from(size.x).expect("Terminal is too wide to encode");
+        let height = u16::try_from(size.y).expect("Terminal is too tall to encode");
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + RUN_SIZE);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+
+        let mut tiles = tiles.iter().peekable();
+        while let Some(tile) = tiles.next() {
+            let mut count: u16 = 1;
+            while count < MAX_RUN && tiles.next_if_eq(&tile).is_some() {
+                count += 1;
+            }
+
+            let bg_index = tile.bg_index.unwrap_or_default();
+            let count = count | (bg_index as u16 >> 4) << 12;
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes.push(code_page_437::glyph_to_index(tile.glyph));
+            bytes.extend_from_slice(&color_to_bytes(tile.fg_color));
+            bytes.extend_from_slice(&color_to_bytes(tile.bg_color));
+            let has_bg_index = if tile.bg_index.is_some() {
+                HAS_BG_INDEX
+            } else {
+                0
+            };
+            let bg_index_low = (bg_index & 0x0F) << BG_INDEX_SHIFT;
+            bytes.push(tile.flags.bits() | bg_index_low | has_bg_index);
+        }
+        bytes
+    }
+}
+
+/// Decodes tiles encoded by a [TerminalGlyphEncoder].
+pub struct TerminalGlyphDecoder;
+
+impl TerminalGlyphDecoder {
+    /// Decode tiles into a [TerminalSnapshot], which can be restored to a
+    /// terminal with [Terminal::restore](crate::Terminal::restore).
+    pub fn decode(bytes: &[u8]) -> Result<TerminalSnapshot, DecodeError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        if bytes[0..2] != MAGIC {
+            return Err(DecodeError::InvalidMagic);
+        }
+        if bytes[2] != VERSION {
+            return Err(DecodeError::UnsupportedVersion(bytes[2]));
+        }
+        let width = u16::from_le_bytes([bytes[3], bytes[4]]) as u32;
+        let height = u16::from_le_bytes([bytes[5], bytes[6]]) as u32;
+        let expected = (width * height) as usize;
+
+        let runs = bytes[HEADER_SIZE..].chunks(RUN_SIZE);
+        // The header can't be trusted, so only allocate what the runs can hold
+        let max_tiles = runs.len() * MAX_RUN as usize;
+        let mut tiles = Vec::with_capacity(expected.min(max_tiles));
+        for run in runs {
+            if run.len() < RUN_SIZE {
+                return Err(DecodeError::UnexpectedEnd);
+            }
+            let count = u16::from_le_bytes([run[0], run[1]]);
+            let bg_index = ((count >> 12) as u8) << 4 | (run[11] >> BG_INDEX_SHIFT) & 0x0F;
+            let count = (count & MAX_RUN) as usize;
+            let tile = Tile {
+                glyph: code_page_437::index_to_glyph(run[2]),
+                fg_color: Color::rgba_u8(run[3], run[4], run[5], run[6]),
+                bg_color: Color::rgba_u8(run[7], run[8], run[9], run[10]),
+                flags: TerminalTileFlags::from_bits_truncate(run[11]),
+                bg_index: if run[11] & HAS_BG_INDEX != 0 {
+                    Some(bg_index)
+                } else {
+                    None
+                },
+            };
+            if tiles.len() + count > expected {
+                return Err(DecodeError::TileCountMismatch {
+                    expected,
+                    found: tiles.len() + count,
+                });
+            }
+            tiles.resize(tiles.len() + count, tile);
+        }
+
+        if tiles.len() != expected {
+            return Err(DecodeError::TileCountMismatch {
+                expected,
+                found: tiles.len(),
+            });
+        }
+        Ok(TerminalSnapshot {
+            tiles,
+            size: UVec2::new(width, height),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start with the expected magic number.
+    InvalidMagic,
+    /// The data was encoded with an unknown version of the format.
+    UnsupportedVersion(u8),
+    /// The data ends part way through the header or a run.
+    UnexpectedEnd,
+    /// The runs don't add up to the number of tiles given by the header.
+    TileCountMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidMagic => write!(f, "Data isn't encoded terminal tiles"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "Unsupported format version {}", v),
+            DecodeError::UnexpectedEnd => write!(f, "Data ended unexpectedly"),
+            DecodeError::TileCountMismatch { expected, found } => {
+                write!(f, "Expected {} tiles, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn color_to_bytes(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CharFormat, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut term = Terminal::with_size([100, 40]);
+        let bytes = TerminalGlyphEncoder::encode(term.tiles.slice(..), term.size());
+        assert!(bytes.len() < 20);
+        let snapshot = TerminalGlyphDecoder::decode(&bytes).unwrap();
+        assert_eq!(term.size(), snapshot.size());
+        assert_eq!(term.tiles.slice(..), snapshot.tiles());
+
+        // Runs longer than the maximum are split
+        let big = Terminal::with_size([100, 50]);
+        let bytes = TerminalGlyphEncoder::encode(big.tiles.slice(..), big.size());
+        assert_eq!(HEADER_SIZE + RUN_SIZE * 2, bytes.len());
+        let snapshot = TerminalGlyphDecoder::decode(&bytes).unwrap();
+        assert_eq!(big.tiles.slice(..), snapshot.tiles());
+
+        // A dungeon of walls with a few rooms
+        for tile in term.iter_mut() {
+            tile.glyph = '#';
+        }
+        for (x, y, w, h) in [(2, 2, 10, 6), (30, 10, 20, 12), (60, 25, 15, 10)] {
+            for y in y..y + h {
+                term.put_string([x, y], &".".repeat(w));
+            }
+        }
+        term.put_char_formatted([5, 5], '@', CharFormat::new(Color::YELLOW, Color::BLACK));
+        term.get_tile_mut([6, 5]).flags = TerminalTileFlags::FLIP_H;
+        term.put_bg_index([7, 5], Some(0));
+        term.put_bg_index([8, 5], Some(0xAB));
+        term.get_tile_mut([8, 5]).flags = TerminalTileFlags::ROTATE_90;
+        term.put_bg_index([9, 5], Some(0xFF));
+        let bytes = TerminalGlyphEncoder::encode(term.tiles.slice(..), term.size());
+        let snapshot = TerminalGlyphDecoder::decode(&bytes).unwrap();
+        assert_eq!(term.tiles.slice(..), snapshot.tiles());
+        // Compared to storing every run's contents for every tile
+        assert!(term.tiles.slice(..).len() * (RUN_SIZE - 2) / bytes.len() >= 4);
+    }
+
+    #[test]
+    fn errors() {
+        let term = Terminal::with_size([4, 4]);
+        let bytes = TerminalGlyphEncoder::encode(term.tiles.slice(..), term.size());
+
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert_eq!(
+            Err(DecodeError::InvalidMagic),
+            TerminalGlyphDecoder::decode(&bad).map(|_| ())
+        );
+        bad = bytes.clone();
+        bad[2] = 7;
+        assert_eq!(
+            Err(DecodeError::UnsupportedVersion(7)),
+            TerminalGlyphDecoder::decode(&bad).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::UnexpectedEnd),
+            TerminalGlyphDecoder::decode(&bytes[..bytes.len() - 1]).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::TileCountMismatch {
+                expected: 16,
+                found: 0
+            }),
+            TerminalGlyphDecoder::decode(&bytes[..HEADER_SIZE]).map(|_| ())
+        );
+
+        // An oversized header isn't allocated up front
+        let mut huge = b"TG\x02\xFF\xFF\xFF\xFF".to_vec();
+        assert_eq!(
+            Err(DecodeError::TileCountMismatch {
+                expected: 65535 * 65535,
+                found: 0
+            }),
+            TerminalGlyphDecoder::decode(&huge).map(|_| ())
+        );
+        huge.extend_from_slice(&bytes[HEADER_SIZE..]);
+        assert_eq!(
+            Err(DecodeError::TileCountMismatch {
+                expected: 65535 * 65535,
+                found: 16
+            }),
+            TerminalGlyphDecoder::decode(&huge).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Finding the differences between two sets of tiles.
+
+use bevy::math::UVec2;
+
+use crate::{Terminal, Tile};
+
+/// The number of tiles compared at once before falling back to comparing
+/// individual tiles.
+const CHUNK_SIZE: usize = 16;
+
+/// Finds the tiles which differ between two terminal buffers, for example to
+/// only send changed tiles over the network.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ascii_terminal::*;
+///
+/// let old = Terminal::with_size([10, 10]);
+/// let mut new = Terminal::with_size([10, 10]);
+/// new.put_char([3, 1], 'a');
+///
+/// let changed: Vec<_> =
+///     TerminalTileComparator::diff(old.tiles.slice(..), new.tiles.slice(..)).collect();
+/// assert_eq!(1, changed.len());
+/// assert_eq!(13, changed[0].0);
+/// assert_eq!('a', changed[0].1.glyph);
+/// ```
+pub struct TerminalTileComparator;
+
+impl TerminalTileComparator {
+    /// Yields the index and tile from `b` for every tile that differs from `a`.
+    ///
+    /// Any tiles in `b` past the end of `a` are always yielded.
+    pub fn diff<'a>(a: &'a [Tile], b: &'a [Tile]) -> impl Iterator<Item = (usize, &'a Tile)> {
+        Self::diff_chunks(a, b, |a, b| a == b)
+    }
+
+    /// Like [TerminalTileComparator::diff], but whole chunks of tiles are
+    /// compared with `comparator`, for example a SIMD implementation.
+    ///
+    /// Only available with the `simd_tile_diff` feature.
+    #[cfg(feature = "simd_tile_diff")]
+    pub fn diff_with<'a>(
+        a: &'a [Tile],
+        b: &'a [Tile],
+        comparator: impl TileChunkComparator + 'a,
+    ) -> impl Iterator<Item = (usize, &'a Tile)> {
+        Self::diff_chunks(a, b, move |a, b| comparator.chunks_eq(a, b))
+    }
+
+    fn diff_chunks<'a>(
+        a: &'a [Tile],
+        b: &'a [Tile],
+        chunks_eq: impl Fn(&[Tile], &[Tile]) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, &'a Tile)> {
+        let len = a.len().min(b.len());
+        // Whole chunks are compared first, so mostly unchanged buffers skip
+        // most per-tile work.
+        a[..len]
+            .chunks(CHUNK_SIZE)
+            .zip(b[..len].chunks(CHUNK_SIZE))
+            .enumerate()
+            .filter(move |(_, (a, b))| !chunks_eq(a, b))
+            .flat_map(|(chunk, (a, b))| {
+                a.iter()
+                    .zip(b)
+                    .enumerate()
+                    .filter(|(_, (a, b))| a != b)
+                    .map(move |(i, (_, b))| (chunk * CHUNK_SIZE + i, b))
+            })
+            .chain(b.iter().enumerate().skip(len))
+    }
+}
+
+/// Compares chunks of tiles for [TerminalTileComparator::diff_with].
+///
+/// Only available with the `simd_tile_diff` feature. A tile is a char and two
+/// [Color](bevy::prelude::Color) enums of floats, so implementations must
+/// treat tiles as equal exactly when their `PartialEq` does.
+#[cfg(feature = "simd_tile_diff")]
+pub trait TileChunkComparator {
+    /// Whether every tile in `a` equals the tile at the same index in `b`.
+    /// Both chunks have the same length.
+    fn chunks_eq(&self, a: &[Tile], b: &[Tile]) -> bool;
+}
+
+/// A copy of a terminal's tiles from the last time they were compared, for
+/// components which react to individual tile changes.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PreviousTiles(Vec<Tile>);
+
+impl PreviousTiles {
+    /// Call `f` with the position, old tile and new tile of every tile which
+    /// changed since the last call, then keep a copy of the terminal's tiles.
+    ///
+    /// A resized terminal is compared against a blank one.
+    pub(crate) fn update(&mut self, term: &Terminal, mut f: impl FnMut(UVec2, &Tile, &Tile)) {
+        let tiles = term.tiles.slice(..);
+        if self.0.len() != tiles.len() {
+            self.0 = vec![Tile::default(); tiles.len()];
+        }
+
+        let width = term.width() as usize;
+        for (i, new) in TerminalTileComparator::diff(&self.0, tiles) {
+            let pos = UVec2::new((i % width) as u32, (i / width) as u32);
+            f(pos, &self.0[i], new);
+        }
+        self.0.copy_from_slice(tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Color;
+
+    use super::*;
+
+    #[test]
+    fn identical() {
+        let a = vec![Tile::default(); 12000];
+        let b = a.clone();
+        assert_eq!(0, TerminalTileComparator::diff(&a, &b).count());
+    }
+
+    #[test]
+    fn different() {
+        let a = vec![Tile::default(); 100];
+        let mut b = a.clone();
+        b[0].glyph = 'a';
+        b[15].fg_color = Color::RED;
+        b[16].glyph = 'b';
+        b[99].bg_color = Color::BLUE;
+
+        let changed: Vec<_> = TerminalTileComparator::diff(&a, &b).collect();
+        assert_eq!(
+            vec![0, 15, 16, 99],
+            changed.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+        assert_eq!(Color::RED, changed[1].1.fg_color);
+
+        // Extra tiles are always different
+        let changed: Vec<_> = TerminalTileComparator::diff(&a[..90], &a).collect();
+        assert_eq!(10, changed.len());
+        assert_eq!(90, changed[0].0);
+    }
+
+    #[cfg(feature = "simd_tile_diff")]
+    #[test]
+    fn chunk_comparator() {
+        use std::cell::Cell;
+
+        struct Counting<'a>(&'a Cell<usize>);
+
+        impl TileChunkComparator for Counting<'_> {
+            fn chunks_eq(&self, a: &[Tile], b: &[Tile]) -> bool {
+                self.0.set(self.0.get() + 1);
+                a == b
+            }
+        }
+
+        let a = vec![Tile::default(); 100];
+        let mut b = a.clone();
+        b[20].glyph = 'a';
+        let calls = Cell::new(0);
+        let changed: Vec<_> = TerminalTileComparator::diff_with(&a, &b, Counting(&calls)).collect();
+        assert_eq!(
+            vec![20],
+            changed.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+        assert_eq!(7, calls.get());
+    }
+
+    #[test]
+    fn previous_tiles() {
+        let mut previous = PreviousTiles::default();
+        let mut term = Terminal::with_size([4, 2]);
+        term.put_char([1, 1], 'a');
+        let mut changed = Vec::new();
+        previous.update(&term, |pos, old, new| {
+            changed.push((pos, old.glyph, new.glyph))
+        });
+        assert_eq!(vec![(UVec2::new(1, 1), ' ', 'a')], changed);
+
+        changed.clear();
+        term.put_char([1, 1], 'b');
+        previous.update(&term, |pos, old, new| {
+            changed.push((pos, old.glyph, new.glyph))
+        });
+        assert_eq!(vec![(UVec2::new(1, 1), 'a', 'b')], changed);
+
+        // A resized terminal is compared against a blank one
+        changed.clear();
+        term.resize([2, 2]);
+        term.put_char([0, 0], 'c');
+        previous.update(&term, |pos, old, new| {
+            changed.push((pos, old.glyph, new.glyph))
+        });
+        assert_eq!(vec![(UVec2::new(0, 0), ' ', 'c')], changed);
+    }
+}
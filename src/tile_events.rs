@@ -0,0 +1,107 @@
+//! Events sent when individual terminal tiles change.
+
+use bevy::prelude::*;
+
+use crate::{tile_diff::PreviousTiles, Terminal, Tile};
+
+/// Terminal component which enables [TileChangedEvent]s for the terminal.
+///
+/// The component keeps a copy of the terminal's tiles from the previous frame,
+/// so events are only sent for terminals which have it.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 20]))
+///         .insert(TerminalEventWriter::default());
+/// }
+///
+/// fn doors(mut events: EventReader<TileChangedEvent>) {
+///     for ev in events.iter() {
+///         if ev.new.glyph == '/' && ev.old.glyph == '+' {
+///             info!("A door opened at {}", ev.pos);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Component, Default, Clone)]
+pub struct TerminalEventWriter {
+    previous: PreviousTiles,
+}
+
+/// Event sent for every tile of a terminal with a [TerminalEventWriter] which
+/// changed since the previous frame.
+#[derive(Debug, Clone)]
+pub struct TileChangedEvent {
+    pub entity: Entity,
+    pub pos: UVec2,
+    pub old: Tile,
+    pub new: Tile,
+}
+
+pub(crate) fn terminal_tile_changed_events(
+    mut events: EventWriter<TileChangedEvent>,
+    mut q: Query<(Entity, &Terminal, &mut TerminalEventWriter), Changed<Terminal>>,
+) {
+    for (entity, term, mut writer) in q.iter_mut() {
+        writer.previous.update(term, |pos, old, new| {
+            events.send(TileChangedEvent {
+                entity,
+                pos,
+                old: *old,
+                new: *new,
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{
+        event::Events,
+        schedule::{Stage, SystemStage},
+    };
+
+    use super::*;
+
+    #[test]
+    fn single_tile() {
+        let mut world = World::new();
+        world.insert_resource(Events::<TileChangedEvent>::default());
+        let mut stage = SystemStage::parallel();
+        stage.add_system(terminal_tile_changed_events);
+
+        let mut term = Terminal::with_size([8, 4]);
+        term.put_char([2, 3], 'a');
+        let entity = world
+            .spawn()
+            .insert(term)
+            .insert(TerminalEventWriter::default())
+            .id();
+        stage.run(&mut world);
+        world
+            .get_resource_mut::<Events<TileChangedEvent>>()
+            .unwrap()
+            .clear();
+
+        world
+            .get_mut::<Terminal>(entity)
+            .unwrap()
+            .put_char([2, 3], 'b');
+        stage.run(&mut world);
+
+        let events = world.get_resource::<Events<TileChangedEvent>>().unwrap();
+        let mut reader = events.get_reader();
+        let sent: Vec<_> = reader.iter(events).collect();
+        assert_eq!(1, sent.len());
+        assert_eq!(entity, sent[0].entity);
+        assert_eq!(UVec2::new(2, 3), sent[0].pos);
+        assert_eq!('a', sent[0].old.glyph);
+        assert_eq!('b', sent[0].new.glyph);
+    }
+}
@@ -0,0 +1,232 @@
+//! Animated transitions between two terminal states.
+
+use bevy::prelude::*;
+
+use crate::{renderer::noise, Terminal, TerminalSnapshot, Tile};
+
+/// How a [TerminalTransition] moves from one state to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionMode {
+    /// Fade the old tiles to black, then fade in the new tiles.
+    Fade,
+    /// Reveal the new tiles column by column from left to right.
+    WipeLeft,
+    /// Reveal the new tiles in a random order.
+    Dissolve,
+}
+
+impl Default for TransitionMode {
+    fn default() -> Self {
+        TransitionMode::Fade
+    }
+}
+
+/// Terminal component which animates the terminal from one [TerminalSnapshot]
+/// to another.
+///
+/// The component is removed and a [TransitionDoneEvent] is sent once the
+/// transition is finished.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn next_screen(mut commands: Commands, q: Query<(Entity, &Terminal)>) {
+///     for (entity, term) in q.iter() {
+///         let from = term.snapshot();
+///         let mut next = Terminal::with_size(term.size().into());
+///         next.put_string([0, 0], "Game Over");
+///
+///         commands
+///             .entity(entity)
+///             .insert(TerminalTransition::new(from, next.snapshot(), TransitionMode::WipeLeft));
+///     }
+/// }
+/// ```
+#[derive(Component, Clone)]
+pub struct TerminalTransition {
+    pub from: TerminalSnapshot,
+    pub to: TerminalSnapshot,
+    pub mode: TransitionMode,
+    /// How far along the transition is, from 0 to 1.
+    pub progress: f32,
+    /// How much progress is made per second.
+    pub speed: f32,
+}
+
+/// Event sent when a terminal's [TerminalTransition] is finished.
+pub struct TransitionDoneEvent(pub Entity);
+
+impl TerminalTransition {
+    /// Create a transition which will take one second.
+    pub fn new(from: TerminalSnapshot, to: TerminalSnapshot, mode: TransitionMode) -> Self {
+        Self {
+            from,
+            to,
+            mode,
+            progress: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Write the state of the transition at it's current progress to the terminal.
+    pub fn apply(&self, terminal: &mut Terminal) {
+        let progress = self.progress.clamp(0.0, 1.0);
+        if progress >= 1.0 || self.from.size() != self.to.size() {
+            terminal.restore(&self.to);
+            return;
+        }
+        if terminal.size() != self.to.size() {
+            terminal.resize(self.to.size().into());
+        }
+
+        let width = self.to.size().x as usize;
+        let tiles = self.from.tiles().iter().zip(self.to.tiles());
+        for (i, (t, (from, to))) in terminal.iter_mut().zip(tiles).enumerate() {
+            *t = match self.mode {
+                TransitionMode::Fade => {
+                    if progress < 0.5 {
+                        fade(from, 1.0 - progress * 2.0)
+                    } else {
+                        fade(to, (progress - 0.5) * 2.0)
+                    }
+                }
+                TransitionMode::WipeLeft => {
+                    let x = (i % width) as f32;
+                    if x < progress * width as f32 {
+                        *to
+                    } else {
+                        *from
+                    }
+                }
+                TransitionMode::Dissolve => {
+                    if noise::unit(noise::hash(0, 0, i as u64)) < progress {
+                        *to
+                    } else {
+                        *from
+                    }
+                }
+            };
+        }
+    }
+}
+
+/// Scale a tile's colors towards black. Alpha is left as is.
+fn fade(tile: &Tile, amount: f32) -> Tile {
+    let scale = |c: Color| Color::rgba(c.r() * amount, c.g() * amount, c.b() * amount, c.a());
+    Tile {
+        glyph: tile.glyph,
+        fg_color: scale(tile.fg_color),
+        bg_color: scale(tile.bg_color),
+        ..*tile
+    }
+}
+
+pub(crate) fn terminal_update_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut events: EventWriter<TransitionDoneEvent>,
+    mut q: Query<(Entity, &mut Terminal, &mut TerminalTransition)>,
+) {
+    for (entity, mut term, mut transition) in q.iter_mut() {
+        transition.progress += transition.speed * time.delta_seconds();
+        transition.apply(&mut term);
+
+        if transition.progress >= 1.0 {
+            commands.entity(entity).remove::<TerminalTransition>();
+            events.send(TransitionDoneEvent(entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{
+        event::Events,
+        schedule::{Stage, SystemStage},
+    };
+
+    use super::*;
+
+    fn snapshots() -> (TerminalSnapshot, TerminalSnapshot) {
+        let mut term = Terminal::with_size([10, 2]);
+        for t in term.iter_mut() {
+            t.glyph = 'a';
+        }
+        let from = term.snapshot();
+        for t in term.iter_mut() {
+            t.glyph = 'b';
+        }
+        (from, term.snapshot())
+    }
+
+    #[test]
+    fn wipe_left() {
+        let (from, to) = snapshots();
+        let mut transition = TerminalTransition::new(from, to, TransitionMode::WipeLeft);
+        let mut term = Terminal::with_size([10, 2]);
+
+        transition.progress = 0.3;
+        transition.apply(&mut term);
+        assert_eq!("bbbaaaaaaa", term.get_string([0, 0], 10));
+        assert_eq!("bbbaaaaaaa", term.get_string([0, 1], 10));
+    }
+
+    #[test]
+    fn fade_and_dissolve() {
+        let (from, to) = snapshots();
+        let mut term = Terminal::with_size([10, 2]);
+
+        let mut fade = TerminalTransition::new(from.clone(), to.clone(), TransitionMode::Fade);
+        fade.progress = 0.25;
+        fade.apply(&mut term);
+        assert_eq!('a', term.get_char([0, 0]));
+        assert_eq!(0.5, term.get_tile([0, 0]).fg_color.r());
+
+        let mut dissolve = TerminalTransition::new(from, to, TransitionMode::Dissolve);
+        dissolve.progress = 0.5;
+        dissolve.apply(&mut term);
+        let revealed = term.iter().filter(|t| t.glyph == 'b').count();
+        assert!(revealed > 0 && revealed < 20);
+    }
+
+    #[test]
+    fn done_event() {
+        let (from, to) = snapshots();
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.insert_resource(Events::<TransitionDoneEvent>::default());
+
+        let mut transition = TerminalTransition::new(from, to, TransitionMode::Fade);
+        transition.progress = 1.0;
+        let entity = world
+            .spawn()
+            .insert(Terminal::with_size([10, 2]))
+            .insert(transition)
+            .id();
+
+        let mut stage = SystemStage::parallel();
+        stage.add_system(terminal_update_transitions);
+        stage.run(&mut world);
+
+        assert!(world.get::<TerminalTransition>(entity).is_none());
+        assert_eq!(
+            "bbbbbbbbbb",
+            world
+                .get::<Terminal>(entity)
+                .unwrap()
+                .get_string([0, 0], 10)
+        );
+
+        let events = world.get_resource::<Events<TransitionDoneEvent>>().unwrap();
+        let mut reader = events.get_reader();
+        assert_eq!(entity, reader.iter(events).next().unwrap().0);
+    }
+}
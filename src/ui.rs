@@ -0,0 +1,453 @@
+//! A minimal layout system for building terminal UIs from nested stacks of
+//! widgets, rather than placing each widget by absolute coordinates.
+//!
+//! Widget rects are [Rect]s in terminal tile coordinates where `left` and
+//! `bottom` are the first tiles inside the rect and `right` and `top` are one
+//! past the last tiles inside it.
+
+use bevy::prelude::*;
+
+use crate::{CharFormat, Terminal, TerminalMenuWidget};
+
+/// Something which can be drawn to an area of a terminal by a [TerminalLayout].
+pub trait TerminalWidget: Send + Sync + 'static {
+    /// Draw the widget within `rect`.
+    fn draw(&self, terminal: &mut Terminal, rect: Rect<i32>);
+
+    /// The width and height the widget would like to take up in a stack, or
+    /// `None` along an axis to share whatever space is left over with other
+    /// flexible widgets.
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        [None, None]
+    }
+}
+
+/// Describes how a terminal's area is divided between widgets.
+///
+/// Layouts are widgets themselves so they can be nested.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+/// use bevy_ascii_terminal::ui::{Label, ProgressBar, Spacer};
+///
+/// fn setup(mut commands: Commands) {
+///     let layout = TerminalLayout::VStack(vec![
+///         Box::new(Label::new("Health")),
+///         Box::new(ProgressBar::new(7.0, 10.0)),
+///         Box::new(Spacer::default()),
+///         Box::new(TerminalMenuWidget::new(["Attack", "Flee"])),
+///     ]);
+///     commands
+///         .spawn_bundle(TerminalBundle::new().with_size([20, 10]))
+///         .insert(TerminalUI::new(layout));
+/// }
+/// ```
+pub enum TerminalLayout {
+    /// Widgets placed side by side from left to right, each filling the height
+    /// of the stack.
+    HStack(Vec<Box<dyn TerminalWidget>>),
+    /// Widgets placed one below the other from top to bottom, each filling the
+    /// width of the stack.
+    VStack(Vec<Box<dyn TerminalWidget>>),
+    /// A widget drawn in a fixed rect, relative to the bottom left of the area
+    /// the layout is drawn in.
+    Fixed(Rect<i32>, Box<dyn TerminalWidget>),
+}
+
+impl TerminalLayout {
+    /// The rect of each widget in the layout when the layout is drawn in `rect`.
+    ///
+    /// Rects are clipped to `rect`, so widgets which don't fit may get an empty
+    /// rect.
+    pub fn child_rects(&self, rect: Rect<i32>) -> Vec<Rect<i32>> {
+        match self {
+            TerminalLayout::HStack(widgets) => {
+                let hints: Vec<_> = widgets.iter().map(|w| w.size_hint()[0]).collect();
+                let mut x = rect.left;
+                split(&hints, rect.right - rect.left)
+                    .into_iter()
+                    .map(|width| {
+                        let child = Rect {
+                            left: x,
+                            right: x + width,
+                            bottom: rect.bottom,
+                            top: rect.top,
+                        };
+                        x += width;
+                        clip(child, rect)
+                    })
+                    .collect()
+            }
+            TerminalLayout::VStack(widgets) => {
+                let hints: Vec<_> = widgets.iter().map(|w| w.size_hint()[1]).collect();
+                let mut y = rect.top;
+                split(&hints, rect.top - rect.bottom)
+                    .into_iter()
+                    .map(|height| {
+                        let child = Rect {
+                            left: rect.left,
+                            right: rect.right,
+                            bottom: y - height,
+                            top: y,
+                        };
+                        y -= height;
+                        clip(child, rect)
+                    })
+                    .collect()
+            }
+            TerminalLayout::Fixed(fixed, _) => {
+                let child = Rect {
+                    left: rect.left + fixed.left,
+                    right: rect.left + fixed.right,
+                    bottom: rect.bottom + fixed.bottom,
+                    top: rect.bottom + fixed.top,
+                };
+                vec![clip(child, rect)]
+            }
+        }
+    }
+
+    fn widgets(&self) -> &[Box<dyn TerminalWidget>] {
+        match self {
+            TerminalLayout::HStack(widgets) | TerminalLayout::VStack(widgets) => widgets,
+            TerminalLayout::Fixed(_, widget) => std::slice::from_ref(widget),
+        }
+    }
+}
+
+/// Divide `available` tiles between widgets, giving each widget with a hint
+/// it's hinted size and sharing the rest evenly between the others.
+fn split(hints: &[Option<u32>], available: i32) -> Vec<i32> {
+    let fixed: i32 = hints.iter().flatten().map(|h| *h as i32).sum();
+    let flexible = hints.iter().filter(|h| h.is_none()).count() as i32;
+    let remaining = (available - fixed).max(0);
+    let mut extra = if flexible > 0 {
+        remaining % flexible
+    } else {
+        0
+    };
+    hints
+        .iter()
+        .map(|hint| match hint {
+            Some(size) => *size as i32,
+            None => {
+                let size = remaining / flexible + (extra > 0) as i32;
+                extra -= 1;
+                size
+            }
+        })
+        .collect()
+}
+
+/// The part of `rect` inside `bounds`. Empty rects keep `left <= right` and
+/// `bottom <= top`.
+fn clip(rect: Rect<i32>, bounds: Rect<i32>) -> Rect<i32> {
+    let left = rect.left.clamp(bounds.left, bounds.right);
+    let bottom = rect.bottom.clamp(bounds.bottom, bounds.top);
+    Rect {
+        left,
+        right: rect.right.clamp(left, bounds.right),
+        bottom,
+        top: rect.top.clamp(bottom, bounds.top),
+    }
+}
+
+impl TerminalWidget for TerminalLayout {
+    fn draw(&self, terminal: &mut Terminal, rect: Rect<i32>) {
+        for (widget, rect) in self.widgets().iter().zip(self.child_rects(rect)) {
+            widget.draw(terminal, rect);
+        }
+    }
+
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        let hints: Vec<_> = self.widgets().iter().map(|w| w.size_hint()).collect();
+        let sum = |axis: usize| hints.iter().map(|h| h[axis]).sum::<Option<u32>>();
+        let max = |axis: usize| {
+            hints
+                .iter()
+                .map(|h| h[axis])
+                .collect::<Option<Vec<_>>>()
+                .map(|sizes| sizes.into_iter().max().unwrap_or(0))
+        };
+        match self {
+            TerminalLayout::HStack(_) => [sum(0), max(1)],
+            TerminalLayout::VStack(_) => [max(0), sum(1)],
+            TerminalLayout::Fixed(rect, _) => {
+                [Some(rect.right.max(0) as u32), Some(rect.top.max(0) as u32)]
+            }
+        }
+    }
+}
+
+/// Terminal component which draws a [TerminalLayout] over the whole terminal.
+///
+/// The terminal is cleared and redrawn whenever the component changes or the
+/// terminal is resized.
+#[derive(Component)]
+pub struct TerminalUI {
+    pub layout: TerminalLayout,
+}
+
+impl TerminalUI {
+    pub fn new(layout: TerminalLayout) -> Self {
+        Self { layout }
+    }
+
+    /// Clear the terminal and draw the layout over it.
+    pub fn draw(&self, terminal: &mut Terminal) {
+        terminal.clear();
+        let size = terminal.size().as_ivec2();
+        let rect = Rect {
+            left: 0,
+            right: size.x,
+            bottom: 0,
+            top: size.y,
+        };
+        self.layout.draw(terminal, rect);
+    }
+}
+
+/// Terminal component holding the size of the terminal when it's [TerminalUI]
+/// was last drawn. Added the first time the ui is drawn.
+#[derive(Component)]
+pub(crate) struct TerminalUIDrawnSize(UVec2);
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn terminal_ui_draw(
+    mut commands: Commands,
+    mut q: Query<(
+        Entity,
+        &mut Terminal,
+        &TerminalUI,
+        ChangeTrackers<TerminalUI>,
+        Option<&mut TerminalUIDrawnSize>,
+    )>,
+) {
+    for (entity, mut terminal, ui, tracker, drawn) in q.iter_mut() {
+        let size = terminal.size();
+        match drawn {
+            Some(drawn) if !tracker.is_changed() && drawn.0 == size => continue,
+            Some(mut drawn) => drawn.0 = size,
+            None => {
+                commands.entity(entity).insert(TerminalUIDrawnSize(size));
+            }
+        }
+        ui.draw(&mut terminal);
+    }
+}
+
+/// A single line of text, truncated to the width of it's rect.
+#[derive(Clone, Default)]
+pub struct Label {
+    pub text: String,
+    pub format: CharFormat,
+}
+
+impl Label {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            format: CharFormat::default(),
+        }
+    }
+
+    pub fn with_format(mut self, format: CharFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl TerminalWidget for Label {
+    fn draw(&self, terminal: &mut Terminal, rect: Rect<i32>) {
+        if rect.top <= rect.bottom {
+            return;
+        }
+        for (x, ch) in (rect.left..rect.right).zip(self.text.chars()) {
+            terminal.put_tile([x, rect.top - 1], self.format.tile(ch));
+        }
+    }
+
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        [Some(self.text.chars().count() as u32), Some(1)]
+    }
+}
+
+/// A horizontal bar filling the width of it's rect, see
+/// [Terminal::draw_horizontal_bar_color].
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    pub value: f32,
+    pub max: f32,
+    pub filled_color: Color,
+    pub empty_color: Color,
+}
+
+impl ProgressBar {
+    pub fn new(value: f32, max: f32) -> Self {
+        Self {
+            value,
+            max,
+            filled_color: Color::WHITE,
+            empty_color: Color::GRAY,
+        }
+    }
+
+    pub fn with_colors(mut self, filled: Color, empty: Color) -> Self {
+        self.filled_color = filled;
+        self.empty_color = empty;
+        self
+    }
+}
+
+impl TerminalWidget for ProgressBar {
+    fn draw(&self, terminal: &mut Terminal, rect: Rect<i32>) {
+        if rect.top <= rect.bottom {
+            return;
+        }
+        let width = rect.right - rect.left;
+        // Scale to whole tiles, draw_horizontal_bar_color works in integers
+        let value = match self.max > 0.0 {
+            true => ((self.value / self.max).clamp(0.0, 1.0) * width as f32).round() as i32,
+            false => 0,
+        };
+        terminal.draw_horizontal_bar_color(
+            [rect.left, rect.top - 1],
+            width,
+            value,
+            width,
+            self.filled_color,
+            self.empty_color,
+        );
+    }
+
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        [None, Some(1)]
+    }
+}
+
+/// Empty space between widgets.
+///
+/// By default a spacer shares the left over space of a stack with any other
+/// flexible widgets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spacer {
+    /// If set, the number of tiles the spacer takes up along both axes.
+    pub size: Option<u32>,
+}
+
+impl Spacer {
+    /// A spacer which always takes up `size` tiles.
+    pub fn fixed(size: u32) -> Self {
+        Self { size: Some(size) }
+    }
+}
+
+impl TerminalWidget for Spacer {
+    fn draw(&self, _terminal: &mut Terminal, _rect: Rect<i32>) {}
+
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        [self.size, self.size]
+    }
+}
+
+impl TerminalWidget for TerminalMenuWidget {
+    fn draw(&self, terminal: &mut Terminal, rect: Rect<i32>) {
+        let rows = (rect.top - rect.bottom).max(0) as usize;
+        let mut menu = self.clone();
+        menu.items.truncate(rows);
+        menu.width = menu.width.min((rect.right - rect.left).max(0) as u32);
+        TerminalMenuWidget::draw(&menu, terminal, [rect.left, rect.top - 1]);
+    }
+
+    fn size_hint(&self) -> [Option<u32>; 2] {
+        [Some(self.width), Some(self.items.len() as u32)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, bottom: i32, right: i32, top: i32) -> Rect<i32> {
+        Rect {
+            left,
+            right,
+            bottom,
+            top,
+        }
+    }
+
+    #[test]
+    fn redraw() {
+        let mut app = App::new();
+        app.add_system(terminal_ui_draw);
+        let layout = TerminalLayout::VStack(vec![Box::new(Label::new("hi"))]);
+        let entity = app
+            .world
+            .spawn()
+            .insert(Terminal::with_size([4, 2]))
+            .insert(TerminalUI::new(layout))
+            .id();
+        app.update();
+        let term = app.world.get::<Terminal>(entity).unwrap();
+        assert_eq!("hi", term.get_string([0, 1], 2));
+
+        // Unchanged ui isn't redrawn
+        app.world
+            .get_mut::<Terminal>(entity)
+            .unwrap()
+            .put_char([0, 0], 'x');
+        app.update();
+        let term = app.world.get::<Terminal>(entity).unwrap();
+        assert_eq!('x', term.get_char([0, 0]));
+
+        app.world
+            .get_mut::<Terminal>(entity)
+            .unwrap()
+            .resize([4, 3]);
+        app.update();
+        let term = app.world.get::<Terminal>(entity).unwrap();
+        assert_eq!("hi", term.get_string([0, 2], 2));
+        assert_eq!(' ', term.get_char([0, 0]));
+    }
+
+    #[test]
+    fn layout() {
+        let row = TerminalLayout::HStack(vec![
+            Box::new(ProgressBar::new(1.0, 2.0)),
+            Box::new(Spacer::fixed(1)),
+            Box::new(Label::new("ok")),
+        ]);
+        assert_eq!([None, Some(1)], row.size_hint());
+        let layout = TerminalLayout::VStack(vec![
+            Box::new(Label::new("Title")),
+            Box::new(row),
+            Box::new(Spacer::default()),
+            Box::new(TerminalMenuWidget::new(["a", "b"])),
+        ]);
+
+        let full = rect(0, 0, 10, 6);
+        let rects = layout.child_rects(full);
+        assert_eq!(rect(0, 5, 10, 6), rects[0]);
+        assert_eq!(rect(0, 4, 10, 5), rects[1]);
+        assert_eq!(rect(0, 2, 10, 4), rects[2]);
+        assert_eq!(rect(0, 0, 10, 2), rects[3]);
+
+        let mut term = Terminal::with_size([10, 6]);
+        TerminalUI::new(layout).draw(&mut term);
+        assert_eq!("Title", term.get_string([0, 5], 5));
+        assert_eq!("▓▓▓▓░░░ ok", term.get_string([0, 4], 10));
+        assert_eq!("a", term.get_string([0, 1], 1));
+        assert_eq!("b", term.get_string([0, 0], 1));
+
+        // Widgets which don't fit are clipped
+        let fixed = TerminalLayout::Fixed(rect(2, 1, 20, 3), Box::new(Label::new("clipped")));
+        assert_eq!(vec![rect(4, 1, 6, 2)], fixed.child_rects(rect(2, 0, 6, 2)));
+        let mut term = Terminal::with_size([6, 2]);
+        fixed.draw(&mut term, rect(2, 0, 6, 2));
+        assert_eq!("    cl", term.get_string([0, 1], 6));
+    }
+}
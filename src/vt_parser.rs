@@ -0,0 +1,268 @@
+//! Drawing text containing VT100/ANSI escape sequences to a terminal.
+
+use bevy::prelude::*;
+
+use crate::{terminal_color256, Terminal, Tile};
+
+const ESC: char = '\x1b';
+
+fn ansi_color(i: u32) -> Color {
+    terminal_color256::from_index((i % 16) as u8)
+}
+
+/// Writes text to a terminal, interpreting a subset of VT100/ANSI escape
+/// sequences.
+///
+/// Supported sequences:
+/// - `ESC[<n>m` sets colors. `0` resets, `30`-`37` and `90`-`97` set the
+///   foreground, `40`-`47` and `100`-`107` set the background, and `39`/`49`
+///   restore the default foreground/background. `38;5;<n>` and `48;5;<n>` set
+///   the foreground/background to an entry of the [256 color palette](crate::terminal_color256).
+/// - `ESC[<n>A`, `B`, `C` and `D` move the cursor up, down, right and left.
+/// - `ESC[2J` clears the terminal.
+/// - `ESC[H` moves the cursor home, or to `ESC[<row>;<col>H`.
+///
+/// Any other sequence is ignored. Sequences split across calls to
+/// [parse_and_draw](TerminalVTParser::parse_and_draw) are handled correctly.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// let mut term = Terminal::with_size([20, 5]);
+/// let mut parser = TerminalVTParser::default();
+/// parser.parse_and_draw(&mut term, "\x1b[31mError\x1b[0m: oops");
+///
+/// assert_eq!("Error: oops", term.get_string([0, 4], 11));
+/// assert_eq!(Color::rgb_u8(170, 0, 0), term.get_tile([0, 4]).fg_color);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TerminalVTParser {
+    /// The cursor position as (column, row), where (0,0) is the top left of
+    /// the terminal and rows increase downwards.
+    pub cursor: IVec2,
+    pub fg: Color,
+    pub bg: Color,
+    /// An escape sequence which hasn't been terminated yet.
+    pending: String,
+}
+
+impl Default for TerminalVTParser {
+    fn default() -> Self {
+        let tile = Tile::default();
+        Self {
+            cursor: IVec2::ZERO,
+            fg: tile.fg_color,
+            bg: tile.bg_color,
+            pending: String::new(),
+        }
+    }
+}
+
+impl TerminalVTParser {
+    /// Draw `input` to the terminal at the cursor, applying any escape sequences.
+    ///
+    /// Text wraps at the right edge of the terminal, and the terminal scrolls up
+    /// when the cursor moves past the bottom row.
+    pub fn parse_and_draw(&mut self, terminal: &mut Terminal, input: &str) {
+        for ch in input.chars() {
+            if !self.pending.is_empty() {
+                self.pending.push(ch);
+                self.continue_sequence(terminal);
+                continue;
+            }
+            match ch {
+                ESC => self.pending.push(ch),
+                '\n' => self.new_line(terminal),
+                '\r' => self.cursor.x = 0,
+                '\t' => self.cursor.x = (self.cursor.x / 8 + 1) * 8,
+                ch if ch.is_control() => {}
+                ch => self.put_char(terminal, ch),
+            }
+        }
+    }
+
+    fn put_char(&mut self, terminal: &mut Terminal, ch: char) {
+        if self.cursor.x >= terminal.width() as i32 {
+            self.new_line(terminal);
+        }
+        let xy = [self.cursor.x, terminal.height() as i32 - 1 - self.cursor.y];
+        if terminal.is_in_bounds(xy) {
+            terminal.put_tile(
+                xy,
+                Tile {
+                    glyph: ch,
+                    fg_color: self.fg,
+                    bg_color: self.bg,
+                    ..Default::default()
+                },
+            );
+        }
+        self.cursor.x += 1;
+    }
+
+    fn new_line(&mut self, terminal: &mut Terminal) {
+        self.cursor.x = 0;
+        self.cursor.y += 1;
+        let height = terminal.height() as i32;
+        if self.cursor.y >= height {
+            scroll_up(terminal, (self.cursor.y - height + 1) as usize);
+            self.cursor.y = height - 1;
+        }
+    }
+
+    /// Process the pending sequence if it's complete.
+    fn continue_sequence(&mut self, terminal: &mut Terminal) {
+        let seq = &self.pending[1..];
+        match seq.chars().next() {
+            // Control sequence
+            Some('[') => {}
+            // Character set designation, ignore it and the character which follows
+            Some('(') | Some(')') if seq.len() < 2 => return,
+            // Only control sequences are supported, skip any other escape
+            _ => {
+                self.pending.clear();
+                return;
+            }
+        }
+
+        let last = match seq[1..].chars().last() {
+            Some(c) if ('\x40'..='\x7e').contains(&c) => c,
+            _ => return,
+        };
+
+        let params: Vec<u32> = seq[1..seq.len() - last.len_utf8()]
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        self.execute(terminal, last, &params);
+        self.pending.clear();
+    }
+
+    fn execute(&mut self, terminal: &mut Terminal, command: char, params: &[u32]) {
+        let count = params.first().copied().unwrap_or(0).max(1) as i32;
+        let max = terminal.size().as_ivec2() - IVec2::ONE;
+        match command {
+            'm' => self.set_graphics(params),
+            'A' => self.cursor.y = (self.cursor.y - count).max(0),
+            'B' => self.cursor.y = (self.cursor.y + count).min(max.y),
+            'C' => self.cursor.x = (self.cursor.x + count).min(max.x),
+            'D' => self.cursor.x = (self.cursor.x - count).max(0),
+            'J' if params.first() == Some(&2) => terminal.clear(),
+            'H' => {
+                let row = params.first().copied().unwrap_or(1).max(1) as i32 - 1;
+                let col = params.get(1).copied().unwrap_or(1).max(1) as i32 - 1;
+                self.cursor = IVec2::new(col, row).min(max).max(IVec2::ZERO);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_graphics(&mut self, params: &[u32]) {
+        let default = Tile::default();
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => {
+                    self.fg = default.fg_color;
+                    self.bg = default.bg_color;
+                }
+                30..=37 => self.fg = ansi_color(param - 30),
+                // Only the 256 color form, `5;<n>`, is supported
+                38 | 48 => {
+                    if params.next() != Some(5) {
+                        return;
+                    }
+                    if let Some(i) = params.next() {
+                        let color = terminal_color256::from_index(i.min(255) as u8);
+                        match param {
+                            38 => self.fg = color,
+                            _ => self.bg = color,
+                        }
+                    }
+                }
+                39 => self.fg = default.fg_color,
+                40..=47 => self.bg = ansi_color(param - 40),
+                49 => self.bg = default.bg_color,
+                90..=97 => self.fg = ansi_color(param - 90 + 8),
+                100..=107 => self.bg = ansi_color(param - 100 + 8),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Move every row of the terminal up by `rows`, clearing the rows at the bottom.
+fn scroll_up(terminal: &mut Terminal, rows: usize) {
+    let width = terminal.width() as usize;
+    let tiles = terminal.tiles.slice_mut(..);
+    let shift = (rows * width).min(tiles.len());
+    let len = tiles.len();
+    tiles.copy_within(0..len - shift, shift);
+    tiles[..shift].fill(Tile::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors() {
+        let mut term = Terminal::with_size([10, 2]);
+        let mut parser = TerminalVTParser::default();
+        parser.parse_and_draw(&mut term, "a\x1b[32;44mb\x1b[39mc\x1b[0md");
+
+        assert_eq!("abcd", term.get_string([0, 1], 4));
+        assert_eq!(Color::WHITE, term.get_tile([0, 1]).fg_color);
+        assert_eq!(ansi_color(2), term.get_tile([1, 1]).fg_color);
+        assert_eq!(ansi_color(4), term.get_tile([1, 1]).bg_color);
+        assert_eq!(Color::WHITE, term.get_tile([2, 1]).fg_color);
+        assert_eq!(ansi_color(4), term.get_tile([2, 1]).bg_color);
+        assert_eq!(Color::BLACK, term.get_tile([3, 1]).bg_color);
+
+        parser.parse_and_draw(&mut term, "\x1b[38;5;196;48;5;21me");
+        assert_eq!(Color::rgb_u8(255, 0, 0), term.get_tile([4, 1]).fg_color);
+        assert_eq!(Color::rgb_u8(0, 0, 255), term.get_tile([4, 1]).bg_color);
+    }
+
+    #[test]
+    fn cursor() {
+        let mut term = Terminal::with_size([10, 3]);
+        let mut parser = TerminalVTParser::default();
+        parser.parse_and_draw(&mut term, "ab\x1b[2Bc\x1b[Ad\x1b[5De\x1b[H");
+        assert_eq!(IVec2::ZERO, parser.cursor);
+
+        assert_eq!('a', term.get_char([0, 2]));
+        assert_eq!('c', term.get_char([2, 0]));
+        assert_eq!('d', term.get_char([3, 1]));
+        assert_eq!('e', term.get_char([0, 1]));
+
+        parser.parse_and_draw(&mut term, "\x1b[2;4Hf");
+        assert_eq!('f', term.get_char([3, 1]));
+        parser.parse_and_draw(&mut term, "\x1b[2J");
+        assert_eq!(' ', term.get_char([3, 1]));
+    }
+
+    #[test]
+    fn split_and_unsupported() {
+        let mut term = Terminal::with_size([10, 2]);
+        let mut parser = TerminalVTParser::default();
+        parser.parse_and_draw(&mut term, "\x1b[3");
+        parser.parse_and_draw(&mut term, "1ma\x1b[?25lb\x1b(Bc");
+
+        assert_eq!("abc", term.get_string([0, 1], 3));
+        assert_eq!(ansi_color(1), term.get_tile([0, 1]).fg_color);
+    }
+
+    #[test]
+    fn scroll() {
+        let mut term = Terminal::with_size([3, 2]);
+        let mut parser = TerminalVTParser::default();
+        parser.parse_and_draw(&mut term, "one\ntwo\nsix");
+
+        assert_eq!("two", term.get_string([0, 1], 3));
+        assert_eq!("six", term.get_string([0, 0], 3));
+    }
+}
@@ -0,0 +1,226 @@
+//! Streaming a large tile world through terminal chunks which are spawned and
+//! despawned as the camera moves.
+
+use bevy::{prelude::*, ui::entity::CameraUi, utils::HashMap};
+
+use crate::{renderer::TerminalRendererBundle, Terminal, TerminalBundle, Tile};
+
+/// Supplies the tiles of a [TerminalWorld].
+pub trait WorldTileProvider: Send + Sync + 'static {
+    /// The tile at the given world tile position.
+    ///
+    /// Called for every tile of a chunk when the chunk is loaded, so it should
+    /// be cheap.
+    fn get_tile(&self, world_pos: IVec2) -> Tile;
+}
+
+impl<F: Fn(IVec2) -> Tile + Send + Sync + 'static> WorldTileProvider for F {
+    fn get_tile(&self, world_pos: IVec2) -> Tile {
+        self(world_pos)
+    }
+}
+
+/// A world of tiles too large for a single terminal, rendered as a grid of
+/// terminal chunks around every camera.
+///
+/// Chunks are normal [Terminal] entities spawned as children of the world
+/// entity, filled from the world's [WorldTileProvider] when they're loaded and
+/// despawned when no camera is near them anymore. Changes made to a chunk's
+/// terminal are lost once it's unloaded.
+///
+/// The world entity needs a [Transform] and [GlobalTransform]. World tile
+/// (0,0) sits at the entity's position, with chunks extending up and to the
+/// right.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_ascii_terminal::*;
+///
+/// fn setup(mut commands: Commands) {
+///     let world = TerminalWorld::new(|pos: IVec2| Tile {
+///         glyph: if (pos.x + pos.y) % 7 == 0 { '#' } else { '.' },
+///         ..Default::default()
+///     })
+///     .with_chunk_size([32, 32])
+///     .with_load_radius(2);
+///     commands
+///         .spawn_bundle(TransformBundle::default())
+///         .insert(world);
+/// }
+/// ```
+#[derive(Component)]
+pub struct TerminalWorld {
+    /// The size of a single tile in world units, which depends on the chunks'
+    /// [TileScaling](crate::renderer::TileScaling). Defaults to (1,1).
+    pub tile_size: UVec2,
+    /// The size of each chunk in tiles. Defaults to (32,32).
+    pub chunk_size: UVec2,
+    /// The currently loaded chunks, by chunk position.
+    pub chunks: HashMap<IVec2, Entity>,
+    /// How many chunks are loaded in each direction from the chunk each camera
+    /// is in. Defaults to 1.
+    pub load_radius: u32,
+    provider: Box<dyn WorldTileProvider>,
+}
+
+impl TerminalWorld {
+    pub fn new(provider: impl WorldTileProvider) -> Self {
+        TerminalWorld {
+            tile_size: UVec2::ONE,
+            chunk_size: UVec2::new(32, 32),
+            chunks: HashMap::default(),
+            load_radius: 1,
+            provider: Box::new(provider),
+        }
+    }
+
+    pub fn with_tile_size(mut self, tile_size: [u32; 2]) -> Self {
+        self.tile_size = UVec2::from(tile_size);
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: [u32; 2]) -> Self {
+        self.chunk_size = UVec2::from(chunk_size);
+        self
+    }
+
+    pub fn with_load_radius(mut self, radius: u32) -> Self {
+        self.load_radius = radius;
+        self
+    }
+
+    /// The world tile position containing a position local to the world entity.
+    pub fn local_to_tile(&self, local: Vec2) -> IVec2 {
+        (local / self.tile_size.as_vec2()).floor().as_ivec2()
+    }
+
+    /// The position of the chunk containing a world tile position.
+    pub fn tile_to_chunk(&self, tile_pos: IVec2) -> IVec2 {
+        let size = self.chunk_size.as_ivec2();
+        IVec2::new(tile_pos.x.div_euclid(size.x), tile_pos.y.div_euclid(size.y))
+    }
+
+    /// A terminal filled with the tiles of the chunk at the given chunk position.
+    pub fn load_chunk(&self, chunk_pos: IVec2) -> Terminal {
+        let mut term = Terminal::with_size(self.chunk_size.into());
+        let origin = chunk_pos * self.chunk_size.as_ivec2();
+        for y in 0..self.chunk_size.y as i32 {
+            for x in 0..self.chunk_size.x as i32 {
+                let tile = self.provider.get_tile(origin + IVec2::new(x, y));
+                term.put_tile([x, y], tile);
+            }
+        }
+        term
+    }
+
+    /// The chunks within the load radius of a position local to the world entity.
+    fn chunks_around(&self, local: Vec2) -> impl Iterator<Item = IVec2> {
+        let center = self.tile_to_chunk(self.local_to_tile(local));
+        let r = self.load_radius as i32;
+        (-r..=r).flat_map(move |y| (-r..=r).map(move |x| center + IVec2::new(x, y)))
+    }
+}
+
+/// Spawn chunks which came into range of a camera and despawn the ones which
+/// went out of range.
+pub(crate) fn terminal_world_update_chunks(
+    mut commands: Commands,
+    cameras: Query<&GlobalTransform, (With<Camera>, Without<CameraUi>)>,
+    mut worlds: Query<(Entity, &mut TerminalWorld, &GlobalTransform)>,
+) {
+    for (entity, mut world, world_transform) in worlds.iter_mut() {
+        let mut wanted: Vec<IVec2> = Vec::new();
+        for camera in cameras.iter() {
+            let local = (camera.translation - world_transform.translation).truncate();
+            wanted.extend(world.chunks_around(local));
+        }
+
+        let unloaded: Vec<_> = world
+            .chunks
+            .keys()
+            .filter(|pos| !wanted.contains(pos))
+            .copied()
+            .collect();
+        for pos in unloaded {
+            if let Some(chunk) = world.chunks.remove(&pos) {
+                commands.entity(chunk).despawn_recursive();
+            }
+        }
+
+        let chunk_world_size = (world.chunk_size * world.tile_size).as_vec2();
+        for pos in wanted {
+            if world.chunks.contains_key(&pos) {
+                continue;
+            }
+            let translation = (pos.as_vec2() * chunk_world_size).extend(0.0);
+            let chunk = commands
+                .spawn_bundle(TerminalBundle {
+                    terminal: world.load_chunk(pos),
+                    transform: Transform::from_translation(translation),
+                    renderer: TerminalRendererBundle::new().with_terminal_pivot(0.0, 0.0),
+                    ..Default::default()
+                })
+                .id();
+            commands.entity(entity).add_child(chunk);
+            world.chunks.insert(pos, chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::schedule::{Stage, SystemStage};
+
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        let world = TerminalWorld::new(|pos: IVec2| Tile {
+            glyph: if pos == IVec2::new(-1, 5) { '@' } else { '.' },
+            ..Default::default()
+        })
+        .with_chunk_size([4, 4])
+        .with_tile_size([2, 2]);
+        assert_eq!(IVec2::new(-1, 2), world.local_to_tile(Vec2::new(-0.5, 5.0)));
+        assert_eq!(IVec2::new(-1, 1), world.tile_to_chunk(IVec2::new(-1, 5)));
+        assert_eq!(IVec2::new(0, -1), world.tile_to_chunk(IVec2::new(3, -4)));
+        let chunk = world.load_chunk(IVec2::new(-1, 1));
+        assert_eq!('@', chunk.get_char([3, 1]));
+        assert_eq!('.', chunk.get_char([3, 0]));
+
+        let mut ecs = World::new();
+        let mut stage = SystemStage::parallel();
+        stage.add_system(terminal_world_update_chunks);
+        let world = ecs
+            .spawn()
+            .insert(world)
+            .insert_bundle(TransformBundle::default())
+            .id();
+        let camera = ecs
+            .spawn()
+            .insert(Camera::default())
+            .insert(GlobalTransform::default())
+            .id();
+        stage.run(&mut ecs);
+
+        let chunks = &ecs.get::<TerminalWorld>(world).unwrap().chunks;
+        assert_eq!(9, chunks.len());
+        let chunk = chunks[&IVec2::new(-1, 1)];
+        assert_eq!('@', ecs.get::<Terminal>(chunk).unwrap().get_char([3, 1]));
+        assert_eq!(
+            Vec3::new(-8.0, 8.0, 0.0),
+            ecs.get::<Transform>(chunk).unwrap().translation
+        );
+
+        // Move the camera three chunks to the right
+        ecs.get_mut::<GlobalTransform>(camera).unwrap().translation = Vec3::new(24.0, 0.0, 0.0);
+        stage.run(&mut ecs);
+        let chunks = &ecs.get::<TerminalWorld>(world).unwrap().chunks;
+        assert_eq!(9, chunks.len());
+        assert!(!chunks.contains_key(&IVec2::new(1, 0)));
+        assert!(chunks.contains_key(&IVec2::new(4, 1)));
+        assert!(ecs.get_entity(chunk).is_none());
+    }
+}